@@ -8,7 +8,8 @@
 use std::collections::HashMap;
 
 use datum::{
-    DatumAtom, DatumErrorKind, DatumMayContainAtom, DatumResult, DatumValue, IntoViaDatumPipe,
+    DatumAtom, DatumBalance, DatumBalanceChecker, DatumErrorKind, DatumMayContainAtom,
+    DatumPattern, DatumResult, DatumValue, IntoViaDatumPipe,
 };
 use rand::RngCore;
 use rustyline::{config::Configurer, validate::ValidationResult};
@@ -84,6 +85,8 @@ impl Function {
 struct Environment {
     /// User-defined functions.
     functions: Vec<Function>,
+    /// Destructures `def` forms; patterns can also be built from values, see DatumPattern::var.
+    def_pattern: DatumPattern,
 }
 
 impl Environment {
@@ -98,6 +101,7 @@ impl Environment {
                 Function::new_binop("max", |a, b| a.max(b)),
                 Function::new("abs", 1, CompiledExpr::Abs(Box::new(CompiledExpr::Arg(0)))),
             ],
+            def_pattern: "(def $name $args... $body)".parse().unwrap(),
         }
     }
     /// Get function index of existing function
@@ -189,13 +193,16 @@ impl Environment {
             match list.first() {
                 Some(DatumValue::Atom(DatumAtom::Symbol(syntax_maybe))) => {
                     if syntax_maybe.eq("def") {
-                        if list.len() < 3 {
-                            return Err("def has to be at least 3 items long".to_string());
-                        }
-                        let res = list[1]
+                        let def = self
+                            .def_pattern
+                            .matches(value)
+                            .ok_or_else(|| "def has to be at least 3 items long".to_string())?;
+                        let res = def
+                            .one("name")
+                            .unwrap()
                             .as_sym_result(|| "def name must be a symbol".to_string())?
                             .to_string();
-                        let argslice = &list[2..list.len() - 1];
+                        let argslice = def.many("args").unwrap();
                         let mut argsyms: HashMap<String, usize> = HashMap::new();
                         for (k, v) in argslice.iter().enumerate() {
                             argsyms.insert(
@@ -204,7 +211,7 @@ impl Environment {
                                 k,
                             );
                         }
-                        let compiled = self.compile_expr(&argsyms, &list[list.len() - 1])?;
+                        let compiled = self.compile_expr(&argsyms, def.one("body").unwrap())?;
                         if let Some(fni) = self.fn_index_for(&res, argslice.len()) {
                             // replace (not override due to inlining)
                             self.functions.remove(fni);
@@ -289,6 +296,16 @@ impl rustyline::validate::Validator for DatumParseHelper {
         &self,
         ctx: &mut rustyline::validate::ValidationContext,
     ) -> rustyline::Result<rustyline::validate::ValidationResult> {
+        // Cheaply check the line is balanced first, so unfinished lines aren't parsed.
+        match DatumBalanceChecker::check_str(ctx.input()) {
+            DatumBalance::Complete => {}
+            DatumBalance::Incomplete => return Ok(ValidationResult::Incomplete),
+            DatumBalance::Invalid => {
+                return Ok(ValidationResult::Invalid(Some(
+                    " unbalanced list end or bad escape".to_string(),
+                )))
+            }
+        }
         // Parse the line to see if it's invalid.
         let res: DatumResult<()> = ctx
             .input()
@@ -0,0 +1,114 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+//! Compile-time embedding of Datum documents.
+//!
+//! _Added in 1.3.0._
+
+#![forbid(unsafe_code)]
+
+extern crate proc_macro;
+
+use core::convert::TryFrom;
+use core::fmt::Write;
+use std::path::PathBuf;
+
+use datum::{DatumAtom, DatumDocument, DatumValue};
+use proc_macro::{TokenStream, TokenTree};
+
+/// Parses a Datum file at compile time, expanding to an expression building its [datum::DatumDocument].
+///
+/// The path is relative to the crate root (the directory containing `Cargo.toml`), not the current file.
+/// If the file can't be read or isn't a valid document, compilation fails; a change to the file causes a rebuild.
+///
+/// The expansion uses `std` (for `String` and `vec!`) and refers to the `datum` crate, which must be a dependency.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumAtom, DatumDocument, DatumValue};
+/// let doc: DatumDocument = datum_macros::include_datum!("tests/example.scm");
+/// assert_eq!(doc.0[0], DatumValue::Atom(DatumAtom::Symbol("level".to_string())));
+/// ```
+#[proc_macro]
+pub fn include_datum(input: TokenStream) -> TokenStream {
+    match include_datum_impl(input) {
+        Ok(v) => v,
+        Err(e) => compile_error(&e),
+    }
+}
+
+fn include_datum_impl(input: TokenStream) -> Result<TokenStream, String> {
+    let path = path_argument(input)?;
+    let mut full = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?);
+    full.push(&path);
+    let text = std::fs::read_to_string(&full).map_err(|e| format!("{}: {}", path, e))?;
+    let doc = DatumDocument::try_from(text.as_str()).map_err(|e| format!("{}: {}", path, e))?;
+    let mut code = String::new();
+    // include_bytes! makes Cargo rebuild when the file changes
+    let _ = write!(
+        code,
+        "{{ const _: &[u8] = ::core::include_bytes!({:?}); ::datum::DatumDocument(::std::vec![",
+        full.to_string_lossy()
+    );
+    for v in doc.iter() {
+        write_value(&mut code, v);
+        code.push(',');
+    }
+    code.push_str("]) }");
+    code.parse()
+        .map_err(|_| "include_datum: generated invalid code".to_string())
+}
+
+/// Gets the path from the macro's argument, which must be a string literal.
+fn path_argument(input: TokenStream) -> Result<String, String> {
+    let mut iter = input.into_iter();
+    let literal = match (iter.next(), iter.next()) {
+        (Some(TokenTree::Literal(v)), None) => v.to_string(),
+        _ => return Err("include_datum: expected a path as a string literal".to_string()),
+    };
+    // simple string literals only; paths with escapes aren't worth the trouble
+    match literal.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(v) if !v.contains('\\') => Ok(v.to_string()),
+        _ => Err("include_datum: expected a path as a string literal without escapes".to_string()),
+    }
+}
+
+fn write_value(code: &mut String, value: &DatumValue) {
+    match value {
+        DatumValue::Atom(atom) => {
+            code.push_str("::datum::DatumValue::Atom(::datum::DatumAtom::");
+            let _ = match atom {
+                DatumAtom::String(v) => {
+                    write!(code, "String(::std::string::String::from({:?}))", v)
+                }
+                DatumAtom::Symbol(v) => {
+                    write!(code, "Symbol(::std::string::String::from({:?}))", v)
+                }
+                DatumAtom::Integer(v) => write!(code, "Integer({}i64)", v),
+                // exact, and works for infinities and NaN
+                DatumAtom::Float(v) => write!(code, "Float(f64::from_bits({}u64))", v.to_bits()),
+                DatumAtom::Boolean(v) => write!(code, "Boolean({})", v),
+                DatumAtom::Nil => write!(code, "Nil"),
+            };
+            code.push(')');
+        }
+        DatumValue::List(list) => {
+            code.push_str("::datum::DatumValue::List(::std::vec![");
+            for v in list {
+                write_value(code, v);
+                code.push(',');
+            }
+            code.push_str("])");
+        }
+    }
+}
+
+fn compile_error(message: &str) -> TokenStream {
+    format!("::core::compile_error!({:?})", message)
+        .parse()
+        .expect("compile_error! should always parse")
+}
@@ -0,0 +1,4 @@
+; Used by the doctest and the tests.
+level "Start"
+(spawn (x 1.5) (y -2))
+#t #nil #i+inf.0
@@ -0,0 +1,19 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::convert::TryFrom;
+
+use datum::DatumDocument;
+use datum_macros::include_datum;
+
+#[test]
+fn test_include_datum() {
+    let doc: DatumDocument = include_datum!("tests/example.scm");
+    let expected = DatumDocument::try_from(include_str!("example.scm")).unwrap();
+    assert_eq!(doc, expected);
+    assert_eq!(doc.len(), 6);
+}
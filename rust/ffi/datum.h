@@ -0,0 +1,66 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+/* C interface to datum-rs (the `ffi` feature). See the `ffi` module documentation. */
+
+#ifndef DATUM_H
+#define DATUM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define DATUM_FFI_EVENT_STRING 0
+#define DATUM_FFI_EVENT_SYMBOL 1
+#define DATUM_FFI_EVENT_SPECIAL_ID 2
+#define DATUM_FFI_EVENT_INTEGER 3
+#define DATUM_FFI_EVENT_FLOAT 4
+#define DATUM_FFI_EVENT_LIST_START 5
+#define DATUM_FFI_EVENT_LIST_END 6
+
+#define DATUM_FFI_ERROR_OUT_OF_ROOM 1
+#define DATUM_FFI_ERROR_INTERRUPTED 2
+#define DATUM_FFI_ERROR_BAD_DATA 3
+#define DATUM_FFI_ERROR_CANCELLED 4
+#define DATUM_FFI_ERROR_OTHER 5
+
+typedef struct datum_ffi_event {
+	uint8_t kind;
+	uint64_t offset;
+	/* UTF-8, not NUL-terminated */
+	const uint8_t * text;
+	size_t text_len;
+	int64_t integer;
+	double float_value;
+} datum_ffi_event_t;
+
+typedef struct datum_ffi_parser datum_ffi_parser_t;
+typedef struct datum_ffi_writer datum_ffi_writer_t;
+
+/* Return nonzero to stop parsing with DATUM_FFI_ERROR_CANCELLED. */
+typedef int32_t (*datum_ffi_callback_t)(void * user, const datum_ffi_event_t * event);
+
+datum_ffi_parser_t * datum_ffi_parser_new(void);
+void datum_ffi_parser_free(datum_ffi_parser_t * parser);
+int32_t datum_ffi_parser_feed(datum_ffi_parser_t * parser, const uint8_t * data, size_t len, int32_t eof, datum_ffi_callback_t callback, void * user);
+uint64_t datum_ffi_parser_error_offset(const datum_ffi_parser_t * parser);
+
+datum_ffi_writer_t * datum_ffi_writer_new(void);
+void datum_ffi_writer_free(datum_ffi_writer_t * writer);
+int32_t datum_ffi_writer_write(datum_ffi_writer_t * writer, const datum_ffi_event_t * event);
+int32_t datum_ffi_writer_newline(datum_ffi_writer_t * writer);
+const uint8_t * datum_ffi_writer_buffer(const datum_ffi_writer_t * writer, size_t * len);
+void datum_ffi_writer_clear(datum_ffi_writer_t * writer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -7,39 +7,69 @@
 
 // Generates an exhaustive list of cargo build commands to ensure all feature combos build.
 
+// Every combination of FLAGS is built.
+// Each of INTEGRATION_FLAGS is then built on top of every combination of FLAGS.
+// (These are independent of each other, and there are too many to build every combination.)
+
 static FLAGS: &'static [&'static str] = &[
     "std",
     "alloc",
     "detailed_errors",
     "serde",
     "_experimental",
-    "_serde_test_features"
+    "_serde_test_features",
+    "sha2"
+];
+
+static INTEGRATION_FLAGS: &'static [&'static str] = &[
+    "core_error",
+    "ffi",
+    "wasm",
+    "python",
+    "chrono",
+    "time",
+    "flate2",
+    "zstd",
+    "tokio_codec",
+    "notify",
+    "tracing"
 ];
 
+fn print_build(combo: u32, integration: Option<&str>) {
+    print!("cargo build -q --no-default-features");
+    let mut first_flag = true;
+    let flags = (0..FLAGS.len())
+        .filter(|j| (combo & (1 << j)) != 0)
+        .map(|j| FLAGS[j])
+        .chain(integration);
+    for flag in flags {
+        if !first_flag {
+            print!(",");
+        } else {
+            print!(" -F ");
+            first_flag = false;
+        }
+        print!("{}", flag);
+    }
+    println!();
+    println!("echo -n .");
+}
+
 fn main() {
     let max: u32 = 1 << FLAGS.len();
+    let total = max as usize * (1 + INTEGRATION_FLAGS.len());
     print!("echo -n \"[");
-    for _i in 0..max {
+    for _i in 0..total {
         print!(" ");
     }
     println!("]\\r[\"");
     for i in 0..max {
-        print!("cargo build -q --no-default-features");
-        let mut first_flag = true;
-        for j in 0..FLAGS.len() {
-            let flag: u32 = 1 << j;
-            if (i & flag) != 0 {
-                if !first_flag {
-                    print!(",");
-                } else {
-                    print!(" -F ");
-                    first_flag = false;
-                }
-                print!("{}", FLAGS[j]);
-            }
+        print_build(i, None);
+    }
+    for integration in INTEGRATION_FLAGS {
+        for i in 0..max {
+            print_build(i, Some(integration));
         }
-        println!();
-        println!("echo -n .");
     }
     println!("echo");
 }
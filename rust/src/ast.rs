@@ -31,11 +31,15 @@
 // Finally, there were a ton of cases which were like "if this specific internal array runs out of memory, bring the parser to an error state" - repeat for practically every line in the parser...
 // Sorry. - 20kdc
 
+use alloc::borrow::Cow;
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::Write;
 use core::fmt::{Debug, Display};
 use core::hash::Hash;
-use core::{convert::TryFrom, fmt::Write};
+use core::marker::PhantomData;
+use core::ops::Deref;
 
 use crate::{
     datum_error, unary, DatumAtom, DatumBoundedPipe, DatumMayContainAtom, DatumOffset, DatumPipe,
@@ -43,62 +47,551 @@ use crate::{
 };
 
 /// Datum AST node / value.
-#[derive(Clone, PartialEq, PartialOrd, Debug)]
+///
+/// [DatumValueOf] is the same with other buffer types and list storage; `DatumValueOf<String>` converts to and from this with [From].
+///
+/// Writing ([Display]), hashing, comparison and cloning use an explicit stack rather than recursion, so they're safe on anything the parser accepts, however deeply nested.
+/// [Debug] and dropping are still recursive; see [DatumValue::drop_iteratively] for the latter.
 pub enum DatumValue {
     Atom(DatumAtom<String>),
     List(Vec<DatumValue>),
 }
 
-impl Default for DatumValue {
-    // tests.rs does cover this, but it's not detected
-    #[cfg(not(tarpaulin_include))]
+/// Datum value with atoms using the buffer type `B`, i.e. [DatumCowValue] (owned or borrowed, node by node).
+///
+/// Any buffer type usable in a [DatumAtom] works, such as shared (`Arc<str>`), fixed-capacity, or interned strings.
+/// Such values can be parsed directly with [DatumParserOf], or converted with [DatumValue::map_atoms].
+///
+/// Lists are stored as chosen by `S`; see [DatumListStorage].
+///
+/// This has the same methods and behaviour as [DatumValue]. Code which only reads values is generic over [DatumValueNode], so it takes either.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use std::sync::Arc;
+/// use datum::{DatumDocument, DatumValueOf};
+/// let doc: DatumDocument = "(name \"example\") (tags a b)".parse().unwrap();
+/// let shared: Vec<DatumValueOf<Arc<str>>> = doc
+///     .iter()
+///     .map(|v| v.map_atoms(|atom| atom.as_borrowed().map_buffer(Arc::from)))
+///     .collect();
+/// assert_eq!(shared[1].to_string(), "(tags a b)");
+/// assert_eq!(shared[1].metrics(), doc[1].metrics());
+/// ```
+pub enum DatumValueOf<B: Deref<Target = str>, S: DatumListStorage<B> = DatumVecStorage> {
+    Atom(DatumAtom<B>),
+    List(S::List),
+}
+
+/// Read access to a value: [DatumValue] or any [DatumValueOf].
+///
+/// Code which reads values (writing, comparison, metrics, token iteration, and the encoders) is generic over this, so it takes either.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumAtom, DatumDocument, DatumNode, DatumValueNode};
+/// fn count_symbols<V: DatumValueNode>(value: &V) -> usize {
+///     match value.node() {
+///         DatumNode::Atom(DatumAtom::Symbol(_)) => 1,
+///         DatumNode::Atom(_) => 0,
+///         DatumNode::List(list) => list.iter().map(count_symbols).sum(),
+///     }
+/// }
+/// let doc: DatumDocument = "(a \"b\" (c 1))".parse().unwrap();
+/// assert_eq!(count_symbols(&doc[0]), 2);
+/// assert_eq!(count_symbols(&doc[0].to_cow()), 2);
+/// ```
+pub trait DatumValueNode: Sized {
+    /// Buffer type of the atoms.
+    type Buffer: Deref<Target = str>;
+    /// Returns the atom, or the elements of the list.
+    fn node(&self) -> DatumNode<'_, Self>;
+}
+
+/// What a [DatumValueNode] is.
+///
+/// _Added in 1.3.0._
+pub enum DatumNode<'a, V: DatumValueNode> {
+    Atom(&'a DatumAtom<V::Buffer>),
+    List(&'a [V]),
+}
+
+/// Values which can be built from atoms and [Vec]s, and taken apart again: [DatumValue] and [DatumValueOf] with [DatumVecStorage].
+pub(crate) trait DatumValueBuild: DatumValueNode {
+    fn from_atom(atom: DatumAtom<Self::Buffer>) -> Self;
+    fn from_list(list: Vec<Self>) -> Self;
+    fn into_node(self) -> Result<DatumAtom<Self::Buffer>, Vec<Self>>;
+}
+
+/// Chooses the container behind [DatumValueOf::List].
+///
+/// Writing, hashing, comparison, metrics and token iteration work with any storage.
+/// Parsing, [Clone] and the owning operations ([DatumValueOf::into_map_atoms], [DatumValueOf::drop_iteratively]) are [DatumVecStorage]-only; [DatumValueOf::map_atoms] copies any value into [DatumVecStorage].
+///
+/// Containers which store elements inline (i.e. `SmallVec`) can't work, as the value would contain itself.
+///
+/// This is deliberately narrower than the generic memory model removed before 1.0 (see the comment at the top of `ast.rs`): strings are just the `B` type parameter, and only the list container is chosen.
+/// Parsing and cloning stay [Vec]-only because a generic [Clone] is what overflowed the trait solver there.
+///
+/// _Added in 1.3.0._
+pub trait DatumListStorage<B: Deref<Target = str>>: Sized {
+    /// List container.
+    type List: Deref<Target = [DatumValueOf<B, Self>]>;
+}
+
+/// [Vec] lists; the default [DatumListStorage].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DatumVecStorage;
+
+impl<B: Deref<Target = str>> DatumListStorage<B> for DatumVecStorage {
+    type List = Vec<DatumValueOf<B, Self>>;
+}
+
+/// Borrowed slice lists, i.e. allocated from an arena. Such values are [Copy] if the buffer is.
+///
+/// Due to how the list type is worked out, slices given to [DatumValueOf::List] may need an explicit `as &[_]` to coerce.
+///
+/// (Without `alloc`, there's [crate::DatumValueRef] instead.)
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumAtom, DatumSliceStorage, DatumValueOf};
+/// type Arena<'a> = DatumValueOf<&'a str, DatumSliceStorage<'a>>;
+/// let inner = [Arena::Atom(DatumAtom::Symbol("b")), Arena::Atom(DatumAtom::Integer(1))];
+/// let outer = [Arena::Atom(DatumAtom::Symbol("a")), Arena::List(&inner)];
+/// let value = Arena::List(&outer as &[_]);
+/// assert_eq!(value.to_string(), "(a (b 1))");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DatumSliceStorage<'a>(PhantomData<&'a ()>);
+
+impl<'a, B: Deref<Target = str> + 'a> DatumListStorage<B> for DatumSliceStorage<'a> {
+    type List = &'a [DatumValueOf<B, Self>];
+}
+
+/// Value which may borrow its strings (from a [DatumValue], see [DatumValueOf::to_cow]) or own them.
+///
+/// Transformations can then replace just the atoms they change, without copying the rest of the tree.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use std::borrow::Cow;
+/// use datum::{DatumAtom, DatumCowValue, DatumDocument};
+/// let doc: DatumDocument = "(greeting \"hello\") (name \"world\")".parse().unwrap();
+/// let upper: Vec<DatumCowValue> = doc
+///     .iter()
+///     .map(|v| {
+///         v.map_atoms(|atom| match atom {
+///             DatumAtom::String(text) => DatumAtom::String(Cow::Owned(text.to_uppercase())),
+///             _ => atom.as_borrowed().map_buffer(Cow::Borrowed),
+///         })
+///     })
+///     .collect();
+/// assert_eq!(upper[1].to_string(), "(name \"WORLD\")");
+/// let expected: DatumDocument = "(name \"WORLD\")".parse().unwrap();
+/// assert_eq!(upper[1].clone().into_owned(), expected[0]);
+/// ```
+pub type DatumCowValue<'a> = DatumValueOf<Cow<'a, str>>;
+
+impl DatumValueNode for DatumValue {
+    type Buffer = String;
     #[inline]
-    fn default() -> Self {
-        Self::Atom(DatumAtom::Nil)
+    fn node(&self) -> DatumNode<'_, Self> {
+        match self {
+            Self::Atom(atm) => DatumNode::Atom(atm),
+            Self::List(list) => DatumNode::List(list),
+        }
     }
 }
 
-impl Hash for DatumValue {
-    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+impl DatumValueBuild for DatumValue {
+    fn from_atom(atom: DatumAtom<String>) -> Self {
+        Self::Atom(atom)
+    }
+    fn from_list(list: Vec<Self>) -> Self {
+        Self::List(list)
+    }
+    fn into_node(self) -> Result<DatumAtom<String>, Vec<Self>> {
         match self {
-            Self::Atom(atm) => {
+            Self::Atom(atm) => Ok(atm),
+            Self::List(list) => Err(list),
+        }
+    }
+}
+
+impl<B: Deref<Target = str>, S: DatumListStorage<B>> DatumValueNode for DatumValueOf<B, S> {
+    type Buffer = B;
+    #[inline]
+    fn node(&self) -> DatumNode<'_, Self> {
+        match self {
+            Self::Atom(atm) => DatumNode::Atom(atm),
+            Self::List(list) => DatumNode::List(list),
+        }
+    }
+}
+
+impl<B: Deref<Target = str>> DatumValueBuild for DatumValueOf<B> {
+    fn from_atom(atom: DatumAtom<B>) -> Self {
+        Self::Atom(atom)
+    }
+    fn from_list(list: Vec<Self>) -> Self {
+        Self::List(list)
+    }
+    fn into_node(self) -> Result<DatumAtom<B>, Vec<Self>> {
+        match self {
+            Self::Atom(atm) => Ok(atm),
+            Self::List(list) => Err(list),
+        }
+    }
+}
+
+// -- Shared implementations --
+
+// Derived, this would require `S::List: Debug`, which overflows as described at the top of this file.
+fn debug_node<V: DatumValueNode + Debug>(
+    value: &V,
+    f: &mut core::fmt::Formatter<'_>,
+) -> core::fmt::Result
+where
+    V::Buffer: Debug,
+{
+    match value.node() {
+        DatumNode::Atom(atm) => f.debug_tuple("Atom").field(atm).finish(),
+        DatumNode::List(list) => f.debug_tuple("List").field(&list).finish(),
+    }
+}
+
+fn hash_node<V: DatumValueNode, H: core::hash::Hasher>(value: &V, state: &mut H) {
+    if let DatumNode::Atom(atm) = value.node() {
+        atm.hash(state);
+        return;
+    }
+    // This produces the same stream as the obvious recursive implementation.
+    let mut stack = alloc::vec![core::slice::from_ref(value).iter()];
+    while let Some(top) = stack.last_mut() {
+        match top.next().map(V::node) {
+            None => {
+                stack.pop();
+            }
+            Some(DatumNode::Atom(atm)) => {
                 atm.hash(state);
             }
-            Self::List(vec) => {
+            Some(DatumNode::List(vec)) => {
                 // **Notice: The 'type ID namespace' is shared with DatumAtom.**
                 state.write_u8(6);
-                vec.hash(state);
+                state.write_usize(vec.len());
+                stack.push(vec.iter());
             }
         }
     }
 }
 
-impl DatumValue {
-    /// Writes a value from AST.
-    pub fn write_to(&self, f: &mut dyn Write, writer: &mut DatumWriter) -> core::fmt::Result {
-        match self {
-            DatumValue::Atom(v) => writer.write_atom(f, v),
-            DatumValue::List(v) => {
-                let ls: DatumToken<&str> = DatumToken::ListStart(0);
-                let le: DatumToken<&str> = DatumToken::ListEnd(0);
+fn eq_node<V: DatumValueNode>(a: &V, b: &V) -> bool
+where
+    V::Buffer: PartialEq,
+{
+    if let (DatumNode::Atom(a), DatumNode::Atom(b)) = (a.node(), b.node()) {
+        return a == b;
+    }
+    let mut stack = alloc::vec![(
+        core::slice::from_ref(a).iter(),
+        core::slice::from_ref(b).iter()
+    )];
+    while let Some((a, b)) = stack.last_mut() {
+        match (a.next().map(V::node), b.next().map(V::node)) {
+            (None, None) => {
+                stack.pop();
+            }
+            (Some(DatumNode::Atom(a)), Some(DatumNode::Atom(b))) => {
+                if a != b {
+                    return false;
+                }
+            }
+            (Some(DatumNode::List(a)), Some(DatumNode::List(b))) => {
+                if a.len() != b.len() {
+                    return false;
+                }
+                stack.push((a.iter(), b.iter()));
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Same ordering as derived: atoms before lists, lists lexicographically.
+fn cmp_node<V: DatumValueNode>(a: &V, b: &V) -> Option<Ordering>
+where
+    V::Buffer: PartialOrd,
+{
+    if let (DatumNode::Atom(a), DatumNode::Atom(b)) = (a.node(), b.node()) {
+        return a.partial_cmp(b);
+    }
+    let mut stack = alloc::vec![(
+        core::slice::from_ref(a).iter(),
+        core::slice::from_ref(b).iter()
+    )];
+    while let Some((a, b)) = stack.last_mut() {
+        match (a.next().map(V::node), b.next().map(V::node)) {
+            (None, None) => {
+                stack.pop();
+            }
+            (None, Some(_)) => return Some(Ordering::Less),
+            (Some(_), None) => return Some(Ordering::Greater),
+            (Some(DatumNode::Atom(a)), Some(DatumNode::Atom(b))) => match a.partial_cmp(b) {
+                Some(Ordering::Equal) => {}
+                res => return res,
+            },
+            (Some(DatumNode::Atom(_)), Some(DatumNode::List(_))) => return Some(Ordering::Less),
+            (Some(DatumNode::List(_)), Some(DatumNode::Atom(_))) => return Some(Ordering::Greater),
+            (Some(DatumNode::List(a)), Some(DatumNode::List(b))) => {
+                stack.push((a.iter(), b.iter()));
+            }
+        }
+    }
+    Some(Ordering::Equal)
+}
+
+pub(crate) fn write_node<V: DatumValueNode>(
+    value: &V,
+    f: &mut dyn Write,
+    writer: &mut DatumWriter,
+) -> core::fmt::Result {
+    let list = match value.node() {
+        DatumNode::Atom(v) => return writer.write_atom(f, v),
+        DatumNode::List(list) => list,
+    };
+    let ls: DatumToken<&str> = DatumToken::ListStart(0);
+    let le: DatumToken<&str> = DatumToken::ListEnd(0);
+    writer.write_token(f, &ls)?;
+    let mut stack = alloc::vec![list.iter()];
+    while let Some(top) = stack.last_mut() {
+        match top.next().map(V::node) {
+            None => {
+                stack.pop();
+                writer.write_token(f, &le)?;
+            }
+            Some(DatumNode::Atom(v)) => writer.write_atom(f, v)?,
+            Some(DatumNode::List(list)) => {
                 writer.write_token(f, &ls)?;
-                for e in v {
-                    e.write_to(f, writer)?;
+                stack.push(list.iter());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn map_node<
+    'a,
+    V: DatumValueNode,
+    W: DatumValueBuild,
+    F: FnMut(&'a DatumAtom<V::Buffer>) -> DatumAtom<W::Buffer>,
+>(
+    value: &'a V,
+    mut f: F,
+) -> W {
+    let list = match value.node() {
+        DatumNode::Atom(atm) => return W::from_atom(f(atm)),
+        DatumNode::List(list) => list,
+    };
+    // each entry is a list being copied, and the copy so far
+    let mut stack = alloc::vec![(list.iter(), Vec::with_capacity(list.len()))];
+    loop {
+        // the stack is never empty here
+        let top = stack.last_mut().unwrap();
+        match top.0.next().map(V::node) {
+            Some(DatumNode::Atom(atm)) => top.1.push(W::from_atom(f(atm))),
+            Some(DatumNode::List(list)) => {
+                stack.push((list.iter(), Vec::with_capacity(list.len())))
+            }
+            None => {
+                let done = W::from_list(stack.pop().unwrap().1);
+                match stack.last_mut() {
+                    Some(parent) => parent.1.push(done),
+                    None => return done,
                 }
-                writer.write_token(f, &le)
             }
         }
     }
+}
 
-    /// If this value is a list, returns a reference to it, otherwise [None].
-    pub fn as_list(&self) -> Option<&Vec<DatumValue>> {
-        match self {
-            DatumValue::List(list) => Some(list),
-            _ => None,
+fn into_map_node<
+    V: DatumValueBuild,
+    W: DatumValueBuild,
+    F: FnMut(DatumAtom<V::Buffer>) -> DatumAtom<W::Buffer>,
+>(
+    value: V,
+    mut f: F,
+) -> W {
+    let list = match value.into_node() {
+        Ok(atm) => return W::from_atom(f(atm)),
+        Err(list) => list,
+    };
+    let len = list.len();
+    let mut stack = alloc::vec![(list.into_iter(), Vec::with_capacity(len))];
+    loop {
+        let top = stack.last_mut().unwrap();
+        match top.0.next().map(V::into_node) {
+            Some(Ok(atm)) => top.1.push(W::from_atom(f(atm))),
+            Some(Err(list)) => {
+                let len = list.len();
+                stack.push((list.into_iter(), Vec::with_capacity(len)))
+            }
+            None => {
+                let done = W::from_list(stack.pop().unwrap().1);
+                match stack.last_mut() {
+                    Some(parent) => parent.1.push(done),
+                    None => return done,
+                }
+            }
+        }
+    }
+}
+
+fn drop_node<V: DatumValueBuild>(value: V) {
+    let mut pending = alloc::vec![value];
+    while let Some(value) = pending.pop() {
+        if let Err(list) = value.into_node() {
+            // the list is emptied here, so dropping it doesn't recurse
+            pending.extend(list);
+        }
+    }
+}
+
+/// Methods for reading values, on both [DatumValue] and [DatumValueOf]; `$b` is the buffer type.
+macro_rules! value_methods {
+    ($b:ty) => {
+        /// Writes a value from AST.
+        pub fn write_to(&self, f: &mut dyn Write, writer: &mut DatumWriter) -> core::fmt::Result {
+            write_node(self, f, writer)
+        }
+
+        /// Copies this value, converting each atom with `f` (i.e. to change the buffer type), without recursing.
+        ///
+        /// _Added in 1.3.0._
+        pub fn map_atoms<
+            'a,
+            C: Deref<Target = str>,
+            F: FnMut(&'a DatumAtom<$b>) -> DatumAtom<C>,
+        >(
+            &'a self,
+            f: F,
+        ) -> DatumValueOf<C> {
+            map_node(self, f)
+        }
+
+        /// Borrows this value as a [DatumCowValue].
+        ///
+        /// _Added in 1.3.0._
+        pub fn to_cow(&self) -> DatumCowValue<'_> {
+            self.map_atoms(|atm| atm.as_borrowed().map_buffer(Cow::Borrowed))
+        }
+
+        /// Returns an iterator over the tokens that make up this value.
+        /// See [DatumValueTokens].
+        ///
+        /// _Added in 1.3.0._
+        pub fn tokens(&self) -> DatumValueTokens<'_, Self> {
+            DatumValueTokens::new(core::slice::from_ref(self))
         }
+
+        /// Measures this value; see [DatumValueMetrics].
+        ///
+        /// _Added in 1.3.0._
+        /// ```
+        /// use datum::{DatumDocument, DatumValueMetrics};
+        /// let doc: DatumDocument = "(a (\"bc\" 1) ())".parse().unwrap();
+        /// assert_eq!(doc[0].metrics(), DatumValueMetrics {
+        ///     depth: 2,
+        ///     nodes: 6,
+        ///     string_bytes: 2,
+        /// });
+        /// ```
+        pub fn metrics(&self) -> DatumValueMetrics {
+            DatumValueMetrics::measure(core::slice::from_ref(self))
+        }
+    };
+}
+
+/// Methods for values with [Vec] lists, on both [DatumValue] and [DatumValueOf]; `$b` is the buffer type.
+macro_rules! owned_value_methods {
+    ($b:ty) => {
+        /// Drops this value without recursing, unlike the regular drop.
+        /// Use this to dispose of values which may be very deeply nested (i.e. from untrusted input).
+        ///
+        /// _Added in 1.3.0._
+        pub fn drop_iteratively(self) {
+            drop_node(self)
+        }
+
+        /// If this value is a list, returns a reference to it, otherwise [None].
+        pub fn as_list(&self) -> Option<&Vec<Self>> {
+            match self {
+                Self::List(list) => Some(list),
+                _ => None,
+            }
+        }
+
+        /// Like [DatumValueOf::map_atoms], but consumes this value, so buffers can be moved rather than copied.
+        ///
+        /// _Added in 1.3.0._
+        pub fn into_map_atoms<C: Deref<Target = str>, F: FnMut(DatumAtom<$b>) -> DatumAtom<C>>(
+            self,
+            f: F,
+        ) -> DatumValueOf<C> {
+            into_map_node(self, f)
+        }
+    };
+}
+
+// -- DatumValue --
+
+impl Debug for DatumValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        debug_node(self, f)
+    }
+}
+
+impl Default for DatumValue {
+    // tests.rs does cover this, but it's not detected
+    #[cfg(not(tarpaulin_include))]
+    #[inline]
+    fn default() -> Self {
+        Self::Atom(DatumAtom::Nil)
+    }
+}
+
+impl Hash for DatumValue {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        hash_node(self, state)
     }
 }
 
+impl Clone for DatumValue {
+    fn clone(&self) -> Self {
+        map_node(self, |atm| atm.clone())
+    }
+}
+
+impl PartialEq for DatumValue {
+    fn eq(&self, other: &Self) -> bool {
+        eq_node(self, other)
+    }
+}
+
+impl PartialOrd for DatumValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        cmp_node(self, other)
+    }
+}
+
+impl DatumValue {
+    value_methods!(String);
+    owned_value_methods!(String);
+}
+
 impl Display for DatumValue {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.write_to(f, &mut DatumWriter::default())
@@ -115,13 +608,232 @@ impl DatumMayContainAtom<String> for DatumValue {
     }
 }
 
+impl From<DatumValueOf<String>> for DatumValue {
+    /// Converts without recursing, moving the strings.
+    fn from(value: DatumValueOf<String>) -> Self {
+        into_map_node(value, |atm| atm)
+    }
+}
+
+impl From<DatumValue> for DatumValueOf<String> {
+    /// Converts without recursing, moving the strings.
+    fn from(value: DatumValue) -> Self {
+        into_map_node(value, |atm| atm)
+    }
+}
+
+// -- DatumValueOf --
+
+impl<B: Debug + Deref<Target = str>, S: DatumListStorage<B>> Debug for DatumValueOf<B, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        debug_node(self, f)
+    }
+}
+
+impl<B: Deref<Target = str>, S: DatumListStorage<B>> Default for DatumValueOf<B, S> {
+    #[inline]
+    fn default() -> Self {
+        Self::Atom(DatumAtom::Nil)
+    }
+}
+
+impl<B: Deref<Target = str>, S: DatumListStorage<B>> Hash for DatumValueOf<B, S> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        hash_node(self, state)
+    }
+}
+
+impl<B: Clone + Deref<Target = str>> Clone for DatumValueOf<B> {
+    fn clone(&self) -> Self {
+        map_node(self, |atm| atm.clone())
+    }
+}
+
+impl<'a, B: Copy + Deref<Target = str> + 'a> Clone for DatumValueOf<B, DatumSliceStorage<'a>> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, B: Copy + Deref<Target = str> + 'a> Copy for DatumValueOf<B, DatumSliceStorage<'a>> {}
+
+impl<B: PartialEq + Deref<Target = str>, S: DatumListStorage<B>> PartialEq for DatumValueOf<B, S> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_node(self, other)
+    }
+}
+
+impl<B: PartialOrd + Deref<Target = str>, S: DatumListStorage<B>> PartialOrd
+    for DatumValueOf<B, S>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        cmp_node(self, other)
+    }
+}
+
+impl<B: Deref<Target = str>, S: DatumListStorage<B>> DatumValueOf<B, S> {
+    value_methods!(B);
+}
+
+impl<B: Deref<Target = str>> DatumValueOf<B> {
+    owned_value_methods!(B);
+}
+
+impl DatumCowValue<'_> {
+    /// Converts to a [DatumValue], copying only the strings that are still borrowed.
+    ///
+    /// _Added in 1.3.0._
+    pub fn into_owned(self) -> DatumValue {
+        into_map_node(self, |atm| atm.map_buffer(Cow::into_owned))
+    }
+}
+
+impl<B: Deref<Target = str>, S: DatumListStorage<B>> Display for DatumValueOf<B, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.write_to(f, &mut DatumWriter::default())
+    }
+}
+
+impl<B: Deref<Target = str>, S: DatumListStorage<B>> DatumMayContainAtom<B> for DatumValueOf<B, S> {
+    fn as_atom(&self) -> Option<&DatumAtom<B>> {
+        if let Self::Atom(a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+}
+
+/// Size and complexity of a value (or sequence of values), from [DatumValue::metrics] or [crate::DatumDocument::metrics].
+///
+/// This is for enforcing quotas after parsing and for reporting document statistics.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DatumValueMetrics {
+    /// Deepest list nesting. Atoms are at depth 0, and the contents of a top-level list at depth 1.
+    pub depth: usize,
+    /// Amount of values (atoms and lists, including the value itself).
+    pub nodes: usize,
+    /// Total length of string atoms' contents, in bytes.
+    pub string_bytes: usize,
+}
+
+impl DatumValueMetrics {
+    /// Measures a sequence of values, without recursing.
+    pub fn measure<V: DatumValueNode>(values: &[V]) -> Self {
+        let mut metrics = Self::default();
+        let mut stack = alloc::vec![values.iter()];
+        while let Some(top) = stack.last_mut() {
+            match top.next() {
+                None => {
+                    stack.pop();
+                }
+                Some(value) => {
+                    metrics.nodes += 1;
+                    match value.node() {
+                        DatumNode::Atom(DatumAtom::String(text)) => {
+                            metrics.string_bytes += text.len()
+                        }
+                        DatumNode::Atom(_) => {}
+                        DatumNode::List(list) => {
+                            // the outermost iterator is the sequence itself, not a list
+                            metrics.depth = metrics.depth.max(stack.len());
+                            stack.push(list.iter());
+                        }
+                    }
+                }
+            }
+        }
+        metrics
+    }
+}
+
+/// Iterator over the tokens making up a sequence of values.
+///
+/// This allows re-using token-based consumers (i.e. the Serde deserializers) on already-parsed values.
+///
+/// The offsets of the tokens are their indices in the token sequence.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumDocument, DatumValueTokens};
+/// let doc: DatumDocument = "(a (#t)) 1".parse().unwrap();
+/// let text: Vec<String> = DatumValueTokens::new(&doc).map(|v| v.to_string()).collect();
+/// assert_eq!(text, ["(", "a", "(", "#t", ")", ")", "1"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DatumValueTokens<'a, V: DatumValueNode = DatumValue> {
+    stack: Vec<core::slice::Iter<'a, V>>,
+    offset: DatumOffset,
+}
+
+impl<'a, V: DatumValueNode> DatumValueTokens<'a, V> {
+    /// Creates an iterator over the tokens of the given values.
+    pub fn new(values: &'a [V]) -> Self {
+        Self {
+            stack: alloc::vec![values.iter()],
+            offset: 0,
+        }
+    }
+}
+
+impl<'a, V: DatumValueNode> Iterator for DatumValueTokens<'a, V> {
+    type Item = DatumToken<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let at = self.offset;
+        let top = self.stack.last_mut()?;
+        let res = match top.next().map(V::node) {
+            None => {
+                self.stack.pop();
+                if self.stack.is_empty() {
+                    return None;
+                }
+                DatumToken::ListEnd(at)
+            }
+            Some(DatumNode::Atom(atom)) => atom.to_token(at),
+            Some(DatumNode::List(list)) => {
+                self.stack.push(list.iter());
+                DatumToken::ListStart(at)
+            }
+        };
+        self.offset += 1;
+        Some(res)
+    }
+}
+
 /// Datum parser (from tokens into values).
+///
+/// List contents are gathered in a single scratch buffer that's reused between values.
+/// Each finished list is then allocated exactly once, at exactly the right size.
+/// (Small lists can't be stored inline in [DatumValue], as the type would contain itself.)
+///
+/// [DatumParserOf] is the same for other buffer types.
+#[derive(Clone, Debug, Default)]
+pub struct DatumParser(ParserState<DatumValue>);
+
+/// Datum parser (from tokens into values) for any buffer type, giving [DatumValueOf]s; see [DatumParser].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Debug, Default)]
+pub struct DatumParserOf<B: Deref<Target = str>>(ParserState<DatumValueOf<B>>);
+
+/// State shared by [DatumParser] and [DatumParserOf].
 #[derive(Clone, Debug, Default)]
-pub struct DatumParser {
+struct ParserState<V> {
     start: DatumOffset,
-    stack: Vec<Vec<DatumValue>>,
+    /// Elements of all currently open lists, innermost last.
+    scratch: Vec<V>,
+    /// Where each currently open list starts in `scratch`.
+    list_starts: Vec<usize>,
+    strict: bool,
+    max_list_len: Option<usize>,
+    memory_budget: Option<usize>,
+    memory_used: usize,
 }
 
+// Every token completes at most one value; lists are only output when the outermost list ends.
 impl DatumBoundedPipe for DatumParser {
     type OutputQueueSize = unary::C1;
 }
@@ -135,52 +847,180 @@ impl DatumPipe for DatumParser {
         at: DatumOffset,
         token: Option<Self::Input>,
         f: &mut F,
+    ) -> DatumResult<()> {
+        self.0.feed(at, token, f)
+    }
+}
+
+impl<B: Default + Deref<Target = str>> DatumBoundedPipe for DatumParserOf<B> {
+    type OutputQueueSize = unary::C1;
+}
+
+impl<B: Default + Deref<Target = str>> DatumPipe for DatumParserOf<B> {
+    type Input = DatumToken<B>;
+    type Output = DatumValueOf<B>;
+
+    fn feed<F: FnMut(DatumOffset, DatumValueOf<B>) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        token: Option<Self::Input>,
+        f: &mut F,
+    ) -> DatumResult<()> {
+        self.0.feed(at, token, f)
+    }
+}
+
+/// Parser settings, on both [DatumParser] and [DatumParserOf].
+macro_rules! parser_methods {
+    () => {
+        /// Sets if the parser is strict about special ID spelling (by default, it isn't); see [DatumAtom::from_token].
+        ///
+        /// _Added in 1.3.0._
+        /// ```
+        /// use datum::{DatumParser, DatumPipe, datum_char_to_token_pipeline};
+        /// let mut pipeline = datum_char_to_token_pipeline().compose(DatumParser::default().strict(true));
+        /// assert!(pipeline.feed_all_to_vec("#t #i+inf.0".chars(), true).is_ok());
+        /// let mut pipeline = datum_char_to_token_pipeline().compose(DatumParser::default().strict(true));
+        /// assert!(pipeline.feed_all_to_vec("#T".chars(), true).is_err());
+        /// ```
+        pub fn strict(mut self, strict: bool) -> Self {
+            self.0.strict = strict;
+            self
+        }
+
+        /// Sets the most elements a single list may have (by default, there's no limit).
+        /// Longer lists are [crate::DatumErrorKind::BadData] errors, reported at the element that went over the limit.
+        ///
+        /// This is meant for untrusted input, so it can't make one list grow without bound.
+        ///
+        /// _Added in 1.3.0._
+        /// ```
+        /// use datum::{DatumParser, DatumPipe, datum_char_to_token_pipeline};
+        /// let mut pipeline = datum_char_to_token_pipeline().compose(DatumParser::default().max_list_len(Some(2)));
+        /// assert!(pipeline.feed_all_to_vec("(a b) c d e".chars(), true).is_ok());
+        /// let mut pipeline = datum_char_to_token_pipeline().compose(DatumParser::default().max_list_len(Some(2)));
+        /// assert!(pipeline.feed_all_to_vec("(a (b c d))".chars(), true).is_err());
+        /// ```
+        pub fn max_list_len(mut self, max_list_len: Option<usize>) -> Self {
+            self.0.max_list_len = max_list_len;
+            self
+        }
+
+        /// Sets a limit on the memory all values produced by this parser may use, in bytes (by default, there's no limit).
+        /// Going over the limit is a [crate::DatumErrorKind::OutOfRoom] error.
+        ///
+        /// Each value counts as the size of the value type (its slot in a list, or being returned), plus the length of its text for strings and symbols.
+        /// This is an estimate of the real usage (it doesn't count allocator overhead or temporary buffers), but it's deterministic and proportional to the input.
+        ///
+        /// The usage accumulates over everything parsed, so use a new parser (or [DatumParser::reset_memory_used]) per request.
+        ///
+        /// _Added in 1.3.0._
+        /// ```
+        /// use datum::{DatumErrorKind, DatumParser, DatumPipe, datum_char_to_token_pipeline};
+        /// let mut pipeline = datum_char_to_token_pipeline().compose(DatumParser::default().memory_budget(Some(1024)));
+        /// assert!(pipeline.feed_all_to_vec("(a b c)".chars(), true).is_ok());
+        /// let mut pipeline = datum_char_to_token_pipeline().compose(DatumParser::default().memory_budget(Some(1024)));
+        /// let big = format!("\"{}\"", "x".repeat(2000));
+        /// let err = pipeline.feed_all_to_vec(big.chars(), true).unwrap_err();
+        /// assert_eq!(err.kind, DatumErrorKind::OutOfRoom);
+        /// ```
+        pub fn memory_budget(mut self, memory_budget: Option<usize>) -> Self {
+            self.0.memory_budget = memory_budget;
+            self
+        }
+
+        /// Memory used so far, as counted for [DatumParser::memory_budget].
+        ///
+        /// _Added in 1.3.0._
+        pub fn memory_used(&self) -> usize {
+            self.0.memory_used
+        }
+
+        /// Resets the count of memory used, i.e. to reuse the parser for another request.
+        ///
+        /// _Added in 1.3.0._
+        pub fn reset_memory_used(&mut self) {
+            self.0.memory_used = 0;
+        }
+    };
+}
+
+impl DatumParser {
+    parser_methods!();
+}
+
+impl<B: Deref<Target = str>> DatumParserOf<B> {
+    parser_methods!();
+}
+
+impl<V: DatumValueBuild> ParserState<V>
+where
+    V::Buffer: Default,
+{
+    fn feed<F: FnMut(DatumOffset, V) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        token: Option<DatumToken<V::Buffer>>,
+        f: &mut F,
     ) -> DatumResult<()> {
         if token.is_none() {
-            return if !self.stack.is_empty() {
+            return if !self.list_starts.is_empty() {
                 Err(datum_error!(Interrupted, at, "eof inside list"))
             } else {
                 Ok(())
             };
         }
         let token = token.unwrap();
-        if self.stack.is_empty() {
+        if self.list_starts.is_empty() {
             // outermost value started here
             self.start = at;
         }
         match token.token_type() {
             DatumTokenType::ListStart => {
-                let list = Vec::new();
-                self.stack.push(list);
+                self.list_starts.push(self.scratch.len());
                 Ok(())
             }
             DatumTokenType::ListEnd => {
-                let res = self.stack.pop();
-                if let Some(v) = res {
-                    self.feed_value(DatumValue::List(v), f)
+                let res = self.list_starts.pop();
+                if let Some(list_start) = res {
+                    let mut list = Vec::with_capacity(self.scratch.len() - list_start);
+                    list.extend(self.scratch.drain(list_start..));
+                    self.feed_value(at, V::from_list(list), f)
                 } else {
                     Err(datum_error!(BadData, at, "end of list while not in list"))
                 }
             }
-            _ => match DatumAtom::try_from(token) {
+            _ => match DatumAtom::from_token(token, self.strict) {
                 Err(e) => Err(e),
-                Ok(v) => self.feed_value(DatumValue::Atom(v), f),
+                Ok(v) => self.feed_value(at, V::from_atom(v), f),
             },
         }
     }
-}
 
-impl DatumParser {
-    fn feed_value<F: FnMut(DatumOffset, DatumValue) -> DatumResult<()>>(
+    fn feed_value<F: FnMut(DatumOffset, V) -> DatumResult<()>>(
         &mut self,
-        v: DatumValue,
+        at: DatumOffset,
+        v: V,
         f: &mut F,
     ) -> DatumResult<()> {
-        match self.stack.pop() {
+        let cost = core::mem::size_of::<V>()
+            + match v.node() {
+                DatumNode::Atom(DatumAtom::String(text))
+                | DatumNode::Atom(DatumAtom::Symbol(text)) => text.len(),
+                _ => 0,
+            };
+        self.memory_used = self.memory_used.saturating_add(cost);
+        if matches!(self.memory_budget, Some(budget) if self.memory_used > budget) {
+            return Err(datum_error!(OutOfRoom, at, "memory budget exceeded"));
+        }
+        match self.list_starts.last() {
             None => f(self.start, v),
-            Some(mut list) => {
-                list.push(v);
-                self.stack.push(list);
+            Some(list_start) => {
+                if matches!(self.max_list_len, Some(max) if self.scratch.len() - list_start >= max)
+                {
+                    return Err(datum_error!(BadData, at, "list too long"));
+                }
+                self.scratch.push(v);
                 Ok(())
             }
         }
@@ -12,7 +12,7 @@ use core::{
     ops::Deref,
 };
 
-use crate::{datum_error, DatumError, DatumResult, DatumToken};
+use crate::{datum_error, DatumError, DatumOffset, DatumResult, DatumToken};
 
 /// Atomic Datum AST value.
 /// This enum also contains the functions that convert between tokens and atoms.
@@ -82,7 +82,107 @@ impl<B: Default + Deref<Target = str>> TryFrom<DatumToken<B>> for DatumAtom<B> {
     }
 }
 
+/// If a special ID is spelled as [crate::DatumWriter] would write it.
+pub(crate) fn special_id_is_canonical(id: &str) -> bool {
+    matches!(
+        id,
+        "t" | "f" | "nil" | "{}#" | "i+nan.0" | "i+inf.0" | "i-inf.0"
+    ) || id.starts_with('x')
+}
+
+impl<B: Default + Deref<Target = str>> DatumAtom<B> {
+    /// Tries to convert from a DatumToken, like [DatumAtom::try_from], but optionally strictly.
+    ///
+    /// In strict mode, special IDs are only accepted in their canonical (lowercase) spelling, as written by [crate::DatumWriter].
+    /// (The case of hex digits in `#x` integers isn't considered.)
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumAtom, DatumToken};
+    /// assert!(DatumAtom::from_token(DatumToken::SpecialID(0, "NIL"), false).is_ok());
+    /// assert!(DatumAtom::from_token(DatumToken::SpecialID(0, "NIL"), true).is_err());
+    /// assert!(DatumAtom::from_token(DatumToken::SpecialID(0, "xFF"), true).is_ok());
+    /// ```
+    pub fn from_token(token: DatumToken<B>, strict: bool) -> DatumResult<DatumAtom<B>> {
+        let non_canonical_at = match &token {
+            DatumToken::SpecialID(at, b) if strict => {
+                if special_id_is_canonical(b) {
+                    None
+                } else {
+                    Some(*at)
+                }
+            }
+            _ => None,
+        };
+        let atom = DatumAtom::try_from(token)?;
+        if let Some(at) = non_canonical_at {
+            Err(datum_error!(
+                BadData,
+                at,
+                "strict: non-canonical special ID"
+            ))
+        } else {
+            Ok(atom)
+        }
+    }
+}
+
 impl<B: Deref<Target = str>> DatumAtom<B> {
+    /// Converts this atom back into a token (borrowing the buffer), with the given offset.
+    /// Atoms which don't have their own token type become [DatumToken::SpecialID]s.
+    /// Converting the result back with [DatumAtom::try_from] gives an equal atom.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumAtom, DatumToken};
+    /// let atom: DatumAtom<&str> = DatumAtom::Boolean(true);
+    /// assert_eq!(atom.to_token(0), DatumToken::SpecialID(0, "t"));
+    /// ```
+    pub fn to_token(&self, at: DatumOffset) -> DatumToken<&str> {
+        match self {
+            DatumAtom::String(v) => DatumToken::String(at, v),
+            DatumAtom::Symbol(v) => DatumToken::Symbol(at, v),
+            DatumAtom::Integer(v) => DatumToken::Integer(at, *v),
+            DatumAtom::Float(v) => DatumToken::Float(at, *v),
+            DatumAtom::Boolean(true) => DatumToken::SpecialID(at, "t"),
+            DatumAtom::Boolean(false) => DatumToken::SpecialID(at, "f"),
+            DatumAtom::Nil => DatumToken::SpecialID(at, "nil"),
+        }
+    }
+
+    /// Borrows the buffer, if any.
+    ///
+    /// _Added in 1.3.0._
+    pub fn as_borrowed(&self) -> DatumAtom<&str> {
+        match self {
+            DatumAtom::String(v) => DatumAtom::String(v),
+            DatumAtom::Symbol(v) => DatumAtom::Symbol(v),
+            DatumAtom::Integer(v) => DatumAtom::Integer(*v),
+            DatumAtom::Float(v) => DatumAtom::Float(*v),
+            DatumAtom::Boolean(v) => DatumAtom::Boolean(*v),
+            DatumAtom::Nil => DatumAtom::Nil,
+        }
+    }
+
+    /// Converts the buffer, if any, with `f`.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::DatumAtom;
+    /// let atom: DatumAtom<&str> = DatumAtom::Symbol("name");
+    /// assert_eq!(atom.map_buffer(String::from), DatumAtom::Symbol(String::from("name")));
+    /// ```
+    pub fn map_buffer<C: Deref<Target = str>, F: FnOnce(B) -> C>(self, f: F) -> DatumAtom<C> {
+        match self {
+            DatumAtom::String(v) => DatumAtom::String(f(v)),
+            DatumAtom::Symbol(v) => DatumAtom::Symbol(f(v)),
+            DatumAtom::Integer(v) => DatumAtom::Integer(v),
+            DatumAtom::Float(v) => DatumAtom::Float(v),
+            DatumAtom::Boolean(v) => DatumAtom::Boolean(v),
+            DatumAtom::Nil => DatumAtom::Nil,
+        }
+    }
+
     /// Writes a value from the atom.
     pub fn write(&self, f: &mut dyn Write) -> core::fmt::Result {
         match &self {
@@ -0,0 +1,305 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+// The binary encoding is a subset of CBOR (RFC 8949), so general CBOR tools can inspect it:
+// * the self-described CBOR tag (55799), wrapping an array of the top-level values
+// * lists are definite-length arrays
+// * strings are text strings; symbols are text strings under tag 39 ("identifier")
+// * integers are major types 0 and 1, floats are always 64-bit, and `#t`/`#f`/`#nil` are true/false/null
+// The encoder always uses the shortest heads; the decoder accepts any head width.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use crate::{
+    datum_error, DatumAtom, DatumDocument, DatumNode, DatumOffset, DatumResult, DatumValue,
+    DatumValueNode,
+};
+
+const MAGIC: [u8; 3] = [0xD9, 0xD9, 0xF7];
+const TAG_SYMBOL: u64 = 39;
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+const SIMPLE_FALSE: u8 = 20;
+const SIMPLE_TRUE: u8 = 21;
+const SIMPLE_NULL: u8 = 22;
+const SIMPLE_F32: u8 = 26;
+const SIMPLE_F64: u8 = 27;
+
+/// Most elements reserved for a list before any have been read.
+const PREALLOCATE_MAX: u64 = 16;
+
+fn write_head(out: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    if arg < 24 {
+        out.push(major | arg as u8);
+    } else if arg <= u8::MAX as u64 {
+        out.push(major | 24);
+        out.push(arg as u8);
+    } else if arg <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(arg as u16).to_be_bytes());
+    } else if arg <= u32::MAX as u64 {
+        out.push(major | 26);
+        out.extend_from_slice(&(arg as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+fn write_atom<B: Deref<Target = str>>(out: &mut Vec<u8>, atom: &DatumAtom<B>) {
+    match atom {
+        DatumAtom::String(text) => {
+            write_head(out, MAJOR_TEXT, text.len() as u64);
+            out.extend_from_slice(text.as_bytes());
+        }
+        DatumAtom::Symbol(text) => {
+            write_head(out, MAJOR_TAG, TAG_SYMBOL);
+            write_head(out, MAJOR_TEXT, text.len() as u64);
+            out.extend_from_slice(text.as_bytes());
+        }
+        DatumAtom::Integer(v) => {
+            if *v >= 0 {
+                write_head(out, MAJOR_UNSIGNED, *v as u64);
+            } else {
+                // -1 - v can't overflow for negative v
+                write_head(out, MAJOR_NEGATIVE, (-1 - *v) as u64);
+            }
+        }
+        DatumAtom::Float(v) => {
+            out.push((MAJOR_SIMPLE << 5) | SIMPLE_F64);
+            out.extend_from_slice(&v.to_bits().to_be_bytes());
+        }
+        DatumAtom::Boolean(false) => out.push((MAJOR_SIMPLE << 5) | SIMPLE_FALSE),
+        DatumAtom::Boolean(true) => out.push((MAJOR_SIMPLE << 5) | SIMPLE_TRUE),
+        DatumAtom::Nil => out.push((MAJOR_SIMPLE << 5) | SIMPLE_NULL),
+    }
+}
+
+/// Appends the compact binary encoding of a sequence of values (i.e. a whole document) to `out`.
+///
+/// This is meant for caching parsed documents, so large unchanged files don't have to be tokenized again on load; it isn't an interchange format in the way the text is.
+/// Loading it back with [datum_binary_decode] gives equal values. Comments and formatting aren't kept, as they aren't part of the values.
+///
+/// The encoding is a subset of CBOR (a self-described CBOR array of the values, with symbols under tag 39), so general CBOR tools can inspect it.
+/// Nothing identifies which source a cache came from; keep that (i.e. its modification time or [DatumValue::canonical_hash]) alongside it.
+///
+/// This doesn't recurse, so deeply nested values are fine.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_binary_decode, datum_binary_encode, DatumDocument};
+/// let doc: DatumDocument = "(a 1)".parse().unwrap();
+/// let mut cache = Vec::new();
+/// datum_binary_encode(&doc, &mut cache);
+/// assert_eq!(cache, [0xD9, 0xD9, 0xF7, 0x81, 0x82, 0xD8, 0x27, 0x61, b'a', 0x01]);
+/// assert_eq!(datum_binary_decode(&cache).unwrap(), doc.0);
+/// ```
+pub fn datum_binary_encode<V: DatumValueNode>(values: &[V], out: &mut Vec<u8>) {
+    out.extend_from_slice(&MAGIC);
+    write_head(out, MAJOR_ARRAY, values.len() as u64);
+    let mut stack = alloc::vec![values.iter()];
+    while let Some(top) = stack.last_mut() {
+        match top.next().map(V::node) {
+            None => {
+                stack.pop();
+            }
+            Some(DatumNode::Atom(atom)) => write_atom(out, atom),
+            Some(DatumNode::List(list)) => {
+                write_head(out, MAJOR_ARRAY, list.len() as u64);
+                stack.push(list.iter());
+            }
+        }
+    }
+}
+
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    fn offset(&self) -> DatumOffset {
+        self.at as DatumOffset
+    }
+
+    fn take(&mut self, len: u64) -> DatumResult<&'a [u8]> {
+        let remaining = self.bytes.len() - self.at;
+        if len > remaining as u64 {
+            return Err(datum_error!(
+                Interrupted,
+                self.bytes.len() as DatumOffset,
+                "binary data ends early"
+            ));
+        }
+        let taken = &self.bytes[self.at..self.at + len as usize];
+        self.at += len as usize;
+        Ok(taken)
+    }
+
+    /// Reads an item head, returning the major type, the additional information, and the argument.
+    fn head(&mut self) -> DatumResult<(u8, u8, u64)> {
+        let at = self.offset();
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 31);
+        let width = match info {
+            0..=23 => return Ok((major, info, info as u64)),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => {
+                return Err(datum_error!(
+                    BadData,
+                    at,
+                    "unsupported binary item (indefinite length or reserved)"
+                ))
+            }
+        };
+        let arg = self
+            .take(width)?
+            .iter()
+            .fold(0u64, |acc, b| (acc << 8) | *b as u64);
+        Ok((major, info, arg))
+    }
+
+    fn text(&mut self, at: DatumOffset, len: u64) -> DatumResult<String> {
+        match core::str::from_utf8(self.take(len)?) {
+            Ok(text) => Ok(String::from(text)),
+            Err(_) => Err(datum_error!(BadData, at, "binary text is not UTF-8")),
+        }
+    }
+
+    /// Reads an item, returning either an atom or the length of a list.
+    fn item(&mut self) -> DatumResult<Result<DatumAtom<String>, u64>> {
+        let at = self.offset();
+        let (major, info, arg) = self.head()?;
+        Ok(Ok(match major {
+            MAJOR_UNSIGNED => {
+                if arg > i64::MAX as u64 {
+                    return Err(datum_error!(BadData, at, "binary integer out of range"));
+                }
+                DatumAtom::Integer(arg as i64)
+            }
+            MAJOR_NEGATIVE => {
+                if arg > i64::MAX as u64 {
+                    return Err(datum_error!(BadData, at, "binary integer out of range"));
+                }
+                DatumAtom::Integer(-1 - arg as i64)
+            }
+            MAJOR_TEXT => DatumAtom::String(self.text(at, arg)?),
+            MAJOR_ARRAY => return Ok(Err(arg)),
+            MAJOR_TAG if arg == TAG_SYMBOL => {
+                let text_at = self.offset();
+                match self.head()? {
+                    (MAJOR_TEXT, _, len) => DatumAtom::Symbol(self.text(text_at, len)?),
+                    _ => return Err(datum_error!(BadData, text_at, "binary symbol is not text")),
+                }
+            }
+            MAJOR_SIMPLE => match info {
+                SIMPLE_FALSE => DatumAtom::Boolean(false),
+                SIMPLE_TRUE => DatumAtom::Boolean(true),
+                SIMPLE_NULL => DatumAtom::Nil,
+                SIMPLE_F32 => DatumAtom::Float(f32::from_bits(arg as u32) as f64),
+                SIMPLE_F64 => DatumAtom::Float(f64::from_bits(arg)),
+                _ => return Err(datum_error!(BadData, at, "unsupported binary simple value")),
+            },
+            _ => return Err(datum_error!(BadData, at, "unsupported binary item")),
+        }))
+    }
+}
+
+/// Loads values written by [datum_binary_encode].
+///
+/// Error offsets are in bytes. Truncated data is [crate::DatumErrorKind::Interrupted]; anything else that isn't valid (including data after the values) is [crate::DatumErrorKind::BadData].
+///
+/// This doesn't recurse, but the values it returns may be deeply nested if the data is untrusted; see [DatumValue::drop_iteratively].
+///
+/// _Added in 1.3.0._
+pub fn datum_binary_decode(bytes: &[u8]) -> DatumResult<Vec<DatumValue>> {
+    let mut reader = BinaryReader { bytes, at: 0 };
+    if reader.take(MAGIC.len() as u64)? != MAGIC {
+        return Err(datum_error!(BadData, 0, "not Datum binary data"));
+    }
+    let len = match reader.item()? {
+        Err(len) => len,
+        Ok(_) => {
+            return Err(datum_error!(
+                BadData,
+                MAGIC.len() as DatumOffset,
+                "binary values are not an array"
+            ))
+        }
+    };
+    // declared lengths can't be trusted, and every open list reserves at once, so lists only reserve a little up front and grow from there
+    let capacity = |reader: &BinaryReader, len: u64| {
+        len.min((bytes.len() - reader.at) as u64)
+            .min(PREALLOCATE_MAX) as usize
+    };
+    let mut stack: Vec<(u64, Vec<DatumValue>)> =
+        alloc::vec![(len, Vec::with_capacity(capacity(&reader, len)))];
+    loop {
+        let top = stack.last_mut().unwrap();
+        if top.0 == 0 {
+            let (_, list) = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some(parent) => parent.1.push(DatumValue::List(list)),
+                None => {
+                    if reader.at != bytes.len() {
+                        return Err(datum_error!(
+                            BadData,
+                            reader.offset(),
+                            "data after binary values"
+                        ));
+                    }
+                    return Ok(list);
+                }
+            }
+            continue;
+        }
+        top.0 -= 1;
+        match reader.item()? {
+            Ok(atom) => top.1.push(DatumValue::Atom(atom)),
+            Err(len) => {
+                let list = Vec::with_capacity(capacity(&reader, len));
+                stack.push((len, list));
+            }
+        }
+    }
+}
+
+impl DatumDocument {
+    /// Encodes this document with [datum_binary_encode].
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::DatumDocument;
+    /// let doc: DatumDocument = "(level (id 3) \"Caves\") #t".parse().unwrap();
+    /// let cache = doc.to_binary();
+    /// assert_eq!(DatumDocument::from_binary(&cache).unwrap(), doc);
+    /// ```
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        datum_binary_encode(&self.0, &mut out);
+        out
+    }
+
+    /// Loads a document with [datum_binary_decode].
+    ///
+    /// _Added in 1.3.0._
+    pub fn from_binary(bytes: &[u8]) -> DatumResult<Self> {
+        datum_binary_decode(bytes).map(Self)
+    }
+}
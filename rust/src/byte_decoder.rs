@@ -19,6 +19,66 @@ pub struct DatumUTF8Decoder {
     buffer_len: u8,
 }
 
+impl DatumUTF8Decoder {
+    /// Feeds a run of bytes, with byte offsets starting at `at`, returning the offset after them (so text can be fed in chunks).
+    ///
+    /// This is the same as feeding each byte, but valid text (i.e. runs of ASCII) is validated and decoded in bulk by [core::str::from_utf8], rather than a byte at a time.
+    /// Characters split between chunks are handled as usual.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumPipe, DatumUTF8Decoder};
+    /// let mut decoder = DatumUTF8Decoder::default();
+    /// let mut chars = Vec::new();
+    /// let text = "caf\u{E9}!".as_bytes();
+    /// let next = decoder.feed_bytes(0, &text[..4], &mut |at, c| {
+    ///     chars.push((at, c));
+    ///     Ok(())
+    /// }).unwrap();
+    /// let next = decoder.feed_bytes(next, &text[4..], &mut |at, c| {
+    ///     chars.push((at, c));
+    ///     Ok(())
+    /// }).unwrap();
+    /// decoder.feed(next, None, &mut |_, _| Ok(())).unwrap();
+    /// assert_eq!(chars, [(0, 'c'), (1, 'a'), (2, 'f'), (3, '\u{E9}'), (5, '!')]);
+    /// ```
+    pub fn feed_bytes<F: FnMut(DatumOffset, char) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        bytes: &[u8],
+        f: &mut F,
+    ) -> DatumResult<DatumOffset> {
+        // finish any character left over from the last chunk
+        let mut start = 0;
+        while start < bytes.len() && self.buffer_len != 0 {
+            self.feed(at + start as DatumOffset, Some(bytes[start]), f)?;
+            start += 1;
+        }
+        let rest = &bytes[start..];
+        let (valid, tail) = match core::str::from_utf8(rest) {
+            Ok(valid) => (valid, &rest[rest.len()..]),
+            Err(error) => {
+                let (valid, tail) = rest.split_at(error.valid_up_to());
+                // this can't fail, but avoiding the check would need unsafe code
+                let valid = core::str::from_utf8(valid).map_err(|_| {
+                    datum_error!(BadData, at + start as DatumOffset, "utf8: invalid")
+                })?;
+                (valid, tail)
+            }
+        };
+        for (offset, v) in valid.char_indices() {
+            f(at + (start + offset) as DatumOffset, v)?;
+        }
+        // an unfinished character, or an error (reported as it would be a byte at a time)
+        let start = start + valid.len();
+        for (offset, byte) in tail.iter().enumerate() {
+            self.feed(at + (start + offset) as DatumOffset, Some(*byte), f)?;
+        }
+        Ok(at + bytes.len() as DatumOffset)
+    }
+}
+
+// Each byte completes at most one character, and EOF never outputs.
 impl DatumBoundedPipe for DatumUTF8Decoder {
     type OutputQueueSize = unary::C1;
 }
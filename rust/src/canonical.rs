@@ -0,0 +1,148 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+// Canonical form is what a default DatumWriter writes: no indentation, single spaces between tokens, no space inside list ends.
+// Documents are written as each value followed by a newline.
+// Since parsing normalizes numbers, special IDs, and escapes, any two texts that parse to equal values have the same canonical form.
+
+use alloc::string::String;
+use core::fmt::Write;
+use core::hash::Hasher;
+use core::ops::Deref;
+
+use crate::{
+    DatumDocument, DatumDocumentStyle, DatumListStorage, DatumValue, DatumValueOf, DatumWriter,
+};
+
+/// Writes the canonical form of any value; for code generic over [crate::DatumValueNode].
+#[cfg(feature = "std")]
+pub(crate) fn write_canonical_node<V: crate::DatumValueNode>(
+    value: &V,
+    f: &mut dyn Write,
+) -> core::fmt::Result {
+    crate::ast::write_node(value, f, &mut DatumWriter::default())
+}
+
+/// Canonical form methods, on both [DatumValue] and [DatumValueOf].
+macro_rules! canonical_methods {
+    () => {
+        /// Writes the canonical form of this value.
+        /// This is the same as [core::fmt::Display], i.e. [DatumWriter::default] output.
+        ///
+        /// _Added in 1.3.0._
+        pub fn write_canonical(&self, f: &mut dyn Write) -> core::fmt::Result {
+            self.write_to(f, &mut DatumWriter::default())
+        }
+
+        /// Feeds the canonical form of this value into a [Hasher] as a single byte slice.
+        /// Unlike the [core::hash::Hash] implementation, this only depends on the canonical text, so it's consistent across platforms and implementations (given a consistent [Hasher]).
+        ///
+        /// _Added in 1.3.0._
+        /// ```
+        /// use datum::DatumDocument;
+        /// use std::collections::hash_map::DefaultHasher;
+        /// use std::hash::Hasher;
+        /// let a: DatumDocument = "(a   #x10 \"\\x41;\")".parse().unwrap();
+        /// let b: DatumDocument = "(a 16 \"A\")".parse().unwrap();
+        /// let mut ha = DefaultHasher::new();
+        /// let mut hb = DefaultHasher::new();
+        /// a[0].canonical_hash(&mut ha);
+        /// b[0].canonical_hash(&mut hb);
+        /// assert_eq!(ha.finish(), hb.finish());
+        /// ```
+        pub fn canonical_hash<H: Hasher>(&self, state: &mut H) {
+            let mut text = String::new();
+            // writing to a String can't fail
+            let _ = self.write_canonical(&mut text);
+            state.write(text.as_bytes());
+        }
+
+        /// SHA-256 digest of the canonical form of this value.
+        ///
+        /// _Added in 1.3.0._
+        #[cfg(feature = "sha2")]
+        pub fn canonical_sha256(&self) -> [u8; 32] {
+            let mut digest = Sha256Writer(sha2::Sha256::default());
+            let _ = self.write_canonical(&mut digest);
+            digest.finish()
+        }
+    };
+}
+
+impl<B: Deref<Target = str>, S: DatumListStorage<B>> DatumValueOf<B, S> {
+    canonical_methods!();
+}
+
+impl DatumValue {
+    canonical_methods!();
+}
+
+impl DatumDocument {
+    /// Writes the canonical form of this document: each value, followed by a newline.
+    /// This is the same as [core::fmt::Display].
+    ///
+    /// _Added in 1.3.0._
+    pub fn write_canonical(&self, f: &mut dyn Write) -> core::fmt::Result {
+        self.write_with(f, &DatumDocumentStyle::default())
+    }
+
+    /// Writes this document like [DatumDocument::write_canonical], but with the given line ending and document end.
+    ///
+    /// _Added in 1.3.0._
+    pub fn write_with(&self, f: &mut dyn Write, style: &DatumDocumentStyle) -> core::fmt::Result {
+        let mut writer = DatumWriter::default().with_line_ending(style.line_ending);
+        for (i, v) in self.iter().enumerate() {
+            if i != 0 {
+                writer.write_newline(f)?;
+            }
+            v.write_to(f, &mut writer)?;
+        }
+        style.write_end(f, self.is_empty())
+    }
+
+    /// Feeds the canonical form of this document into a [Hasher] as a single byte slice.
+    /// See [DatumValueOf::canonical_hash].
+    ///
+    /// _Added in 1.3.0._
+    pub fn canonical_hash<H: Hasher>(&self, state: &mut H) {
+        let mut text = String::new();
+        let _ = self.write_canonical(&mut text);
+        state.write(text.as_bytes());
+    }
+
+    /// SHA-256 digest of the canonical form of this document.
+    /// Useful for integrity-checking configs and save files.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "sha2")]
+    pub fn canonical_sha256(&self) -> [u8; 32] {
+        let mut digest = Sha256Writer(sha2::Sha256::default());
+        let _ = self.write_canonical(&mut digest);
+        digest.finish()
+    }
+}
+
+/// Streams written text into a SHA-256 digest.
+#[cfg(feature = "sha2")]
+struct Sha256Writer(sha2::Sha256);
+
+#[cfg(feature = "sha2")]
+impl Sha256Writer {
+    fn finish(self) -> [u8; 32] {
+        use sha2::Digest;
+        self.0.finalize().into()
+    }
+}
+
+#[cfg(feature = "sha2")]
+impl Write for Sha256Writer {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        use sha2::Digest;
+        self.0.update(s.as_bytes());
+        Ok(())
+    }
+}
@@ -11,30 +11,62 @@ use core::{
 };
 
 /// Datum character class.
+///
+/// The numeric values (i.e. `DatumCharClass::Digit as u8`) are stable, for use by bindings; see [DATUM_BYTE_CLASS_TABLE].
+#[repr(u8)]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum DatumCharClass {
     /// Escaped characters, anything else
-    Content,
+    Content = 0,
     /// 32 and 9
-    Whitespace,
+    Whitespace = 1,
     /// 10
-    Newline,
+    Newline = 2,
     /// ';'
-    LineComment,
+    LineComment = 3,
     /// '"'
-    String,
+    String = 4,
     /// '('
-    ListStart,
+    ListStart = 5,
     /// ')'
-    ListEnd,
+    ListEnd = 6,
     /// '#'
-    SpecialID,
+    SpecialID = 7,
     /// '-'
-    Sign,
+    Sign = 8,
     /// '0' - '9'
-    Digit,
+    Digit = 9,
 }
 
+/// Value in [DATUM_BYTE_CLASS_TABLE] for meta-class bytes (control characters and backslash), which have no class.
+///
+/// _Added in 1.3.0._
+pub const DATUM_BYTE_CLASS_META: u8 = 255;
+
+/// Character class of every byte, as per [DatumCharClass::identify_byte], with classes as their numeric values and [DATUM_BYTE_CLASS_META] for [None].
+///
+/// This is meant to be copied into (or checked against by) other implementations and bindings, so that they classify exactly the same way.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumCharClass, DATUM_BYTE_CLASS_META, DATUM_BYTE_CLASS_TABLE};
+/// assert_eq!(DATUM_BYTE_CLASS_TABLE[b'(' as usize], DatumCharClass::ListStart as u8);
+/// assert_eq!(DATUM_BYTE_CLASS_TABLE[b'\\' as usize], DATUM_BYTE_CLASS_META);
+/// assert_eq!(DATUM_BYTE_CLASS_TABLE[0xC3], DatumCharClass::Content as u8);
+/// ```
+pub const DATUM_BYTE_CLASS_TABLE: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = match DatumCharClass::identify_byte(i as u8) {
+            Some(class) => class as u8,
+            None => DATUM_BYTE_CLASS_META,
+        };
+        i += 1;
+    }
+    table
+};
+
 impl DatumCharClass {
     /// If this character class is a potential identifier.
     /// Note that this can be accessed via [DatumChar] via [DatumChar::deref].
@@ -89,6 +121,64 @@ impl DatumCharClass {
             Some(DatumCharClass::Content)
         }
     }
+
+    /// Identifies a byte of UTF-8 text.
+    ///
+    /// Every character outside of ASCII is [DatumCharClass::Content], so every byte of one (i.e. 0x80 and up) is too.
+    /// This means a byte-oriented implementation can classify without decoding UTF-8 (though it should still validate it).
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::DatumCharClass;
+    /// for c in ['a', '(', '\\', '\n', '\u{7F}'] {
+    ///     assert_eq!(DatumCharClass::identify_byte(c as u8), DatumCharClass::identify(c));
+    /// }
+    /// assert_eq!(DatumCharClass::identify_byte(0xE2), Some(DatumCharClass::Content));
+    /// ```
+    pub const fn identify_byte(v: u8) -> Option<Self> {
+        if v >= 0x80 {
+            Some(DatumCharClass::Content)
+        } else {
+            Self::identify(v as char)
+        }
+    }
+
+    /// Converts a numeric value (i.e. from [DATUM_BYTE_CLASS_TABLE]) back into a class.
+    ///
+    /// _Added in 1.3.0._
+    pub const fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Content),
+            1 => Some(Self::Whitespace),
+            2 => Some(Self::Newline),
+            3 => Some(Self::LineComment),
+            4 => Some(Self::String),
+            5 => Some(Self::ListStart),
+            6 => Some(Self::ListEnd),
+            7 => Some(Self::SpecialID),
+            8 => Some(Self::Sign),
+            9 => Some(Self::Digit),
+            _ => None,
+        }
+    }
+
+    /// Identifies a character, optionally treating `+` as [DatumCharClass::Sign].
+    /// This is the compatibility option used by [crate::DatumDecoder::plus_sign]; without it, this is the same as [DatumCharClass::identify].
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::DatumCharClass;
+    /// assert_eq!(DatumCharClass::identify_with_plus_sign('+', false), Some(DatumCharClass::Content));
+    /// assert_eq!(DatumCharClass::identify_with_plus_sign('+', true), Some(DatumCharClass::Sign));
+    /// assert_eq!(DatumCharClass::identify_with_plus_sign('a', true), Some(DatumCharClass::Content));
+    /// ```
+    pub const fn identify_with_plus_sign(v: char, plus_sign: bool) -> Option<Self> {
+        if plus_sign && v == '+' {
+            Some(DatumCharClass::Sign)
+        } else {
+            Self::identify(v)
+        }
+    }
 }
 
 const fn make_hex_digit(v: u8) -> char {
@@ -190,6 +280,17 @@ impl DatumChar {
         }
     }
 
+    /// Identifies an unescaped character like [DatumChar::identify], but using [DatumCharClass::identify_with_plus_sign].
+    ///
+    /// _Added in 1.3.0._
+    #[inline]
+    pub const fn identify_with_plus_sign(v: char, plus_sign: bool) -> Option<DatumChar> {
+        match DatumCharClass::identify_with_plus_sign(v, plus_sign) {
+            None => None,
+            Some(class) => Some(DatumChar { char: v, class }),
+        }
+    }
+
     /// Creates a content character for the given value.
     pub const fn content(v: char) -> DatumChar {
         DatumChar {
@@ -0,0 +1,202 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::convert::TryFrom;
+use core::fmt::Display;
+use std::io;
+use std::string::{String, ToString};
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{DatumDocument, DatumValue, DatumValueNode};
+
+fn invalid_data<E: Display>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// How [DatumCodec] separates messages.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DatumCodecFraming {
+    /// Each message is a 32-bit big-endian byte length followed by the value's text. This is the default.
+    LengthPrefixed,
+    /// Each message is a line of text. Blank lines (and lines with only comments) are skipped.
+    ///
+    /// The canonical form of a value never contains a newline, so this is safe for any value, and easy to type into a terminal.
+    NewlineDelimited,
+}
+
+// `#[default]` on enum variants is newer than the MSRV.
+#[allow(clippy::derivable_impls)]
+impl Default for DatumCodecFraming {
+    fn default() -> Self {
+        Self::LengthPrefixed
+    }
+}
+
+/// A [tokio_util::codec] implementation sending one value per message, for using Datum as an RPC/IPC wire format.
+///
+/// Values are sent in canonical form (see [DatumValue::write_canonical]). Any [DatumValue] or [crate::DatumValueOf] can be encoded by reference; decoding produces [DatumValue]s.
+///
+/// Errors (including parse errors) are [io::Error]s, as [tokio_util::codec::FramedRead] needs.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use bytes::BytesMut;
+/// use datum::{DatumCodec, DatumDocument};
+/// use tokio_util::codec::{Decoder, Encoder};
+/// let doc: DatumDocument = "(call \"ping\")".parse().unwrap();
+/// let value = doc[0].clone();
+/// let mut codec = DatumCodec::default();
+/// let mut buffer = BytesMut::new();
+/// codec.encode(&value, &mut buffer).unwrap();
+/// assert_eq!(&buffer[..], b"\0\0\0\x0d(call \"ping\")");
+/// assert_eq!(codec.decode(&mut buffer).unwrap(), Some(value));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DatumCodec {
+    /// How messages are separated.
+    pub framing: DatumCodecFraming,
+    /// Longest message (in bytes, not including the length prefix or newline) that will be sent or received.
+    /// This limits how much a peer can make the decoder buffer.
+    pub max_length: usize,
+    /// Position in the buffer up to which no newline has been found.
+    scanned: usize,
+}
+
+impl Default for DatumCodec {
+    fn default() -> Self {
+        Self::new(DatumCodecFraming::default())
+    }
+}
+
+impl DatumCodec {
+    /// Creates a codec with the given framing and a maximum message length of 8 MiB.
+    pub const fn new(framing: DatumCodecFraming) -> Self {
+        Self {
+            framing,
+            max_length: 8 * 1024 * 1024,
+            scanned: 0,
+        }
+    }
+
+    /// Parses a message, which may be empty.
+    fn parse(message: &[u8]) -> io::Result<Option<DatumValue>> {
+        let mut doc = DatumDocument::try_from(message).map_err(invalid_data)?;
+        match doc.len() {
+            0 => Ok(None),
+            1 => Ok(doc.pop()),
+            _ => Err(invalid_data("codec: more than one value in message")),
+        }
+    }
+
+    fn too_long() -> io::Error {
+        invalid_data("codec: message too long")
+    }
+}
+
+impl Decoder for DatumCodec {
+    type Item = DatumValue;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<DatumValue>> {
+        match self.framing {
+            DatumCodecFraming::LengthPrefixed => {
+                if src.len() < 4 {
+                    return Ok(None);
+                }
+                let length = u32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize;
+                if length > self.max_length {
+                    return Err(Self::too_long());
+                }
+                if src.len() < 4 + length {
+                    src.reserve(4 + length - src.len());
+                    return Ok(None);
+                }
+                src.advance(4);
+                let message = src.split_to(length);
+                Self::parse(&message)?
+                    .map(Some)
+                    .ok_or_else(|| invalid_data("codec: empty message"))
+            }
+            DatumCodecFraming::NewlineDelimited => loop {
+                let newline = src[self.scanned..].iter().position(|v| *v == b'\n');
+                let line = match newline {
+                    Some(index) => src.split_to(self.scanned + index + 1),
+                    None => {
+                        self.scanned = src.len();
+                        if self.scanned > self.max_length {
+                            return Err(Self::too_long());
+                        }
+                        return Ok(None);
+                    }
+                };
+                self.scanned = 0;
+                if line.len() - 1 > self.max_length {
+                    return Err(Self::too_long());
+                }
+                if let Some(value) = Self::parse(&line)? {
+                    return Ok(Some(value));
+                }
+            },
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<DatumValue>> {
+        if let Some(value) = self.decode(src)? {
+            return Ok(Some(value));
+        }
+        if src.is_empty() {
+            return Ok(None);
+        }
+        if self.framing == DatumCodecFraming::NewlineDelimited {
+            // last line without a newline
+            self.scanned = 0;
+            let line = src.split();
+            return Self::parse(&line);
+        }
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "codec: connection closed mid-message",
+        ))
+    }
+}
+
+impl<'a, V: DatumValueNode> Encoder<&'a V> for DatumCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &'a V, dst: &mut BytesMut) -> io::Result<()> {
+        let mut text = String::new();
+        crate::canonical::write_canonical_node(item, &mut text).map_err(invalid_data)?;
+        if text.len() > self.max_length || text.len() > u32::MAX as usize {
+            return Err(Self::too_long());
+        }
+        match self.framing {
+            DatumCodecFraming::LengthPrefixed => {
+                dst.reserve(4 + text.len());
+                dst.put_u32(text.len() as u32);
+                dst.put_slice(text.as_bytes());
+            }
+            DatumCodecFraming::NewlineDelimited => {
+                dst.reserve(text.len() + 1);
+                dst.put_slice(text.as_bytes());
+                dst.put_u8(b'\n');
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encoder<DatumValue> for DatumCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: DatumValue, dst: &mut BytesMut) -> io::Result<()> {
+        self.encode(&item, dst)
+    }
+}
@@ -0,0 +1,134 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{
+    DatumBalanceChecker, DatumDecoder, DatumOffset, DatumPipe, DatumResult, DatumStringTokenizer,
+    DatumToken,
+};
+
+/// A list around the cursor, from [datum_completion_context].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DatumCompletionForm {
+    /// Where the list starts (its `(`), in characters.
+    pub start: DatumOffset,
+    /// The list's first element, if it's a symbol before the cursor (i.e. the name of a function or special form).
+    pub head: Option<String>,
+    /// Which element of the list the cursor is at (the amount of complete elements before it).
+    pub index: usize,
+}
+
+/// What's around a cursor in a partial document, for context-aware completion; see [datum_completion_context].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DatumCompletionContext {
+    /// The lists the cursor is inside, outermost first. Empty at the top level.
+    pub forms: Vec<DatumCompletionForm>,
+    /// The (unescaped) part of a symbol before the cursor, or empty if the cursor isn't in or at the end of one.
+    pub partial: String,
+    /// Where [DatumCompletionContext::partial] starts, in characters; a completion replaces the text from here to the cursor. The cursor if there's no partial symbol.
+    pub partial_start: DatumOffset,
+    /// If the cursor is inside a string (so there's no partial symbol).
+    pub in_string: bool,
+    /// If the cursor is inside a comment (so there's no partial symbol).
+    pub in_comment: bool,
+}
+
+impl DatumCompletionContext {
+    /// The innermost list around the cursor, if any.
+    pub fn innermost(&self) -> Option<&DatumCompletionForm> {
+        self.forms.last()
+    }
+}
+
+fn completion_token(forms: &mut Vec<DatumCompletionForm>, token: DatumToken<String>) {
+    let element = match token {
+        DatumToken::ListStart(at) => {
+            forms.push(DatumCompletionForm {
+                start: at,
+                head: None,
+                index: 0,
+            });
+            return;
+        }
+        DatumToken::ListEnd(_) => {
+            forms.pop();
+            None
+        }
+        DatumToken::Symbol(_, name) => Some(name),
+        _ => None,
+    };
+    if let Some(form) = forms.last_mut() {
+        if form.index == 0 {
+            form.head = element;
+        }
+        form.index += 1;
+    }
+}
+
+/// Works out what's around a cursor (in characters, clamped to the end of the text) in a partial document: the lists it's in, and the symbol it's at.
+///
+/// Only the text before the cursor is looked at, and it doesn't have to be complete (i.e. lists and strings can be left open).
+/// It's an error if that text has a bad escape or closes a list that was never opened.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::datum_completion_context;
+/// let source = "(def area (w h)\n  (* w he";
+/// let context = datum_completion_context(source, source.chars().count()).unwrap();
+/// let heads: Vec<_> = context.forms.iter().map(|f| f.head.as_deref()).collect();
+/// assert_eq!(heads, [Some("def"), Some("*")]);
+/// assert_eq!(context.innermost().unwrap().index, 2);
+/// assert_eq!((context.partial.as_str(), context.partial_start), ("he", 23));
+/// // just after a space, there's nothing to complete yet
+/// let context = datum_completion_context(source, 23).unwrap();
+/// assert_eq!((context.partial.as_str(), context.partial_start), ("", 23));
+/// ```
+pub fn datum_completion_context(
+    source: &str,
+    cursor: usize,
+) -> DatumResult<DatumCompletionContext> {
+    let mut decoder = DatumDecoder::default();
+    let mut checker = DatumBalanceChecker::default();
+    let mut tokenizer = DatumStringTokenizer::default();
+    let mut forms = Vec::new();
+    let mut at = 0;
+    for c in source.chars().take(cursor) {
+        decoder.feed(at, Some(c), &mut |at, chr| {
+            checker.feed(at, Some(chr), &mut |at, chr| {
+                tokenizer.feed(at, Some(chr), &mut |_, token| {
+                    completion_token(&mut forms, token);
+                    Ok(())
+                })
+            })
+        })?;
+        at += 1;
+    }
+    let mut context = DatumCompletionContext {
+        forms,
+        partial: String::new(),
+        partial_start: at,
+        in_string: checker.in_string(),
+        in_comment: checker.in_comment(),
+    };
+    if !(context.in_string || context.in_comment) {
+        // anything the tokenizer is still working on is what's at the cursor
+        let _ = tokenizer.feed(at, None, &mut |_, token| {
+            if let DatumToken::Symbol(_, name) = &token {
+                context.partial_start = token.source_span(source).start;
+                context.partial = name.clone();
+            }
+            Ok(())
+        });
+    }
+    Ok(context)
+}
@@ -0,0 +1,159 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::string::String;
+
+use crate::log_writer::io_error;
+use crate::{datum_byte_to_value_pipeline, DatumDocument, DatumDocumentStyle, DatumPipe};
+
+/// Compression of a document file. Which kinds are available depends on features.
+///
+/// Compressed documents can be told apart from plain ones by their first bytes (neither gzip's nor zstd's magic numbers can start a valid document), so [DatumDocument::read_from] doesn't need to be told which kind it's reading.
+///
+/// As the functions here work on any [Read] or [Write], other layers (i.e. encryption with the `age` crate's stream wrappers) can be added around them.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumCompression, DatumDocument, DatumDocumentStyle};
+/// let doc: DatumDocument = "(save (level 3))".parse().unwrap();
+/// let mut file = Vec::new();
+/// doc.write_to(&mut file, DatumCompression::None, &DatumDocumentStyle::default()).unwrap();
+/// assert_eq!(file, b"(save (level 3))\n");
+/// assert_eq!(DatumDocument::read_from(&file[..]).unwrap(), doc);
+/// ```
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DatumCompression {
+    /// Plain text. This is the default.
+    None,
+    /// gzip (`flate2` feature).
+    #[cfg(feature = "flate2")]
+    Gzip,
+    /// Zstandard (`zstd` feature).
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+// `#[default]` on enum variants is newer than the MSRV.
+#[allow(clippy::derivable_impls)]
+impl Default for DatumCompression {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl DatumCompression {
+    /// Works out the compression of a file from its first few bytes (at least 4, if available).
+    ///
+    /// Compression that isn't supported by enabled features is detected as [DatumCompression::None], and so fails to parse.
+    pub fn detect(start: &[u8]) -> Self {
+        #[cfg(feature = "flate2")]
+        if start.starts_with(&[0x1F, 0x8B]) {
+            return Self::Gzip;
+        }
+        #[cfg(feature = "zstd")]
+        if start.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            return Self::Zstd;
+        }
+        let _ = start;
+        Self::None
+    }
+
+    /// Wraps a reader so that it reads decompressed data.
+    pub fn reader<'a, R: Read + 'a>(&self, reader: R) -> io::Result<Box<dyn Read + 'a>> {
+        match self {
+            Self::None => Ok(Box::new(reader)),
+            #[cfg(feature = "flate2")]
+            Self::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Ok(Box::new(zstd::Decoder::new(reader)?)),
+        }
+    }
+
+    /// Compresses all of `data` into the writer, finishing the compressed stream.
+    pub fn write_all<W: Write>(&self, writer: W, data: &[u8]) -> io::Result<W> {
+        match self {
+            Self::None => {
+                let mut writer = writer;
+                writer.write_all(data)?;
+                Ok(writer)
+            }
+            #[cfg(feature = "flate2")]
+            Self::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(writer, Default::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd => {
+                let mut encoder = zstd::Encoder::new(writer, 0)?;
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+impl DatumDocument {
+    /// Reads a document from a reader, decompressing it if necessary (see [DatumCompression::detect]).
+    ///
+    /// The data is parsed as it's read, through the byte pipeline. Offsets in errors are in decompressed bytes.
+    ///
+    /// _Added in 1.3.0._
+    pub fn read_from<R: Read>(reader: R) -> io::Result<Self> {
+        datum_span!(
+            span,
+            "datum_read_document",
+            compression = tracing::field::Empty,
+            values = tracing::field::Empty
+        );
+        let mut reader = BufReader::new(reader);
+        let compression = DatumCompression::detect(reader.fill_buf()?);
+        datum_span_record!(span, "compression", tracing::field::debug(compression));
+        let reader = BufReader::new(compression.reader(reader)?);
+        let mut read_error = None;
+        let bytes = reader
+            .bytes()
+            .scan(&mut read_error, |read_error, byte| match byte {
+                Ok(byte) => Some(byte),
+                Err(e) => {
+                    **read_error = Some(e);
+                    None
+                }
+            });
+        let result = datum_byte_to_value_pipeline().feed_all_to_vec(bytes, true);
+        if let Some(e) = read_error {
+            return Err(e);
+        }
+        let result = result.map_err(io_error)?;
+        datum_span_record!(span, "values", result.len());
+        Ok(Self(result))
+    }
+
+    /// Writes this document to a writer (like [DatumDocument::write_with]), compressing it.
+    ///
+    /// _Added in 1.3.0._
+    pub fn write_to<W: Write>(
+        &self,
+        writer: W,
+        compression: DatumCompression,
+        style: &DatumDocumentStyle,
+    ) -> io::Result<W> {
+        datum_span!(
+            span,
+            "datum_write_document",
+            ?compression,
+            values = self.len(),
+            bytes = tracing::field::Empty
+        );
+        let mut text = String::new();
+        self.write_with(&mut text, style).map_err(io_error)?;
+        datum_span_record!(span, "bytes", text.len());
+        compression.write_all(writer, text.as_bytes())
+    }
+}
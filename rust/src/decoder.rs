@@ -17,15 +17,85 @@ enum DatumDecoderState {
 
 /// Decoder for the Datum encoding layer.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-pub struct DatumDecoder(DatumDecoderState);
+pub struct DatumDecoder(DatumDecoderState, bool);
 
 impl Default for DatumDecoder {
     #[inline]
     fn default() -> DatumDecoder {
-        DatumDecoder(DatumDecoderState::Normal)
+        DatumDecoder(DatumDecoderState::Normal, false)
     }
 }
 
+impl DatumDecoder {
+    /// Sets if unescaped `+` is treated as a sign, like `-` (by default, it isn't).
+    ///
+    /// This is a compatibility option for people used to other S-expression dialects.
+    /// With it, `+1` and `+1.5` are numbers, while a lone `+` (as in `(+ 1 2)`) is still a symbol.
+    /// Like with `-`, a `+` followed by something that isn't a number is an error unless escaped.
+    ///
+    /// To write text that means the same thing either way, set [crate::DatumWriter::plus_sign].
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumDecoder, DatumPipe, DatumPipeTokenizer, DatumToken};
+    /// let mut pipeline = DatumDecoder::default().plus_sign(true).compose(DatumPipeTokenizer::<String>::default());
+    /// let tokens = pipeline.feed_all_to_vec("+1 + \\+a".chars(), true).unwrap();
+    /// assert!(matches!(tokens[0], DatumToken::Integer(_, 1)));
+    /// assert!(matches!(&tokens[1], DatumToken::Symbol(_, s) if s == "+"));
+    /// assert!(matches!(&tokens[2], DatumToken::Symbol(_, s) if s == "+a"));
+    /// ```
+    pub fn plus_sign(mut self, plus_sign: bool) -> Self {
+        self.1 = plus_sign;
+        self
+    }
+
+    /// Feeds a run of text, with character offsets starting at `at`, returning the offset after it (so text can be fed in chunks).
+    ///
+    /// This is the same as feeding each character, but outside of escapes, characters which decode to themselves are passed on without going through the state machine, which is much quicker for string-heavy documents.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumChar, DatumDecoder, DatumPipe};
+    /// let mut decoder = DatumDecoder::default();
+    /// let mut chars = Vec::new();
+    /// let next = decoder.feed_str(0, "\"a\\n", &mut |at, c: DatumChar| {
+    ///     chars.push((at, c.char()));
+    ///     Ok(())
+    /// }).unwrap();
+    /// let next = decoder.feed_str(next, "b\"", &mut |at, c: DatumChar| {
+    ///     chars.push((at, c.char()));
+    ///     Ok(())
+    /// }).unwrap();
+    /// decoder.feed(next, None, &mut |_, _| Ok(())).unwrap();
+    /// assert_eq!(chars, [(0, '"'), (1, 'a'), (2, '\n'), (4, 'b'), (5, '"')]);
+    /// ```
+    pub fn feed_str<F: FnMut(DatumOffset, DatumChar) -> DatumResult<()>>(
+        &mut self,
+        mut at: DatumOffset,
+        text: &str,
+        f: &mut F,
+    ) -> DatumResult<DatumOffset> {
+        for char in text.chars() {
+            let quick = if self.0 != DatumDecoderState::Normal {
+                None
+            } else if char >= '\u{80}' {
+                // everything outside of ASCII is content
+                Some(DatumChar::content(char))
+            } else {
+                // escapes, carriage returns and forbidden characters aren't identified
+                DatumChar::identify_with_plus_sign(char, self.1)
+            };
+            match quick {
+                Some(v) => f(at, v)?,
+                None => self.feed(at, Some(char), f)?,
+            }
+            at += 1;
+        }
+        Ok(at)
+    }
+}
+
+// Escapes only ever complete into a single character, and EOF never outputs.
 impl DatumBoundedPipe for DatumDecoder {
     type OutputQueueSize = unary::C1;
 }
@@ -57,7 +127,7 @@ impl DatumPipe for DatumDecoder {
                 if char == '\\' {
                     Ok(DatumDecoderState::Escaping(at))
                 } else {
-                    match DatumChar::identify(char) {
+                    match DatumChar::identify_with_plus_sign(char, self.1) {
                         Some(v) => {
                             f(at, v)?;
                             Ok(DatumDecoderState::Normal)
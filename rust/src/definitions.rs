@@ -0,0 +1,189 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::highlight::datum_highlight_lines;
+use crate::outline::atom_text;
+use crate::{DatumHighlightKind, DatumOffset, DatumSpan};
+
+/// Where a symbol is defined, from [DatumDefinitionIndex].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DatumDefinition {
+    /// Name of the document it's in, as given to [DatumDefinitionIndex::add_document].
+    pub document: String,
+    /// Where the symbol is, in characters (end inclusive).
+    pub span: DatumSpan,
+    /// Line the symbol is on (starting at 0, as in the Language Server Protocol).
+    pub line: usize,
+    /// Where the defining form is, in characters (end inclusive), from the `(` to the `)` (or the end of the text, if it isn't closed).
+    pub form: DatumSpan,
+}
+
+/// A list being walked by [DatumDefinitionIndex::add_document].
+struct DefinitionFrame {
+    start: DatumOffset,
+    /// Elements seen so far.
+    index: usize,
+    /// Which element defines a symbol, from the head.
+    position: Option<usize>,
+    /// If this list is in a defining position, so its own head is the symbol (as in `(define (name args...) ...)`), the index in the stack of the list it's defining for.
+    head_defines: Option<usize>,
+    /// Definitions waiting for this list to end, to fill in [DatumDefinition::form].
+    pending: Vec<usize>,
+}
+
+/// Index of where symbols are defined across documents, for go-to-definition in languages written in Datum.
+///
+/// What defines a symbol is configured with rules: "lists with this head symbol define the symbol at this position" (the head is at position 0).
+/// If the element at that position is a list, its head is the symbol instead, so `(define (f x) ...)` defines `f`. Lists are looked at however deeply they're nested.
+///
+/// Documents are indexed by name (i.e. a path or URI); adding a document again replaces what was found in it before, so an editor can re-index documents as they change.
+/// Like [crate::DatumHighlighter], this works on broken documents.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::DatumDefinitionIndex;
+/// let mut index = DatumDefinitionIndex::new().rule("def", 1).rule("define", 1);
+/// index.add_document("a.scm", "(def x 1)\n(define (f y) (+ x y))");
+/// index.add_document("b.scm", "(def f 2)");
+/// let f: Vec<_> = index.get("f").iter().map(|d| (d.document.as_str(), d.line, d.span.start)).collect();
+/// assert_eq!(f, [("a.scm", 1, 19), ("b.scm", 0, 5)]);
+/// // re-indexing replaces a document's definitions
+/// index.add_document("a.scm", "(def z 1)");
+/// assert!(index.get("x").is_empty());
+/// assert_eq!(index.get("f").len(), 1);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct DatumDefinitionIndex {
+    rules: BTreeMap<String, usize>,
+    definitions: BTreeMap<String, Vec<DatumDefinition>>,
+}
+
+impl DatumDefinitionIndex {
+    /// Creates an empty index with no rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule: lists with the given head symbol define the symbol at the given position.
+    ///
+    /// Only affects documents added afterwards.
+    pub fn rule(mut self, head: &str, position: usize) -> Self {
+        self.rules.insert(head.to_string(), position);
+        self
+    }
+
+    /// Indexes a document, replacing anything previously indexed under the same name.
+    pub fn add_document(&mut self, document: &str, source: &str) {
+        self.remove_document(document);
+        let mut found: Vec<(String, DatumDefinition)> = Vec::new();
+        let mut stack: Vec<DefinitionFrame> = Vec::new();
+        let mut end = 0;
+        for (span, text, line) in datum_highlight_lines(source) {
+            end = span.span.end;
+            let kind = span.kind;
+            if matches!(
+                kind,
+                DatumHighlightKind::Whitespace
+                    | DatumHighlightKind::Comment
+                    | DatumHighlightKind::Invalid
+            ) {
+                continue;
+            }
+            if kind == DatumHighlightKind::ListEnd {
+                if let Some(frame) = stack.pop() {
+                    for i in frame.pending {
+                        found[i].1.form = DatumSpan {
+                            start: frame.start,
+                            end: span.span.end,
+                        };
+                    }
+                }
+                continue;
+            }
+            // this is an element of the innermost list
+            let mut head_defines = None;
+            if let Some(top) = stack.len().checked_sub(1) {
+                let symbol = match kind {
+                    DatumHighlightKind::Symbol => atom_text(&span, text),
+                    _ => None,
+                };
+                let frame = &mut stack[top];
+                if frame.index == 0 {
+                    frame.position = symbol.as_ref().and_then(|s| self.rules.get(s)).copied();
+                }
+                // the list with the rule's head that this defines for
+                let owner = match frame.head_defines {
+                    Some(owner) if frame.index == 0 => Some(owner),
+                    _ if frame.index != 0 && frame.position == Some(frame.index) => Some(top),
+                    _ => None,
+                };
+                frame.index += 1;
+                if kind == DatumHighlightKind::ListStart {
+                    head_defines = owner;
+                } else if let (Some(owner), Some(symbol)) = (owner, symbol) {
+                    let owner = &mut stack[owner];
+                    owner.pending.push(found.len());
+                    found.push((
+                        symbol,
+                        DatumDefinition {
+                            document: document.to_string(),
+                            span: span.span,
+                            line,
+                            form: DatumSpan {
+                                start: owner.start,
+                                end: owner.start,
+                            },
+                        },
+                    ));
+                }
+            }
+            if kind == DatumHighlightKind::ListStart {
+                stack.push(DefinitionFrame {
+                    start: span.span.start,
+                    index: 0,
+                    position: None,
+                    head_defines,
+                    pending: Vec::new(),
+                });
+            }
+        }
+        // lists left open run to the end
+        for frame in stack {
+            for i in frame.pending {
+                found[i].1.form.end = end;
+            }
+        }
+        for (symbol, definition) in found {
+            self.definitions.entry(symbol).or_default().push(definition);
+        }
+    }
+
+    /// Removes everything indexed from a document.
+    pub fn remove_document(&mut self, document: &str) {
+        for definitions in self.definitions.values_mut() {
+            definitions.retain(|d| d.document != document);
+        }
+        self.definitions
+            .retain(|_, definitions| !definitions.is_empty());
+    }
+
+    /// Where a symbol is defined, in the order the documents were added and then in document order.
+    pub fn get(&self, symbol: &str) -> &[DatumDefinition] {
+        self.definitions.get(symbol).map_or(&[], |v| v.as_slice())
+    }
+
+    /// All the definitions, by symbol.
+    pub fn definitions(&self) -> &BTreeMap<String, Vec<DatumDefinition>> {
+        &self.definitions
+    }
+}
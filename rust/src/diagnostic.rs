@@ -0,0 +1,189 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::fmt::{Display, Write};
+
+use crate::{
+    DatumError, DatumErrorKind, DatumOffset, DatumToken, DatumWarning, DatumWarningKind,
+    DatumWriter,
+};
+
+/// Severity of a [DatumDiagnostic].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum DatumSeverity {
+    /// From a [DatumWarning].
+    Warning,
+    /// From a [DatumError].
+    Error,
+}
+
+impl DatumSeverity {
+    /// Lowercase name, as used in diagnostic output.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl Display for DatumSeverity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Range of offsets a [DatumDiagnostic] applies to (end inclusive).
+/// Errors and warnings only have a single offset, so for those, start and end are the same.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DatumSpan {
+    /// First offset.
+    pub start: DatumOffset,
+    /// Last offset.
+    pub end: DatumOffset,
+}
+
+/// A problem in a document, in a form suited to tools (i.e. editor plugins and CI).
+///
+/// These are made from [DatumError]s and [DatumWarning]s, and can be written as Datum ([DatumDiagnostic::write_datum]) or as JSON ([DatumDiagnostic::write_json]).
+///
+/// The code is a stable, lowercase, hyphenated name for the kind of problem (i.e. `bad-data`, `trailing-whitespace`).
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_error, DatumDiagnostic};
+/// let diagnostic = DatumDiagnostic::from(datum_error!(BadData, 4, "decoder: forbidden character"));
+/// let mut text = String::new();
+/// diagnostic.write_datum(&mut text).unwrap();
+/// assert!(text.starts_with("(severity error code bad-data start 4 end 4 message "));
+/// let mut json = String::new();
+/// diagnostic.write_json(&mut json).unwrap();
+/// assert!(json.starts_with("{\"severity\":\"error\",\"code\":\"bad-data\",\"start\":4,\"end\":4,"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DatumDiagnostic {
+    /// Severity.
+    pub severity: DatumSeverity,
+    /// Stable code for the kind of problem.
+    pub code: &'static str,
+    /// Location.
+    pub span: DatumSpan,
+    /// Message. (Beware: If `detailed_errors` is off, error messages are empty.)
+    pub message: &'static str,
+}
+
+impl From<DatumError> for DatumDiagnostic {
+    fn from(e: DatumError) -> Self {
+        let code = match e.kind {
+            DatumErrorKind::OutOfRoom => "out-of-room",
+            DatumErrorKind::Interrupted => "interrupted",
+            DatumErrorKind::BadData => "bad-data",
+            DatumErrorKind::Cancelled => "cancelled",
+            DatumErrorKind::Custom => "custom",
+        };
+        Self {
+            severity: DatumSeverity::Error,
+            code,
+            span: DatumSpan {
+                start: e.offset,
+                end: e.offset,
+            },
+            message: e.message,
+        }
+    }
+}
+
+impl From<DatumWarning> for DatumDiagnostic {
+    fn from(w: DatumWarning) -> Self {
+        let code = match w.kind {
+            DatumWarningKind::UnknownEscape => "unknown-escape",
+            DatumWarningKind::TrailingWhitespace => "trailing-whitespace",
+            DatumWarningKind::NonCanonicalSpecialID => "non-canonical-special-id",
+            DatumWarningKind::PlusSign => "plus-sign",
+        };
+        Self {
+            severity: DatumSeverity::Warning,
+            code,
+            span: DatumSpan {
+                start: w.offset,
+                end: w.offset,
+            },
+            message: w.message,
+        }
+    }
+}
+
+impl DatumDiagnostic {
+    /// Writes this diagnostic as a Datum key/value list, i.e. `(severity warning code plus-sign start 1 end 1 message "...")`.
+    ///
+    /// No newline is written.
+    pub fn write_datum(&self, f: &mut dyn Write) -> core::fmt::Result {
+        let mut writer = DatumWriter::default();
+        let tokens: [DatumToken<&str>; 12] = [
+            DatumToken::ListStart(0),
+            DatumToken::Symbol(0, "severity"),
+            DatumToken::Symbol(0, self.severity.name()),
+            DatumToken::Symbol(0, "code"),
+            DatumToken::Symbol(0, self.code),
+            DatumToken::Symbol(0, "start"),
+            DatumToken::Integer(0, self.span.start as i64),
+            DatumToken::Symbol(0, "end"),
+            DatumToken::Integer(0, self.span.end as i64),
+            DatumToken::Symbol(0, "message"),
+            DatumToken::String(0, self.message),
+            DatumToken::ListEnd(0),
+        ];
+        for token in &tokens {
+            writer.write_token(f, token)?;
+        }
+        Ok(())
+    }
+
+    /// Writes this diagnostic as a JSON object with the same keys as [DatumDiagnostic::write_datum].
+    ///
+    /// No newline is written, so a sequence of these separated by newlines is valid JSON Lines.
+    pub fn write_json(&self, f: &mut dyn Write) -> core::fmt::Result {
+        write!(
+            f,
+            "{{\"severity\":\"{}\",\"code\":\"{}\",\"start\":{},\"end\":{},\"message\":",
+            self.severity, self.code, self.span.start, self.span.end
+        )?;
+        write_json_string(f, self.message)?;
+        f.write_char('}')
+    }
+}
+
+impl Display for DatumDiagnostic {
+    /// Human-readable form, i.e. `error[bad-data] @ 4: decoder: forbidden character`.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}[{}] @ {}", self.severity, self.code, self.span.start)?;
+        if self.span.end != self.span.start {
+            write!(f, "-{}", self.span.end)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+fn write_json_string(f: &mut dyn Write, text: &str) -> core::fmt::Result {
+    f.write_char('"')?;
+    for c in text.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
@@ -0,0 +1,211 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::convert::TryFrom;
+use core::fmt::Display;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::string::ToString;
+use std::vec::Vec;
+
+use crate::{DatumContextError, DatumDiagnostic, DatumDocument};
+
+/// Why a file couldn't be loaded by [DatumDirLoader].
+///
+/// _Added in 1.3.0._
+#[derive(Debug)]
+pub enum DatumFileError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file couldn't be parsed. The error's source name is the file's path, and offsets are in bytes.
+    Parse(DatumContextError),
+}
+
+impl Display for DatumFileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::Parse(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DatumFileError {}
+
+/// A file loaded by [DatumDirLoader].
+///
+/// _Added in 1.3.0._
+#[derive(Debug)]
+pub struct DatumLoadedFile {
+    /// Path of the file (the directory's path joined with the file's path within it).
+    pub path: PathBuf,
+    /// The file's document, or why it couldn't be loaded.
+    pub result: Result<DatumDocument, DatumFileError>,
+}
+
+/// The results of [DatumDirLoader::load].
+///
+/// Displaying this writes every error, one per line, each starting with the file's path.
+///
+/// _Added in 1.3.0._
+#[derive(Debug, Default)]
+pub struct DatumDirReport {
+    /// Every matching file, sorted by path.
+    pub files: Vec<DatumLoadedFile>,
+}
+
+impl DatumDirReport {
+    /// Returns true if every file loaded.
+    pub fn is_ok(&self) -> bool {
+        self.files.iter().all(|file| file.result.is_ok())
+    }
+
+    /// Documents that loaded, in order, with their paths.
+    pub fn documents(&self) -> impl Iterator<Item = (&Path, &DatumDocument)> {
+        self.files
+            .iter()
+            .filter_map(|file| Some((file.path.as_path(), file.result.as_ref().ok()?)))
+    }
+
+    /// Errors, in order, with their paths.
+    pub fn errors(&self) -> impl Iterator<Item = (&Path, &DatumFileError)> {
+        self.files
+            .iter()
+            .filter_map(|file| Some((file.path.as_path(), file.result.as_ref().err()?)))
+    }
+
+    /// Parse errors as [DatumDiagnostic]s, for tools, in order, with their paths.
+    ///
+    /// I/O errors have no diagnostic form, and so aren't included; see [DatumDirReport::errors].
+    pub fn diagnostics(&self) -> impl Iterator<Item = (&Path, DatumDiagnostic)> {
+        self.errors().filter_map(|(path, e)| match e {
+            DatumFileError::Parse(e) => Some((path, DatumDiagnostic::from(e.error))),
+            DatumFileError::Io(_) => None,
+        })
+    }
+}
+
+impl Display for DatumDirReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (path, e) in self.errors() {
+            match e {
+                // already named
+                DatumFileError::Parse(e) => writeln!(f, "{}", e)?,
+                DatumFileError::Io(e) => writeln!(f, "{}: {}", path.display(), e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads every `*.scm` and `*.datum` file in a directory, i.e. a "config.d" directory of fragments.
+///
+/// Files are loaded in order of path, so that the order they're applied in is predictable (fragments are often named like `10-base.scm`, `20-local.scm`).
+/// A file that can't be loaded doesn't stop the others from loading; all of the errors are reported together in the [DatumDirReport].
+///
+/// Hidden files (starting with `.`) are skipped, as editors leave backups and such there.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::DatumDirLoader;
+/// let dir = std::env::temp_dir().join(format!("datum-dir-doctest-{}", std::process::id()));
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("20-local.scm"), "(port 8080)").unwrap();
+/// std::fs::write(dir.join("10-base.datum"), "(host \"example.com\") (port 80)").unwrap();
+/// std::fs::write(dir.join("30-broken.scm"), "(port").unwrap();
+/// std::fs::write(dir.join("README.txt"), "not a config file").unwrap();
+/// let report = DatumDirLoader::new().load(&dir).unwrap();
+/// assert_eq!(report.files.len(), 3);
+/// let values: Vec<String> = report.documents().flat_map(|(_, doc)| doc.iter().map(|v| v.to_string())).collect();
+/// assert_eq!(values, ["(host \"example.com\")", "(port 80)", "(port 8080)"]);
+/// assert!(!report.is_ok());
+/// assert!(report.to_string().contains("30-broken.scm: "));
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DatumDirLoader {
+    recursive: bool,
+}
+
+impl DatumDirLoader {
+    /// Creates a loader, which by default only looks at the directory itself (not subdirectories).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets if subdirectories (other than hidden ones) are loaded too.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Returns true if the loader would load a file with this name.
+    pub fn matches(name: &Path) -> bool {
+        let hidden = match name.file_name() {
+            Some(v) => v.to_string_lossy().starts_with('.'),
+            None => true,
+        };
+        let extension = name.extension().and_then(|v| v.to_str());
+        !hidden && matches!(extension, Some("scm") | Some("datum"))
+    }
+
+    fn find(&self, dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.is_dir() {
+                if self.recursive && !entry.file_name().to_string_lossy().starts_with('.') {
+                    self.find(&path, paths)?;
+                }
+            } else if Self::matches(&path) {
+                paths.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Lists the files in a directory that would be loaded, sorted by path.
+    pub fn list<P: AsRef<Path>>(&self, dir: P) -> io::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        self.find(dir.as_ref(), &mut paths)?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Loads the files in a directory.
+    ///
+    /// Errors listing the directory (or a subdirectory) are returned directly, as then it's not known which files are missing.
+    pub fn load<P: AsRef<Path>>(&self, dir: P) -> io::Result<DatumDirReport> {
+        datum_span!(
+            span,
+            "datum_load_dir",
+            dir = %dir.as_ref().display(),
+            files = tracing::field::Empty,
+            errors = tracing::field::Empty
+        );
+        let files = self
+            .list(dir)?
+            .into_iter()
+            .map(|path| {
+                let result = match std::fs::read(&path) {
+                    Ok(bytes) => DatumDocument::try_from(&bytes[..]).map_err(|e| {
+                        DatumFileError::Parse(
+                            DatumContextError::from_bytes(e, &bytes)
+                                .with_source_name(path.display().to_string()),
+                        )
+                    }),
+                    Err(e) => Err(DatumFileError::Io(e)),
+                };
+                DatumLoadedFile { path, result }
+            })
+            .collect();
+        let report = DatumDirReport { files };
+        datum_span_record!(span, "files", report.files.len());
+        datum_span_record!(span, "errors", report.errors().count());
+        Ok(report)
+    }
+}
@@ -0,0 +1,129 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::vec::Vec;
+use core::{
+    convert::TryFrom,
+    fmt::Display,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+use crate::{
+    datum_byte_to_value_pipeline, datum_char_to_value_pipeline, DatumContextError, DatumError,
+    DatumPipe, DatumValue, DatumValueMetrics,
+};
+
+/// A whole parsed document: the sequence of top-level values.
+///
+/// This exists so that the standard conversion traits can be used to parse entire buffers.
+/// (They can't be implemented on `Vec<DatumValue>` directly, as neither [TryFrom] nor [Vec] are local to this crate.)
+///
+/// _Added in 1.3.0._
+/// ```
+/// use core::convert::TryInto;
+/// use datum::{DatumDocument, DatumValue};
+/// fn parse(input: &str) -> Result<Vec<DatumValue>, datum::DatumError> {
+///     let doc: DatumDocument = input.try_into()?;
+///     Ok(doc.into())
+/// }
+/// assert_eq!(parse("a (b c)").unwrap().len(), 2);
+/// assert!(parse("(").is_err());
+/// ```
+#[derive(Clone, PartialEq, PartialOrd, Debug, Default, Hash)]
+pub struct DatumDocument(pub Vec<DatumValue>);
+
+impl DatumDocument {
+    /// Measures the whole document; see [DatumValueMetrics].
+    /// The node count includes every top-level value.
+    ///
+    /// _Added in 1.3.0._
+    pub fn metrics(&self) -> DatumValueMetrics {
+        DatumValueMetrics::measure(&self.0)
+    }
+
+    /// Parses a document from text, like `TryFrom<&str>`, but errors say which document they came from (i.e. a file path), and include context.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::DatumDocument;
+    /// let error = DatumDocument::parse_named("(a", "config/main.scm").unwrap_err();
+    /// assert_eq!(error.source_name.as_deref(), Some("config/main.scm"));
+    /// assert!(error.to_string().starts_with("config/main.scm: "));
+    /// ```
+    pub fn parse_named(text: &str, name: &str) -> Result<Self, DatumContextError> {
+        Self::try_from(text)
+            .map_err(|e| DatumContextError::from_chars(e, text).with_source_name(name))
+    }
+}
+
+impl Deref for DatumDocument {
+    type Target = Vec<DatumValue>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for DatumDocument {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<DatumValue>> for DatumDocument {
+    fn from(value: Vec<DatumValue>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<DatumDocument> for Vec<DatumValue> {
+    fn from(value: DatumDocument) -> Self {
+        value.0
+    }
+}
+
+impl IntoIterator for DatumDocument {
+    type Item = DatumValue;
+    type IntoIter = alloc::vec::IntoIter<DatumValue>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl TryFrom<&str> for DatumDocument {
+    type Error = DatumError;
+    /// Parses a document from text. Offsets in errors are in characters.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(Self(
+            datum_char_to_value_pipeline().feed_all_to_vec(value.chars(), true)?,
+        ))
+    }
+}
+
+impl TryFrom<&[u8]> for DatumDocument {
+    type Error = DatumError;
+    /// Parses a document from UTF-8 bytes. Offsets in errors are in bytes.
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Self(
+            datum_byte_to_value_pipeline().feed_all_to_vec(value.iter().copied(), true)?,
+        ))
+    }
+}
+
+impl FromStr for DatumDocument {
+    type Err = DatumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl Display for DatumDocument {
+    /// Writes each top-level value on its own line.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.write_canonical(f)
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::{collections::BTreeMap, string::String, string::ToString};
+use core::{fmt::Display, marker::PhantomData, ops::Deref};
+
+use crate::{
+    datum_error, unary, DatumAtom, DatumBoundedPipe, DatumError, DatumNode, DatumOffset, DatumPipe,
+    DatumResult, DatumValueNode,
+};
+
+/// A record name seen twice by [DatumDuplicateKeyPipe].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DatumDuplicateKey {
+    /// The record name.
+    pub key: String,
+    /// Where the first record with this name starts.
+    pub first: DatumOffset,
+    /// Where the repeat starts.
+    pub second: DatumOffset,
+}
+
+impl DatumDuplicateKey {
+    /// Converts this into a [DatumError] at the repeat, for when duplicates are fatal.
+    pub fn to_error(&self) -> DatumError {
+        datum_error!(BadData, self.second, "duplicate key")
+    }
+}
+
+impl Display for DatumDuplicateKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "duplicate key {:?} @ {} (first @ {})",
+            self.key, self.second, self.first
+        )
+    }
+}
+
+/// Transparent pipeline stage over values, checking the common `(name ...)` document shape for repeated names.
+///
+/// A top-level value is a record if it's a list starting with a symbol or string; that first element is its name. Symbols and strings with the same text are the same name. Other values pass through unchecked.
+///
+/// Repeats are reported to the callback with both offsets (as supplied by the previous stage, i.e. where a [crate::DatumParser] value starts). If the callback returns an error, it's passed on; [DatumDuplicateKey::to_error] makes one.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumDuplicateKeyPipe, DatumPipe, datum_char_to_value_pipeline};
+/// let source = "(alpha 1)\n(beta 2)\n(alpha 3)";
+/// let mut duplicates = Vec::new();
+/// let mut pipeline = datum_char_to_value_pipeline().compose(DatumDuplicateKeyPipe::new(|dup| {
+///     duplicates.push(dup);
+///     Ok(())
+/// }));
+/// assert_eq!(pipeline.feed_all_to_vec(source.chars(), true).unwrap().len(), 3);
+/// drop(pipeline);
+/// assert_eq!((duplicates[0].key.as_str(), duplicates[0].first, duplicates[0].second), ("alpha", 0, 19));
+/// // or stop at the first one
+/// let mut pipeline = datum_char_to_value_pipeline().compose(DatumDuplicateKeyPipe::new(|dup| Err(dup.to_error())));
+/// assert_eq!(pipeline.feed_all_to_vec(source.chars(), true).unwrap_err().offset, 19);
+/// ```
+pub struct DatumDuplicateKeyPipe<V: DatumValueNode, F: FnMut(DatumDuplicateKey) -> DatumResult<()>>
+{
+    seen: BTreeMap<String, DatumOffset>,
+    report: F,
+    _value: PhantomData<V>,
+}
+
+impl<V: DatumValueNode, F: FnMut(DatumDuplicateKey) -> DatumResult<()>>
+    DatumDuplicateKeyPipe<V, F>
+{
+    /// Creates a new DatumDuplicateKeyPipe reporting to the given callback.
+    pub fn new(report: F) -> Self {
+        Self {
+            seen: BTreeMap::new(),
+            report,
+            _value: PhantomData,
+        }
+    }
+
+    /// Where each name seen so far was first seen.
+    pub fn seen(&self) -> &BTreeMap<String, DatumOffset> {
+        &self.seen
+    }
+}
+
+impl<V: DatumValueNode, F: FnMut(DatumDuplicateKey) -> DatumResult<()>> DatumPipe
+    for DatumDuplicateKeyPipe<V, F>
+{
+    type Input = V;
+    type Output = V;
+    fn feed<G: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut G,
+    ) -> DatumResult<()> {
+        if let Some(value) = i {
+            let first = match value.node() {
+                DatumNode::List(list) => list.first().map(V::node),
+                _ => None,
+            };
+            let name = match first {
+                Some(DatumNode::Atom(DatumAtom::Symbol(name)))
+                | Some(DatumNode::Atom(DatumAtom::String(name))) => Some(name.deref()),
+                _ => None,
+            };
+            if let Some(name) = name {
+                if let Some(first) = self.seen.get(name) {
+                    (self.report)(DatumDuplicateKey {
+                        key: name.to_string(),
+                        first: *first,
+                        second: at,
+                    })?;
+                } else {
+                    self.seen.insert(name.to_string(), at);
+                }
+            }
+            f(at, value)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<V: DatumValueNode, F: FnMut(DatumDuplicateKey) -> DatumResult<()>> DatumBoundedPipe
+    for DatumDuplicateKeyPipe<V, F>
+{
+    type OutputQueueSize = unary::C1;
+}
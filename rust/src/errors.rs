@@ -7,6 +7,12 @@
 
 use core::fmt::Display;
 
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::DatumCharClass;
+
 /// Any error producible by Datum.
 #[non_exhaustive]
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -19,6 +25,10 @@ pub enum DatumErrorKind {
     Interrupted,
     /// Bad data.
     BadData,
+    /// Cancelled by request, i.e. via [crate::DatumCancelPipe].
+    ///
+    /// _Added in 1.3.0._
+    Cancelled,
     /// Custom error signal. Will never be generated by Datum.
     /// Beware that external libraries may have their own user stages.
     Custom,
@@ -45,9 +55,10 @@ pub struct DatumError {
     pub message: &'static str,
 }
 
-// this should be migrated to core::error::Error once that stabilizes
+// With `core_error`, `core::error::Error` is implemented instead; `std::error::Error` is the same trait, so std users lose nothing.
+// It isn't unconditional as it needs a newer compiler than the MSRV.
 
-#[cfg(feature = "std")]
+#[cfg(all(feature = "std", not(feature = "core_error")))]
 impl std::error::Error for DatumError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         None
@@ -60,6 +71,19 @@ impl std::error::Error for DatumError {
     }
 }
 
+#[cfg(feature = "core_error")]
+impl core::error::Error for DatumError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        None
+    }
+    fn description(&self) -> &str {
+        self.message
+    }
+    fn cause(&self) -> Option<&dyn core::error::Error> {
+        None
+    }
+}
+
 impl Display for DatumError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{} @ {}: {}", self.kind, self.offset, self.message)
@@ -94,5 +118,160 @@ macro_rules! datum_error {
     };
 }
 
+/// [DatumError] with formatted context, i.e. the offending character and the text of the token around it.
+///
+/// As [DatumError] is `Copy` and its message is `&'static str`, it can't carry details of the input. This can, so errors are as useful without `std` (or without `detailed_errors`).
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumContextError, DatumDocument};
+/// use std::convert::TryFrom;
+/// let source = "(a b\\";
+/// let error = DatumDocument::try_from(source).unwrap_err();
+/// let error = DatumContextError::from_chars(error, source);
+/// assert_eq!(error.context, "at end of input, after \"b\\\\\"");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct DatumContextError {
+    /// The underlying error.
+    pub error: DatumError,
+    /// Context (may be empty).
+    pub context: String,
+    /// Name of the document the error came from (i.e. a file path or URI), if known. Shown before the error.
+    ///
+    /// This matters when errors from several documents end up in one place, i.e. with includes or batch tools.
+    ///
+    /// _Added in 1.3.0._
+    pub source_name: Option<String>,
+}
+
+/// Limit on how far a [DatumContextError] token window extends either side of the error.
+#[cfg(feature = "alloc")]
+const CONTEXT_RADIUS: usize = 32;
+
+/// Finds the token (or as much of it as is within [CONTEXT_RADIUS]) around an index.
+#[cfg(feature = "alloc")]
+fn context_window<T: Copy>(
+    items: &[T],
+    at: usize,
+    is_delimiter: impl Fn(T) -> bool,
+) -> (usize, usize) {
+    let at = at.min(items.len());
+    let mut start = at;
+    while start > 0 && at - start < CONTEXT_RADIUS && !is_delimiter(items[start - 1]) {
+        start -= 1;
+    }
+    let mut end = at;
+    while end < items.len() && end - at < CONTEXT_RADIUS && !is_delimiter(items[end]) {
+        end += 1;
+    }
+    (start, end)
+}
+
+#[cfg(feature = "alloc")]
+fn is_delimiter_class(class: Option<DatumCharClass>) -> bool {
+    matches!(
+        class,
+        Some(DatumCharClass::Whitespace)
+            | Some(DatumCharClass::Newline)
+            | Some(DatumCharClass::ListStart)
+            | Some(DatumCharClass::ListEnd)
+    )
+}
+
+#[cfg(feature = "alloc")]
+impl DatumContextError {
+    /// Creates a DatumContextError with the given context.
+    pub fn new(error: DatumError, context: String) -> Self {
+        Self {
+            error,
+            context,
+            source_name: None,
+        }
+    }
+
+    /// Sets [DatumContextError::source_name].
+    ///
+    /// _Added in 1.3.0._
+    pub fn with_source_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.source_name = Some(name.into());
+        self
+    }
+
+    /// Creates a DatumContextError from an error and the text it came from, assuming offsets are in characters (as from [crate::DatumDocument]'s `TryFrom<&str>`).
+    pub fn from_chars(error: DatumError, source: &str) -> Self {
+        let chars: Vec<char> = source.chars().collect();
+        let at = error.offset.min(chars.len() as DatumOffset) as usize;
+        let (start, end) = context_window(&chars, at, |c| {
+            is_delimiter_class(DatumCharClass::identify(c))
+        });
+        let window: String = chars[start..end].iter().collect();
+        Self::new(error, Self::describe(chars.get(at).copied(), &window))
+    }
+
+    /// Creates a DatumContextError from an error and the UTF-8 bytes it came from, assuming offsets are in bytes (as from [crate::DatumDocument]'s `TryFrom<&[u8]>`).
+    ///
+    /// Invalid UTF-8 is shown with replacement characters.
+    pub fn from_bytes(error: DatumError, source: &[u8]) -> Self {
+        let at = error.offset.min(source.len() as DatumOffset) as usize;
+        let (start, end) = context_window(source, at, |b| {
+            is_delimiter_class(DatumCharClass::identify_byte(b))
+        });
+        let window = String::from_utf8_lossy(&source[start..end]);
+        let found = source.get(at).map(|b| {
+            if *b < 0x80 {
+                *b as char
+            } else {
+                char::REPLACEMENT_CHARACTER
+            }
+        });
+        Self::new(error, Self::describe(found, &window))
+    }
+
+    fn describe(found: Option<char>, window: &str) -> String {
+        match found {
+            Some(c) => format!("at {:?} in {:?}", c, window),
+            None if window.is_empty() => String::from("at end of input"),
+            None => format!("at end of input, after {:?}", window),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<DatumError> for DatumContextError {
+    fn from(error: DatumError) -> Self {
+        Self::new(error, String::new())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for DatumContextError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(name) = &self.source_name {
+            write!(f, "{}: ", name)?;
+        }
+        if self.context.is_empty() {
+            self.error.fmt(f)
+        } else {
+            write!(f, "{} ({})", self.error, self.context)
+        }
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "core_error")))]
+impl std::error::Error for DatumContextError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "core_error"))]
+impl core::error::Error for DatumContextError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
 /// Datum result for the given value.
 pub type DatumResult<V> = Result<V, DatumError>;
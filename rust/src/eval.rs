@@ -0,0 +1,268 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use crate::{
+    datum_char_to_value_pipeline, datum_error, DatumAtom, DatumError, DatumOffset, DatumPipe,
+    DatumValue,
+};
+
+/// Error from [DatumEvaluator::eval_str]: the evaluator's error, and where the top-level form it came from starts.
+///
+/// Parse errors are converted into the evaluator's error type, and are at the offset the parser reported.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DatumEvalError<E> {
+    /// Offset (in characters) of the top-level form being evaluated, or of the parse error.
+    pub offset: DatumOffset,
+    /// The error itself.
+    pub error: E,
+}
+
+impl<E: Display> Display for DatumEvalError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "@ {}: {}", self.offset, self.error)
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "core_error")))]
+impl<E: std::error::Error + 'static> std::error::Error for DatumEvalError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(feature = "core_error")]
+impl<E: core::error::Error + 'static> core::error::Error for DatumEvalError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Scaffold for small Datum-scripted languages, in the shape of the calculator example: lists are dispatched on their head symbol, either to a special form (which sees its arguments unevaluated) or to a call (which sees them evaluated).
+///
+/// Implementations supply [DatumEvaluator::eval_atom] and [DatumEvaluator::eval_call], and usually [DatumEvaluator::eval_special_form]; variables can be kept in a [DatumEnvironment].
+///
+/// Errors must be convertible from [DatumError], which is used for parse errors and by the default methods.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumAtom, DatumEnvironment, DatumError, DatumEvaluator, DatumValue, datum_error};
+/// struct Calc(DatumEnvironment<i64>);
+/// impl DatumEvaluator for Calc {
+///     type Output = i64;
+///     type Error = DatumError;
+///     fn eval_atom(&mut self, atom: &DatumAtom<String>) -> Result<i64, DatumError> {
+///         match atom {
+///             DatumAtom::Integer(v) => Ok(*v),
+///             DatumAtom::Symbol(name) => self.0.get(name).copied().ok_or(datum_error!(BadData, 0, "unknown variable")),
+///             _ => Err(datum_error!(BadData, 0, "not a number")),
+///         }
+///     }
+///     fn eval_special_form(&mut self, head: &str, args: &[DatumValue]) -> Option<Result<i64, DatumError>> {
+///         match (head, args) {
+///             ("set", [DatumValue::Atom(DatumAtom::Symbol(name)), value]) => Some(self.eval(value).map(|v| {
+///                 self.0.define(name, v);
+///                 v
+///             })),
+///             _ => None,
+///         }
+///     }
+///     fn eval_call(&mut self, head: &str, args: Vec<i64>) -> Result<i64, DatumError> {
+///         match head {
+///             "+" => Ok(args.iter().sum()),
+///             "*" => Ok(args.iter().product()),
+///             _ => Err(datum_error!(BadData, 0, "unknown function")),
+///         }
+///     }
+/// }
+/// let mut calc = Calc(DatumEnvironment::new());
+/// assert_eq!(calc.eval_str("(set x 6) (* x (+ 3 4))").unwrap(), vec![6, 42]);
+/// // errors are at the form they came from
+/// assert_eq!(calc.eval_str("1\n(+ y 1)").unwrap_err().offset, 2);
+/// ```
+pub trait DatumEvaluator {
+    /// Result of evaluating a value.
+    type Output;
+    /// Error type.
+    type Error: From<DatumError>;
+
+    /// Evaluates an atom (i.e. looks up a symbol, or converts a number).
+    fn eval_atom(&mut self, atom: &DatumAtom<String>) -> Result<Self::Output, Self::Error>;
+
+    /// Evaluates a call to the function named by a list's head symbol, with its evaluated arguments.
+    fn eval_call(
+        &mut self,
+        head: &str,
+        args: Vec<Self::Output>,
+    ) -> Result<Self::Output, Self::Error>;
+
+    /// Evaluates a special form, given its head symbol and its (unevaluated) arguments.
+    /// Returns [None] if the head symbol isn't a special form, so the list is a call.
+    ///
+    /// By default, there are no special forms.
+    fn eval_special_form(
+        &mut self,
+        head: &str,
+        args: &[DatumValue],
+    ) -> Option<Result<Self::Output, Self::Error>> {
+        let _ = (head, args);
+        None
+    }
+
+    /// Evaluates a list that doesn't start with a symbol (including the empty list).
+    ///
+    /// By default, this is an error.
+    fn eval_other_list(&mut self, list: &[DatumValue]) -> Result<Self::Output, Self::Error> {
+        let _ = list;
+        Err(datum_error!(BadData, 0, "eval: list doesn't start with a symbol").into())
+    }
+
+    /// Evaluates a value, dispatching to the other methods.
+    fn eval(&mut self, value: &DatumValue) -> Result<Self::Output, Self::Error> {
+        match value {
+            DatumValue::Atom(atom) => self.eval_atom(atom),
+            DatumValue::List(list) => match list.split_first() {
+                Some((DatumValue::Atom(DatumAtom::Symbol(head)), args)) => {
+                    if let Some(result) = self.eval_special_form(head, args) {
+                        return result;
+                    }
+                    let mut evaluated = Vec::with_capacity(args.len());
+                    for arg in args {
+                        evaluated.push(self.eval(arg)?);
+                    }
+                    self.eval_call(head, evaluated)
+                }
+                _ => self.eval_other_list(list),
+            },
+        }
+    }
+
+    /// Parses and evaluates each top-level form of some text in turn, stopping at the first error.
+    fn eval_str(&mut self, source: &str) -> Result<Vec<Self::Output>, DatumEvalError<Self::Error>> {
+        let mut pipeline = datum_char_to_value_pipeline();
+        let mut results = Vec::new();
+        let mut failure = None;
+        let mut output = |at, value: DatumValue| match self.eval(&value) {
+            Ok(result) => {
+                results.push(result);
+                Ok(())
+            }
+            Err(error) => {
+                failure = Some(DatumEvalError { offset: at, error });
+                Err(datum_error!(Cancelled, at, "eval: form failed"))
+            }
+        };
+        let mut at = 0;
+        let mut parsed = Ok(());
+        for c in source.chars() {
+            parsed = pipeline.feed(at, Some(c), &mut output);
+            if parsed.is_err() {
+                break;
+            }
+            at += 1;
+        }
+        if parsed.is_ok() {
+            parsed = pipeline.feed(at, None, &mut output);
+        }
+        match (failure, parsed) {
+            (Some(failure), _) => Err(failure),
+            (None, Err(error)) => Err(DatumEvalError {
+                offset: error.offset,
+                error: error.into(),
+            }),
+            (None, Ok(())) => Ok(results),
+        }
+    }
+}
+
+/// Nested scopes of named values, for a [DatumEvaluator]'s variables.
+///
+/// There's always a global scope; [DatumEnvironment::push_scope] and [DatumEnvironment::pop_scope] add and remove inner scopes (i.e. for function calls or `let`).
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::DatumEnvironment;
+/// let mut env = DatumEnvironment::new();
+/// env.define("x", 1);
+/// env.push_scope();
+/// env.define("x", 2);
+/// assert!(env.set("x", 3));
+/// assert_eq!(env.get("x"), Some(&3));
+/// env.pop_scope();
+/// assert_eq!(env.get("x"), Some(&1));
+/// assert!(!env.set("y", 1));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DatumEnvironment<V> {
+    scopes: Vec<BTreeMap<String, V>>,
+}
+
+impl<V> Default for DatumEnvironment<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> DatumEnvironment<V> {
+    /// Creates an environment with an empty global scope.
+    pub fn new() -> Self {
+        Self {
+            scopes: alloc::vec![BTreeMap::new()],
+        }
+    }
+
+    /// Looks up a name, innermost scope first.
+    pub fn get(&self, name: &str) -> Option<&V> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// Defines (or redefines) a name in the innermost scope.
+    pub fn define(&mut self, name: &str, value: V) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+    }
+
+    /// Changes the value of a name where it's defined (innermost first). Returns false if it isn't defined.
+    pub fn set(&mut self, name: &str, value: V) -> bool {
+        match self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+        {
+            Some(existing) => {
+                *existing = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts a new innermost scope.
+    pub fn push_scope(&mut self) {
+        self.scopes.push(BTreeMap::new());
+    }
+
+    /// Ends the innermost scope, dropping its names. The global scope can't be removed.
+    pub fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    /// How many scopes are in use, including the global scope.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+}
@@ -0,0 +1,97 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{
+    datum_error, datum_skip_value, DatumParser, DatumPipe, DatumResult, DatumToken, DatumValue,
+};
+
+/// Reads one value from a token iterator.
+fn datum_read_value(
+    iterator: &mut dyn Iterator<Item = DatumResult<DatumToken<String>>>,
+) -> DatumResult<Option<DatumValue>> {
+    let mut parser = DatumParser::default();
+    let mut result = None;
+    let mut last_seen_offset = 0;
+    while result.is_none() {
+        let token = iterator.next().transpose()?;
+        if let Some(token) = &token {
+            last_seen_offset = token.offset();
+        }
+        let eof = token.is_none();
+        parser.feed(last_seen_offset, token, &mut |_, v| {
+            result = Some(v);
+            Ok(())
+        })?;
+        if eof {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+/// Pulls the values of specific keys out of a document laid out as key/value pairs (i.e. a struct written with the serde `DocLayout::Root`).
+///
+/// Values of other keys are skipped with [datum_skip_value], and reading stops as soon as every key has been found, so this is cheap even for large files with the interesting keys at the start.
+///
+/// The results are in the same order as the requested keys. If a key appears more than once, the first value is used.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_char_to_token_pipeline, datum_extract_fields, DatumAtom, DatumValue, IntoViaDatumPipe};
+/// let source = "name \"example\" version 3 data (huge (list of) stuff)";
+/// let mut tokens = source.chars().via_datum_pipe(datum_char_to_token_pipeline());
+/// let fields = datum_extract_fields(&mut tokens, &["version", "name", "author"]).unwrap();
+/// assert_eq!(fields[0], Some(DatumValue::Atom(DatumAtom::Integer(3))));
+/// assert_eq!(fields[1], Some(DatumValue::Atom(DatumAtom::String("example".to_string()))));
+/// assert_eq!(fields[2], None);
+/// ```
+pub fn datum_extract_fields(
+    iterator: &mut dyn Iterator<Item = DatumResult<DatumToken<String>>>,
+    keys: &[&str],
+) -> DatumResult<Vec<Option<DatumValue>>> {
+    let mut results: Vec<Option<DatumValue>> = keys.iter().map(|_| None).collect();
+    let mut remaining = keys.len();
+    while remaining > 0 {
+        let (at, key) = match iterator.next().transpose()? {
+            None => break,
+            Some(DatumToken::Symbol(at, key)) | Some(DatumToken::String(at, key)) => (at, key),
+            Some(token) => {
+                return Err(datum_error!(
+                    BadData,
+                    token.offset(),
+                    "extract: expected key, got something else"
+                ))
+            }
+        };
+        let index = keys
+            .iter()
+            .zip(results.iter())
+            .position(|(k, v)| v.is_none() && *k == key);
+        if let Some(index) = index {
+            let value = datum_read_value(iterator)?;
+            if value.is_none() {
+                return Err(datum_error!(
+                    Interrupted,
+                    at,
+                    "extract: unexpected EOF, expected value"
+                ));
+            }
+            results[index] = value;
+            remaining -= 1;
+        } else if !datum_skip_value(iterator)? {
+            return Err(datum_error!(
+                Interrupted,
+                at,
+                "extract: unexpected EOF, expected value"
+            ));
+        }
+    }
+    Ok(results)
+}
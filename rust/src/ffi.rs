@@ -0,0 +1,338 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+//! C interface to the byte-to-token parser and the writer.
+//!
+//! The matching header is `ffi/datum.h` (next to `Cargo.toml`).
+//! To get a C library, make a crate with `crate-type = ["cdylib"]` (or `staticlib`) which depends on this one with the `ffi` feature, and `pub use datum::ffi::*;`.
+//! (This crate doesn't build a cdylib itself, as that doesn't work without `std`.)
+//!
+//! Tokens are passed as [DatumFfiEvent]s. Parsing is push-based: bytes go in, and events come out via a callback.
+//!
+//! Functions returning `i32` return 0 on success or a `DATUM_FFI_ERROR_*` code.
+//!
+//! _Added in 1.3.0._
+
+#![allow(unsafe_code)]
+
+use core::ffi::c_void;
+use std::boxed::Box;
+use std::string::String;
+
+use crate::{
+    DatumByteToTokenPipeline, DatumError, DatumErrorKind, DatumOffset, DatumPipe, DatumToken,
+    DatumWriter,
+};
+
+/// [DatumFfiEvent::kind]: String. The text is the unescaped contents.
+pub const DATUM_FFI_EVENT_STRING: u8 = 0;
+/// [DatumFfiEvent::kind]: Symbol. The text is the symbol.
+pub const DATUM_FFI_EVENT_SYMBOL: u8 = 1;
+/// [DatumFfiEvent::kind]: Special ID. The text is after (not including) the '#'.
+pub const DATUM_FFI_EVENT_SPECIAL_ID: u8 = 2;
+/// [DatumFfiEvent::kind]: Integer, in [DatumFfiEvent::integer].
+pub const DATUM_FFI_EVENT_INTEGER: u8 = 3;
+/// [DatumFfiEvent::kind]: Float, in [DatumFfiEvent::float].
+pub const DATUM_FFI_EVENT_FLOAT: u8 = 4;
+/// [DatumFfiEvent::kind]: List start.
+pub const DATUM_FFI_EVENT_LIST_START: u8 = 5;
+/// [DatumFfiEvent::kind]: List end.
+pub const DATUM_FFI_EVENT_LIST_END: u8 = 6;
+
+/// See [crate::DatumErrorKind::OutOfRoom].
+pub const DATUM_FFI_ERROR_OUT_OF_ROOM: i32 = 1;
+/// See [crate::DatumErrorKind::Interrupted].
+pub const DATUM_FFI_ERROR_INTERRUPTED: i32 = 2;
+/// See [crate::DatumErrorKind::BadData]. Also returned for invalid arguments.
+pub const DATUM_FFI_ERROR_BAD_DATA: i32 = 3;
+/// The callback returned nonzero.
+pub const DATUM_FFI_ERROR_CANCELLED: i32 = 4;
+/// Any other error.
+pub const DATUM_FFI_ERROR_OTHER: i32 = 5;
+
+/// A token, as passed across the C interface.
+///
+/// Text is UTF-8, not NUL-terminated, and (when received from the parser) only valid during the callback.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct DatumFfiEvent {
+    /// One of the `DATUM_FFI_EVENT_*` constants.
+    pub kind: u8,
+    /// Offset (in bytes) given by the parser. Ignored by the writer.
+    pub offset: u64,
+    /// Text for string, symbol and special ID events.
+    pub text: *const u8,
+    /// Length of text in bytes.
+    pub text_len: usize,
+    /// Value for integer events.
+    pub integer: i64,
+    /// Value for float events.
+    pub float: f64,
+}
+
+/// Callback for [datum_ffi_parser_feed]. Returning nonzero stops parsing with [DATUM_FFI_ERROR_CANCELLED].
+pub type DatumFfiCallback = extern "C" fn(user: *mut c_void, event: *const DatumFfiEvent) -> i32;
+
+fn error_code(e: &DatumError) -> i32 {
+    match e.kind {
+        DatumErrorKind::OutOfRoom => DATUM_FFI_ERROR_OUT_OF_ROOM,
+        DatumErrorKind::Interrupted => DATUM_FFI_ERROR_INTERRUPTED,
+        DatumErrorKind::BadData => DATUM_FFI_ERROR_BAD_DATA,
+        DatumErrorKind::Cancelled => DATUM_FFI_ERROR_CANCELLED,
+        _ => DATUM_FFI_ERROR_OTHER,
+    }
+}
+
+fn event_from_token(token: &DatumToken<String>) -> DatumFfiEvent {
+    let mut event = DatumFfiEvent {
+        kind: DATUM_FFI_EVENT_LIST_END,
+        offset: token.offset(),
+        text: core::ptr::null(),
+        text_len: 0,
+        integer: 0,
+        float: 0.0,
+    };
+    match token {
+        DatumToken::String(_, b) | DatumToken::Symbol(_, b) | DatumToken::SpecialID(_, b) => {
+            event.kind = match token {
+                DatumToken::String(_, _) => DATUM_FFI_EVENT_STRING,
+                DatumToken::Symbol(_, _) => DATUM_FFI_EVENT_SYMBOL,
+                _ => DATUM_FFI_EVENT_SPECIAL_ID,
+            };
+            event.text = b.as_ptr();
+            event.text_len = b.len();
+        }
+        DatumToken::Integer(_, v) => {
+            event.kind = DATUM_FFI_EVENT_INTEGER;
+            event.integer = *v;
+        }
+        DatumToken::Float(_, v) => {
+            event.kind = DATUM_FFI_EVENT_FLOAT;
+            event.float = *v;
+        }
+        DatumToken::ListStart(_) => event.kind = DATUM_FFI_EVENT_LIST_START,
+        DatumToken::ListEnd(_) => {}
+    }
+    event
+}
+
+/// Opaque push parser (bytes to tokens).
+pub struct DatumFfiParser {
+    pipeline: DatumByteToTokenPipeline<String>,
+    offset: DatumOffset,
+    error_offset: DatumOffset,
+}
+
+/// Creates a parser. Free it with [datum_ffi_parser_free].
+#[no_mangle]
+pub extern "C" fn datum_ffi_parser_new() -> *mut DatumFfiParser {
+    Box::into_raw(Box::new(DatumFfiParser {
+        pipeline: DatumByteToTokenPipeline::default(),
+        offset: 0,
+        error_offset: 0,
+    }))
+}
+
+/// Frees a parser.
+///
+/// # Safety
+///
+/// The parser must have come from [datum_ffi_parser_new] and not have been freed already. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn datum_ffi_parser_free(parser: *mut DatumFfiParser) {
+    if !parser.is_null() {
+        drop(Box::from_raw(parser));
+    }
+}
+
+/// Feeds bytes to the parser, calling the callback for each complete token.
+/// If `eof` is nonzero, the end of the input is processed after the bytes (and the parser is ready for a new document).
+///
+/// After an error, the parser's state is unspecified; free it (or get the offset first with [datum_ffi_parser_error_offset]).
+///
+/// # Safety
+///
+/// The parser must be valid, and `data` must point to `len` readable bytes (it may be null if `len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn datum_ffi_parser_feed(
+    parser: *mut DatumFfiParser,
+    data: *const u8,
+    len: usize,
+    eof: i32,
+    callback: DatumFfiCallback,
+    user: *mut c_void,
+) -> i32 {
+    let parser = match parser.as_mut() {
+        Some(v) => v,
+        None => return DATUM_FFI_ERROR_BAD_DATA,
+    };
+    let data: &[u8] = if len == 0 {
+        &[]
+    } else if data.is_null() {
+        return DATUM_FFI_ERROR_BAD_DATA;
+    } else {
+        core::slice::from_raw_parts(data, len)
+    };
+    let mut emit = |_, token: DatumToken<String>| {
+        if callback(user, &event_from_token(&token)) != 0 {
+            Err(crate::datum_error!(
+                Cancelled,
+                token.offset(),
+                "ffi: cancelled by callback"
+            ))
+        } else {
+            Ok(())
+        }
+    };
+    let mut res = Ok(());
+    for b in data {
+        res = parser.pipeline.feed(parser.offset, Some(*b), &mut emit);
+        if res.is_err() {
+            break;
+        }
+        parser.offset += 1;
+    }
+    if res.is_ok() && eof != 0 {
+        res = parser.pipeline.feed(parser.offset, None, &mut emit);
+        parser.offset = 0;
+    }
+    match res {
+        Ok(()) => 0,
+        Err(e) => {
+            parser.error_offset = e.offset;
+            error_code(&e)
+        }
+    }
+}
+
+/// Returns the offset of the last error returned by [datum_ffi_parser_feed].
+///
+/// # Safety
+///
+/// The parser must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn datum_ffi_parser_error_offset(parser: *const DatumFfiParser) -> u64 {
+    parser.as_ref().map_or(0, |v| v.error_offset)
+}
+
+/// Opaque writer (tokens to text), writing into an internal buffer.
+pub struct DatumFfiWriter {
+    writer: DatumWriter,
+    buffer: String,
+}
+
+/// Creates a writer. Free it with [datum_ffi_writer_free].
+#[no_mangle]
+pub extern "C" fn datum_ffi_writer_new() -> *mut DatumFfiWriter {
+    Box::into_raw(Box::new(DatumFfiWriter {
+        writer: DatumWriter::default(),
+        buffer: String::new(),
+    }))
+}
+
+/// Frees a writer.
+///
+/// # Safety
+///
+/// The writer must have come from [datum_ffi_writer_new] and not have been freed already. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn datum_ffi_writer_free(writer: *mut DatumFfiWriter) {
+    if !writer.is_null() {
+        drop(Box::from_raw(writer));
+    }
+}
+
+/// Writes a token (with spacing as needed) to the writer's buffer.
+/// Returns [DATUM_FFI_ERROR_BAD_DATA] for unknown kinds or text that isn't UTF-8.
+///
+/// # Safety
+///
+/// The writer must be valid, and `event` must point to a valid event, with valid text if the kind has text.
+#[no_mangle]
+pub unsafe extern "C" fn datum_ffi_writer_write(
+    writer: *mut DatumFfiWriter,
+    event: *const DatumFfiEvent,
+) -> i32 {
+    let (writer, event) = match (writer.as_mut(), event.as_ref()) {
+        (Some(w), Some(e)) => (w, e),
+        _ => return DATUM_FFI_ERROR_BAD_DATA,
+    };
+    let text = if event.text_len == 0 {
+        ""
+    } else if event.text.is_null() {
+        return DATUM_FFI_ERROR_BAD_DATA;
+    } else {
+        match core::str::from_utf8(core::slice::from_raw_parts(event.text, event.text_len)) {
+            Ok(v) => v,
+            Err(_) => return DATUM_FFI_ERROR_BAD_DATA,
+        }
+    };
+    let token: DatumToken<&str> = match event.kind {
+        DATUM_FFI_EVENT_STRING => DatumToken::String(0, text),
+        DATUM_FFI_EVENT_SYMBOL => DatumToken::Symbol(0, text),
+        DATUM_FFI_EVENT_SPECIAL_ID => DatumToken::SpecialID(0, text),
+        DATUM_FFI_EVENT_INTEGER => DatumToken::Integer(0, event.integer),
+        DATUM_FFI_EVENT_FLOAT => DatumToken::Float(0, event.float),
+        DATUM_FFI_EVENT_LIST_START => DatumToken::ListStart(0),
+        DATUM_FFI_EVENT_LIST_END => DatumToken::ListEnd(0),
+        _ => return DATUM_FFI_ERROR_BAD_DATA,
+    };
+    match writer.writer.write_token(&mut writer.buffer, &token) {
+        Ok(()) => 0,
+        Err(_) => DATUM_FFI_ERROR_OUT_OF_ROOM,
+    }
+}
+
+/// Writes a newline to the writer's buffer.
+///
+/// # Safety
+///
+/// The writer must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn datum_ffi_writer_newline(writer: *mut DatumFfiWriter) -> i32 {
+    match writer.as_mut() {
+        Some(w) => match w.writer.write_newline(&mut w.buffer) {
+            Ok(()) => 0,
+            Err(_) => DATUM_FFI_ERROR_OUT_OF_ROOM,
+        },
+        None => DATUM_FFI_ERROR_BAD_DATA,
+    }
+}
+
+/// Returns the writer's buffer (UTF-8, not NUL-terminated), storing its length in `len`.
+/// The pointer is valid until the writer is next used.
+///
+/// # Safety
+///
+/// The writer must be valid, and `len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn datum_ffi_writer_buffer(
+    writer: *const DatumFfiWriter,
+    len: *mut usize,
+) -> *const u8 {
+    match writer.as_ref() {
+        Some(w) => {
+            if let Some(len) = len.as_mut() {
+                *len = w.buffer.len();
+            }
+            w.buffer.as_ptr()
+        }
+        None => core::ptr::null(),
+    }
+}
+
+/// Empties the writer's buffer (i.e. after the contents have been copied elsewhere). Spacing/indentation state is kept.
+///
+/// # Safety
+///
+/// The writer must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn datum_ffi_writer_clear(writer: *mut DatumFfiWriter) {
+    if let Some(w) = writer.as_mut() {
+        w.buffer.clear();
+    }
+}
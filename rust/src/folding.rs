@@ -0,0 +1,69 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::vec::Vec;
+
+use crate::highlight::datum_highlight_lines;
+use crate::{DatumHighlightKind, DatumOffset, DatumSpan};
+
+/// A foldable list, from [datum_folding_ranges].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DatumFoldingRange {
+    /// Where the list is, in characters (end inclusive), from the `(` to the `)`.
+    pub span: DatumSpan,
+    /// Line of the `(` (starting at 0, as in the Language Server Protocol).
+    pub start_line: usize,
+    /// Line of the `)`.
+    pub end_line: usize,
+    /// How many lists this list is inside of (0 for a top-level list).
+    pub depth: usize,
+}
+
+/// Finds the lists of a document that span more than one line, for editor folding (i.e. LSP `textDocument/foldingRange`).
+///
+/// The ranges are in order of where they start. Like [crate::DatumHighlighter], this works on broken documents: lists that aren't closed, and stray list ends, are ignored.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::datum_folding_ranges;
+/// let source = "(config\n  (a 1)\n  (b\n    2))\n(c)";
+/// let lines: Vec<_> = datum_folding_ranges(source)
+///     .iter()
+///     .map(|r| (r.start_line, r.end_line, r.depth))
+///     .collect();
+/// assert_eq!(lines, [(0, 3, 0), (2, 3, 1)]);
+/// ```
+pub fn datum_folding_ranges(source: &str) -> Vec<DatumFoldingRange> {
+    let mut ranges = Vec::new();
+    // (start, line) of each open list
+    let mut open: Vec<(DatumOffset, usize)> = Vec::new();
+    for (span, _, line) in datum_highlight_lines(source) {
+        match span.kind {
+            DatumHighlightKind::ListStart => open.push((span.span.start, line)),
+            DatumHighlightKind::ListEnd => {
+                if let Some((start, start_line)) = open.pop() {
+                    if line > start_line {
+                        ranges.push(DatumFoldingRange {
+                            span: DatumSpan {
+                                start,
+                                end: span.span.end,
+                            },
+                            start_line,
+                            end_line: line,
+                            depth: open.len(),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    ranges.sort_by_key(|range| range.span.start);
+    ranges
+}
@@ -0,0 +1,305 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{
+    datum_error, DatumChar, DatumCharClass, DatumDecoder, DatumDocumentStyle, DatumOffset,
+    DatumPipe, DatumPipeTokenizer, DatumResult, DatumToken,
+};
+
+/// Layout settings for [datum_format_with].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DatumFormatConfig {
+    /// Lists are kept on one line if they fit within this many columns (and contain no comments).
+    pub max_width: usize,
+    /// Width of an indentation tab, for the purposes of [DatumFormatConfig::max_width].
+    pub tab_width: usize,
+    /// Line endings and how the document ends.
+    pub document: DatumDocumentStyle,
+}
+
+impl Default for DatumFormatConfig {
+    fn default() -> Self {
+        Self {
+            max_width: 80,
+            tab_width: 4,
+            document: DatumDocumentStyle::default(),
+        }
+    }
+}
+
+/// Source document, as needed for formatting.
+/// Each node carries the amount of newlines between it and the previous node.
+enum FormatNode<'src> {
+    Atom(String, usize),
+    /// Children, newlines, and width on one line (if it can be on one line).
+    List(Vec<FormatNode<'src>>, usize, Option<usize>),
+    /// Comment text (after the ';'), newlines.
+    Comment(&'src str, usize),
+}
+
+impl FormatNode<'_> {
+    fn newlines(&self) -> usize {
+        match self {
+            Self::Atom(_, nl) | Self::List(_, nl, _) | Self::Comment(_, nl) => *nl,
+        }
+    }
+
+    fn flat_width(&self) -> Option<usize> {
+        match self {
+            Self::Atom(text, _) => Some(text.chars().count()),
+            Self::List(_, _, width) => *width,
+            Self::Comment(_, _) => None,
+        }
+    }
+}
+
+/// Reads the document into nodes, keeping comments and newlines.
+struct DatumFormatReader<'src> {
+    source: &'src str,
+    /// List contents being built; the bottom entry is the document.
+    stack: Vec<(Vec<FormatNode<'src>>, usize)>,
+    newlines: usize,
+    in_string: bool,
+    /// Start offset and newlines before.
+    comment: Option<(usize, usize)>,
+}
+
+impl<'src> DatumFormatReader<'src> {
+    fn push(&mut self, node: FormatNode<'src>) {
+        // the bottom entry is never popped
+        self.stack.last_mut().unwrap().0.push(node);
+    }
+
+    fn end_comment(&mut self, start: usize, end: usize, newlines: usize) {
+        let text = self.source[start + 1..end].trim_end();
+        self.push(FormatNode::Comment(text, newlines));
+    }
+
+    fn on_token(&mut self, token: DatumToken<String>) -> DatumResult<()> {
+        let newlines = self.newlines;
+        self.newlines = 0;
+        match token {
+            DatumToken::ListStart(_) => self.stack.push((Vec::new(), newlines)),
+            DatumToken::ListEnd(at) => {
+                if self.stack.len() == 1 {
+                    return Err(datum_error!(BadData, at, "format: unexpected list end"));
+                }
+                let (children, newlines) = self.stack.pop().unwrap();
+                let mut width = Some(children.len().saturating_sub(1) + 2);
+                for v in &children {
+                    width = width.and_then(|w| Some(w + v.flat_width()?));
+                }
+                self.push(FormatNode::List(children, newlines, width));
+            }
+            token => self.push(FormatNode::Atom(token.to_string(), newlines)),
+        }
+        Ok(())
+    }
+
+    fn on_char(
+        &mut self,
+        at: DatumOffset,
+        c: DatumChar,
+        tokenizer: &mut DatumPipeTokenizer<String>,
+    ) -> DatumResult<()> {
+        let class = c.class();
+        let in_comment = self.comment.is_some();
+        if let Some((start, newlines)) = self.comment {
+            if class == DatumCharClass::Newline {
+                self.end_comment(start, at as usize, newlines);
+                self.comment = None;
+                self.newlines = 1;
+            }
+        }
+        // the tokenizer still needs to see comments, as they end tokens
+        tokenizer.feed(at, Some(c), &mut |_, token| self.on_token(token))?;
+        if self.in_string {
+            self.in_string = class != DatumCharClass::String;
+        } else if !in_comment {
+            match class {
+                DatumCharClass::String => self.in_string = true,
+                DatumCharClass::LineComment => {
+                    self.comment = Some((at as usize, self.newlines));
+                    self.newlines = 0;
+                }
+                DatumCharClass::Newline => self.newlines += 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn datum_format_read(source: &str) -> DatumResult<Vec<FormatNode<'_>>> {
+    let mut decoder = DatumDecoder::default();
+    let mut tokenizer: DatumPipeTokenizer<String> = DatumPipeTokenizer::default();
+    let mut reader = DatumFormatReader {
+        source,
+        stack: alloc::vec![(Vec::new(), 0)],
+        newlines: 0,
+        in_string: false,
+        comment: None,
+    };
+    // offsets are byte offsets so that comments can be sliced out of the source
+    for (at, c) in source.char_indices() {
+        decoder.feed(at as u64, Some(c), &mut |at, c| {
+            reader.on_char(at, c, &mut tokenizer)
+        })?;
+    }
+    let end = source.len() as u64;
+    decoder.feed(end, None, &mut |_, _| Ok(()))?;
+    tokenizer.feed(end, None, &mut |_, token| reader.on_token(token))?;
+    if let Some((start, newlines)) = reader.comment {
+        reader.end_comment(start, source.len(), newlines);
+    }
+    if reader.stack.len() != 1 {
+        return Err(datum_error!(Interrupted, end, "format: unterminated list"));
+    }
+    Ok(reader.stack.pop().unwrap().0)
+}
+
+struct DatumFormatter<'cfg> {
+    config: &'cfg DatumFormatConfig,
+    out: String,
+    line_empty: bool,
+}
+
+impl DatumFormatter<'_> {
+    fn newline(&mut self) {
+        self.out.push_str(self.config.document.line_ending.as_str());
+        self.line_empty = true;
+    }
+
+    fn write_indent(&mut self, indent: usize) {
+        for _ in 0..indent {
+            self.out.push('\t');
+        }
+    }
+
+    fn write_flat(&mut self, node: &FormatNode) {
+        match node {
+            FormatNode::Atom(text, _) => self.out.push_str(text),
+            FormatNode::List(children, _, _) => {
+                self.out.push('(');
+                for (i, v) in children.iter().enumerate() {
+                    if i != 0 {
+                        self.out.push(' ');
+                    }
+                    self.write_flat(v);
+                }
+                self.out.push(')');
+            }
+            // flat lists never contain comments
+            FormatNode::Comment(_, _) => {}
+        }
+    }
+
+    fn write_items(&mut self, items: &[FormatNode], indent: usize) {
+        for (i, item) in items.iter().enumerate() {
+            if let FormatNode::Comment(text, 0) = item {
+                if !self.line_empty {
+                    self.out.push_str(" ;");
+                    self.out.push_str(text);
+                    self.newline();
+                    continue;
+                }
+            }
+            if !self.line_empty {
+                self.newline();
+            }
+            if i != 0 && item.newlines() >= 2 {
+                self.newline();
+            }
+            self.write_indent(indent);
+            self.line_empty = false;
+            match item {
+                FormatNode::Comment(text, _) => {
+                    self.out.push(';');
+                    self.out.push_str(text);
+                    self.newline();
+                }
+                FormatNode::List(children, _, width) => {
+                    let column = indent * self.config.tab_width;
+                    if matches!(width, Some(w) if column + w <= self.config.max_width) {
+                        self.write_flat(item);
+                    } else {
+                        self.out.push('(');
+                        self.write_items(children, indent + 1);
+                        if !self.line_empty {
+                            self.newline();
+                        }
+                        self.write_indent(indent);
+                        self.out.push(')');
+                        self.line_empty = false;
+                    }
+                }
+                FormatNode::Atom(_, _) => self.write_flat(item),
+            }
+        }
+    }
+}
+
+/// Formats a document with the default [DatumFormatConfig]; see [datum_format_with].
+///
+/// _Added in 1.3.0._
+/// ```
+/// assert_eq!(datum::datum_format("(a   b)  ; note\n\n\n1").unwrap(), "(a b) ; note\n\n1\n");
+/// ```
+pub fn datum_format(source: &str) -> DatumResult<String> {
+    datum_format_with(source, &DatumFormatConfig::default())
+}
+
+/// Formats a document, keeping comments.
+///
+/// * Each top-level value goes on its own line.
+/// * Lists that fit (and contain no comments) go on one line. Other lists put each element on its own line, indented by a tab, with the list end on its own line.
+/// * Comments stay on their own line, or at the end of the line they were on. Trailing whitespace is removed.
+/// * Runs of blank lines become a single blank line.
+/// * Atoms are written as [crate::DatumWriter] would, so numbers and strings are normalized.
+///
+/// Formatting is idempotent: formatting the output again gives the same output.
+///
+/// _Added in 1.3.0._
+pub fn datum_format_with(source: &str, config: &DatumFormatConfig) -> DatumResult<String> {
+    let nodes = datum_format_read(source)?;
+    let mut formatter = DatumFormatter {
+        config,
+        out: String::new(),
+        line_empty: true,
+    };
+    formatter.write_items(&nodes, 0);
+    let document = &config.document;
+    // the last newline is written here, as the document style decides it
+    if formatter.out.ends_with(document.line_ending.as_str()) {
+        let len = formatter.out.len() - document.line_ending.as_str().len();
+        formatter.out.truncate(len);
+    }
+    // writing to a String can't fail
+    let _ = document.write_end(&mut formatter.out, nodes.is_empty());
+    Ok(formatter.out)
+}
+
+/// Checks if a document is already formatted, i.e. formatting it wouldn't change it. Unparsable documents aren't formatted.
+///
+/// This is meant for CI checks.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_is_formatted, DatumFormatConfig};
+/// let config = DatumFormatConfig::default();
+/// assert!(datum_is_formatted("(a b)\n", &config));
+/// assert!(!datum_is_formatted("(a  b)", &config));
+/// assert!(!datum_is_formatted("(a b", &config));
+/// ```
+pub fn datum_is_formatted(source: &str, config: &DatumFormatConfig) -> bool {
+    matches!(datum_format_with(source, config), Ok(v) if v == source)
+}
@@ -0,0 +1,126 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::ops::Deref;
+
+use crate::{unary, DatumBoundedPipe, DatumOffset, DatumPipe, DatumResult, DatumToken};
+
+#[cfg(feature = "alloc")]
+use crate::{
+    datum_byte_to_token_pipeline, datum_char_to_token_pipeline, DatumComposePipe, DatumParser,
+    DatumValue,
+};
+
+/// Special ID (without the '#') used as a document separator by [DatumDocumentFramer].
+///
+/// In a file, this is written as `#---`. Note that it isn't a valid atom, so it can't appear in a document by accident.
+///
+/// _Added in 1.3.0._
+pub const DATUM_DOCUMENT_SEPARATOR: &str = "---";
+
+/// Output of [DatumDocumentFramer].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DatumFrame<V> {
+    /// Output of the inner pipeline.
+    Value(V),
+    /// A document separator.
+    Separator {
+        /// False if the inner pipeline failed at EOF, i.e. the document before the separator was truncated.
+        complete: bool,
+    },
+}
+
+/// Splits a token stream into documents at [DATUM_DOCUMENT_SEPARATOR] special IDs.
+///
+/// The inner pipeline (usually a [crate::DatumParser]) is given EOF and then reset (via [Default]) at each separator, even inside a list.
+/// This means a truncated document (i.e. in an appended log file) doesn't affect the documents after it.
+///
+/// The inner pipeline's errors at separators aren't returned (as that would break earlier pipeline stages); see [DatumFrame::Separator] instead.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumFrame, DatumValue, IntoViaDatumPipe, datum_char_to_framed_value_pipeline};
+/// let values: Vec<DatumFrame<DatumValue>> = "1 2 #--- 3"
+///     .chars()
+///     .via_datum_pipe(datum_char_to_framed_value_pipeline())
+///     .map(|v| v.unwrap())
+///     .collect();
+/// assert_eq!(values.len(), 4);
+/// assert_eq!(values[2], DatumFrame::Separator { complete: true });
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DatumDocumentFramer<P>(pub P);
+
+impl<B: Deref<Target = str>, P: DatumPipe<Input = DatumToken<B>> + Default> DatumPipe
+    for DatumDocumentFramer<P>
+{
+    type Input = DatumToken<B>;
+    type Output = DatumFrame<P::Output>;
+
+    fn feed<F: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut F,
+    ) -> DatumResult<()> {
+        match i {
+            Some(DatumToken::SpecialID(_, b)) if b.deref() == DATUM_DOCUMENT_SEPARATOR => {
+                // errors from further down the pipeline still need to be returned
+                let mut output_error = None;
+                let complete = self
+                    .0
+                    .feed(at, None, &mut |at, v| {
+                        let res = f(at, DatumFrame::Value(v));
+                        if let Err(err) = res {
+                            output_error = Some(err);
+                        }
+                        res
+                    })
+                    .is_ok();
+                self.0 = P::default();
+                if let Some(err) = output_error {
+                    return Err(err);
+                }
+                f(at, DatumFrame::Separator { complete })
+            }
+            i => self.0.feed(at, i, &mut |at, v| f(at, DatumFrame::Value(v))),
+        }
+    }
+}
+
+// Separators can cause the inner pipeline to flush, then output the separator.
+impl<B: Deref<Target = str>, P: DatumBoundedPipe<Input = DatumToken<B>> + Default> DatumBoundedPipe
+    for DatumDocumentFramer<P>
+{
+    type OutputQueueSize = <P::OutputQueueSize as unary::Num>::Inc;
+}
+
+/// Byte to value parsing pipeline, split into documents by [DatumDocumentFramer].
+///
+/// _Added in 1.3.0._
+#[cfg(feature = "alloc")]
+pub fn datum_byte_to_framed_value_pipeline(
+) -> impl DatumPipe<Input = u8, Output = DatumFrame<DatumValue>> {
+    DatumComposePipe(
+        datum_byte_to_token_pipeline(),
+        DatumDocumentFramer(DatumParser::default()),
+    )
+}
+
+/// Char to value parsing pipeline, split into documents by [DatumDocumentFramer].
+///
+/// _Added in 1.3.0._
+#[cfg(feature = "alloc")]
+pub fn datum_char_to_framed_value_pipeline(
+) -> impl DatumPipe<Input = char, Output = DatumFrame<DatumValue>> {
+    DatumComposePipe(
+        datum_char_to_token_pipeline(),
+        DatumDocumentFramer(DatumParser::default()),
+    )
+}
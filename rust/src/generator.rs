@@ -0,0 +1,171 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{DatumAtom, DatumDocument, DatumValue};
+
+/// Settings for [DatumGenerator].
+///
+/// Chances are out of 256.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DatumGeneratorConfig {
+    /// Amount of top-level values in a document.
+    pub values: usize,
+    /// Deepest list nesting (0 means no lists).
+    pub max_depth: usize,
+    /// Longest list.
+    pub max_list_len: usize,
+    /// Longest string or symbol, in characters.
+    pub max_text_len: usize,
+    /// Chance of any given value being a list (if allowed by depth).
+    pub list_chance: u8,
+    /// Chance of any given character being outside of ASCII.
+    pub non_ascii_chance: u8,
+    /// Chance of any given character being one of those which need escaping somewhere (i.e. quotes, backslashes, parentheses, whitespace and control characters).
+    pub special_char_chance: u8,
+}
+
+impl Default for DatumGeneratorConfig {
+    fn default() -> Self {
+        Self {
+            values: 16,
+            max_depth: 8,
+            max_list_len: 8,
+            max_text_len: 16,
+            list_chance: 64,
+            non_ascii_chance: 16,
+            special_char_chance: 32,
+        }
+    }
+}
+
+/// Characters with meaning to Datum, for [DatumGeneratorConfig::special_char_chance].
+const SPECIAL_CHARS: &[char] = &[
+    '"', '\\', '(', ')', ';', '#', '+', '-', '.', ' ', '\t', '\n', '\r', '\0', '\x7F',
+];
+
+/// Deterministic pseudo-random document generator, for benchmarking and differential testing (i.e. against other Datum implementations).
+///
+/// The same seed and config always give the same documents, on any platform.
+/// Documents are always valid, and every generated value survives being written and parsed back (so NaN is never generated, as it isn't equal to itself).
+///
+/// This doesn't aim to be a good random number generator; it's SplitMix64.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumDocument, DatumGenerator, DatumGeneratorConfig};
+/// let doc = DatumGenerator::new(1234, DatumGeneratorConfig::default()).document();
+/// let text = doc.to_string();
+/// assert_eq!(text.parse::<DatumDocument>().unwrap(), doc);
+/// let again = DatumGenerator::new(1234, DatumGeneratorConfig::default()).document();
+/// assert_eq!(again.to_string(), text);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DatumGenerator {
+    state: u64,
+    /// Settings. These can be changed between documents.
+    pub config: DatumGeneratorConfig,
+}
+
+impl DatumGenerator {
+    /// Creates a generator from a seed.
+    pub fn new(seed: u64, config: DatumGeneratorConfig) -> Self {
+        Self {
+            state: seed,
+            config,
+        }
+    }
+
+    /// Next raw pseudo-random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Pseudo-random number from 0 to max inclusive.
+    fn up_to(&mut self, max: usize) -> usize {
+        (self.next_u64() % (max as u64 + 1)) as usize
+    }
+
+    fn chance(&mut self, chance: u8) -> bool {
+        ((self.next_u64() & 0xFF) as u8) < chance
+    }
+
+    fn char(&mut self) -> char {
+        if self.chance(self.config.special_char_chance) {
+            SPECIAL_CHARS[self.up_to(SPECIAL_CHARS.len() - 1)]
+        } else if self.chance(self.config.non_ascii_chance) {
+            loop {
+                if let Some(c) = core::char::from_u32(0x80 + self.up_to(0x10FF7F) as u32) {
+                    return c;
+                }
+            }
+        } else {
+            // printable ASCII
+            (b' ' + 1 + self.up_to(0x7E - 0x21) as u8) as char
+        }
+    }
+
+    fn text(&mut self) -> String {
+        let len = self.up_to(self.config.max_text_len);
+        (0..len).map(|_| self.char()).collect()
+    }
+
+    fn float(&mut self) -> f64 {
+        match self.up_to(3) {
+            0 => f64::INFINITY,
+            1 => f64::NEG_INFINITY,
+            // 'nice' values, as most real ones are
+            2 => (self.next_u64() as i32) as f64 / (1 << self.up_to(16)) as f64,
+            _ => loop {
+                let v = f64::from_bits(self.next_u64());
+                if !v.is_nan() {
+                    return v;
+                }
+            },
+        }
+    }
+
+    fn atom(&mut self) -> DatumAtom<String> {
+        match self.up_to(6) {
+            0 => DatumAtom::String(self.text()),
+            1 => DatumAtom::Symbol(self.text()),
+            2 => DatumAtom::Integer(self.next_u64() as i64),
+            3 => DatumAtom::Integer(self.up_to(200) as i64 - 100),
+            4 => DatumAtom::Float(self.float()),
+            5 => DatumAtom::Boolean(self.chance(128)),
+            _ => DatumAtom::Nil,
+        }
+    }
+
+    /// Generates a value.
+    pub fn value(&mut self) -> DatumValue {
+        self.value_at(0)
+    }
+
+    fn value_at(&mut self, depth: usize) -> DatumValue {
+        if depth < self.config.max_depth && self.chance(self.config.list_chance) {
+            let len = self.up_to(self.config.max_list_len);
+            DatumValue::List((0..len).map(|_| self.value_at(depth + 1)).collect())
+        } else {
+            DatumValue::Atom(self.atom())
+        }
+    }
+
+    /// Generates a document of [DatumGeneratorConfig::values] values.
+    pub fn document(&mut self) -> DatumDocument {
+        let values: Vec<DatumValue> = (0..self.config.values).map(|_| self.value()).collect();
+        DatumDocument(values)
+    }
+}
@@ -0,0 +1,199 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::{fmt::Write, ops::Deref, ops::RangeInclusive};
+
+use crate::{datum_error, DatumAtom, DatumResult, DatumToken, DatumWriter};
+#[cfg(feature = "alloc")]
+use crate::{DatumNode, DatumValueNode};
+
+/// A conventional file format header, which lets applications evolve their formats detectably.
+///
+/// Datum itself has no notion of headers; this is a convention built out of ordinary values. It comes in two forms:
+///
+/// * As the first value of the document: `(example-config 2)`. This is what [DatumHeader::write] and [DatumHeader::read] use.
+/// * As a 'magic comment' on the first line: `; example-config 2`. This is invisible to readers that don't know about it, so it can be added to an existing format.
+///
+/// The format name is a symbol identifying the application's format. The version is an integer, which should be increased whenever the format changes incompatibly.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_char_to_token_pipeline, DatumHeader, DatumHeaderCheck, DatumWriter, IntoViaDatumPipe};
+/// let mut text = String::new();
+/// DatumHeader::new("example-config", 2).write(&mut DatumWriter::default(), &mut text).unwrap();
+/// text.push_str("(name \"example\")\n");
+/// assert_eq!(text, "(example-config 2)\n(name \"example\")\n");
+///
+/// let mut tokens = text.chars().via_datum_pipe(datum_char_to_token_pipeline());
+/// let header = DatumHeader::read(&mut tokens).unwrap();
+/// assert_eq!(header.check("example-config", 1..=2), DatumHeaderCheck::Supported(2));
+/// assert_eq!(header.check("example-config", 3..=4), DatumHeaderCheck::TooOld);
+/// // the rest of the document follows
+/// assert!(tokens.next().is_some());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DatumHeader<B: Deref<Target = str>> {
+    /// Format name.
+    pub format: B,
+    /// Format version.
+    pub version: i64,
+}
+
+/// Result of [DatumHeader::check].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DatumHeaderCheck {
+    /// The format is right and the version is supported.
+    Supported(i64),
+    /// The version predates the supported range. The application may want to migrate it.
+    TooOld,
+    /// The version is newer than the supported range, i.e. the file was written by a newer version of the application.
+    TooNew,
+    /// This is some other format.
+    WrongFormat,
+}
+
+impl<B: Deref<Target = str>> DatumHeader<B> {
+    /// Creates a header.
+    pub fn new(format: B, version: i64) -> Self {
+        Self { format, version }
+    }
+
+    /// Converts to a borrowing header.
+    pub fn as_borrowed(&self) -> DatumHeader<&str> {
+        DatumHeader::new(&self.format, self.version)
+    }
+
+    /// Checks the header against the format name and range of versions the application supports.
+    pub fn check(&self, format: &str, supported: RangeInclusive<i64>) -> DatumHeaderCheck {
+        if self.format.deref() != format {
+            DatumHeaderCheck::WrongFormat
+        } else if self.version < *supported.start() {
+            DatumHeaderCheck::TooOld
+        } else if self.version > *supported.end() {
+            DatumHeaderCheck::TooNew
+        } else {
+            DatumHeaderCheck::Supported(self.version)
+        }
+    }
+
+    /// Writes the header as a value, i.e. `(example-config 2)`, followed by a newline.
+    pub fn write(&self, writer: &mut DatumWriter, f: &mut dyn Write) -> core::fmt::Result {
+        writer.write_token(f, &DatumToken::<&str>::ListStart(0))?;
+        writer.write_atom(f, &DatumAtom::Symbol(self.format.deref()))?;
+        writer.write_atom::<&str>(f, &DatumAtom::Integer(self.version))?;
+        writer.write_token(f, &DatumToken::<&str>::ListEnd(0))?;
+        writer.write_newline(f)
+    }
+
+    /// Writes the header as a magic comment, i.e. `; example-config 2`.
+    /// This should be the first thing in the document.
+    pub fn write_magic_comment(
+        &self,
+        writer: &mut DatumWriter,
+        f: &mut dyn Write,
+    ) -> core::fmt::Result {
+        writer.emit_whitespace(f, false)?;
+        write!(f, "; {} {}", self.format.deref(), self.version)?;
+        writer.write_newline(f)
+    }
+
+    /// Reads a header value from the start of a token stream.
+    ///
+    /// Only the header's tokens are consumed, so the rest of the document can be read from the same iterator.
+    /// If the document doesn't start with a header, this is an error.
+    pub fn read(
+        iterator: &mut dyn Iterator<Item = DatumResult<DatumToken<B>>>,
+    ) -> DatumResult<Self> {
+        let mut at = 0;
+        let mut next = || -> DatumResult<DatumToken<B>> {
+            match iterator.next().transpose()? {
+                Some(token) => {
+                    at = token.offset();
+                    Ok(token)
+                }
+                None => Err(datum_error!(Interrupted, at, "header: unexpected EOF")),
+            }
+        };
+        match next()? {
+            DatumToken::ListStart(_) => {}
+            token => {
+                return Err(datum_error!(
+                    BadData,
+                    token.offset(),
+                    "header: expected (format version)"
+                ))
+            }
+        }
+        let format = match next()? {
+            DatumToken::Symbol(_, format) => format,
+            token => {
+                return Err(datum_error!(
+                    BadData,
+                    token.offset(),
+                    "header: expected format name"
+                ))
+            }
+        };
+        let version = match next()? {
+            DatumToken::Integer(_, version) => version,
+            token => {
+                return Err(datum_error!(
+                    BadData,
+                    token.offset(),
+                    "header: expected version"
+                ))
+            }
+        };
+        match next()? {
+            DatumToken::ListEnd(_) => Ok(Self::new(format, version)),
+            token => Err(datum_error!(
+                BadData,
+                token.offset(),
+                "header: expected end of header"
+            )),
+        }
+    }
+}
+
+impl<'a> DatumHeader<&'a str> {
+    /// Reads a header from a value, i.e. the first value of a [crate::DatumDocument], if it is one.
+    #[cfg(feature = "alloc")]
+    pub fn from_value<V: DatumValueNode>(value: &'a V) -> Option<Self> {
+        let (format, version) = match value.node() {
+            DatumNode::List([format, version]) => (format.node(), version.node()),
+            _ => return None,
+        };
+        match (format, version) {
+            (
+                DatumNode::Atom(DatumAtom::Symbol(format)),
+                DatumNode::Atom(DatumAtom::Integer(version)),
+            ) => Some(Self::new(format, *version)),
+            _ => None,
+        }
+    }
+
+    /// Reads a magic comment header from the first line of a document, if there is one.
+    ///
+    /// ```
+    /// use datum::DatumHeader;
+    /// let header = DatumHeader::from_magic_comment("; example-config 2\n(name \"example\")\n");
+    /// assert_eq!(header, Some(DatumHeader::new("example-config", 2)));
+    /// assert_eq!(DatumHeader::from_magic_comment("; just a comment\n"), None);
+    /// ```
+    pub fn from_magic_comment(text: &'a str) -> Option<Self> {
+        let line = text.split('\n').next()?;
+        let mut words = line.strip_prefix(';')?.split_whitespace();
+        let format = words.next()?;
+        let version = words.next()?.parse().ok()?;
+        if words.next().is_some() {
+            return None;
+        }
+        Some(Self::new(format, version))
+    }
+}
@@ -0,0 +1,258 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::str::Chars;
+
+use crate::token::char_to_byte;
+use crate::{
+    DatumCharClass, DatumCharToSpanTokenPipeline, DatumOffset, DatumPipe, DatumSpan,
+    DatumSpanToken, DatumTokenType,
+};
+
+/// What a [DatumHighlightSpan] is, for choosing its colour.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DatumHighlightKind {
+    /// Spaces, tabs and newlines.
+    Whitespace,
+    /// A line comment, from the `;` up to (not including) the newline.
+    Comment,
+    /// A string, including its quotes. Also used for a string left open at the end of the text.
+    String,
+    /// A symbol.
+    Symbol,
+    /// A number.
+    Numeric,
+    /// A special ID, i.e. `#t`.
+    SpecialID,
+    /// `(`
+    ListStart,
+    /// `)`
+    ListEnd,
+    /// Text the tokenizer couldn't make sense of (i.e. from a bad escape to the end of the text).
+    Invalid,
+}
+
+impl From<DatumTokenType> for DatumHighlightKind {
+    fn from(token_type: DatumTokenType) -> Self {
+        match token_type {
+            DatumTokenType::String => Self::String,
+            DatumTokenType::Symbol => Self::Symbol,
+            DatumTokenType::SpecialID => Self::SpecialID,
+            DatumTokenType::Numeric => Self::Numeric,
+            DatumTokenType::ListStart => Self::ListStart,
+            DatumTokenType::ListEnd => Self::ListEnd,
+        }
+    }
+}
+
+/// A span of text from [DatumHighlighter].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DatumHighlightSpan {
+    /// What the span is.
+    pub kind: DatumHighlightKind,
+    /// Where the span is, in characters (end inclusive).
+    pub span: DatumSpan,
+    /// List nesting depth, for rainbow parentheses. For `(` and `)`, this is the depth of the list they belong to (0 for a top-level list); for anything else, it's how many lists it's inside.
+    pub depth: usize,
+}
+
+impl DatumHighlightSpan {
+    /// The span's text in the source.
+    pub fn source_text<'a>(&self, source: &'a str) -> &'a str {
+        let start = char_to_byte(source, self.span.start);
+        let end = start + char_to_byte(&source[start..], self.span.end + 1 - self.span.start);
+        &source[start..end]
+    }
+}
+
+/// Iterator classifying all of a document's text into [DatumHighlightSpan]s, for syntax highlighting.
+///
+/// The spans are in order and cover every character, so a highlighter can colour each one as it comes.
+///
+/// Documents being edited are often broken, so this never fails: a string left open runs to the end of the text, and anything else the tokenizer rejects (i.e. a bad escape) is [DatumHighlightKind::Invalid] from there on. Unbalanced lists are highlighted as they are, with depth not going below zero.
+///
+/// Like [crate::DatumSpanTokenizer], this doesn't need `alloc`.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumHighlightKind, DatumHighlighter};
+/// let source = "(a (1)) ; hi\n\"open";
+/// let spans: Vec<_> = DatumHighlighter::new(source)
+///     .map(|s| (s.kind, s.source_text(source), s.depth))
+///     .collect();
+/// assert_eq!(spans, [
+///     (DatumHighlightKind::ListStart, "(", 0),
+///     (DatumHighlightKind::Symbol, "a", 1),
+///     (DatumHighlightKind::Whitespace, " ", 1),
+///     (DatumHighlightKind::ListStart, "(", 1),
+///     (DatumHighlightKind::Numeric, "1", 2),
+///     (DatumHighlightKind::ListEnd, ")", 1),
+///     (DatumHighlightKind::ListEnd, ")", 0),
+///     (DatumHighlightKind::Whitespace, " ", 0),
+///     (DatumHighlightKind::Comment, "; hi", 0),
+///     (DatumHighlightKind::Whitespace, "\n", 0),
+///     (DatumHighlightKind::String, "\"open", 0),
+/// ]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DatumHighlighter<'a> {
+    /// Characters not yet fed to the pipeline.
+    input: Chars<'a>,
+    /// Characters not yet covered by a span.
+    rest: Chars<'a>,
+    pipeline: DatumCharToSpanTokenPipeline,
+    /// Tokens out of the pipeline (it makes at most two per character).
+    queue: [Option<DatumSpanToken>; 2],
+    /// The token after the current gap, if there is one.
+    next_token: Option<DatumSpanToken>,
+    fed: DatumOffset,
+    pos: DatumOffset,
+    len: DatumOffset,
+    depth: usize,
+    finished: bool,
+    failed: bool,
+}
+
+impl<'a> DatumHighlighter<'a> {
+    /// Creates a highlighter over the given text.
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            input: source.chars(),
+            rest: source.chars(),
+            pipeline: DatumCharToSpanTokenPipeline::default(),
+            queue: [None, None],
+            next_token: None,
+            fed: 0,
+            pos: 0,
+            len: source.chars().count() as DatumOffset,
+            depth: 0,
+            finished: false,
+            failed: false,
+        }
+    }
+
+    /// Takes the next token, feeding the pipeline until there is one (or there's no more input).
+    fn pull_token(&mut self) -> Option<DatumSpanToken> {
+        while self.queue[0].is_none() && !self.finished {
+            let c = self.input.next();
+            let queue = &mut self.queue;
+            let res = self.pipeline.feed(self.fed, c, &mut |_, token| {
+                let slot = if queue[0].is_none() { 0 } else { 1 };
+                queue[slot] = Some(token);
+                Ok(())
+            });
+            self.fed += 1;
+            if res.is_err() {
+                self.failed = true;
+            }
+            self.finished = c.is_none() || self.failed;
+        }
+        let token = self.queue[0].take();
+        self.queue[0] = self.queue[1].take();
+        token
+    }
+
+    /// Makes a span of the next `n` characters, moving past them.
+    fn advance(
+        &mut self,
+        kind: DatumHighlightKind,
+        n: DatumOffset,
+        depth: usize,
+    ) -> DatumHighlightSpan {
+        for _ in 0..n {
+            self.rest.next();
+        }
+        let span = DatumSpan {
+            start: self.pos,
+            end: self.pos + n - 1,
+        };
+        self.pos += n;
+        DatumHighlightSpan { kind, span, depth }
+    }
+}
+
+impl Iterator for DatumHighlighter<'_> {
+    type Item = DatumHighlightSpan;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_token.is_none() {
+            self.next_token = self.pull_token();
+        }
+        let gap_end = match self.next_token {
+            Some(token) => token.span.start,
+            None => self.len,
+        };
+        if self.pos < gap_end {
+            // between tokens, there's only whitespace and comments; after a failure, there's the rest
+            let is_space = |c: char| {
+                matches!(
+                    DatumCharClass::identify(c),
+                    Some(DatumCharClass::Whitespace) | Some(DatumCharClass::Newline)
+                )
+            };
+            let mut chars = self.rest.clone();
+            let first = chars.next()?;
+            let (kind, run): (DatumHighlightKind, fn(char) -> bool) =
+                match DatumCharClass::identify(first) {
+                    Some(DatumCharClass::LineComment) => (DatumHighlightKind::Comment, |c| {
+                        DatumCharClass::identify(c) != Some(DatumCharClass::Newline)
+                    }),
+                    Some(DatumCharClass::Whitespace) | Some(DatumCharClass::Newline) => {
+                        (DatumHighlightKind::Whitespace, is_space)
+                    }
+                    Some(DatumCharClass::String) => (DatumHighlightKind::String, |_| true),
+                    _ => (DatumHighlightKind::Invalid, |_| true),
+                };
+            let mut n = 1;
+            while self.pos + n < gap_end && matches!(chars.next(), Some(c) if run(c)) {
+                n += 1;
+            }
+            return Some(self.advance(kind, n, self.depth));
+        }
+        let token = self.next_token.take()?;
+        let depth = match token.token_type {
+            DatumTokenType::ListStart => {
+                self.depth += 1;
+                self.depth - 1
+            }
+            DatumTokenType::ListEnd => {
+                self.depth = self.depth.saturating_sub(1);
+                self.depth
+            }
+            _ => self.depth,
+        };
+        let n = token.span.end + 1 - token.span.start;
+        Some(self.advance(token.token_type.into(), n, depth))
+    }
+}
+
+/// Highlights text, giving each span's text and the line (starting at 0) it starts on too.
+#[cfg(feature = "alloc")]
+pub(crate) fn datum_highlight_lines(
+    source: &str,
+) -> impl Iterator<Item = (DatumHighlightSpan, &str, usize)> {
+    let mut chars = source.chars();
+    let mut line = 0;
+    let mut byte = 0;
+    DatumHighlighter::new(source).map(move |span| {
+        let start_line = line;
+        let start_byte = byte;
+        for _ in span.span.start..=span.span.end {
+            if let Some(c) = chars.next() {
+                byte += c.len_utf8();
+                if c == '\n' {
+                    line += 1;
+                }
+            }
+        }
+        (span, &source[start_byte..byte], start_line)
+    })
+}
@@ -0,0 +1,193 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::{
+    datum_byte_to_value_pipeline, datum_error, DatumCharToSpanTokenPipeline, DatumComposePipe,
+    DatumOffset, DatumPipe, DatumResult, DatumSpan, DatumSpanToken, DatumTokenType,
+    DatumUTF8Decoder, DatumValue,
+};
+
+/// A list found by [DatumIndex::build_with_lists].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DatumIndexedList {
+    /// Where the list is, in bytes (end inclusive), from the `(` to the `)`.
+    pub span: DatumSpan,
+    /// How many lists this list is inside of (0 for a top-level list).
+    pub depth: usize,
+}
+
+/// Table of where each top-level value of a document is, so that huge (read-only) documents can be read from at random.
+///
+/// The index is built in one pass over the document's UTF-8 bytes, without unescaping or allocating anything but the table itself (see [crate::DatumSpanTokenizer]).
+/// Afterwards, the Nth value can be found straight away, and values can be parsed one at a time.
+///
+/// Offsets are in bytes. Building the index only checks that lists are balanced; bad atoms (i.e. invalid special IDs) are reported when the value is parsed.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumAtom, DatumIndex, DatumValue};
+/// let source = b"(1 apple) (2 banana) (5 cherry) (9 damson)";
+/// let index = DatumIndex::build(source).unwrap();
+/// assert_eq!(index.len(), 4);
+/// assert_eq!(index.text(2, source).unwrap(), b"(5 cherry)");
+/// // the values are sorted by their first element, so they can be searched
+/// let key = |value: &DatumValue| match value.as_list().unwrap()[0] {
+///     DatumValue::Atom(DatumAtom::Integer(v)) => v,
+///     _ => panic!("no key"),
+/// };
+/// assert_eq!(index.binary_search_by(source, |v| key(v).cmp(&9)).unwrap(), Ok(3));
+/// assert_eq!(index.binary_search_by(source, |v| key(v).cmp(&3)).unwrap(), Err(2));
+/// let value = index.value(3, source).unwrap().unwrap();
+/// assert_eq!(value.as_list().unwrap()[1], DatumValue::Atom(DatumAtom::Symbol("damson".to_string())));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DatumIndex {
+    /// Where each top-level value is, in bytes (end inclusive).
+    pub values: Vec<DatumSpan>,
+    /// Every list (including nested lists), in order of where they start. Only filled in by [DatumIndex::build_with_lists].
+    pub lists: Vec<DatumIndexedList>,
+}
+
+impl DatumIndex {
+    /// Indexes the top-level values of a document.
+    pub fn build(source: &[u8]) -> DatumResult<Self> {
+        Self::build_inner(source, false)
+    }
+
+    /// Indexes the top-level values of a document, and every list in it.
+    pub fn build_with_lists(source: &[u8]) -> DatumResult<Self> {
+        Self::build_inner(source, true)
+    }
+
+    fn build_inner(source: &[u8], with_lists: bool) -> DatumResult<Self> {
+        let mut index = Self::default();
+        // starts of the lists the tokenizer is in, and where they are in the list table
+        let mut open: Vec<(DatumOffset, usize)> = Vec::new();
+        let mut pipe =
+            DatumComposePipe::<DatumUTF8Decoder, DatumCharToSpanTokenPipeline>::default();
+        let mut output = |_, token: DatumSpanToken| {
+            match token.token_type {
+                DatumTokenType::ListStart => {
+                    if with_lists {
+                        index.lists.push(DatumIndexedList {
+                            span: token.span,
+                            depth: open.len(),
+                        });
+                    }
+                    open.push((token.span.start, index.lists.len().wrapping_sub(1)));
+                }
+                DatumTokenType::ListEnd => {
+                    let (start, list) = open.pop().ok_or(datum_error!(
+                        BadData,
+                        token.span.start,
+                        "index: unexpected list end"
+                    ))?;
+                    if with_lists {
+                        index.lists[list].span.end = token.span.end;
+                    }
+                    if open.is_empty() {
+                        index.values.push(DatumSpan {
+                            start,
+                            end: token.span.end,
+                        });
+                    }
+                }
+                _ => {
+                    if open.is_empty() {
+                        index.values.push(token.span);
+                    }
+                }
+            }
+            Ok(())
+        };
+        for (at, b) in source.iter().enumerate() {
+            pipe.feed(at as DatumOffset, Some(*b), &mut output)?;
+        }
+        pipe.feed(source.len() as DatumOffset, None, &mut output)?;
+        if !open.is_empty() {
+            return Err(datum_error!(
+                Interrupted,
+                source.len() as DatumOffset,
+                "index: unexpected EOF inside list"
+            ));
+        }
+        Ok(index)
+    }
+
+    /// Number of top-level values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if there are no top-level values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The text of the Nth top-level value, if it exists.
+    ///
+    /// The source must be the document the index was built from.
+    pub fn text<'a>(&self, n: usize, source: &'a [u8]) -> Option<&'a [u8]> {
+        let span = self.values.get(n)?;
+        source.get(span.start as usize..=span.end as usize)
+    }
+
+    /// Parses the Nth top-level value, if it exists. Error offsets are in bytes from the start of the document.
+    ///
+    /// The source must be the document the index was built from.
+    pub fn value(&self, n: usize, source: &[u8]) -> Option<DatumResult<DatumValue>> {
+        let start = self.values.get(n)?.start;
+        let text = self.text(n, source)?;
+        let mut pipe = datum_byte_to_value_pipeline();
+        let mut value = None;
+        let mut output = |_, v| {
+            value = Some(v);
+            Ok(())
+        };
+        let mut at = start;
+        for b in text {
+            if let Err(e) = pipe.feed(at, Some(*b), &mut output) {
+                return Some(Err(e));
+            }
+            at += 1;
+        }
+        if let Err(e) = pipe.feed(at, None, &mut output) {
+            return Some(Err(e));
+        }
+        Some(value.ok_or(datum_error!(BadData, start, "index: no value")))
+    }
+
+    /// Searches the top-level values, which must be sorted as the comparator expects, parsing only the values the search looks at.
+    ///
+    /// The result is as with [slice::binary_search_by] (`Ok` with the index of a matching value, or `Err` with where one would be inserted), unless a value couldn't be parsed.
+    pub fn binary_search_by<F: FnMut(&DatumValue) -> Ordering>(
+        &self,
+        source: &[u8],
+        mut f: F,
+    ) -> DatumResult<Result<usize, usize>> {
+        let mut low = 0;
+        let mut high = self.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let value = match self.value(mid, source) {
+                Some(v) => v?,
+                None => return Err(datum_error!(BadData, 0, "index: doesn't match source")),
+            };
+            match f(&value) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(Ok(mid)),
+            }
+        }
+        Ok(Err(low))
+    }
+}
@@ -0,0 +1,28 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+//! Internal `tracing` helpers. These compile to nothing without the `tracing` feature, so instrumented code doesn't need `cfg`s everywhere.
+
+// Only `alloc`/`std` code is instrumented.
+#![allow(unused_macros)]
+
+/// Enters a debug-level span, bound to the given name, for the rest of the enclosing block.
+/// Fields that are recorded later (with [datum_span_record]) must be declared as `tracing::field::Empty`.
+macro_rules! datum_span {
+    ($span:ident, $name:literal $($fields:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let $span = tracing::debug_span!($name $($fields)*).entered();
+    };
+}
+
+/// Records a field of a span entered by [datum_span].
+macro_rules! datum_span_record {
+    ($span:ident, $field:literal, $value:expr) => {
+        #[cfg(feature = "tracing")]
+        $span.record($field, $value);
+    };
+}
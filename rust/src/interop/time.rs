@@ -0,0 +1,363 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+//! Timestamps and durations.
+//!
+//! These are written as enum variants, so with [crate::serde::ser::PlainSerializer]:
+//!
+//! * Timestamps are `(instant "2024-01-01T00:00:00Z")`: RFC 3339, written in UTC. `(instant 1704067200)` (Unix time in seconds) is also accepted.
+//! * Durations are `(duration SECONDS)` or `(duration SECONDS NANOSECONDS)`. The nanoseconds are from 0 to 999999999 and add to the seconds, so -1.5 seconds is `(duration -2 500000000)`.
+//!
+//! Each supported type has a module for `#[serde(with = "...")]`:
+//!
+//! * [duration] for [core::time::Duration]
+//! * [chrono_datetime] for `chrono::DateTime<Utc>` (`chrono` feature)
+//! * [chrono_duration] for `chrono::TimeDelta` (`chrono` feature)
+//! * [time_datetime] for `time::OffsetDateTime` (`time` feature)
+//! * [time_duration] for `time::Duration` (`time` feature)
+//!
+//! Other crates' types can be supported through [Timestamp] and [Span].
+//!
+//! _Added in 1.3.0._
+//! ```
+//! use core::time::Duration;
+//! use datum::serde::{ser::Style, DocLayout};
+//! use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Job {
+//!     #[serde(with = "datum::interop::time::duration")]
+//!     timeout: Duration,
+//! }
+//! let job = Job { timeout: Duration::from_millis(1500) };
+//! let text = DocLayout::Root.serialize_to_string(&job, Style::SpacingOnly).unwrap();
+//! assert_eq!(text, "timeout (duration 1 500000000)");
+//! assert_eq!(DocLayout::Root.deserialize_str::<Job, _>(text).unwrap(), job);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use core::convert::TryFrom;
+use core::fmt;
+
+use serde::de::{self, DeserializeSeed, EnumAccess, SeqAccess, VariantAccess, Visitor};
+use serde::ser::SerializeTupleVariant;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "alloc")]
+const INSTANT: &str = "instant";
+const DURATION: &str = "duration";
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Reads an enum variant name, accepting only the given one.
+#[derive(Clone, Copy)]
+struct Tag(&'static str);
+
+impl<'de> DeserializeSeed<'de> for Tag {
+    type Value = ();
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Tag {
+    type Value = ();
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}`", self.0)
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<(), E> {
+        if v == self.0 {
+            Ok(())
+        } else {
+            Err(E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+    }
+}
+
+/// Writes `(instant "text")`.
+#[cfg(feature = "alloc")]
+fn serialize_rfc3339<S: Serializer>(text: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_newtype_variant("Timestamp", 0, INSTANT, text)
+}
+
+/// A timestamp as written, before conversion to (or after conversion from) a specific crate's type.
+///
+/// _Added in 1.3.0._
+#[cfg(feature = "alloc")]
+#[non_exhaustive]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Timestamp {
+    /// RFC 3339 text, i.e. `2024-01-01T00:00:00Z`.
+    Rfc3339(String),
+    /// Unix time in seconds.
+    Unix(i64),
+}
+
+#[cfg(feature = "alloc")]
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Rfc3339(text) => serialize_rfc3339(text, serializer),
+            Self::Unix(v) => serializer.serialize_newtype_variant("Timestamp", 0, INSTANT, v),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+struct TimestampVisitor;
+
+#[cfg(feature = "alloc")]
+impl<'de> Visitor<'de> for TimestampVisitor {
+    type Value = Timestamp;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an instant")
+    }
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Timestamp, A::Error> {
+        let ((), v) = data.variant_seed(Tag(INSTANT))?;
+        v.newtype_variant_seed(self)
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Timestamp, E> {
+        Ok(Timestamp::Rfc3339(v.into()))
+    }
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Timestamp, E> {
+        Ok(Timestamp::Unix(v))
+    }
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Timestamp, E> {
+        i64::try_from(v)
+            .map(Timestamp::Unix)
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+}
+
+/// The value inside the variant.
+#[cfg(feature = "alloc")]
+impl<'de> DeserializeSeed<'de> for TimestampVisitor {
+    type Value = Timestamp;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Timestamp, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("Timestamp", &[INSTANT], TimestampVisitor)
+    }
+}
+
+/// A duration as written, before conversion to (or after conversion from) a specific crate's type.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Span {
+    /// Whole seconds (rounded down, so negative durations with nanoseconds have one less).
+    pub secs: i64,
+    /// Nanoseconds added to the seconds; from 0 to 999999999.
+    pub nanos: u32,
+}
+
+impl Span {
+    /// Creates a span from seconds and nanoseconds of the same sign (as most time crates split durations), rounding the seconds down.
+    pub fn from_signed(secs: i64, nanos: i32) -> Self {
+        if nanos < 0 {
+            Self {
+                secs: secs - 1,
+                nanos: (nanos + NANOS_PER_SEC as i32) as u32,
+            }
+        } else {
+            Self {
+                secs,
+                nanos: nanos as u32,
+            }
+        }
+    }
+}
+
+impl Serialize for Span {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let len = if self.nanos == 0 { 1 } else { 2 };
+        let mut tv = serializer.serialize_tuple_variant("Span", 0, DURATION, len)?;
+        tv.serialize_field(&self.secs)?;
+        if self.nanos != 0 {
+            tv.serialize_field(&self.nanos)?;
+        }
+        tv.end()
+    }
+}
+
+struct SpanVisitor;
+
+impl<'de> Visitor<'de> for SpanVisitor {
+    type Value = Span;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a duration")
+    }
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Span, A::Error> {
+        let ((), v) = data.variant_seed(Tag(DURATION))?;
+        v.tuple_variant(2, self)
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Span, A::Error> {
+        let secs = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let nanos: u32 = seq.next_element()?.unwrap_or(0);
+        if nanos >= NANOS_PER_SEC {
+            return Err(de::Error::invalid_value(
+                de::Unexpected::Unsigned(nanos as u64),
+                &"nanoseconds below 1000000000",
+            ));
+        }
+        Ok(Span { secs, nanos })
+    }
+}
+
+impl<'de> Deserialize<'de> for Span {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("Span", &[DURATION], SpanVisitor)
+    }
+}
+
+/// `#[serde(with = "datum::interop::time::duration")]` for [core::time::Duration].
+///
+/// _Added in 1.3.0._
+pub mod duration {
+    use core::convert::TryFrom;
+    use core::time::Duration;
+
+    use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Span;
+
+    /// Serializes a duration.
+    pub fn serialize<S: Serializer>(v: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        let secs = i64::try_from(v.as_secs())
+            .map_err(|_| <S::Error as ser::Error>::custom("duration: out of range"))?;
+        Span {
+            secs,
+            nanos: v.subsec_nanos(),
+        }
+        .serialize(serializer)
+    }
+
+    /// Deserializes a duration.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let span = Span::deserialize(deserializer)?;
+        let secs = u64::try_from(span.secs)
+            .map_err(|_| <D::Error as de::Error>::custom("duration: negative"))?;
+        Ok(Duration::new(secs, span.nanos))
+    }
+}
+
+/// `#[serde(with = "datum::interop::time::chrono_datetime")]` for `chrono::DateTime<Utc>`.
+///
+/// _Added in 1.3.0._
+#[cfg(all(feature = "chrono", feature = "alloc"))]
+pub mod chrono_datetime {
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use super::Timestamp;
+
+    /// Serializes a timestamp.
+    pub fn serialize<S: Serializer>(v: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_rfc3339(&v.to_rfc3339_opts(SecondsFormat::AutoSi, true), serializer)
+    }
+
+    /// Deserializes a timestamp.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        match Timestamp::deserialize(deserializer)? {
+            Timestamp::Rfc3339(text) => DateTime::parse_from_rfc3339(&text)
+                .map(|v| v.with_timezone(&Utc))
+                .map_err(de::Error::custom),
+            Timestamp::Unix(v) => DateTime::from_timestamp(v, 0)
+                .ok_or_else(|| de::Error::custom("instant: out of range")),
+        }
+    }
+}
+
+/// `#[serde(with = "datum::interop::time::chrono_duration")]` for `chrono::TimeDelta` (also known as `chrono::Duration`).
+///
+/// _Added in 1.3.0._
+#[cfg(feature = "chrono")]
+pub mod chrono_duration {
+    use chrono::TimeDelta;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Span;
+
+    /// Serializes a duration.
+    pub fn serialize<S: Serializer>(v: &TimeDelta, serializer: S) -> Result<S::Ok, S::Error> {
+        Span::from_signed(v.num_seconds(), v.subsec_nanos()).serialize(serializer)
+    }
+
+    /// Deserializes a duration.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TimeDelta, D::Error> {
+        let span = Span::deserialize(deserializer)?;
+        TimeDelta::new(span.secs, span.nanos)
+            .ok_or_else(|| de::Error::custom("duration: out of range"))
+    }
+}
+
+/// `#[serde(with = "datum::interop::time::time_datetime")]` for `time::OffsetDateTime`.
+///
+/// Timestamps are converted to UTC when written.
+///
+/// _Added in 1.3.0._
+#[cfg(all(feature = "time", feature = "alloc"))]
+pub mod time_datetime {
+    use ::time::format_description::well_known::Rfc3339;
+    use ::time::{OffsetDateTime, UtcOffset};
+    use serde::{de, ser, Deserialize, Deserializer, Serializer};
+
+    use super::Timestamp;
+
+    /// Serializes a timestamp.
+    pub fn serialize<S: Serializer>(v: &OffsetDateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let text = v
+            .to_offset(UtcOffset::UTC)
+            .format(&Rfc3339)
+            .map_err(ser::Error::custom)?;
+        super::serialize_rfc3339(&text, serializer)
+    }
+
+    /// Deserializes a timestamp.
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<OffsetDateTime, D::Error> {
+        match Timestamp::deserialize(deserializer)? {
+            Timestamp::Rfc3339(text) => {
+                OffsetDateTime::parse(&text, &Rfc3339).map_err(de::Error::custom)
+            }
+            Timestamp::Unix(v) => OffsetDateTime::from_unix_timestamp(v).map_err(de::Error::custom),
+        }
+    }
+}
+
+/// `#[serde(with = "datum::interop::time::time_duration")]` for `time::Duration`.
+///
+/// _Added in 1.3.0._
+#[cfg(feature = "time")]
+pub mod time_duration {
+    use ::time::Duration;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Span;
+
+    /// Serializes a duration.
+    pub fn serialize<S: Serializer>(v: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        Span::from_signed(v.whole_seconds(), v.subsec_nanoseconds()).serialize(serializer)
+    }
+
+    /// Deserializes a duration.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let span = Span::deserialize(deserializer)?;
+        // can't overflow, as the nanoseconds are less than a second
+        Ok(Duration::new(span.secs, span.nanos as i32))
+    }
+}
@@ -11,7 +11,16 @@
 // Meta
 
 #![cfg_attr(not(feature = "std"), no_std)]
-#![forbid(unsafe_code)]
+// The C, JavaScript and Python interfaces need `unsafe` (raw pointers and generated glue).
+// They're the only places allowed to use it.
+#![cfg_attr(
+    not(any(feature = "ffi", feature = "wasm", feature = "python")),
+    forbid(unsafe_code)
+)]
+#![cfg_attr(
+    any(feature = "ffi", feature = "wasm", feature = "python"),
+    deny(unsafe_code)
+)]
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
@@ -19,6 +28,9 @@ extern crate alloc;
 mod errors;
 pub use errors::*;
 
+#[macro_use]
+mod instrument;
+
 pub mod unary;
 
 mod queue;
@@ -49,16 +61,31 @@ pub use token_core::*;
 mod token;
 pub use token::*;
 
+mod span_token;
+pub use span_token::*;
+
+mod highlight;
+pub use highlight::*;
+
 // Values
 
 mod atom;
 pub use atom::*;
 
+mod value_ref;
+pub use value_ref::*;
+
 // Writing
 
 mod writer;
 pub use writer::*;
 
+mod stream_writer;
+pub use stream_writer::*;
+
+mod header;
+pub use header::*;
+
 // AST (alloc-only)
 
 #[cfg(feature = "alloc")]
@@ -66,11 +93,151 @@ mod ast;
 #[cfg(feature = "alloc")]
 pub use ast::*;
 
+#[cfg(feature = "alloc")]
+mod binary;
+#[cfg(feature = "alloc")]
+pub use binary::*;
+
+#[cfg(feature = "alloc")]
+mod definitions;
+#[cfg(feature = "alloc")]
+pub use definitions::*;
+
+#[cfg(feature = "alloc")]
+mod document;
+#[cfg(feature = "alloc")]
+pub use document::*;
+
+#[cfg(feature = "alloc")]
+mod canonical;
+
+#[cfg(feature = "alloc")]
+mod completion;
+#[cfg(feature = "alloc")]
+pub use completion::*;
+
+#[cfg(feature = "alloc")]
+mod extract;
+#[cfg(feature = "alloc")]
+pub use extract::*;
+
+#[cfg(feature = "alloc")]
+mod folding;
+#[cfg(feature = "alloc")]
+pub use folding::*;
+
+#[cfg(feature = "alloc")]
+mod format;
+#[cfg(feature = "alloc")]
+pub use format::*;
+
+#[cfg(feature = "alloc")]
+mod generator;
+#[cfg(feature = "alloc")]
+pub use generator::*;
+
+#[cfg(feature = "alloc")]
+mod index;
+#[cfg(feature = "alloc")]
+pub use index::*;
+
+#[cfg(feature = "alloc")]
+mod duplicates;
+#[cfg(feature = "alloc")]
+pub use duplicates::*;
+
+#[cfg(feature = "alloc")]
+mod eval;
+#[cfg(feature = "alloc")]
+pub use eval::*;
+
+#[cfg(feature = "alloc")]
+mod migrate;
+#[cfg(feature = "alloc")]
+pub use migrate::*;
+
+#[cfg(feature = "alloc")]
+mod outline;
+#[cfg(feature = "alloc")]
+pub use outline::*;
+
+#[cfg(feature = "alloc")]
+mod path;
+#[cfg(feature = "alloc")]
+pub use path::*;
+
+#[cfg(feature = "alloc")]
+mod pattern;
+#[cfg(feature = "alloc")]
+pub use pattern::*;
+
+#[cfg(feature = "alloc")]
+mod redact;
+#[cfg(feature = "alloc")]
+pub use redact::*;
+
+#[cfg(feature = "alloc")]
+mod rewrite;
+#[cfg(feature = "alloc")]
+pub use rewrite::*;
+
+#[cfg(feature = "alloc")]
+mod rename;
+#[cfg(feature = "alloc")]
+pub use rename::*;
+
+#[cfg(feature = "alloc")]
+mod trace;
+#[cfg(feature = "alloc")]
+pub use trace::*;
+
+#[cfg(feature = "std")]
+mod log_writer;
+#[cfg(feature = "std")]
+pub use log_writer::*;
+
+#[cfg(feature = "std")]
+mod ndd;
+#[cfg(feature = "std")]
+pub use ndd::*;
+
+#[cfg(feature = "std")]
+mod dir_loader;
+#[cfg(feature = "std")]
+pub use dir_loader::*;
+
+#[cfg(feature = "std")]
+mod store;
+#[cfg(feature = "std")]
+pub use store::*;
+
+#[cfg(feature = "std")]
+mod compression;
+#[cfg(feature = "std")]
+pub use compression::*;
+
+#[cfg(feature = "tokio_codec")]
+mod codec;
+#[cfg(feature = "tokio_codec")]
+pub use codec::*;
+
 // Pipelines (partially alloc-only)
 
 mod pipelines;
 pub use pipelines::*;
 
+mod framing;
+pub use framing::*;
+
+mod warnings;
+pub use warnings::*;
+
+mod diagnostic;
+pub use diagnostic::*;
+
+#[cfg(feature = "alloc")]
+pub mod repl;
+
 // Big test battery
 
 #[cfg(feature = "alloc")]
@@ -81,3 +248,21 @@ mod tests;
 
 #[cfg(feature = "serde")]
 pub mod serde;
+
+/// [serde::DatumSpanned], by the name other formats use for it (i.e. `toml::Spanned`).
+///
+/// _Added in 1.3.0._
+#[cfg(feature = "serde")]
+pub type Spanned<T> = serde::DatumSpanned<T>;
+
+#[cfg(feature = "serde")]
+pub mod interop;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "python")]
+pub mod python;
@@ -0,0 +1,124 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::string::{String, ToString};
+
+use crate::{DatumStreamWriter, DatumValue};
+
+// `std::io::Error::other` is newer than the MSRV.
+#[allow(clippy::io_other_error)]
+pub(crate) fn io_error<E: Display>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+}
+
+/// Appends values to a file, one per line, i.e. for structured logs and journals.
+///
+/// Each entry is written with a single write call, and if enabled, synced to disk afterwards.
+/// If the file doesn't end in a newline when opened (i.e. an earlier writer crashed mid-entry), one is added before the first entry, so at worst the broken entry fails to parse.
+///
+/// The result can be read back with any value pipeline. See [DatumLogWriter::append_separator] for use with [crate::DatumDocumentFramer].
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumDocument, DatumLogWriter};
+/// let path = std::env::temp_dir().join(format!("datum-log-doctest-{}.scm", std::process::id()));
+/// # let _ = std::fs::remove_file(&path);
+/// let mut log = DatumLogWriter::open(&path).unwrap();
+/// let entries: DatumDocument = "(started) (request 1)".parse().unwrap();
+/// for entry in entries.iter() {
+///     log.append_value(entry).unwrap();
+/// }
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "(started)\n(request 1)\n");
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct DatumLogWriter {
+    file: File,
+    sync: bool,
+    needs_newline: bool,
+}
+
+impl DatumLogWriter {
+    /// Opens (or creates) a file for appending.
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let mut needs_newline = false;
+        if file.seek(SeekFrom::End(0))? > 0 {
+            let mut last = [0u8];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            needs_newline = last[0] != b'\n';
+        }
+        Ok(Self {
+            file,
+            sync: false,
+            needs_newline,
+        })
+    }
+
+    /// Sets if each entry is synced to disk (via [File::sync_data]) after being written (by default, it isn't).
+    pub fn sync_each_entry(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Writes a finished entry, which must end in a newline.
+    fn append_entry(&mut self, mut entry: String) -> std::io::Result<()> {
+        if self.needs_newline {
+            entry.insert(0, '\n');
+        }
+        self.file.write_all(entry.as_bytes())?;
+        self.needs_newline = false;
+        if self.sync {
+            self.file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    /// Appends a value.
+    pub fn append_value(&mut self, value: &DatumValue) -> std::io::Result<()> {
+        let mut entry = String::new();
+        let mut writer = DatumStreamWriter::new(&mut entry, false);
+        writer.value(value).map_err(io_error)?;
+        self.append_entry(entry)
+    }
+
+    /// Appends a value, serialized with [crate::serde::DocLayout::Plain].
+    #[cfg(feature = "serde")]
+    pub fn append<T: serde::Serialize>(&mut self, value: &T) -> std::io::Result<()> {
+        let mut entry = crate::serde::DocLayout::Plain
+            .serialize_to_string(value, crate::serde::ser::Style::SpacingOnly)
+            .map_err(io_error)?;
+        entry.push('\n');
+        self.append_entry(entry)
+    }
+
+    /// Appends a document separator (see [crate::DatumDocumentFramer]).
+    pub fn append_separator(&mut self) -> std::io::Result<()> {
+        let mut entry = String::new();
+        let mut writer = DatumStreamWriter::new(&mut entry, false);
+        writer.separator().map_err(io_error)?;
+        self.append_entry(entry)
+    }
+
+    /// Syncs the file to disk.
+    pub fn sync(&mut self) -> std::io::Result<()> {
+        self.file.sync_data()
+    }
+
+    /// Unwraps the file.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+}
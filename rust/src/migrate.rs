@@ -0,0 +1,146 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::{boxed::Box, string::ToString, vec, vec::Vec};
+
+use crate::{
+    datum_error, DatumAtom, DatumDocument, DatumHeader, DatumHeaderCheck, DatumResult, DatumValue,
+};
+
+/// A migration step; see [DatumMigrator::step].
+///
+/// _Added in 1.3.0._
+pub type DatumMigrationStep = Box<dyn Fn(&mut DatumDocument) -> DatumResult<()>>;
+
+/// Brings documents written by older versions of an application up to the current version, so old files keep loading.
+///
+/// Documents are versioned with a [DatumHeader] as their first value.
+/// Each registered step transforms the document body (everything after the header) from one version to the next; the migrator runs whichever steps a given document needs and updates the header.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumAtom, DatumDocument, DatumMigrator, DatumValue};
+/// // version 1 was a bare name, version 2 puts it in a list with a size
+/// let migrator = DatumMigrator::new("example-save", 1).step(|doc| {
+///     let name = doc.remove(0);
+///     doc.push(DatumValue::List(vec![name, DatumValue::Atom(DatumAtom::Integer(1))]));
+///     Ok(())
+/// });
+/// assert_eq!(migrator.current_version(), 2);
+/// let mut doc: DatumDocument = "(example-save 1) \"old\"".parse().unwrap();
+/// assert_eq!(migrator.migrate(&mut doc).unwrap(), 1);
+/// assert_eq!(doc.to_string(), "(example-save 2)\n(\"old\" 1)\n");
+/// ```
+pub struct DatumMigrator {
+    format: &'static str,
+    first_version: i64,
+    headerless_version: Option<i64>,
+    steps: Vec<DatumMigrationStep>,
+}
+
+impl DatumMigrator {
+    /// Creates a migrator for the given format, where the oldest supported version is `first_version`.
+    /// Until steps are added, that's also the current version.
+    pub fn new(format: &'static str, first_version: i64) -> Self {
+        Self {
+            format,
+            first_version,
+            headerless_version: None,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Adds a step migrating from the current version to the next one, which then becomes the current version.
+    pub fn step<F: Fn(&mut DatumDocument) -> DatumResult<()> + 'static>(mut self, step: F) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// Treats documents without a header as being of the given version.
+    /// This is for formats that didn't have a header to begin with.
+    pub fn headerless_version(mut self, version: Option<i64>) -> Self {
+        self.headerless_version = version;
+        self
+    }
+
+    /// The format name.
+    pub fn format(&self) -> &'static str {
+        self.format
+    }
+
+    /// The version documents are migrated to.
+    pub fn current_version(&self) -> i64 {
+        self.first_version + self.steps.len() as i64
+    }
+
+    /// The header of the current version, for writing new documents.
+    pub fn current_header(&self) -> DatumHeader<&'static str> {
+        DatumHeader::new(self.format, self.current_version())
+    }
+
+    /// Migrates a document to the current version, returning the version it was.
+    ///
+    /// Afterwards, the first value of the document is the header of the current version (even if there was no header before).
+    /// If a step fails, the document is left part-way through migration, with the original header (if any).
+    pub fn migrate(&self, doc: &mut DatumDocument) -> DatumResult<i64> {
+        let header = doc.first().and_then(DatumHeader::from_value);
+        let has_header = header.is_some();
+        let version = match header {
+            Some(header) => {
+                match header.check(self.format, self.first_version..=self.current_version()) {
+                    DatumHeaderCheck::Supported(version) => version,
+                    DatumHeaderCheck::TooOld => {
+                        return Err(datum_error!(
+                            BadData,
+                            0,
+                            "migrate: version no longer supported"
+                        ))
+                    }
+                    DatumHeaderCheck::TooNew => {
+                        return Err(datum_error!(
+                            BadData,
+                            0,
+                            "migrate: version is newer than supported"
+                        ))
+                    }
+                    DatumHeaderCheck::WrongFormat => {
+                        return Err(datum_error!(BadData, 0, "migrate: wrong format"))
+                    }
+                }
+            }
+            None => match self.headerless_version {
+                Some(version)
+                    if version >= self.first_version && version <= self.current_version() =>
+                {
+                    version
+                }
+                Some(_) => {
+                    return Err(datum_error!(
+                        BadData,
+                        0,
+                        "migrate: version no longer supported"
+                    ))
+                }
+                None => return Err(datum_error!(BadData, 0, "migrate: missing header")),
+            },
+        };
+        // steps see the body only
+        let mut body = DatumDocument(doc.split_off(if has_header { 1 } else { 0 }));
+        let result = self.steps[(version - self.first_version) as usize..]
+            .iter()
+            .try_for_each(|step| step(&mut body));
+        if result.is_ok() {
+            doc.clear();
+            doc.push(DatumValue::List(vec![
+                DatumValue::Atom(DatumAtom::Symbol(self.format.to_string())),
+                DatumValue::Atom(DatumAtom::Integer(self.current_version())),
+            ]));
+        }
+        doc.append(&mut body);
+        result.map(|_| version)
+    }
+}
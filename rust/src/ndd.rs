@@ -0,0 +1,160 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::convert::TryFrom;
+use std::io::{BufRead, Write};
+use std::string::String;
+use std::vec::Vec;
+
+use crate::log_writer::io_error;
+use crate::{datum_error, DatumContextError, DatumDocument, DatumValue, DatumValueNode};
+
+/// A line read by [DatumLineReader].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Debug)]
+pub struct DatumLine {
+    /// Line number, starting at 1.
+    pub number: u64,
+    /// The value on the line, or why it couldn't be read. Error offsets are in bytes from the start of the line.
+    pub value: Result<DatumValue, DatumContextError>,
+}
+
+/// Reads newline-delimited Datum (like NDJSON): one value per line, i.e. from a log.
+///
+/// Lines that don't contain exactly one complete value are errors, but reading continues at the next line, so one bad line (i.e. a truncated write) doesn't lose the rest.
+/// Blank lines and lines containing only comments are skipped. I/O errors end the iteration.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::DatumLineReader;
+/// let log = "(start)\n(broken\n\n(request 1) ; comment\n";
+/// let lines: Vec<_> = DatumLineReader::new(log.as_bytes()).map(|v| v.unwrap()).collect();
+/// assert_eq!(lines.len(), 3);
+/// assert_eq!(lines[0].value.as_ref().unwrap().to_string(), "(start)");
+/// assert!(lines[1].value.is_err());
+/// assert_eq!(lines[2].number, 4);
+/// assert_eq!(lines[2].value.as_ref().unwrap().to_string(), "(request 1)");
+/// ```
+pub struct DatumLineReader<R> {
+    reader: R,
+    number: u64,
+    buffer: Vec<u8>,
+}
+
+impl<R: BufRead> DatumLineReader<R> {
+    /// Creates a reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            number: 0,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Number of the last line read, starting at 1.
+    pub fn line_number(&self) -> u64 {
+        self.number
+    }
+
+    /// Unwraps the reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for DatumLineReader<R> {
+    type Item = std::io::Result<DatumLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            match self.reader.read_until(b'\n', &mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+            self.number += 1;
+            let value = match DatumDocument::try_from(&self.buffer[..]) {
+                Ok(doc) if doc.is_empty() => continue,
+                Ok(mut doc) if doc.len() == 1 => Ok(doc.0.remove(0)),
+                Ok(_) => Err(DatumContextError::from(datum_error!(
+                    BadData,
+                    0,
+                    "ndd: more than one value on line"
+                ))),
+                Err(e) => Err(DatumContextError::from_bytes(e, &self.buffer)),
+            };
+            return Some(Ok(DatumLine {
+                number: self.number,
+                value,
+            }));
+        }
+    }
+}
+
+/// Writes newline-delimited Datum (see [DatumLineReader]).
+///
+/// Each value is written with a single write call. Values are written in canonical form, which never spans lines (newlines in strings are escaped).
+///
+/// For appending to files that may have been left mid-line, see [crate::DatumLogWriter].
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumDocument, DatumLineWriter};
+/// let doc: DatumDocument = "(message \"two\nlines\") (b)".parse().unwrap();
+/// let mut writer = DatumLineWriter::new(Vec::new());
+/// for value in doc.iter() {
+///     writer.write_value(value).unwrap();
+/// }
+/// assert_eq!(writer.into_inner(), b"(message \"two\\nlines\")\n(b)\n");
+/// ```
+pub struct DatumLineWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> DatumLineWriter<W> {
+    /// Creates a writer.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Writes a line, checking that it is one.
+    fn write_line(&mut self, mut line: String) -> std::io::Result<()> {
+        if line.contains('\n') {
+            return Err(io_error("ndd: value spans lines"));
+        }
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())
+    }
+
+    /// Writes a value.
+    pub fn write_value<V: DatumValueNode>(&mut self, value: &V) -> std::io::Result<()> {
+        let mut line = String::new();
+        crate::canonical::write_canonical_node(value, &mut line).map_err(io_error)?;
+        self.write_line(line)
+    }
+
+    /// Writes a value, serialized with [crate::serde::DocLayout::Plain].
+    #[cfg(feature = "serde")]
+    pub fn write<T: serde::Serialize>(&mut self, value: &T) -> std::io::Result<()> {
+        let line = crate::serde::DocLayout::Plain
+            .serialize_to_string(value, crate::serde::ser::Style::SpacingOnly)
+            .map_err(io_error)?;
+        self.write_line(line)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Unwraps the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
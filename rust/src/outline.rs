@@ -0,0 +1,126 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::highlight::datum_highlight_lines;
+use crate::{DatumHighlightKind, DatumHighlightSpan, DatumSpan, DatumSpanToken, DatumTokenType};
+
+/// A top-level list, from [datum_outline].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DatumOutlineEntry {
+    /// The list's first element, if it's a symbol (i.e. `define`).
+    pub head: Option<String>,
+    /// A name to show for the list: its second element if that's an atom, or the first element of its second element if that's a list starting with an atom (as in `(define (name args...) ...)`).
+    ///
+    /// Symbols and strings are unescaped; other atoms are as written.
+    pub name: Option<String>,
+    /// Where the name is, in characters (end inclusive).
+    pub name_span: Option<DatumSpan>,
+    /// Where the list is, in characters (end inclusive), from the `(` to the `)`.
+    pub span: DatumSpan,
+    /// Line of the `(` (starting at 0, as in the Language Server Protocol).
+    pub start_line: usize,
+    /// Line of the `)`.
+    pub end_line: usize,
+}
+
+/// The text of an atom span, unescaped for symbols and strings.
+pub(crate) fn atom_text(span: &DatumHighlightSpan, text: &str) -> Option<String> {
+    match span.kind {
+        DatumHighlightKind::Symbol | DatumHighlightKind::String => {
+            let token = DatumSpanToken {
+                token_type: DatumTokenType::Symbol,
+                span: DatumSpan {
+                    start: 0,
+                    end: span.span.end - span.span.start,
+                },
+            };
+            // an unfinished string can't be resolved
+            token.resolve(text).ok()?.buffer().cloned()
+        }
+        DatumHighlightKind::Numeric | DatumHighlightKind::SpecialID => Some(text.to_string()),
+        _ => None,
+    }
+}
+
+/// Lists the top-level lists of a document, with their heads and names, for outlines (i.e. LSP `textDocument/documentSymbol`, or a table of contents).
+///
+/// Like [crate::DatumHighlighter], this works on broken documents; lists that aren't closed are left out, and other top-level values are skipped.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::datum_outline;
+/// let source = "(define (area w h) (* w h))\n(section \"Intro\\x21;\"\n  (text hi))\n42";
+/// let outline: Vec<_> = datum_outline(source)
+///     .into_iter()
+///     .map(|e| (e.head.unwrap(), e.name.unwrap(), e.start_line, e.end_line))
+///     .collect();
+/// assert_eq!(outline, [
+///     ("define".to_string(), "area".to_string(), 0, 0),
+///     ("section".to_string(), "Intro!".to_string(), 1, 2),
+/// ]);
+/// ```
+pub fn datum_outline(source: &str) -> Vec<DatumOutlineEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<DatumOutlineEntry> = None;
+    // elements seen in the current list, and if the name is the start of a list element
+    let mut index = 0;
+    let mut name_in_list = false;
+    for (span, text, start_line) in datum_highlight_lines(source) {
+        match (span.kind, span.depth) {
+            (DatumHighlightKind::Whitespace, _) | (DatumHighlightKind::Comment, _) => {}
+            (DatumHighlightKind::ListStart, 0) => {
+                current = Some(DatumOutlineEntry {
+                    head: None,
+                    name: None,
+                    name_span: None,
+                    span: span.span,
+                    start_line,
+                    end_line: start_line,
+                });
+                index = 0;
+            }
+            (DatumHighlightKind::ListEnd, 0) => {
+                if let Some(mut entry) = current.take() {
+                    entry.span.end = span.span.end;
+                    entry.end_line = start_line;
+                    entries.push(entry);
+                }
+            }
+            (kind, depth) => {
+                let entry = match &mut current {
+                    Some(entry) => entry,
+                    None => continue,
+                };
+                if depth == 2 && name_in_list {
+                    entry.name = atom_text(&span, text);
+                    entry.name_span = entry.name.as_ref().map(|_| span.span);
+                }
+                name_in_list = false;
+                if depth != 1 || kind == DatumHighlightKind::ListEnd {
+                    continue;
+                }
+                if index == 0 && kind == DatumHighlightKind::Symbol {
+                    entry.head = atom_text(&span, text);
+                } else if index == 1 {
+                    if kind == DatumHighlightKind::ListStart {
+                        name_in_list = true;
+                    } else {
+                        entry.name = atom_text(&span, text);
+                        entry.name_span = entry.name.as_ref().map(|_| span.span);
+                    }
+                }
+                index += 1;
+            }
+        }
+    }
+    entries
+}
@@ -0,0 +1,85 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+/// A single step of a [DatumPath].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum DatumPathSegment {
+    /// Map/struct key.
+    Key(String),
+    /// Sequence index.
+    Index(usize),
+}
+
+impl Display for DatumPathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Key(k) => write!(f, ".{}", k),
+            Self::Index(i) => write!(f, "[{}]", i),
+        }
+    }
+}
+
+/// Location within a structured value, as a sequence of keys and indices.
+///
+/// This is displayed in the usual way, i.e. `server.listeners[2].port`.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumPath, DatumPathSegment};
+/// let mut path = DatumPath::default();
+/// path.push(DatumPathSegment::Key("server".into()));
+/// path.push(DatumPathSegment::Key("listeners".into()));
+/// path.push(DatumPathSegment::Index(2));
+/// path.push(DatumPathSegment::Key("port".into()));
+/// assert_eq!(path.to_string(), "server.listeners[2].port");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DatumPath(pub Vec<DatumPathSegment>);
+
+impl DatumPath {
+    /// Appends a segment.
+    pub fn push(&mut self, segment: DatumPathSegment) {
+        self.0.push(segment);
+    }
+
+    /// Removes the last segment.
+    pub fn pop(&mut self) -> Option<DatumPathSegment> {
+        self.0.pop()
+    }
+
+    /// Returns true for the root path.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the segments.
+    pub fn segments(&self) -> &[DatumPathSegment] {
+        &self.0
+    }
+}
+
+impl Display for DatumPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str(".");
+        }
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                // no leading dot
+                DatumPathSegment::Key(k) if i == 0 => f.write_str(k)?,
+                _ => segment.fmt(f)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::collections::BTreeMap;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::{convert::TryFrom, str::FromStr};
+
+use crate::{datum_error, DatumAtom, DatumDocument, DatumError, DatumResult, DatumValue};
+
+/// What a variable of a [DatumPattern] matched.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DatumBinding<'a> {
+    /// A single value, from `$name`.
+    One(&'a DatumValue),
+    /// Any number of consecutive list elements, from `$name...`.
+    Many(&'a [DatumValue]),
+}
+
+/// Variables bound by a successful [DatumPattern::matches].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DatumBindings<'a>(pub BTreeMap<&'a str, DatumBinding<'a>>);
+
+impl<'a> DatumBindings<'a> {
+    /// The value bound to a `$name` variable.
+    pub fn one(&self, name: &str) -> Option<&'a DatumValue> {
+        match self.0.get(name) {
+            Some(DatumBinding::One(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// The values bound to a `$name...` variable.
+    pub fn many(&self, name: &str) -> Option<&'a [DatumValue]> {
+        match self.0.get(name) {
+            Some(DatumBinding::Many(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Fills in a template (written like a [DatumPattern]) with these bindings.
+    ///
+    /// `$name` is replaced with its value, `$name...` is replaced with its values (spliced into the surrounding list), and `$$name` becomes the plain symbol `$name`.
+    /// Variables without a binding of the right kind are left as they are.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumDocument, DatumPattern};
+    /// let pattern: DatumPattern = "(swap $a $b $rest...)".parse().unwrap();
+    /// let doc: DatumDocument = "(swap 1 2 3 4) (list $b $a $rest... $$c)".parse().unwrap();
+    /// let bindings = pattern.matches(&doc[0]).unwrap();
+    /// assert_eq!(bindings.substitute(&doc[1]).to_string(), "(list 2 1 3 4 $c)");
+    /// ```
+    pub fn substitute(&self, template: &DatumValue) -> DatumValue {
+        match template {
+            DatumValue::Atom(DatumAtom::Symbol(symbol)) => match pattern_symbol(symbol) {
+                PatternSymbol::One(name) => match self.one(name) {
+                    Some(value) => value.clone(),
+                    None => template.clone(),
+                },
+                PatternSymbol::Literal(literal) => {
+                    DatumValue::Atom(DatumAtom::Symbol(literal.to_string()))
+                }
+                PatternSymbol::Many(_) => template.clone(),
+            },
+            DatumValue::List(list) => {
+                let mut result = Vec::with_capacity(list.len());
+                for element in list {
+                    match pattern_rest(element).and_then(|name| self.many(name)) {
+                        Some(values) => result.extend(values.iter().cloned()),
+                        None => result.push(self.substitute(element)),
+                    }
+                }
+                DatumValue::List(result)
+            }
+            _ => template.clone(),
+        }
+    }
+}
+
+/// What a pattern symbol means.
+pub(crate) enum PatternSymbol<'a> {
+    Literal(&'a str),
+    One(&'a str),
+    Many(&'a str),
+}
+
+pub(crate) fn pattern_symbol(symbol: &str) -> PatternSymbol<'_> {
+    if symbol.starts_with("$$") {
+        // `$$x` is the plain symbol `$x`
+        PatternSymbol::Literal(&symbol[1..])
+    } else if let Some(name) = symbol.strip_prefix('$') {
+        match name.strip_suffix("...") {
+            Some(name) => PatternSymbol::Many(name),
+            None => PatternSymbol::One(name),
+        }
+    } else {
+        PatternSymbol::Literal(symbol)
+    }
+}
+
+fn pattern_rest(value: &DatumValue) -> Option<&str> {
+    match value {
+        DatumValue::Atom(DatumAtom::Symbol(symbol)) => match pattern_symbol(symbol) {
+            PatternSymbol::Many(name) => Some(name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Structural pattern over [DatumValue]s, binding variables to the parts that vary.
+///
+/// Patterns are values, written as Datum text or built from values (see [DatumPattern::var] and [DatumPattern::rest]). Within a pattern:
+///
+/// * `$name` matches any one value, binding it to `name`.
+/// * `$name...` matches any number of consecutive list elements (including none), binding them to `name`. Each list can have one of these, anywhere in it.
+/// * `$_` and `$_...` match the same way, but don't bind anything.
+/// * `$$name` matches the plain symbol `$name`.
+/// * Lists match lists of elements matching their elements, and other atoms match equal atoms.
+///
+/// If a variable appears more than once, each match must be equal.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumAtom, DatumDocument, DatumPattern};
+/// let def: DatumPattern = "(def $name $args... $body)".parse().unwrap();
+/// let doc: DatumDocument = "(def area w h (* w h)) (set x 1)".parse().unwrap();
+/// let bindings = def.matches(&doc[0]).unwrap();
+/// assert_eq!(bindings.one("name").unwrap().to_string(), "area");
+/// assert_eq!(bindings.many("args").unwrap().len(), 2);
+/// assert_eq!(bindings.one("body").unwrap().to_string(), "(* w h)");
+/// assert!(def.matches(&doc[1]).is_none());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct DatumPattern(DatumValue);
+
+impl DatumPattern {
+    /// Creates a pattern from a value, checking that no list has more than one `$name...` variable, and that the pattern isn't one by itself.
+    pub fn new(pattern: DatumValue) -> DatumResult<Self> {
+        if pattern_rest(&pattern).is_some() {
+            return Err(datum_error!(
+                BadData,
+                0,
+                "pattern: rest variable outside of a list"
+            ));
+        }
+        let mut pending = alloc::vec![&pattern];
+        while let Some(value) = pending.pop() {
+            if let DatumValue::List(list) = value {
+                if list.iter().filter_map(pattern_rest).count() > 1 {
+                    return Err(datum_error!(
+                        BadData,
+                        0,
+                        "pattern: more than one rest variable in a list"
+                    ));
+                }
+                pending.extend(list.iter());
+            }
+        }
+        Ok(Self(pattern))
+    }
+
+    /// A `$name` variable, for building patterns from values.
+    pub fn var(name: &str) -> DatumValue {
+        DatumValue::Atom(DatumAtom::Symbol(alloc::format!("${}", name)))
+    }
+
+    /// A `$name...` variable, for building patterns from values.
+    pub fn rest(name: &str) -> DatumValue {
+        DatumValue::Atom(DatumAtom::Symbol(alloc::format!("${}...", name)))
+    }
+
+    /// The pattern as a value.
+    pub fn value(&self) -> &DatumValue {
+        &self.0
+    }
+
+    /// Names of the variables this pattern binds, and whether each is a `$name...` variable.
+    pub(crate) fn variables(&self) -> BTreeMap<&str, bool> {
+        let mut variables = BTreeMap::new();
+        let mut pending = alloc::vec![&self.0];
+        while let Some(value) = pending.pop() {
+            match value {
+                DatumValue::Atom(DatumAtom::Symbol(symbol)) => match pattern_symbol(symbol) {
+                    PatternSymbol::One(name) => {
+                        variables.insert(name, false);
+                    }
+                    PatternSymbol::Many(name) => {
+                        variables.insert(name, true);
+                    }
+                    PatternSymbol::Literal(_) => {}
+                },
+                DatumValue::List(list) => pending.extend(list.iter()),
+                _ => {}
+            }
+        }
+        variables.remove("_");
+        variables
+    }
+
+    /// Matches a value against this pattern, returning the bound variables if it matches.
+    pub fn matches<'a>(&'a self, value: &'a DatumValue) -> Option<DatumBindings<'a>> {
+        let mut bindings = DatumBindings::default();
+        if match_value(&self.0, value, &mut bindings) {
+            Some(bindings)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for DatumPattern {
+    type Err = DatumError;
+    /// Parses a pattern from text, which must be a single value.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut doc = DatumDocument::try_from(s)?;
+        if doc.len() != 1 {
+            return Err(datum_error!(
+                BadData,
+                0,
+                "pattern: expected exactly one value"
+            ));
+        }
+        Self::new(doc.0.remove(0))
+    }
+}
+
+/// Binds a variable, checking repeats are equal.
+fn bind<'a>(bindings: &mut DatumBindings<'a>, name: &'a str, binding: DatumBinding<'a>) -> bool {
+    if name == "_" {
+        return true;
+    }
+    match bindings.0.get(name) {
+        Some(existing) => *existing == binding,
+        None => {
+            bindings.0.insert(name, binding);
+            true
+        }
+    }
+}
+
+fn match_value<'a>(
+    pattern: &'a DatumValue,
+    value: &'a DatumValue,
+    bindings: &mut DatumBindings<'a>,
+) -> bool {
+    match (pattern, value) {
+        (DatumValue::Atom(DatumAtom::Symbol(symbol)), _) => match pattern_symbol(symbol) {
+            PatternSymbol::One(name) => bind(bindings, name, DatumBinding::One(value)),
+            // lists handle these, and they can't be at the root
+            PatternSymbol::Many(_) => false,
+            PatternSymbol::Literal(literal) => {
+                matches!(value, DatumValue::Atom(DatumAtom::Symbol(v)) if v == literal)
+            }
+        },
+        (DatumValue::List(patterns), DatumValue::List(values)) => {
+            match patterns.iter().position(|v| pattern_rest(v).is_some()) {
+                None => {
+                    patterns.len() == values.len()
+                        && patterns
+                            .iter()
+                            .zip(values.iter())
+                            .all(|(p, v)| match_value(p, v, bindings))
+                }
+                Some(rest) => {
+                    let after = patterns.len() - rest - 1;
+                    if values.len() < rest + after {
+                        return false;
+                    }
+                    let rest_end = values.len() - after;
+                    patterns[..rest]
+                        .iter()
+                        .zip(values[..rest].iter())
+                        .all(|(p, v)| match_value(p, v, bindings))
+                        && patterns[rest + 1..]
+                            .iter()
+                            .zip(values[rest_end..].iter())
+                            .all(|(p, v)| match_value(p, v, bindings))
+                        && bind(
+                            bindings,
+                            pattern_rest(&patterns[rest]).unwrap_or_default(),
+                            DatumBinding::Many(&values[rest..rest_end]),
+                        )
+                }
+            }
+        }
+        (DatumValue::Atom(p), DatumValue::Atom(v)) => p == v,
+        _ => false,
+    }
+}
@@ -5,7 +5,10 @@
  * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
  */
 
-use crate::{unary, DatumBoundedQueue, DatumOffset, DatumResult, DatumUnaryNumIntoQueue};
+use crate::{
+    unary, DatumBoundedQueue, DatumCancelPipe, DatumMetric, DatumMetricsPipe, DatumOffset,
+    DatumProgress, DatumProgressPipe, DatumResult, DatumUnaryNumIntoQueue,
+};
 
 /// Generic "input X, get Y" function
 pub trait DatumPipe {
@@ -57,6 +60,26 @@ pub trait DatumPipe {
         }
     }
 
+    /// Feeds from a slice or similar into a newly allocated [alloc::vec::Vec], which is returned.
+    /// Otherwise the same as [DatumPipe::feed_iter_to_vec].
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumDecoder, DatumPipe};
+    /// let results = DatumDecoder::default().feed_all_to_vec("example text".chars(), true).unwrap();
+    /// assert_eq!(results.len(), 12);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn feed_all_to_vec<S: IntoIterator<Item = Self::Input>>(
+        &mut self,
+        source: S,
+        eof: bool,
+    ) -> DatumResult<alloc::vec::Vec<Self::Output>> {
+        let mut target = alloc::vec::Vec::new();
+        self.feed_iter_to_vec(&mut target, source, eof)?;
+        Ok(target)
+    }
+
     /// Composes with another pipeline.
     fn compose<P: DatumPipe<Input = Self::Output>>(self, other: P) -> DatumComposePipe<Self, P>
     where
@@ -64,6 +87,47 @@ pub trait DatumPipe {
     {
         DatumComposePipe(self, other)
     }
+
+    /// Wraps this pipeline in a [DatumProgressPipe], which reports progress to the given callback after every feed call.
+    ///
+    /// _Added in 1.3.0._
+    fn with_progress<F: FnMut(DatumProgress)>(self, callback: F) -> DatumProgressPipe<Self, F>
+    where
+        Self: Sized,
+    {
+        DatumProgressPipe::new(self, callback)
+    }
+
+    /// Wraps this pipeline in a [DatumMetricsPipe], which reports counters to the given callback.
+    ///
+    /// _Added in 1.3.0._
+    fn with_metrics<F: FnMut(DatumMetric, u64)>(self, callback: F) -> DatumMetricsPipe<Self, F>
+    where
+        Self: Sized,
+    {
+        DatumMetricsPipe::new(self, callback)
+    }
+
+    /// Wraps this pipeline in a [DatumCancelPipe], which checks the given function before every feed call and fails if it returns true.
+    ///
+    /// _Added in 1.3.0._
+    fn with_cancel<F: FnMut() -> bool>(self, cancelled: F) -> DatumCancelPipe<Self, F>
+    where
+        Self: Sized,
+    {
+        DatumCancelPipe::new(self, cancelled)
+    }
+
+    /// Wraps this pipeline in a [crate::DatumTracingPipe], which feeds it within a `tracing` span.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "tracing")]
+    fn with_tracing(self, stage: &'static str) -> crate::DatumTracingPipe<Self>
+    where
+        Self: Sized,
+    {
+        crate::DatumTracingPipe::new(self, stage)
+    }
 }
 
 /// [DatumPipe] of bounded output size.
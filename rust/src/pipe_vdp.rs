@@ -9,7 +9,7 @@
 mod vdp {
     use alloc::collections::VecDeque;
 
-    use crate::{DatumOffset, DatumPipe, DatumResult};
+    use crate::{DatumErrorKind, DatumOffset, DatumPipe, DatumResult};
 
     /// This is used in [IntoViaDatumPipe::via_datum_pipe].
     #[derive(Clone)]
@@ -19,6 +19,34 @@ mod vdp {
         pipeline: P,
         buffer: VecDeque<P::Output>,
         eof: bool,
+        errors_are_fatal: bool,
+    }
+
+    impl<I: Iterator<Item = S>, S, P: DatumPipe<Input = S>> ViaDatumPipe<I, S, P> {
+        /// Sets if errors are fatal (by default, they aren't).
+        /// If they are, then once an error has been returned, the iterator ends.
+        /// Any buffered values are discarded and the source is not read any further.
+        ///
+        /// Cancellation (a [crate::DatumErrorKind::Cancelled] error, i.e. from [crate::DatumCancelPipe]) is always fatal.
+        ///
+        /// _Added in 1.3.0._
+        pub fn errors_are_fatal(mut self, fatal: bool) -> Self {
+            self.errors_are_fatal = fatal;
+            self
+        }
+
+        /// Makes errors fatal; see [ViaDatumPipe::errors_are_fatal].
+        ///
+        /// _Added in 1.3.0._
+        /// ```
+        /// use datum::{IntoViaDatumPipe, datum_char_to_value_pipeline};
+        /// let mut iterator = ") 1".chars().via_datum_pipe(datum_char_to_value_pipeline()).fuse_on_error();
+        /// assert!(iterator.next().unwrap().is_err());
+        /// assert!(iterator.next().is_none());
+        /// ```
+        pub fn fuse_on_error(self) -> Self {
+            self.errors_are_fatal(true)
+        }
     }
 
     impl<I: Iterator<Item = S>, S, P: DatumPipe<Input = S>> Iterator for ViaDatumPipe<I, S, P> {
@@ -41,6 +69,10 @@ mod vdp {
                         Ok(())
                     });
                     if let Err(err) = res {
+                        if self.errors_are_fatal || err.kind == DatumErrorKind::Cancelled {
+                            self.buffer.clear();
+                            self.eof = true;
+                        }
                         return Some(Err(err));
                     }
                     self.offset += 1;
@@ -67,6 +99,7 @@ mod vdp {
                 pipeline: pipe,
                 buffer: VecDeque::new(),
                 eof: false,
+                errors_are_fatal: false,
             }
         }
     }
@@ -76,22 +109,64 @@ mod vdp {
 pub use vdp::*;
 
 mod vdbp {
-    use crate::{DatumBoundedPipeEx, DatumBoundedQueue, DatumOffset, DatumResult};
+    use crate::{
+        DatumArrayPipeQueue, DatumBoundedPipeEx, DatumBoundedQueue, DatumErrorKind, DatumOffset,
+        DatumResult, DatumUnaryNumIntoArrayQueue,
+    };
 
     /// This is used in [IntoViaDatumBufPipe::via_datum_buf_pipe].
     ///
+    /// The queue type is the pipe's [DatumBoundedPipeEx::OutputQueue] by default; [IntoViaDatumBufPipe::via_datum_array_pipe] uses a [crate::DatumArrayQueue] instead.
+    ///
     /// _Added in 1.2.0._
     #[derive(Clone)]
-    pub struct ViaDatumBufPipe<I: Iterator<Item = S>, S, P: DatumBoundedPipeEx<Input = S>> {
+    pub struct ViaDatumBufPipe<
+        I: Iterator<Item = S>,
+        S,
+        P: DatumBoundedPipeEx<Input = S>,
+        Q: DatumBoundedQueue<(DatumOffset, P::Output)> = <P as DatumBoundedPipeEx>::OutputQueue,
+    > {
         offset: DatumOffset,
         iterator: I,
         pipeline: P,
-        buffer: P::OutputQueue,
+        buffer: Q,
         eof: bool,
+        errors_are_fatal: bool,
+    }
+
+    impl<
+            I: Iterator<Item = S>,
+            S,
+            P: DatumBoundedPipeEx<Input = S>,
+            Q: DatumBoundedQueue<(DatumOffset, P::Output)>,
+        > ViaDatumBufPipe<I, S, P, Q>
+    {
+        /// Sets if errors are fatal (by default, they aren't).
+        /// If they are, then once an error has been returned, the iterator ends.
+        /// Any buffered values are discarded and the source is not read any further.
+        ///
+        /// Cancellation (a [crate::DatumErrorKind::Cancelled] error, i.e. from [crate::DatumCancelPipe]) is always fatal.
+        ///
+        /// _Added in 1.3.0._
+        pub fn errors_are_fatal(mut self, fatal: bool) -> Self {
+            self.errors_are_fatal = fatal;
+            self
+        }
+
+        /// Makes errors fatal; see [ViaDatumBufPipe::errors_are_fatal].
+        ///
+        /// _Added in 1.3.0._
+        pub fn fuse_on_error(self) -> Self {
+            self.errors_are_fatal(true)
+        }
     }
 
-    impl<I: Iterator<Item = S>, S, P: DatumBoundedPipeEx<Input = S>> Iterator
-        for ViaDatumBufPipe<I, S, P>
+    impl<
+            I: Iterator<Item = S>,
+            S,
+            P: DatumBoundedPipeEx<Input = S>,
+            Q: DatumBoundedQueue<(DatumOffset, P::Output)>,
+        > Iterator for ViaDatumBufPipe<I, S, P, Q>
     {
         type Item = DatumResult<P::Output>;
 
@@ -112,6 +187,10 @@ mod vdbp {
                         Ok(())
                     });
                     if let Err(err) = res {
+                        if self.errors_are_fatal || err.kind == DatumErrorKind::Cancelled {
+                            self.buffer = Q::default();
+                            self.eof = true;
+                        }
                         return Some(Err(err));
                     }
                     self.offset += 1;
@@ -133,6 +212,27 @@ mod vdbp {
             self,
             pipe: P,
         ) -> ViaDatumBufPipe<Self, I, P>;
+
+        /// Like [IntoViaDatumBufPipe::via_datum_buf_pipe], but the buffer is a [crate::DatumArrayQueue].
+        /// This is faster, but only works for output queue sizes supported by [crate::DatumArrayQueue].
+        ///
+        /// _Added in 1.3.0._
+        fn via_datum_array_pipe<P: DatumBoundedPipeEx<Input = I>>(
+            self,
+            pipe: P,
+        ) -> ViaDatumBufPipe<Self, I, P, DatumArrayPipeQueue<P>>
+        where
+            P::OutputQueueSize: DatumUnaryNumIntoArrayQueue<(DatumOffset, P::Output)>,
+        {
+            ViaDatumBufPipe {
+                offset: 0,
+                iterator: self,
+                pipeline: pipe,
+                buffer: Default::default(),
+                eof: false,
+                errors_are_fatal: false,
+            }
+        }
     }
 
     impl<I, V: Iterator<Item = I> + Sized> IntoViaDatumBufPipe<I> for V {
@@ -146,6 +246,7 @@ mod vdbp {
                 pipeline: pipe,
                 buffer: Default::default(),
                 eof: false,
+                errors_are_fatal: false,
             }
         }
     }
@@ -5,18 +5,19 @@
  * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
  */
 
-use core::{cell::Cell, marker::PhantomData};
+use core::{cell::Cell, marker::PhantomData, ops::Deref};
 
 use crate::{
-    unary, DatumBoundedPipe, DatumComposePipe, DatumDecoder, DatumOffset, DatumPipe,
-    DatumPipeTokenizer, DatumResult, DatumUTF8Decoder,
+    datum_error, unary, DatumBoundedPipe, DatumChar, DatumCharClass, DatumComposePipe,
+    DatumDecoder, DatumError, DatumErrorKind, DatumOffset, DatumPipe, DatumPipeTokenizer,
+    DatumResult, DatumSpanToken, DatumSpanTokenizer, DatumToken, DatumUTF8Decoder,
 };
 
 #[cfg(feature = "alloc")]
 use alloc::string::String;
 
 #[cfg(feature = "alloc")]
-use crate::{DatumParser, DatumToken, DatumValue};
+use crate::{DatumParser, DatumValue};
 
 // -- token outputting --
 
@@ -41,6 +42,20 @@ pub fn datum_char_to_token_pipeline() -> impl DatumPipe<Input = char, Output = D
     DatumCharToTokenPipeline::default()
 }
 
+/// Char to [DatumSpanToken] pipeline.
+/// _Added in 1.3.0._
+pub type DatumCharToSpanTokenPipeline = DatumComposePipe<DatumDecoder, DatumSpanTokenizer>;
+
+/// Character to [DatumSpanToken] pipeline, for scanning; see [DatumSpanTokenizer].
+///
+/// Unlike the other pipelines, this doesn't need `alloc`.
+///
+/// _Added in 1.3.0._
+pub fn datum_char_to_span_token_pipeline() -> impl DatumPipe<Input = char, Output = DatumSpanToken>
+{
+    DatumCharToSpanTokenPipeline::default()
+}
+
 // -- value outputting --
 
 /// Byte to value parsing pipeline.
@@ -124,3 +139,739 @@ impl<V: Copy + Into<u32>> DatumPipe for DatumLineNumberTracker<'_, V> {
 impl<V: Copy + Into<u32>> DatumBoundedPipe for DatumLineNumberTracker<'_, V> {
     type OutputQueueSize = unary::C1;
 }
+
+/// Statistics gathered by [DatumStatsPipe].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DatumStats {
+    /// Amount of [DatumToken::String] tokens.
+    pub strings: u64,
+    /// Amount of [DatumToken::Symbol] tokens.
+    pub symbols: u64,
+    /// Amount of [DatumToken::SpecialID] tokens.
+    pub special_ids: u64,
+    /// Amount of [DatumToken::Integer] tokens.
+    pub integers: u64,
+    /// Amount of [DatumToken::Float] tokens.
+    pub floats: u64,
+    /// Amount of [DatumToken::ListStart] tokens.
+    pub list_starts: u64,
+    /// Amount of [DatumToken::ListEnd] tokens.
+    pub list_ends: u64,
+    /// Total length in bytes of the (unescaped) contents of string tokens.
+    pub string_bytes: u64,
+    /// Deepest list nesting seen.
+    pub max_depth: u64,
+    /// Amount of completed top-level values.
+    pub values: u64,
+}
+
+impl DatumStats {
+    /// Total amount of tokens seen.
+    pub fn tokens(&self) -> u64 {
+        self.strings
+            + self.symbols
+            + self.special_ids
+            + self.integers
+            + self.floats
+            + self.list_starts
+            + self.list_ends
+    }
+}
+
+/// 'Tracking' pipeline stage which gathers [DatumStats] about passing tokens into a cell by reference.
+///
+/// Like [DatumLineNumberTracker], this is transparent and can be 'lost in' pipelines.
+///
+/// Depth and value counting assume the token stream is well-formed; stray list ends are counted but don't reduce depth below zero.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumPipe, DatumStats, DatumStatsPipe, datum_char_to_token_pipeline};
+/// use core::cell::Cell;
+/// let stats = Cell::new(DatumStats::default());
+/// let mut pipeline = datum_char_to_token_pipeline().compose(DatumStatsPipe::new(&stats));
+/// pipeline.feed_all_to_vec("(a \"bc\" (1 2.5)) #t".chars(), true).unwrap();
+/// let stats = stats.get();
+/// assert_eq!(stats.tokens(), 9);
+/// assert_eq!(stats.string_bytes, 2);
+/// assert_eq!(stats.max_depth, 2);
+/// assert_eq!(stats.values, 2);
+/// ```
+pub struct DatumStatsPipe<'stats, B: Deref<Target = str>> {
+    stats: &'stats Cell<DatumStats>,
+    depth: u64,
+    _buffer: PhantomData<B>,
+}
+
+impl<'stats, B: Deref<Target = str>> DatumStatsPipe<'stats, B> {
+    /// Creates a new DatumStatsPipe with the given statistics storage.
+    pub fn new(stats: &'stats Cell<DatumStats>) -> Self {
+        Self {
+            stats,
+            depth: 0,
+            _buffer: PhantomData,
+        }
+    }
+}
+
+impl<B: Deref<Target = str>> DatumPipe for DatumStatsPipe<'_, B> {
+    type Input = DatumToken<B>;
+    type Output = DatumToken<B>;
+    fn feed<F: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut F,
+    ) -> DatumResult<()> {
+        if let Some(token) = i {
+            let mut stats = self.stats.get();
+            let mut completes_value = self.depth == 0;
+            match &token {
+                DatumToken::String(_, b) => {
+                    stats.strings += 1;
+                    stats.string_bytes += b.len() as u64;
+                }
+                DatumToken::Symbol(_, _) => stats.symbols += 1,
+                DatumToken::SpecialID(_, _) => stats.special_ids += 1,
+                DatumToken::Integer(_, _) => stats.integers += 1,
+                DatumToken::Float(_, _) => stats.floats += 1,
+                DatumToken::ListStart(_) => {
+                    stats.list_starts += 1;
+                    self.depth += 1;
+                    stats.max_depth = stats.max_depth.max(self.depth);
+                    completes_value = false;
+                }
+                DatumToken::ListEnd(_) => {
+                    stats.list_ends += 1;
+                    completes_value = self.depth == 1;
+                    self.depth = self.depth.saturating_sub(1);
+                }
+            }
+            if completes_value {
+                stats.values += 1;
+            }
+            self.stats.set(stats);
+            f(at, token)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<B: Deref<Target = str>> DatumBoundedPipe for DatumStatsPipe<'_, B> {
+    type OutputQueueSize = unary::C1;
+}
+
+/// Progress report from [DatumProgressPipe].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DatumProgress {
+    /// Amount of input elements consumed (i.e. bytes, for a byte pipeline).
+    pub consumed: u64,
+    /// Amount of output elements emitted (i.e. values, for a value pipeline).
+    pub emitted: u64,
+    /// If EOF has been fed.
+    pub eof: bool,
+}
+
+/// Wraps a pipeline and reports [DatumProgress] to a callback after every feed call.
+///
+/// Since this is a wrapper rather than a stage, it works with any driver (i.e. [crate::IntoViaDatumPipe::via_datum_pipe] or [DatumPipe::feed_iter_to_vec]).
+///
+/// The callback is called often; if it's expensive (i.e. redrawing a progress bar), it should do its own throttling.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumPipe, IntoViaDatumPipe, datum_byte_to_value_pipeline};
+/// let mut last = None;
+/// let source = "(1 2 3) (4 5 6)";
+/// let pipeline = datum_byte_to_value_pipeline().with_progress(|p| last = Some(p));
+/// assert_eq!(source.bytes().via_datum_pipe(pipeline).count(), 2);
+/// let last = last.unwrap();
+/// assert_eq!(last.consumed, source.len() as u64);
+/// assert_eq!(last.emitted, 2);
+/// assert!(last.eof);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DatumProgressPipe<P: DatumPipe, F: FnMut(DatumProgress)> {
+    pipe: P,
+    callback: F,
+    progress: DatumProgress,
+}
+
+impl<P: DatumPipe, F: FnMut(DatumProgress)> DatumProgressPipe<P, F> {
+    /// Creates a new DatumProgressPipe wrapping the given pipeline.
+    pub fn new(pipe: P, callback: F) -> Self {
+        Self {
+            pipe,
+            callback,
+            progress: DatumProgress::default(),
+        }
+    }
+
+    /// Returns the progress so far.
+    pub fn progress(&self) -> DatumProgress {
+        self.progress
+    }
+
+    /// Unwraps the inner pipeline.
+    pub fn into_inner(self) -> P {
+        self.pipe
+    }
+}
+
+impl<P: DatumPipe, F: FnMut(DatumProgress)> DatumPipe for DatumProgressPipe<P, F> {
+    type Input = P::Input;
+    type Output = P::Output;
+    fn feed<G: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut G,
+    ) -> DatumResult<()> {
+        if i.is_some() {
+            self.progress.consumed += 1;
+        } else {
+            self.progress.eof = true;
+        }
+        let progress = &mut self.progress;
+        let res = self.pipe.feed(at, i, &mut |at, v| {
+            progress.emitted += 1;
+            f(at, v)
+        });
+        (self.callback)(self.progress);
+        res
+    }
+}
+
+impl<P: DatumBoundedPipe, F: FnMut(DatumProgress)> DatumBoundedPipe for DatumProgressPipe<P, F> {
+    type OutputQueueSize = P::OutputQueueSize;
+}
+
+/// Counter reported by [DatumMetricsPipe].
+///
+/// _Added in 1.3.0._
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DatumMetric {
+    /// Input elements consumed (i.e. bytes, for a byte pipeline).
+    Consumed,
+    /// Output elements emitted (i.e. values, for a value pipeline).
+    Emitted,
+    /// The pipeline returned an error of this kind.
+    Error(DatumErrorKind),
+    /// EOF was fed without error; that is, a whole input was parsed.
+    Completed,
+}
+
+impl DatumMetric {
+    /// Stable lowercase name, for use as a metric name or label (i.e. `consumed`, `error-bad-data`).
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Consumed => "consumed",
+            Self::Emitted => "emitted",
+            Self::Completed => "completed",
+            Self::Error(DatumErrorKind::OutOfRoom) => "error-out-of-room",
+            Self::Error(DatumErrorKind::Interrupted) => "error-interrupted",
+            Self::Error(DatumErrorKind::BadData) => "error-bad-data",
+            Self::Error(DatumErrorKind::Cancelled) => "error-cancelled",
+            Self::Error(DatumErrorKind::Custom) => "error-custom",
+        }
+    }
+}
+
+/// Wraps a pipeline and reports counters ([DatumMetric]s, with an amount to add) to a callback, for exporting to a metrics system (i.e. parse failure rates and throughput).
+///
+/// Like [DatumProgressPipe], this works with any driver. Unlike it, counters are reported as increments, and only when they change, so the callback can add them to shared (i.e. atomic) counters across many parses.
+///
+/// Errors returned by the output function (i.e. from later stages) aren't counted as this pipeline's errors.
+///
+/// This can't see memory allocations; those are best measured with a counting global allocator.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use core::sync::atomic::{AtomicU64, Ordering};
+/// use datum::{DatumMetric, DatumPipe, IntoViaDatumPipe, datum_byte_to_value_pipeline};
+/// static BYTES: AtomicU64 = AtomicU64::new(0);
+/// static FAILURES: AtomicU64 = AtomicU64::new(0);
+/// fn metrics(metric: DatumMetric, amount: u64) {
+///     match metric {
+///         DatumMetric::Consumed => BYTES.fetch_add(amount, Ordering::Relaxed),
+///         DatumMetric::Error(_) => FAILURES.fetch_add(amount, Ordering::Relaxed),
+///         _ => 0,
+///     };
+/// }
+/// for request in ["(get 1)", "(get", "(put 2 3)"] {
+///     let pipeline = datum_byte_to_value_pipeline().with_metrics(metrics);
+///     let _ = request.bytes().via_datum_pipe(pipeline).collect::<Result<Vec<_>, _>>();
+/// }
+/// assert_eq!(BYTES.load(Ordering::Relaxed), 20);
+/// assert_eq!(FAILURES.load(Ordering::Relaxed), 1);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DatumMetricsPipe<P: DatumPipe, F: FnMut(DatumMetric, u64)> {
+    pipe: P,
+    callback: F,
+}
+
+impl<P: DatumPipe, F: FnMut(DatumMetric, u64)> DatumMetricsPipe<P, F> {
+    /// Creates a new DatumMetricsPipe wrapping the given pipeline.
+    pub fn new(pipe: P, callback: F) -> Self {
+        Self { pipe, callback }
+    }
+
+    /// Unwraps the inner pipeline.
+    pub fn into_inner(self) -> P {
+        self.pipe
+    }
+}
+
+impl<P: DatumPipe, F: FnMut(DatumMetric, u64)> DatumPipe for DatumMetricsPipe<P, F> {
+    type Input = P::Input;
+    type Output = P::Output;
+    fn feed<G: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut G,
+    ) -> DatumResult<()> {
+        let eof = i.is_none();
+        if !eof {
+            (self.callback)(DatumMetric::Consumed, 1);
+        }
+        let mut emitted = 0;
+        let mut downstream_error = false;
+        let res = self.pipe.feed(at, i, &mut |at, v| {
+            emitted += 1;
+            let res = f(at, v);
+            downstream_error = res.is_err();
+            res
+        });
+        if emitted != 0 {
+            (self.callback)(DatumMetric::Emitted, emitted);
+        }
+        match res {
+            Err(e) if !downstream_error => (self.callback)(DatumMetric::Error(e.kind), 1),
+            Ok(()) if eof => (self.callback)(DatumMetric::Completed, 1),
+            _ => {}
+        }
+        res
+    }
+}
+
+impl<P: DatumBoundedPipe, F: FnMut(DatumMetric, u64)> DatumBoundedPipe for DatumMetricsPipe<P, F> {
+    type OutputQueueSize = P::OutputQueueSize;
+}
+
+/// Wraps a pipeline so that it can be cooperatively cancelled, i.e. from another thread.
+///
+/// Before every feed call, the given function is checked. If it returns true, a [crate::DatumErrorKind::Cancelled] error is returned and the inner pipeline is not fed.
+///
+/// The iterator drivers ([crate::ViaDatumPipe], [crate::ViaDatumBufPipe]) always end after returning this error, whether or not errors are otherwise fatal, so this aborts the parse at the next input element.
+/// [crate::DatumPipe::feed_all_to_vec] stops at the first error anyway.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumErrorKind, DatumPipe, IntoViaDatumPipe, datum_char_to_value_pipeline};
+/// use core::sync::atomic::{AtomicBool, Ordering};
+/// let cancel = AtomicBool::new(false);
+/// let pipeline = datum_char_to_value_pipeline().with_cancel(|| cancel.load(Ordering::Relaxed));
+/// let mut iterator = "1 2 3".chars().via_datum_pipe(pipeline);
+/// assert!(iterator.next().unwrap().is_ok());
+/// // ...some other thread decides the parse should stop...
+/// cancel.store(true, Ordering::Relaxed);
+/// assert_eq!(iterator.next().unwrap().unwrap_err().kind, DatumErrorKind::Cancelled);
+/// assert!(iterator.next().is_none());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DatumCancelPipe<P: DatumPipe, F: FnMut() -> bool> {
+    pipe: P,
+    cancelled: F,
+}
+
+impl<P: DatumPipe, F: FnMut() -> bool> DatumCancelPipe<P, F> {
+    /// Creates a new DatumCancelPipe wrapping the given pipeline.
+    pub fn new(pipe: P, cancelled: F) -> Self {
+        Self { pipe, cancelled }
+    }
+
+    /// Unwraps the inner pipeline.
+    pub fn into_inner(self) -> P {
+        self.pipe
+    }
+}
+
+impl<P: DatumPipe, F: FnMut() -> bool> DatumPipe for DatumCancelPipe<P, F> {
+    type Input = P::Input;
+    type Output = P::Output;
+    fn feed<G: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut G,
+    ) -> DatumResult<()> {
+        if (self.cancelled)() {
+            Err(datum_error!(Cancelled, at, "cancelled"))
+        } else {
+            self.pipe.feed(at, i, f)
+        }
+    }
+}
+
+impl<P: DatumBoundedPipe, F: FnMut() -> bool> DatumBoundedPipe for DatumCancelPipe<P, F> {
+    type OutputQueueSize = P::OutputQueueSize;
+}
+
+/// Wraps a pipeline so that feeding it happens within a debug-level `tracing` span named `datum_pipe`, so time spent in it can be observed.
+///
+/// The span has a `stage` field (given when creating it), and at EOF, `inputs` and `outputs` fields are recorded with the number of each so far.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_char_to_value_pipeline, DatumPipe};
+/// let mut pipeline = datum_char_to_value_pipeline().with_tracing("config");
+/// let values = pipeline.feed_all_to_vec("(a) (b)".chars(), true).unwrap();
+/// assert_eq!(values.len(), 2);
+/// ```
+#[cfg(feature = "tracing")]
+#[derive(Clone, Debug)]
+pub struct DatumTracingPipe<P: DatumPipe> {
+    pipe: P,
+    span: tracing::Span,
+    inputs: u64,
+    outputs: u64,
+}
+
+#[cfg(feature = "tracing")]
+impl<P: DatumPipe> DatumTracingPipe<P> {
+    /// Creates a new DatumTracingPipe wrapping the given pipeline. The span is created here.
+    pub fn new(pipe: P, stage: &'static str) -> Self {
+        Self {
+            pipe,
+            span: tracing::debug_span!(
+                "datum_pipe",
+                stage,
+                inputs = tracing::field::Empty,
+                outputs = tracing::field::Empty
+            ),
+            inputs: 0,
+            outputs: 0,
+        }
+    }
+
+    /// Returns the span.
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Unwraps the inner pipeline.
+    pub fn into_inner(self) -> P {
+        self.pipe
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<P: DatumPipe> DatumPipe for DatumTracingPipe<P> {
+    type Input = P::Input;
+    type Output = P::Output;
+    fn feed<G: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut G,
+    ) -> DatumResult<()> {
+        let _entered = self.span.enter();
+        let eof = i.is_none();
+        if !eof {
+            self.inputs += 1;
+        }
+        let outputs = &mut self.outputs;
+        let res = self.pipe.feed(at, i, &mut |at, v| {
+            *outputs += 1;
+            f(at, v)
+        });
+        if eof {
+            self.span.record("inputs", self.inputs);
+            self.span.record("outputs", self.outputs);
+        }
+        res
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<P: DatumBoundedPipe> DatumBoundedPipe for DatumTracingPipe<P> {
+    type OutputQueueSize = P::OutputQueueSize;
+}
+
+/// Returns an output function (for [DatumPipe::feed] or [DatumPipeWriter]) which adds outputs to anything implementing [Extend], i.e. a Vec or VecDeque.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use std::collections::VecDeque;
+/// use datum::{datum_char_to_value_pipeline, datum_extend_output, DatumPipe};
+/// let mut values = VecDeque::new();
+/// let mut pipeline = datum_char_to_value_pipeline();
+/// for (at, c) in "1 2".chars().enumerate() {
+///     pipeline.feed(at as u64, Some(c), &mut datum_extend_output(&mut values)).unwrap();
+/// }
+/// pipeline.feed(3, None, &mut datum_extend_output(&mut values)).unwrap();
+/// assert_eq!(values.len(), 2);
+/// ```
+pub fn datum_extend_output<T, E: Extend<T>>(
+    target: &mut E,
+) -> impl FnMut(DatumOffset, T) -> DatumResult<()> + '_ {
+    move |_, v| {
+        target.extend(Some(v));
+        Ok(())
+    }
+}
+
+/// Adapter which feeds a pipeline from the outside, with offsets counting up from 0; the reverse of [crate::IntoViaDatumPipe].
+///
+/// Input can be given with [Extend] (i.e. `writer.extend(text.bytes())`). For char pipelines, [core::fmt::Write] is also implemented, which allows formatting a [core::fmt::Display] implementor (i.e. with `write!`) straight into a parser, without an intermediate String.
+///
+/// If the pipeline (or the output function) fails, the [DatumError] is kept to be returned by [DatumPipeWriter::finish], and nothing more is fed. Writes then fail with [core::fmt::Error], and extending does nothing.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use core::fmt::Write;
+/// use datum::{datum_char_to_value_pipeline, DatumPipeWriter};
+/// let mut values = Vec::new();
+/// let mut writer = DatumPipeWriter::new(datum_char_to_value_pipeline(), |_, v| {
+///     values.push(v);
+///     Ok(())
+/// });
+/// write!(writer, "(request {} {:?})", 42, "path").unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!(values[0].to_string(), "(request 42 \"path\")");
+/// ```
+/// With bytes, and [datum_extend_output]:
+/// ```
+/// use datum::{datum_byte_to_value_pipeline, datum_extend_output, DatumPipeWriter};
+/// let mut values = Vec::new();
+/// let mut writer = DatumPipeWriter::new(datum_byte_to_value_pipeline(), datum_extend_output(&mut values));
+/// writer.extend(b"(a) ".iter().copied());
+/// writer.extend(b"(b)".iter().copied());
+/// writer.finish().unwrap();
+/// assert_eq!(values.len(), 2);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DatumPipeWriter<P: DatumPipe, F: FnMut(DatumOffset, P::Output) -> DatumResult<()>> {
+    pipe: P,
+    output: F,
+    offset: DatumOffset,
+    error: Option<DatumError>,
+}
+
+impl<P: DatumPipe, F: FnMut(DatumOffset, P::Output) -> DatumResult<()>> DatumPipeWriter<P, F> {
+    /// Creates a new DatumPipeWriter feeding the given pipeline, which gives its output to the given function.
+    pub fn new(pipe: P, output: F) -> Self {
+        Self {
+            pipe,
+            output,
+            offset: 0,
+            error: None,
+        }
+    }
+
+    /// Offset of the next input (i.e. the number of inputs so far).
+    pub fn offset(&self) -> DatumOffset {
+        self.offset
+    }
+
+    /// The error that stopped writing, if any.
+    pub fn error(&self) -> Option<DatumError> {
+        self.error
+    }
+
+    /// Feeds a single input, unless an error has already happened.
+    pub fn push(&mut self, i: P::Input) -> DatumResult<()> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        match self.pipe.feed(self.offset, Some(i), &mut self.output) {
+            Ok(()) => {
+                self.offset += 1;
+                Ok(())
+            }
+            Err(error) => {
+                self.error = Some(error);
+                Err(error)
+            }
+        }
+    }
+
+    /// Feeds EOF into the pipeline and returns it, or returns the error that stopped writing.
+    pub fn finish(mut self) -> DatumResult<P> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.pipe.feed(self.offset, None, &mut self.output)?;
+        Ok(self.pipe)
+    }
+}
+
+impl<P: DatumPipe, F: FnMut(DatumOffset, P::Output) -> DatumResult<()>> Extend<P::Input>
+    for DatumPipeWriter<P, F>
+{
+    fn extend<T: IntoIterator<Item = P::Input>>(&mut self, iter: T) {
+        for i in iter {
+            if self.push(i).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl<P: DatumPipe<Input = char>, F: FnMut(DatumOffset, P::Output) -> DatumResult<()>>
+    core::fmt::Write for DatumPipeWriter<P, F>
+{
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            self.write_char(c)?;
+        }
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> core::fmt::Result {
+        self.push(c).map_err(|_| core::fmt::Error)
+    }
+}
+
+/// Result of [DatumBalanceChecker].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DatumBalance {
+    /// All lists and strings are closed.
+    Complete,
+    /// Some list or string is still open; more input could complete it.
+    Incomplete,
+    /// A list was closed that was never opened (or an escape was bad); more input won't help.
+    Invalid,
+}
+
+/// Lightweight pipeline stage which only tracks list depth and string/comment state.
+///
+/// This is much cheaper than parsing values, and is meant for things like REPL line validation and editors.
+///
+/// It is transparent, taking and passing on [DatumChar]s (so it goes after a [DatumDecoder]).
+/// On a stray list end, it returns a [crate::DatumErrorKind::BadData] error, and at EOF, if incomplete, a [crate::DatumErrorKind::Interrupted] error.
+/// Either way, the current state is available via [DatumBalanceChecker::balance].
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumBalance, DatumBalanceChecker};
+/// assert_eq!(DatumBalanceChecker::check_str("(a (b) \"c\")"), DatumBalance::Complete);
+/// assert_eq!(DatumBalanceChecker::check_str("(a (b"), DatumBalance::Incomplete);
+/// assert_eq!(DatumBalanceChecker::check_str("(a \")"), DatumBalance::Incomplete);
+/// assert_eq!(DatumBalanceChecker::check_str("(a ; )"), DatumBalance::Incomplete);
+/// assert_eq!(DatumBalanceChecker::check_str("\"\\\")\""), DatumBalance::Complete);
+/// assert_eq!(DatumBalanceChecker::check_str("a)"), DatumBalance::Invalid);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DatumBalanceChecker {
+    depth: usize,
+    in_string: bool,
+    in_comment: bool,
+    invalid: bool,
+}
+
+impl DatumBalanceChecker {
+    /// Checks the balance of a string.
+    pub fn check_str(text: &str) -> DatumBalance {
+        let mut decoder = DatumDecoder::default();
+        let mut checker = DatumBalanceChecker::default();
+        for (at, chr) in text.chars().enumerate() {
+            let at = at as DatumOffset;
+            if decoder
+                .feed(at, Some(chr), &mut |at, chr| {
+                    checker.feed(at, Some(chr), &mut |_, _| Ok(()))
+                })
+                .is_err()
+            {
+                // stray list end or bad escape
+                return DatumBalance::Invalid;
+            }
+        }
+        if decoder.feed(0, None, &mut |_, _| Ok(())).is_err() {
+            // unfinished escape
+            return DatumBalance::Incomplete;
+        }
+        checker.balance()
+    }
+
+    /// Returns the balance of the input so far.
+    pub fn balance(&self) -> DatumBalance {
+        if self.invalid {
+            DatumBalance::Invalid
+        } else if self.depth != 0 || self.in_string {
+            DatumBalance::Incomplete
+        } else {
+            DatumBalance::Complete
+        }
+    }
+
+    /// Current list depth.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// If currently inside a string.
+    pub fn in_string(&self) -> bool {
+        self.in_string
+    }
+
+    /// If currently inside a line comment.
+    pub fn in_comment(&self) -> bool {
+        self.in_comment
+    }
+}
+
+impl DatumPipe for DatumBalanceChecker {
+    type Input = DatumChar;
+    type Output = DatumChar;
+    fn feed<F: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut F,
+    ) -> DatumResult<()> {
+        if let Some(chr) = i {
+            if self.in_comment {
+                self.in_comment = chr.class() != DatumCharClass::Newline;
+            } else if self.in_string {
+                self.in_string = chr.class() != DatumCharClass::String;
+            } else {
+                match chr.class() {
+                    DatumCharClass::LineComment => self.in_comment = true,
+                    DatumCharClass::String => self.in_string = true,
+                    DatumCharClass::ListStart => self.depth += 1,
+                    DatumCharClass::ListEnd => {
+                        if self.depth == 0 {
+                            self.invalid = true;
+                            return Err(datum_error!(BadData, at, "balance: unexpected list end"));
+                        }
+                        self.depth -= 1;
+                    }
+                    _ => {}
+                }
+            }
+            f(at, chr)
+        } else {
+            self.in_comment = false;
+            if self.balance() == DatumBalance::Incomplete {
+                Err(datum_error!(Interrupted, at, "balance: incomplete"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+impl DatumBoundedPipe for DatumBalanceChecker {
+    type OutputQueueSize = unary::C1;
+}
@@ -0,0 +1,259 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+//! Python interface via `pyo3`.
+//!
+//! To use this, make a crate with `crate-type = ["cdylib"]` which depends on this one with the `python` feature (and `pyo3/extension-module`), and `pub use datum::python::*;`, then build it with `maturin`.
+//! The resulting module is named `datum`, and contains:
+//!
+//! * `loads(text)`: Parses a document into a list of its top-level values.
+//! * `dumps(values)`: Writes a sequence of top-level values as a document.
+//! * `Reader(file)`: Iterates over the top-level values of a document read incrementally from a file-like object (anything with `read(size)` returning `bytes` or `str`).
+//! * `Symbol(name)`: Represents a symbol.
+//! * `DatumError`: Raised for parse errors.
+//!
+//! Values map to Python as follows:
+//!
+//! | Datum | Python |
+//! |-|-|
+//! | String | `str` |
+//! | Symbol | `Symbol` |
+//! | Integer | `int` |
+//! | Float | `float` |
+//! | Boolean | `bool` |
+//! | Nil | `None` |
+//! | List | `list` (any sequence is accepted by `dumps`) |
+//!
+//! _Added in 1.3.0._
+
+// The glue pyo3 generates uses `unsafe`.
+#![allow(unsafe_code)]
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyFloat, PyInt, PyList, PyString};
+
+use crate::{
+    DatumAtom, DatumByteToTokenPipeline, DatumComposePipe, DatumDocument, DatumError, DatumOffset,
+    DatumParser, DatumPipe, DatumValue,
+};
+
+create_exception!(
+    datum,
+    DatumPyError,
+    PyException,
+    "Error parsing a Datum document."
+);
+
+fn py_datum_error(e: DatumError) -> PyErr {
+    DatumPyError::new_err(e.to_string())
+}
+
+/// Python `Symbol`.
+///
+/// _Added in 1.3.0._
+#[pyclass(
+    frozen,
+    eq,
+    hash,
+    skip_from_py_object,
+    name = "Symbol",
+    module = "datum"
+)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DatumPySymbol {
+    /// Symbol text.
+    #[pyo3(get)]
+    pub name: String,
+}
+
+#[pymethods]
+impl DatumPySymbol {
+    #[new]
+    fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    fn __repr__(&self) -> String {
+        std::format!("Symbol({:?})", self.name)
+    }
+}
+
+/// Converts a [DatumValue] to the Python representation described in the module documentation.
+///
+/// _Added in 1.3.0._
+pub fn datum_value_to_py<'py>(py: Python<'py>, value: &DatumValue) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        DatumValue::Atom(DatumAtom::String(v)) => PyString::new(py, v).into_any(),
+        DatumValue::Atom(DatumAtom::Symbol(v)) => {
+            Bound::new(py, DatumPySymbol { name: v.clone() })?.into_any()
+        }
+        DatumValue::Atom(DatumAtom::Integer(v)) => v.into_pyobject(py)?.into_any(),
+        DatumValue::Atom(DatumAtom::Float(v)) => PyFloat::new(py, *v).into_any(),
+        DatumValue::Atom(DatumAtom::Boolean(v)) => PyBool::new(py, *v).to_owned().into_any(),
+        DatumValue::Atom(DatumAtom::Nil) => py.None().into_bound(py),
+        DatumValue::List(list) => {
+            let mut items = Vec::with_capacity(list.len());
+            for v in list {
+                items.push(datum_value_to_py(py, v)?);
+            }
+            PyList::new(py, items)?.into_any()
+        }
+    })
+}
+
+/// Converts from the Python representation described in the module documentation to a [DatumValue].
+///
+/// _Added in 1.3.0._
+pub fn datum_value_from_py(value: &Bound<'_, PyAny>) -> PyResult<DatumValue> {
+    // bool is a subclass of int, so it must be checked first
+    if let Ok(v) = value.cast::<PyBool>() {
+        Ok(DatumValue::Atom(DatumAtom::Boolean(v.is_true())))
+    } else if value.is_instance_of::<PyInt>() {
+        Ok(DatumValue::Atom(DatumAtom::Integer(value.extract()?)))
+    } else if value.is_instance_of::<PyFloat>() {
+        Ok(DatumValue::Atom(DatumAtom::Float(value.extract()?)))
+    } else if value.is_instance_of::<PyString>() {
+        Ok(DatumValue::Atom(DatumAtom::String(value.extract()?)))
+    } else if value.is_none() {
+        Ok(DatumValue::Atom(DatumAtom::Nil))
+    } else if let Ok(v) = value.cast::<DatumPySymbol>() {
+        Ok(DatumValue::Atom(DatumAtom::Symbol(v.get().name.clone())))
+    } else if value.is_instance_of::<PyBytes>() {
+        // bytes are iterable, but turning them into a list of integers would be a surprise
+        Err(PyTypeError::new_err(
+            "datum: bytes have no Datum equivalent",
+        ))
+    } else if let Ok(iter) = value.try_iter() {
+        let mut list = Vec::new();
+        for v in iter {
+            list.push(datum_value_from_py(&v?)?);
+        }
+        Ok(DatumValue::List(list))
+    } else {
+        Err(PyTypeError::new_err("datum: value has no Datum equivalent"))
+    }
+}
+
+/// Python `loads(text)`: Parses a document into a list of its top-level values.
+///
+/// _Added in 1.3.0._
+#[pyfunction]
+#[pyo3(name = "loads")]
+pub fn datum_loads_py<'py>(py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyList>> {
+    let document = DatumDocument::try_from(text).map_err(py_datum_error)?;
+    let mut items = Vec::with_capacity(document.len());
+    for v in document.iter() {
+        items.push(datum_value_to_py(py, v)?);
+    }
+    PyList::new(py, items)
+}
+
+/// Python `dumps(values)`: Writes a sequence of top-level values as a document, each value on its own line.
+///
+/// _Added in 1.3.0._
+#[pyfunction]
+#[pyo3(name = "dumps")]
+pub fn datum_dumps_py(values: &Bound<'_, PyAny>) -> PyResult<String> {
+    let mut document = Vec::new();
+    for v in values.try_iter()? {
+        document.push(datum_value_from_py(&v?)?);
+    }
+    Ok(DatumDocument::from(document).to_string())
+}
+
+/// Python `Reader(file)`: Iterates over the top-level values of a document, reading it from a file-like object in chunks.
+///
+/// _Added in 1.3.0._
+#[pyclass(name = "Reader", module = "datum")]
+pub struct DatumPyReader {
+    file: Py<PyAny>,
+    pipeline: DatumComposePipe<DatumByteToTokenPipeline<String>, DatumParser>,
+    values: VecDeque<DatumValue>,
+    offset: DatumOffset,
+    eof: bool,
+}
+
+impl DatumPyReader {
+    /// Size of each `read` call.
+    const CHUNK_SIZE: usize = 4096;
+
+    fn feed(&mut self, i: Option<u8>) -> PyResult<()> {
+        let values = &mut self.values;
+        self.pipeline
+            .feed(self.offset, i, &mut |_, v| {
+                values.push_back(v);
+                Ok(())
+            })
+            .map_err(py_datum_error)
+    }
+}
+
+#[pymethods]
+impl DatumPyReader {
+    /// Creates a reader over the given file-like object.
+    #[new]
+    pub fn new(file: Py<PyAny>) -> Self {
+        Self {
+            file,
+            pipeline: DatumComposePipe::default(),
+            values: VecDeque::new(),
+            offset: 0,
+            eof: false,
+        }
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        while self.values.is_empty() && !self.eof {
+            let chunk = self
+                .file
+                .call_method1(py, "read", (Self::CHUNK_SIZE,))?
+                .into_bound(py);
+            let bytes: Vec<u8> = if let Ok(text) = chunk.cast::<PyString>() {
+                text.to_str()?.as_bytes().to_vec()
+            } else {
+                chunk.extract()?
+            };
+            if bytes.is_empty() {
+                self.eof = true;
+                self.feed(None)?;
+            }
+            for b in bytes {
+                self.feed(Some(b))?;
+                self.offset += 1;
+            }
+        }
+        match self.values.pop_front() {
+            Some(v) => datum_value_to_py(py, &v).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The Python module, `datum`.
+///
+/// _Added in 1.3.0._
+#[pymodule]
+#[pyo3(name = "datum")]
+pub fn datum_python_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(datum_loads_py, m)?)?;
+    m.add_function(wrap_pyfunction!(datum_dumps_py, m)?)?;
+    m.add_class::<DatumPyReader>()?;
+    m.add_class::<DatumPySymbol>()?;
+    m.add("DatumError", m.py().get_type::<DatumPyError>())?;
+    Ok(())
+}
@@ -68,3 +68,120 @@ impl<T, Q: DatumBoundedQueue<T>> DatumBoundedQueue<T> for Option<(T, Q)> {
         }
     }
 }
+
+/// Ring buffer queue of fixed capacity `N`, implementing [DatumBoundedQueue] with O(1) operations.
+///
+/// [DatumBoundedQueue] is implemented for capacities 0 through 32 (as [unary::Num] can't be derived from a const generic).
+/// In practice, [DatumArrayPipeQueue] is the easiest way to get one of these for a pipeline.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumArrayQueue, DatumBoundedQueue};
+/// let mut queue: DatumArrayQueue<u8, 2> = Default::default();
+/// queue.push_back(1);
+/// queue.push_back(2);
+/// assert_eq!(queue.pop_front(), Some(1));
+/// queue.push_back(3);
+/// assert_eq!(queue.len(), 2);
+/// assert_eq!(queue.pop_front(), Some(2));
+/// assert_eq!(queue.pop_front(), Some(3));
+/// assert_eq!(queue.pop_front(), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DatumArrayQueue<T, const N: usize> {
+    buffer: [Option<T>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> DatumArrayQueue<T, N> {
+    const EMPTY: Option<T> = None;
+
+    /// Amount of elements in the queue.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// If the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn wrap(index: usize) -> usize {
+        if index >= N {
+            index - N
+        } else {
+            index
+        }
+    }
+
+    fn array_push_back(&mut self, v: T) {
+        if self.len == N {
+            panic!("Ran out of DatumBoundedQueue space")
+        }
+        self.buffer[Self::wrap(self.head + self.len)] = Some(v);
+        self.len += 1;
+    }
+
+    fn array_pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            let v = self.buffer[self.head].take();
+            self.head = Self::wrap(self.head + 1);
+            self.len -= 1;
+            v
+        }
+    }
+}
+
+impl<T, const N: usize> Default for DatumArrayQueue<T, N> {
+    fn default() -> Self {
+        Self {
+            buffer: [Self::EMPTY; N],
+            head: 0,
+            len: 0,
+        }
+    }
+}
+
+/// Converts a [unary::Num] into the matching [DatumArrayQueue].
+///
+/// _Added in 1.3.0._
+pub trait DatumUnaryNumIntoArrayQueue<T>: unary::Num {
+    type ArrayQueue: DatumBoundedQueue<T>;
+}
+
+/// The [DatumArrayQueue] big enough to buffer the output of a [crate::DatumBoundedPipe].
+///
+/// _Added in 1.3.0._
+pub type DatumArrayPipeQueue<P> =
+    <<P as crate::DatumBoundedPipe>::OutputQueueSize as DatumUnaryNumIntoArrayQueue<(
+        crate::DatumOffset,
+        <P as crate::DatumPipe>::Output,
+    )>>::ArrayQueue;
+
+macro_rules! array_queue_impls {
+    ($len:ty; $n:literal) => {
+        impl<T> DatumBoundedQueue<T> for DatumArrayQueue<T, $n> {
+            type Len = $len;
+
+            fn push_back(&mut self, v: T) {
+                self.array_push_back(v)
+            }
+            fn pop_front(&mut self) -> Option<T> {
+                self.array_pop_front()
+            }
+        }
+
+        impl<T> DatumUnaryNumIntoArrayQueue<T> for $len {
+            type ArrayQueue = DatumArrayQueue<T, $n>;
+        }
+    };
+    ($len:ty; $n:literal, $($rest:literal),*) => {
+        array_queue_impls!($len; $n);
+        array_queue_impls!(unary::Digit<$len>; $($rest),*);
+    };
+}
+
+array_queue_impls!(unary::C0; 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32);
@@ -0,0 +1,159 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use crate::{
+    datum_char_to_token_pipeline, DatumAtom, DatumDocument, DatumOffset, DatumPath,
+    DatumPathSegment, DatumPipe, DatumResult, DatumToken,
+};
+
+/// Walks the values of a token stream, finding the ones to redact.
+struct Redactor<'a, F: FnMut(&DatumPath) -> bool> {
+    tokens: &'a [DatumToken<String>],
+    source: &'a str,
+    path: DatumPath,
+    filter: F,
+    /// Character spans (end inclusive) to replace.
+    spans: Vec<(DatumOffset, DatumOffset)>,
+}
+
+impl<F: FnMut(&DatumPath) -> bool> Redactor<'_, F> {
+    /// Visits the elements of a list (or the document) starting at token index `i`, returning the index of the list end (or the end of the tokens).
+    fn visit(&mut self, mut i: usize) -> usize {
+        // token index ranges (end inclusive) of each element
+        let mut elements = Vec::new();
+        while i < self.tokens.len() {
+            match self.tokens[i] {
+                DatumToken::ListEnd(_) => break,
+                DatumToken::ListStart(_) => {
+                    let mut depth = 0;
+                    let start = i;
+                    loop {
+                        match self.tokens[i] {
+                            DatumToken::ListStart(_) => depth += 1,
+                            DatumToken::ListEnd(_) => depth -= 1,
+                            _ => {}
+                        }
+                        if depth == 0 {
+                            break;
+                        }
+                        i += 1;
+                    }
+                    elements.push((start, i));
+                }
+                _ => elements.push((i, i)),
+            }
+            i += 1;
+        }
+        let is_map = elements.len() % 2 == 0
+            && elements.iter().step_by(2).all(|(start, end)| {
+                start == end
+                    && matches!(
+                        self.tokens[*start],
+                        DatumToken::Symbol(_, _) | DatumToken::String(_, _)
+                    )
+            });
+        for (index, (start, end)) in elements.iter().enumerate() {
+            let segment = if !is_map {
+                DatumPathSegment::Index(index)
+            } else if index % 2 == 1 {
+                let key = self.tokens[start - 1].buffer().cloned().unwrap_or_default();
+                DatumPathSegment::Key(key)
+            } else {
+                continue;
+            };
+            self.path.push(segment);
+            if (self.filter)(&self.path) {
+                let first = self.tokens[*start].source_span(self.source);
+                let last = self.tokens[*end].source_span(self.source);
+                self.spans.push((first.start, last.end));
+            } else if start != end {
+                self.visit(start + 1);
+            }
+            self.path.pop();
+        }
+        i
+    }
+}
+
+/// Replaces values in a document with a placeholder, leaving the rest of the text (including formatting and comments) untouched.
+///
+/// The filter is given the [DatumPath] of each value, and returns true to replace it. Values inside replaced lists aren't visited.
+///
+/// As this works on text rather than a schema, paths are worked out from the shape of each list (and of the document itself, as with the serde `DocLayout::Root`):
+///
+/// * If every other element (starting with the first) is a symbol or string, and there are an even number of elements, the list is treated as key/value pairs (i.e. a struct or map). Keys aren't redacted, only their values.
+/// * Otherwise, the list is treated as a sequence, with each element at an index.
+///
+/// This is meant for sharing configuration files (i.e. in bug reports) without their secrets.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_redact, DatumAtom, DatumPathSegment};
+/// let config = "; server settings\nserver (host \"example.com\" password \"hunter2\")\nusers ((name \"a\" token \"xyz\"))\n";
+/// let redacted = datum_redact(config, &DatumAtom::String("REDACTED"), |path| {
+///     path.to_string() == "server.password"
+///         || path.segments().last() == Some(&DatumPathSegment::Key("token".into()))
+/// })
+/// .unwrap();
+/// assert_eq!(
+///     redacted,
+///     "; server settings\nserver (host \"example.com\" password \"REDACTED\")\nusers ((name \"a\" token \"REDACTED\"))\n"
+/// );
+/// ```
+pub fn datum_redact<F: FnMut(&DatumPath) -> bool>(
+    source: &str,
+    placeholder: &DatumAtom<&str>,
+    filter: F,
+) -> DatumResult<String> {
+    // catches errors the tokenizer doesn't, i.e. unbalanced lists
+    DatumDocument::try_from(source)?;
+    let tokens = datum_char_to_token_pipeline().feed_all_to_vec(source.chars(), true)?;
+    let mut redactor = Redactor {
+        tokens: &tokens,
+        source,
+        path: DatumPath::default(),
+        filter,
+        spans: Vec::new(),
+    };
+    redactor.visit(0);
+    let mut placeholder_text = String::new();
+    // writing to a String can't fail
+    let _ = placeholder.write(&mut placeholder_text);
+    Ok(replace_char_spans(
+        source,
+        &redactor.spans,
+        &placeholder_text,
+    ))
+}
+
+/// Replaces character spans (end inclusive, in order, not overlapping) of the source text.
+pub(crate) fn replace_char_spans(
+    source: &str,
+    spans: &[(DatumOffset, DatumOffset)],
+    replacement: &str,
+) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut spans = spans.iter().peekable();
+    for (at, c) in source.chars().enumerate() {
+        let at = at as DatumOffset;
+        match spans.peek() {
+            Some((start, _)) if at == *start => result.push_str(replacement),
+            Some((start, end)) if at > *start && at <= *end => {}
+            _ => result.push(c),
+        }
+        if let Some((_, end)) = spans.peek() {
+            if at >= *end {
+                spans.next();
+            }
+        }
+    }
+    result
+}
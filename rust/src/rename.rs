@@ -0,0 +1,74 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use crate::redact::replace_char_spans;
+use crate::{
+    datum_char_to_token_pipeline, DatumAtom, DatumDocument, DatumOffset, DatumPipe, DatumResult,
+    DatumToken, DatumValue,
+};
+
+/// Renames every occurrence of a symbol in a document, leaving the rest of the text (including formatting and comments) untouched.
+///
+/// Symbols are compared after unescaping, so `a\ b` is the symbol `a b`. Strings with the same text aren't symbols, so they're left alone.
+/// The new name is escaped as [crate::DatumWriter] would write it.
+///
+/// Returns the new text and how many symbols were renamed. See [datum_rename_symbol_values] for the same thing on parsed values.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::datum_rename_symbol;
+/// let source = "; old name\n(define (old-name x)\n  (old-name \"old-name\"))";
+/// let (renamed, count) = datum_rename_symbol(source, "old-name", "new name").unwrap();
+/// assert_eq!(renamed, "; old name\n(define (new\\ name x)\n  (new\\ name \"old-name\"))");
+/// assert_eq!(count, 2);
+/// ```
+pub fn datum_rename_symbol(source: &str, old: &str, new: &str) -> DatumResult<(String, usize)> {
+    // catches errors the tokenizer doesn't, i.e. unbalanced lists
+    DatumDocument::try_from(source)?;
+    let tokens = datum_char_to_token_pipeline().feed_all_to_vec(source.chars(), true)?;
+    let spans: Vec<(DatumOffset, DatumOffset)> = tokens
+        .iter()
+        .filter(|token| matches!(token, DatumToken::Symbol(_, name) if name == old))
+        .map(|token| {
+            let span = token.source_span(source);
+            (span.start, span.end)
+        })
+        .collect();
+    let result = replace_char_spans(source, &spans, &DatumAtom::Symbol(new).to_string());
+    Ok((result, spans.len()))
+}
+
+/// Renames every occurrence of a symbol in some values (i.e. a [DatumDocument]), returning how many were renamed.
+///
+/// This is the parsed equivalent of [datum_rename_symbol]; it doesn't recurse, so deeply nested values are fine.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_rename_symbol_values, DatumDocument};
+/// let mut doc: DatumDocument = "(old (old \"old\")) old".parse().unwrap();
+/// assert_eq!(datum_rename_symbol_values(&mut doc, "old", "new"), 3);
+/// assert_eq!(doc.to_string(), "(new (new \"old\"))\nnew\n");
+/// ```
+pub fn datum_rename_symbol_values(values: &mut [DatumValue], old: &str, new: &str) -> usize {
+    let mut count = 0;
+    let mut pending: Vec<&mut DatumValue> = values.iter_mut().collect();
+    while let Some(value) = pending.pop() {
+        match value {
+            DatumValue::Atom(DatumAtom::Symbol(name)) if name == old => {
+                *name = new.to_string();
+                count += 1;
+            }
+            DatumValue::List(list) => pending.extend(list.iter_mut()),
+            _ => {}
+        }
+    }
+    count
+}
@@ -0,0 +1,199 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+//! Helpers for read-eval-print loops, independent of any line editor.
+//!
+//! [DatumRepl] collects lines until they form complete input, and [DatumRepl::run] drives a [DatumEvaluator] from any source of lines (i.e. a line editor, or standard input).
+//!
+//! _Added in 1.3.0._
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{
+    DatumBalance, DatumBalanceChecker, DatumDecoder, DatumEvalError, DatumEvaluator, DatumOffset,
+    DatumPipe,
+};
+
+/// Line buffer for a REPL, tracking whether the lines so far are complete input.
+///
+/// Lines are checked as they're added, without going over earlier lines again, so long multi-line inputs stay cheap.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::DatumBalance;
+/// use datum::repl::DatumRepl;
+/// let mut repl = DatumRepl::default();
+/// assert_eq!(repl.prompt(), "> ");
+/// assert_eq!(repl.push_line("(def f x"), DatumBalance::Incomplete);
+/// assert_eq!(repl.prompt(), "... ");
+/// assert_eq!(repl.depth(), 1);
+/// assert_eq!(repl.push_line("  (+ x 1)) (f 2)"), DatumBalance::Complete);
+/// assert_eq!(repl.take(), "(def f x\n  (+ x 1)) (f 2)\n");
+/// assert_eq!(repl.prompt(), "> ");
+/// ```
+#[derive(Clone, Debug)]
+pub struct DatumRepl {
+    /// Prompt when no input is pending.
+    pub prompt: String,
+    /// Prompt when more input is needed to complete what's pending.
+    pub continuation_prompt: String,
+    buffer: String,
+    decoder: DatumDecoder,
+    checker: DatumBalanceChecker,
+    at: DatumOffset,
+    invalid: bool,
+}
+
+impl Default for DatumRepl {
+    fn default() -> Self {
+        Self::new("> ", "... ")
+    }
+}
+
+impl DatumRepl {
+    /// Creates a REPL buffer with the given prompts.
+    pub fn new(prompt: &str, continuation_prompt: &str) -> Self {
+        Self {
+            prompt: prompt.into(),
+            continuation_prompt: continuation_prompt.into(),
+            buffer: String::new(),
+            decoder: DatumDecoder::default(),
+            checker: DatumBalanceChecker::default(),
+            at: 0,
+            invalid: false,
+        }
+    }
+
+    /// The prompt to show for the next line.
+    pub fn prompt(&self) -> &str {
+        if self.buffer.is_empty() {
+            &self.prompt
+        } else {
+            &self.continuation_prompt
+        }
+    }
+
+    /// Adds a line of input (a line ending at the end is ignored, and one is added), returning the balance of everything pending.
+    ///
+    /// After [DatumBalance::Complete] or [DatumBalance::Invalid], the input should be taken with [DatumRepl::take] (for an invalid input, to report the error).
+    pub fn push_line(&mut self, line: &str) -> DatumBalance {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        for c in line.chars().chain(core::iter::once('\n')) {
+            self.buffer.push(c);
+            if !self.invalid {
+                let checker = &mut self.checker;
+                self.invalid = self
+                    .decoder
+                    .feed(self.at, Some(c), &mut |at, chr| {
+                        checker.feed(at, Some(chr), &mut |_, _| Ok(()))
+                    })
+                    .is_err();
+            }
+            self.at += 1;
+        }
+        self.balance()
+    }
+
+    /// The balance of everything pending.
+    pub fn balance(&self) -> DatumBalance {
+        // lines always end in a newline, which can't be in an escape, so there's no unfinished escape to check for
+        if self.invalid {
+            DatumBalance::Invalid
+        } else {
+            self.checker.balance()
+        }
+    }
+
+    /// How many lists are open in the pending input (i.e. to indent the next line).
+    pub fn depth(&self) -> usize {
+        self.checker.depth()
+    }
+
+    /// The pending input.
+    pub fn pending(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Returns true if no input is pending.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Takes the pending input, starting afresh.
+    pub fn take(&mut self) -> String {
+        self.decoder = DatumDecoder::default();
+        self.checker = DatumBalanceChecker::default();
+        self.at = 0;
+        self.invalid = false;
+        core::mem::take(&mut self.buffer)
+    }
+
+    /// Runs a REPL: reads lines with `read_line` (given the prompt to show, and returning [None] at the end of input), and evaluates each complete input with the evaluator, giving the results of all its values to `report`.
+    ///
+    /// Blank input is skipped. Invalid input and input left incomplete at the end are still evaluated, so that the parse error is reported.
+    ///
+    /// Error offsets are relative to the start of each input.
+    /// ```
+    /// use datum::{DatumAtom, DatumError, DatumEvaluator, datum_error};
+    /// use datum::repl::DatumRepl;
+    /// struct Sum;
+    /// impl DatumEvaluator for Sum {
+    ///     type Output = i64;
+    ///     type Error = DatumError;
+    ///     fn eval_atom(&mut self, atom: &DatumAtom<String>) -> Result<i64, DatumError> {
+    ///         match atom {
+    ///             DatumAtom::Integer(v) => Ok(*v),
+    ///             _ => Err(datum_error!(BadData, 0, "not an integer")),
+    ///         }
+    ///     }
+    ///     fn eval_call(&mut self, _: &str, args: Vec<i64>) -> Result<i64, DatumError> {
+    ///         Ok(args.iter().sum())
+    ///     }
+    /// }
+    /// let mut lines = vec!["1 (+ 2", "   3)", "", "4)"].into_iter();
+    /// let mut prompts = Vec::new();
+    /// let mut reports = Vec::new();
+    /// DatumRepl::default().run(
+    ///     &mut Sum,
+    ///     |prompt| {
+    ///         prompts.push(prompt.to_string());
+    ///         lines.next().map(String::from)
+    ///     },
+    ///     |result| reports.push(result.map_err(|e| e.offset)),
+    /// );
+    /// assert_eq!(prompts, ["> ", "... ", "> ", "> ", "> "]);
+    /// assert_eq!(reports, [Ok(vec![1, 5]), Err(1)]);
+    /// ```
+    pub fn run<
+        E: DatumEvaluator,
+        L: FnMut(&str) -> Option<String>,
+        R: FnMut(Result<Vec<E::Output>, DatumEvalError<E::Error>>),
+    >(
+        &mut self,
+        evaluator: &mut E,
+        mut read_line: L,
+        mut report: R,
+    ) {
+        loop {
+            let balance = match read_line(self.prompt()) {
+                Some(line) => self.push_line(&line),
+                None if self.is_empty() => return,
+                // evaluating reports it as incomplete
+                None => DatumBalance::Invalid,
+            };
+            if balance == DatumBalance::Incomplete {
+                continue;
+            }
+            let input = self.take();
+            if !input.trim().is_empty() {
+                report(evaluator.eval_str(&input));
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::vec::Vec;
+use core::{convert::TryFrom, str::FromStr};
+
+use crate::pattern::{pattern_symbol, PatternSymbol};
+use crate::{datum_error, DatumAtom, DatumDocument, DatumPattern, DatumResult, DatumValue};
+
+/// A rewrite rule for [DatumRewriter]: values matching the pattern are replaced with the template, filled in with [crate::DatumBindings::substitute].
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::DatumRewriteRule;
+/// let rule = DatumRewriteRule::parse("(unless $c $body...)", "(if $c #nil (begin $body...))").unwrap();
+/// let value = "(unless done (step) (check))".parse::<datum::DatumDocument>().unwrap().0.remove(0);
+/// assert_eq!(rule.apply(&value).unwrap().to_string(), "(if done #nil (begin (step) (check)))");
+/// // templates can only use the pattern's variables
+/// assert!(DatumRewriteRule::parse("(a $x)", "(b $y)").is_err());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct DatumRewriteRule {
+    pattern: DatumPattern,
+    template: DatumValue,
+}
+
+impl DatumRewriteRule {
+    /// Creates a rule, checking that every variable in the template is bound by the pattern (with the same kind, `$name` or `$name...`).
+    pub fn new(pattern: DatumPattern, template: DatumValue) -> DatumResult<Self> {
+        let variables = pattern.variables();
+        // (value, if it's a list element)
+        let mut pending = alloc::vec![(&template, false)];
+        while let Some((value, in_list)) = pending.pop() {
+            match value {
+                DatumValue::Atom(DatumAtom::Symbol(symbol)) => {
+                    let (name, many) = match pattern_symbol(symbol) {
+                        PatternSymbol::One(name) => (name, false),
+                        PatternSymbol::Many(name) => (name, true),
+                        PatternSymbol::Literal(_) => continue,
+                    };
+                    if many && !in_list {
+                        return Err(datum_error!(
+                            BadData,
+                            0,
+                            "rewrite: rest variable outside of a list"
+                        ));
+                    }
+                    if variables.get(name) != Some(&many) {
+                        return Err(datum_error!(
+                            BadData,
+                            0,
+                            "rewrite: template variable not bound by pattern"
+                        ));
+                    }
+                }
+                DatumValue::List(list) => pending.extend(list.iter().map(|v| (v, true))),
+                _ => {}
+            }
+        }
+        Ok(Self { pattern, template })
+    }
+
+    /// Creates a rule from the text of the pattern and template (each a single value).
+    pub fn parse(pattern: &str, template: &str) -> DatumResult<Self> {
+        let mut template = DatumDocument::try_from(template)?;
+        if template.len() != 1 {
+            return Err(datum_error!(
+                BadData,
+                0,
+                "rewrite: expected exactly one value"
+            ));
+        }
+        Self::new(DatumPattern::from_str(pattern)?, template.0.remove(0))
+    }
+
+    /// The rule's pattern.
+    pub fn pattern(&self) -> &DatumPattern {
+        &self.pattern
+    }
+
+    /// The rule's template.
+    pub fn template(&self) -> &DatumValue {
+        &self.template
+    }
+
+    /// If the value matches the pattern, returns the filled-in template.
+    pub fn apply(&self, value: &DatumValue) -> Option<DatumValue> {
+        self.pattern
+            .matches(value)
+            .map(|bindings| bindings.substitute(&self.template))
+    }
+}
+
+/// Applies [DatumRewriteRule]s to values until none match anywhere, for preprocessing (i.e. macro expansion in a DSL).
+///
+/// Rewriting is outermost first: each value is rewritten by the first matching rule until no rule matches it, and then its elements are rewritten. If that changes any of them, the value is tried again.
+///
+/// Rules can expand forever (i.e. `$x` to `($x)`), so there's a limit on how many rewrites are done, and on how deep the rewriter goes; hitting either is an [crate::DatumErrorKind::OutOfRoom] error.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumDocument, DatumErrorKind, DatumRewriteRule, DatumRewriter};
+/// let rewriter = DatumRewriter::new(vec![
+///     DatumRewriteRule::parse("(inc $x)", "(+ $x 1)").unwrap(),
+///     DatumRewriteRule::parse("(twice $f $x)", "($f ($f $x))").unwrap(),
+/// ]);
+/// let value = "(twice inc (inc 5))".parse::<DatumDocument>().unwrap().0.remove(0);
+/// assert_eq!(rewriter.rewrite(value).unwrap().to_string(), "(+ (+ (+ 5 1) 1) 1)");
+/// // rules that never finish are stopped
+/// let rewriter = DatumRewriter::new(vec![DatumRewriteRule::parse("(a $x)", "(a ($x))").unwrap()]).max_steps(100);
+/// let value = "(a 1)".parse::<DatumDocument>().unwrap().0.remove(0);
+/// assert_eq!(rewriter.rewrite(value).unwrap_err().kind, DatumErrorKind::OutOfRoom);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct DatumRewriter {
+    rules: Vec<DatumRewriteRule>,
+    max_steps: usize,
+    max_depth: usize,
+}
+
+impl DatumRewriter {
+    /// Creates a rewriter with the given rules, tried in order, and the default limits (10000 rewrites, 256 lists deep).
+    pub fn new(rules: Vec<DatumRewriteRule>) -> Self {
+        Self {
+            rules,
+            max_steps: 10000,
+            max_depth: 256,
+        }
+    }
+
+    /// Sets the maximum amount of rewrites per [DatumRewriter::rewrite] call.
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Sets how deep in lists the rewriter goes.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The rules, in the order they're tried.
+    pub fn rules(&self) -> &[DatumRewriteRule] {
+        &self.rules
+    }
+
+    /// Rewrites a value until no rule matches anywhere in it.
+    pub fn rewrite(&self, value: DatumValue) -> DatumResult<DatumValue> {
+        let mut steps = 0;
+        self.rewrite_inner(value, 0, &mut steps)
+    }
+
+    fn rewrite_inner(
+        &self,
+        mut value: DatumValue,
+        depth: usize,
+        steps: &mut usize,
+    ) -> DatumResult<DatumValue> {
+        if depth > self.max_depth {
+            return Err(datum_error!(OutOfRoom, 0, "rewrite: nested too deeply"));
+        }
+        loop {
+            while let Some(rewritten) = self.rules.iter().find_map(|rule| rule.apply(&value)) {
+                *steps += 1;
+                if *steps > self.max_steps {
+                    return Err(datum_error!(OutOfRoom, 0, "rewrite: too many steps"));
+                }
+                value = rewritten;
+            }
+            let list = match value {
+                DatumValue::List(list) => list,
+                atom => return Ok(atom),
+            };
+            let before = *steps;
+            let mut result = Vec::with_capacity(list.len());
+            for element in list {
+                result.push(self.rewrite_inner(element, depth + 1, steps)?);
+            }
+            value = DatumValue::List(result);
+            if *steps == before {
+                return Ok(value);
+            }
+        }
+    }
+}
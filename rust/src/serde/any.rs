@@ -0,0 +1,190 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::de::{self, Visitor};
+use serde::ser::{Error, Impossible};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::serde::error;
+use crate::{datum_char_to_token_pipeline, DatumDocument, DatumPipe, DatumToken, DatumValue};
+
+/// Newtype struct name that the Datum serializers and deserializers recognize as [DatumAny].
+/// The newtype's content is the value's Datum text.
+pub(crate) const DATUM_ANY: &str = "$datum::DatumAny";
+
+/// Any value, kept exactly as written (symbols stay symbols, and so on), for passing through sections a program doesn't understand, i.e. plugin configuration.
+///
+/// With the Datum serializers and deserializers, the value is read and written as itself.
+/// Other formats see a newtype struct containing the value's Datum text, as a string.
+///
+/// This can't be the root of a [crate::serde::DocLayout::Root] document.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::serde::{ser::Style, DatumAny, DocLayout};
+/// use serde::{Deserialize, Serialize};
+/// #[derive(Serialize, Deserialize)]
+/// struct Config {
+///     name: String,
+///     plugin: DatumAny,
+/// }
+/// let text = "name \"main\" plugin (mode fast #t (ratio 1.5))";
+/// let config: Config = DocLayout::Root.deserialize_str(text).unwrap();
+/// assert_eq!(config.plugin.to_string(), "(mode fast #t (ratio 1.5))");
+/// let written = DocLayout::Root.serialize_to_string(&config, Style::SpacingOnly).unwrap();
+/// assert_eq!(written, text);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct DatumAny(pub DatumValue);
+
+impl DatumAny {
+    /// Parses a value from Datum text, which must contain exactly one value.
+    pub fn from_text(text: &str) -> error::Result<Self> {
+        let mut doc: DatumDocument = text.parse().map_err(error::error_from_datum)?;
+        if doc.len() != 1 {
+            return Err(de::Error::custom("DatumAny: expected exactly one value"));
+        }
+        Ok(Self(doc.0.remove(0)))
+    }
+}
+
+impl From<DatumValue> for DatumAny {
+    fn from(v: DatumValue) -> Self {
+        Self(v)
+    }
+}
+
+impl From<DatumAny> for DatumValue {
+    fn from(v: DatumAny) -> Self {
+        v.0
+    }
+}
+
+impl fmt::Display for DatumAny {
+    /// Writes the value's canonical form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.write_canonical(f)
+    }
+}
+
+impl Serialize for DatumAny {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(DATUM_ANY, self.to_string().as_str())
+    }
+}
+
+struct DatumAnyVisitor;
+
+impl<'de> Visitor<'de> for DatumAnyVisitor {
+    type Value = DatumAny;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Datum value")
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<DatumAny, E> {
+        DatumAny::from_text(v).map_err(E::custom)
+    }
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<DatumAny, D::Error> {
+        d.deserialize_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DatumAny {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(DATUM_ANY, DatumAnyVisitor)
+    }
+}
+
+/// For the serializers: Gets the tokens of a [DatumAny] from the content of its newtype struct.
+pub(crate) fn datum_any_tokens<T: Serialize + ?Sized>(
+    value: &T,
+) -> error::Result<Vec<DatumToken<String>>> {
+    let text = value.serialize(TextCapture)?;
+    datum_char_to_token_pipeline()
+        .feed_all_to_vec(text.chars(), true)
+        .map_err(error::error_from_datum)
+}
+
+/// Serializer which only accepts a string, and returns it.
+struct TextCapture;
+
+macro_rules! text_capture_rejects {
+    ($($name:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $name(self, $(_: $arg),*) -> error::Result<$ret> {
+                Err(error::Error::custom("DatumAny: expected text"))
+            }
+        )*
+    };
+}
+
+impl Serializer for TextCapture {
+    type Ok = String;
+    type Error = error::Error;
+    type SerializeSeq = Impossible<String, error::Error>;
+    type SerializeTuple = Impossible<String, error::Error>;
+    type SerializeTupleStruct = Impossible<String, error::Error>;
+    type SerializeTupleVariant = Impossible<String, error::Error>;
+    type SerializeMap = Impossible<String, error::Error>;
+    type SerializeStruct = Impossible<String, error::Error>;
+    type SerializeStructVariant = Impossible<String, error::Error>;
+
+    fn serialize_str(self, v: &str) -> error::Result<String> {
+        Ok(v.to_string())
+    }
+    fn collect_str<T: fmt::Display + ?Sized>(self, v: &T) -> error::Result<String> {
+        Ok(v.to_string())
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> error::Result<String> {
+        Err(error::Error::custom("DatumAny: expected text"))
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: &T,
+    ) -> error::Result<String> {
+        Err(error::Error::custom("DatumAny: expected text"))
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> error::Result<String> {
+        Err(error::Error::custom("DatumAny: expected text"))
+    }
+    text_capture_rejects! {
+        serialize_bool(bool) -> String;
+        serialize_i8(i8) -> String;
+        serialize_i16(i16) -> String;
+        serialize_i32(i32) -> String;
+        serialize_i64(i64) -> String;
+        serialize_u8(u8) -> String;
+        serialize_u16(u16) -> String;
+        serialize_u32(u32) -> String;
+        serialize_u64(u64) -> String;
+        serialize_f32(f32) -> String;
+        serialize_f64(f64) -> String;
+        serialize_char(char) -> String;
+        serialize_bytes(&[u8]) -> String;
+        serialize_none() -> String;
+        serialize_unit() -> String;
+        serialize_unit_struct(&'static str) -> String;
+        serialize_unit_variant(&'static str, u32, &'static str) -> String;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+}
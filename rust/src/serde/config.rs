@@ -0,0 +1,144 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use std::boxed::Box;
+use std::path::PathBuf;
+use std::sync::Arc;
+#[cfg(feature = "notify")]
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::de::DeserializeOwned;
+
+use crate::serde::{error, DocLayout};
+
+/// Keeps a configuration file loaded, reloading it when it changes.
+///
+/// Change detection is either by polling the file's modification time via [DatumConfigWatcher::poll], or, with the `notify` feature, by filesystem notifications via [DatumConfigWatcher::watch].
+/// If you have your own event source, call [DatumConfigWatcher::reload] from its handler instead.
+///
+/// Failed reloads keep the previous value and are reported to the error callback.
+///
+/// _Added in 1.3.0._
+pub struct DatumConfigWatcher<T: DeserializeOwned> {
+    path: PathBuf,
+    layout: DocLayout,
+    current: Arc<T>,
+    modified: Option<SystemTime>,
+    on_error: Box<dyn FnMut(error::Error) + Send>,
+}
+
+impl<T: DeserializeOwned> DatumConfigWatcher<T> {
+    /// Loads the file. Unlike later reloads, this must succeed, as there's no previous value to fall back on.
+    pub fn new<P: Into<PathBuf>, F: FnMut(error::Error) + Send + 'static>(
+        path: P,
+        layout: DocLayout,
+        on_error: F,
+    ) -> error::Result<Self> {
+        let path = path.into();
+        let modified = Self::modified_time(&path);
+        let current = Arc::new(layout.deserialize_file(&path)?);
+        Ok(Self {
+            path,
+            layout,
+            current,
+            modified,
+            on_error: Box::new(on_error),
+        })
+    }
+
+    fn modified_time(path: &PathBuf) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Returns the latest successfully loaded value.
+    pub fn get(&self) -> Arc<T> {
+        self.current.clone()
+    }
+
+    /// Returns the path being watched.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Reloads the file if its modification time has changed.
+    /// Returns true if a new value was loaded.
+    pub fn poll(&mut self) -> bool {
+        let modified = Self::modified_time(&self.path);
+        if modified == self.modified {
+            false
+        } else {
+            self.modified = modified;
+            self.reload()
+        }
+    }
+
+    /// Starts reloading the watcher whenever its file changes, using the platform's filesystem notifications.
+    /// Reloads happen on the notification thread; drop the returned [notify::RecommendedWatcher] to stop.
+    ///
+    /// The file's directory is watched rather than the file itself, so editors that save by replacing the file are followed.
+    /// A single save may cause several reloads, some of which may see a partially written file and report errors; the last one sees the finished file.
+    ///
+    /// _Requires the `notify` feature._
+    #[cfg(feature = "notify")]
+    pub fn watch(this: &Arc<Mutex<Self>>) -> notify::Result<notify::RecommendedWatcher>
+    where
+        T: Send + Sync + 'static,
+    {
+        use notify::{EventKind, RecursiveMode, Watcher};
+        let (dir, name) = {
+            let locked = this.lock().unwrap_or_else(|e| e.into_inner());
+            let dir = match locked.path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            (dir, locked.path.file_name().map(|v| v.to_os_string()))
+        };
+        let target = this.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let mut target = target.lock().unwrap_or_else(|e| e.into_inner());
+                match event {
+                    Ok(event) => {
+                        if matches!(event.kind, EventKind::Access(_))
+                            || !event.paths.iter().any(|v| v.file_name() == name.as_deref())
+                        {
+                            return;
+                        }
+                        target.modified = Self::modified_time(&target.path);
+                        target.reload();
+                    }
+                    Err(err) => {
+                        use serde::de::Error;
+                        let err = error::Error::custom(std::format!(
+                            "{}: {}",
+                            target.path.display(),
+                            err
+                        ));
+                        (target.on_error)(err);
+                    }
+                }
+            })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    }
+
+    /// Reloads the file unconditionally.
+    /// Returns true if a new value was loaded; on failure, the error callback is called and the previous value is kept.
+    pub fn reload(&mut self) -> bool {
+        match self.layout.deserialize_file(&self.path) {
+            Ok(v) => {
+                self.current = Arc::new(v);
+                true
+            }
+            Err(err) => {
+                (self.on_error)(err);
+                false
+            }
+        }
+    }
+}
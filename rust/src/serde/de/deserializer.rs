@@ -15,15 +15,162 @@ use serde::{
 
 use crate::serde::error;
 use crate::serde::error::error_from_datum;
+use crate::serde::spanned::{SpannedAccess, SpannedSource};
+
+#[cfg(feature = "alloc")]
+use crate::DatumTokenType;
+#[cfg(feature = "alloc")]
+use crate::{DatumPath, DatumPathSegment};
+#[cfg(feature = "alloc")]
+use alloc::collections::{BTreeSet, VecDeque};
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Most tokens [PlainDeserializer::looks_like_map] looks at.
+#[cfg(feature = "alloc")]
+const MAP_LOOKAHEAD: usize = 256;
 
 /// A 'plain' deserializer.
 /// Expects values in sequence, fails on EOF.
 ///
 /// _Added in 1.1.0._
 pub struct PlainDeserializer<'iterator, B: Default + Deref<Target = str>> {
-    iterator: &'iterator mut dyn Iterator<Item = DatumResult<DatumToken<B>>>,
+    source: TokenSource<'iterator, B>,
     hold: Option<DatumToken<B>>,
+    /// A second token of lookahead, read before the source but after [PlainDeserializer::hold].
+    unread: Option<DatumToken<B>>,
     last_seen_offset: DatumOffset,
+    lenient: bool,
+    variant_strings: bool,
+    newtype_names: bool,
+    #[cfg(feature = "alloc")]
+    detect_maps: bool,
+    #[cfg(feature = "alloc")]
+    reject_duplicate_keys: bool,
+    /// Current path, if tracking it.
+    #[cfg(feature = "alloc")]
+    path: Option<DatumPath>,
+    #[cfg(feature = "alloc")]
+    error_path: Option<DatumPath>,
+    #[cfg(feature = "alloc")]
+    replay: Replay<B>,
+}
+
+/// Position saved by [PlainDeserializer::checkpoint].
+///
+/// _Added in 1.3.0._
+#[cfg(feature = "alloc")]
+#[must_use = "checkpoints buffer tokens until rewound or released"]
+#[derive(Debug)]
+pub struct PlainCheckpoint {
+    position: usize,
+    last_seen_offset: DatumOffset,
+    had_error_path: bool,
+}
+
+/// Tokens read from an iterator source since the oldest unfinished checkpoint, and tokens to read again after a rewind.
+#[cfg(feature = "alloc")]
+struct Replay<B: Deref<Target = str>> {
+    tokens: VecDeque<DatumToken<B>>,
+    /// Index in `tokens` of the next token to read. Always 0 when there are no checkpoints.
+    position: usize,
+    checkpoints: usize,
+    clone: fn(&DatumToken<B>) -> DatumToken<B>,
+}
+
+#[cfg(feature = "alloc")]
+impl<B: Deref<Target = str>> Replay<B> {
+    fn next(&mut self) -> Option<DatumToken<B>> {
+        if self.checkpoints == 0 {
+            self.tokens.pop_front()
+        } else {
+            let token = (self.clone)(self.tokens.get(self.position)?);
+            self.position += 1;
+            Some(token)
+        }
+    }
+
+    fn record(&mut self, token: &DatumToken<B>) {
+        if self.checkpoints != 0 {
+            self.tokens.push_back((self.clone)(token));
+            self.position += 1;
+        }
+    }
+
+    /// Finishes a checkpoint, dropping tokens that can't be rewound to anymore.
+    fn finish(&mut self) {
+        self.checkpoints -= 1;
+        if self.checkpoints == 0 {
+            self.tokens.drain(..self.position);
+            self.position = 0;
+        }
+    }
+}
+
+/// Where a [PlainDeserializer] gets tokens from.
+enum TokenSource<'iterator, B: Deref<Target = str>> {
+    Iterator(&'iterator mut dyn Iterator<Item = DatumResult<DatumToken<B>>>),
+    Slice {
+        tokens: &'iterator [DatumToken<B>],
+        index: usize,
+        // this keeps the Clone bound on [PlainDeserializer::from_tokens] alone
+        clone: fn(&DatumToken<B>) -> DatumToken<B>,
+    },
+}
+
+impl<B: Deref<Target = str>> TokenSource<'_, B> {
+    fn next(&mut self) -> Option<DatumResult<DatumToken<B>>> {
+        match self {
+            Self::Iterator(iterator) => iterator.next(),
+            Self::Slice {
+                tokens,
+                index,
+                clone,
+            } => {
+                let token = tokens.get(*index)?;
+                *index += 1;
+                Some(Ok(clone(token)))
+            }
+        }
+    }
+}
+
+/// What [PlainDeserializer::coerce_next] is looking for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Coerce {
+    Boolean,
+    Number,
+}
+
+/// Visits a value produced by [PlainDeserializer::coerce_next].
+fn visit_coerced<'de, V: serde::de::Visitor<'de>, B: Deref<Target = str>>(
+    atom: DatumAtom<B>,
+    visitor: V,
+) -> error::Result<V::Value> {
+    match atom {
+        DatumAtom::Boolean(v) => visitor.visit_bool(v),
+        DatumAtom::Integer(v) => visitor.visit_i64(v),
+        DatumAtom::Float(v) => visitor.visit_f64(v),
+        // coerce_next doesn't produce these
+        _ => visitor.visit_unit(),
+    }
+}
+
+/// State of a single seq/map/enum access.
+#[derive(Default)]
+pub(crate) struct AccessState {
+    /// Keys seen so far, for [PlainDeserializer::reject_duplicate_keys].
+    /// Strings are stored as symbols, so the two count as the same key.
+    #[cfg(feature = "alloc")]
+    seen_keys: BTreeSet<(DatumTokenType, String)>,
+    /// Index of the next sequence element, for the path.
+    #[cfg(feature = "alloc")]
+    index: usize,
+    /// Key of the next map value, for the path.
+    #[cfg(feature = "alloc")]
+    key: Option<String>,
 }
 
 impl<'iterator, B: Default + Deref<Target = str>> PlainDeserializer<'iterator, B> {
@@ -31,22 +178,584 @@ impl<'iterator, B: Default + Deref<Target = str>> PlainDeserializer<'iterator, B
     pub fn from_iterator(
         iterator: &'iterator mut dyn Iterator<Item = DatumResult<DatumToken<B>>>,
     ) -> Self {
+        Self::from_source(TokenSource::Iterator(iterator))
+    }
+
+    /// Creates the Deserializer from a slice of tokens, i.e. a cached token buffer which may be deserialized several times.
+    ///
+    /// This avoids needing an iterator to borrow. Error offsets are those of the tokens.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumToken, serde::de::PlainDeserializer};
+    /// use serde::Deserialize;
+    /// let tokens = [DatumToken::ListStart(0), DatumToken::Integer(1, 1), DatumToken::Integer(2, 2), DatumToken::ListEnd(3)];
+    /// let mut de = PlainDeserializer::<&str>::from_tokens(&tokens);
+    /// assert_eq!(<(i32, i32)>::deserialize(&mut de).unwrap(), (1, 2));
+    /// let mut de = PlainDeserializer::<&str>::from_tokens(&tokens);
+    /// assert_eq!(<[u8; 2]>::deserialize(&mut de).unwrap(), [1, 2]);
+    /// assert_eq!(de.token_index(), Some(4));
+    /// ```
+    pub fn from_tokens(tokens: &'iterator [DatumToken<B>]) -> Self
+    where
+        B: Clone,
+    {
+        Self::from_source(TokenSource::Slice {
+            tokens,
+            index: 0,
+            clone: DatumToken::clone,
+        })
+    }
+
+    fn from_source(source: TokenSource<'iterator, B>) -> Self {
         Self {
-            iterator,
+            source,
             hold: None,
+            unread: None,
             last_seen_offset: 0,
+            lenient: false,
+            variant_strings: false,
+            newtype_names: false,
+            #[cfg(feature = "alloc")]
+            detect_maps: false,
+            #[cfg(feature = "alloc")]
+            reject_duplicate_keys: false,
+            #[cfg(feature = "alloc")]
+            path: None,
+            #[cfg(feature = "alloc")]
+            error_path: None,
+            #[cfg(feature = "alloc")]
+            replay: Replay {
+                tokens: VecDeque::new(),
+                position: 0,
+                checkpoints: 0,
+                // replaced by checkpoint, which is what needs tokens to be cloneable
+                clone: |_| DatumToken::ListEnd(0),
+            },
         }
     }
 
+    /// Sets if a map/struct with the same key twice is an error (by default, it isn't; the deserialized type decides what happens).
+    /// Symbol and string keys with the same text count as the same key, as they deserialize to the same field.
+    /// Other keys only match keys of the same type, so `1` and `"1"` are different keys.
+    /// Only atom keys are checked; list keys (i.e. tuples or enum variants with values) are left to the deserialized type.
+    ///
+    /// This is mainly useful for configuration files, where it catches copy-paste mistakes that would otherwise silently override earlier values.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn reject_duplicate_keys(mut self, reject: bool) -> Self {
+        self.reject_duplicate_keys = reject;
+        self
+    }
+
+    /// Sets if strings and symbols are accepted in place of numbers and booleans (by default, they aren't).
+    ///
+    /// This is for hand-edited configuration files, where quoting is easy to get wrong. The rules are:
+    ///
+    /// * Where a number is expected, a string is accepted if its contents are a number as Datum would write it; that is, they start with a digit, or `+` or `-` followed by a digit (i.e. `"8080"`, `"-1.5"`).
+    /// * Where a boolean is expected, a string or symbol is accepted if it's `true`, `yes` or `on` (for true), or `false`, `no` or `off` (for false), ignoring ASCII case.
+    ///
+    /// Anything else is deserialized as usual, so documents which are already correct mean the same thing either way.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{datum_char_to_token_pipeline, IntoViaDatumPipe, serde::de::PlainDeserializer};
+    /// use serde::Deserialize;
+    /// let mut tokens = "(\"8080\" yes)".chars().via_datum_pipe(datum_char_to_token_pipeline());
+    /// let mut de = PlainDeserializer::from_iterator(&mut tokens).lenient(true);
+    /// assert_eq!(<(u16, bool)>::deserialize(&mut de).unwrap(), (8080, true));
+    /// ```
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets if a string is accepted as a unit enum variant, i.e. `"Red"` as well as `Red` (by default, only symbols are).
+    ///
+    /// This is for documents generated by other tools. A string is always accepted as the variant name at the start of a list, i.e. `("Rgb" 1 2 3)`.
+    /// The matching serializer option is [crate::serde::ser::PlainSerializer::variant_strings].
+    ///
+    /// _Added in 1.3.0._
+    pub fn variant_strings(mut self, variant_strings: bool) -> Self {
+        self.variant_strings = variant_strings;
+        self
+    }
+
+    /// Sets if newtype structs may be written with their name, as `(Name value)` (by default, newtype structs are transparent).
+    ///
+    /// The transparent form is still accepted. The matching serializer option is [crate::serde::ser::PlainSerializer::newtype_names].
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{datum_char_to_token_pipeline, IntoViaDatumPipe, serde::de::PlainDeserializer};
+    /// use serde::Deserialize;
+    /// #[derive(Deserialize, PartialEq, Debug)]
+    /// struct Meters(f64);
+    /// let mut tokens = "(Meters 1.5) 2.0".chars().via_datum_pipe(datum_char_to_token_pipeline());
+    /// let mut de = PlainDeserializer::from_iterator(&mut tokens).newtype_names(true);
+    /// assert_eq!(Meters::deserialize(&mut de).unwrap(), Meters(1.5));
+    /// assert_eq!(Meters::deserialize(&mut de).unwrap(), Meters(2.0));
+    /// ```
+    pub fn newtype_names(mut self, newtype_names: bool) -> Self {
+        self.newtype_names = newtype_names;
+        self
+    }
+
+    /// Sets if `deserialize_any` gives lists that look like maps to the visitor as maps, rather than as sequences (it doesn't by default).
+    ///
+    /// Datum doesn't say if a list is a map or a sequence, so this guesses: a list with an even number of elements (at least two), where every other element (starting with the first) is a symbol, looks like a map. That's how structs are written.
+    /// The same goes for the whole document with [crate::serde::de::RootDeserializer].
+    /// Only the first few hundred tokens of a list are looked at; if a long list looks like a map that far, it's taken to be one.
+    ///
+    /// `deserialize_any` is what `#[serde(untagged)]` and `#[serde(tag = "...")]` enums use (via serde's buffering), so turning this on is what lets them contain structs.
+    /// Data which is ambiguous (i.e. a `Vec` of an even number of unit enum variants) can be visited the wrong way, which is why it's off by default.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{datum_char_to_token_pipeline, IntoViaDatumPipe};
+    /// use datum::serde::de::PlainDeserializer;
+    /// use serde::Deserialize;
+    /// #[derive(Deserialize, PartialEq, Debug)]
+    /// #[serde(tag = "type")]
+    /// enum Shape {
+    ///     Circle { radius: f64 },
+    ///     Rect { w: f64, h: f64 },
+    /// }
+    /// #[derive(Deserialize, PartialEq, Debug)]
+    /// #[serde(untagged)]
+    /// enum Port {
+    ///     Number(u16),
+    ///     Named { service: String },
+    /// }
+    /// let mut tokens = "((type Rect w 1 h 2) (type Circle radius 0.5))".chars().via_datum_pipe(datum_char_to_token_pipeline());
+    /// let mut de = PlainDeserializer::from_iterator(&mut tokens).detect_maps(true);
+    /// let shapes = Vec::<Shape>::deserialize(&mut de).unwrap();
+    /// assert_eq!(shapes, [Shape::Rect { w: 1.0, h: 2.0 }, Shape::Circle { radius: 0.5 }]);
+    /// let mut tokens = "(80 (service \"http\"))".chars().via_datum_pipe(datum_char_to_token_pipeline());
+    /// let mut de = PlainDeserializer::from_iterator(&mut tokens).detect_maps(true);
+    /// let ports = Vec::<Port>::deserialize(&mut de).unwrap();
+    /// assert_eq!(ports, [Port::Number(80), Port::Named { service: "http".to_string() }]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn detect_maps(mut self, detect_maps: bool) -> Self {
+        self.detect_maps = detect_maps;
+        self
+    }
+
+    /// Sets if the path of the value being deserialized is tracked (it isn't by default).
+    ///
+    /// When tracking, the first error inside a map or sequence is prefixed with its path (i.e. `at server.listeners[2].port: ...`), and the path is available from [PlainDeserializer::error_path].
+    /// This costs a small amount of allocation per map key.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn track_path(mut self, track: bool) -> Self {
+        self.path = if track {
+            Some(DatumPath::default())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Returns the path at which the first error occurred (see [PlainDeserializer::track_path]).
+    ///
+    /// This is [None] if there was no error, the error was outside any map or sequence, or the path isn't being tracked.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn error_path(&self) -> Option<&DatumPath> {
+        self.error_path.as_ref()
+    }
+
+    /// Checks the next token (if any) as a map key for [PlainDeserializer::reject_duplicate_keys], and remembers it for the path.
+    pub(crate) fn check_key(&mut self, state: &mut AccessState) -> error::Result<()> {
+        #[cfg(feature = "alloc")]
+        {
+            state.key = None;
+            if (self.reject_duplicate_keys || self.path.is_some()) && self.has_next_token()? {
+                let (token_type, key) = match &self.hold {
+                    Some(DatumToken::String(_, b)) | Some(DatumToken::Symbol(_, b)) => {
+                        (DatumTokenType::Symbol, b.to_string())
+                    }
+                    Some(DatumToken::ListStart(_)) | Some(DatumToken::ListEnd(_)) | None => {
+                        return Ok(())
+                    }
+                    Some(token) => {
+                        let mut text = String::new();
+                        // writing to a String can't fail
+                        let _ = token.write(&mut text);
+                        (token.token_type(), text)
+                    }
+                };
+                if self.reject_duplicate_keys && !state.seen_keys.insert((token_type, key.clone()))
+                {
+                    let at = self
+                        .hold
+                        .as_ref()
+                        .map_or(self.last_seen_offset, |v| v.offset());
+                    return Err(error_from_datum(datum_error!(
+                        BadData,
+                        at,
+                        "map: duplicate key"
+                    )));
+                }
+                if self.path.is_some() {
+                    state.key = Some(key);
+                }
+            }
+        }
+        #[cfg(not(feature = "alloc"))]
+        let _ = state;
+        Ok(())
+    }
+
+    /// Deserializes a sequence element via `f`, tracking its index in the path.
+    pub(crate) fn at_index<T>(
+        &mut self,
+        state: &mut AccessState,
+        f: impl FnOnce(&mut Self) -> error::Result<T>,
+    ) -> error::Result<T> {
+        #[cfg(feature = "alloc")]
+        {
+            let index = state.index;
+            state.index += 1;
+            self.at_segment(DatumPathSegment::Index(index), f)
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = state;
+            f(self)
+        }
+    }
+
+    /// Deserializes a map value via `f`, tracking the key found by [PlainDeserializer::check_key] in the path.
+    pub(crate) fn at_key<T>(
+        &mut self,
+        state: &mut AccessState,
+        f: impl FnOnce(&mut Self) -> error::Result<T>,
+    ) -> error::Result<T> {
+        #[cfg(feature = "alloc")]
+        match state.key.take() {
+            Some(key) => self.at_segment(DatumPathSegment::Key(key), f),
+            None => f(self),
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = state;
+            f(self)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn at_segment<T>(
+        &mut self,
+        segment: DatumPathSegment,
+        f: impl FnOnce(&mut Self) -> error::Result<T>,
+    ) -> error::Result<T> {
+        if let Some(path) = &mut self.path {
+            path.push(segment);
+        } else {
+            return f(self);
+        }
+        let res = match f(self) {
+            // innermost error; outer segments will see error_path is set and leave it alone
+            Err(err) if self.error_path.is_none() => {
+                let path = self.path.clone().unwrap_or_default();
+                let err = serde::de::Error::custom(format_args!("at {}: {}", path, err));
+                self.error_path = Some(path);
+                Err(err)
+            }
+            res => res,
+        };
+        if let Some(path) = &mut self.path {
+            path.pop();
+        }
+        res
+    }
+
+    /// If created with [PlainDeserializer::from_tokens], returns the index of the next token to be read from the slice.
+    ///
+    /// This allows deserializing consecutive values from one buffer, or finding where an error occurred.
+    ///
+    /// _Added in 1.3.0._
+    pub fn token_index(&self) -> Option<usize> {
+        match &self.source {
+            TokenSource::Slice { index, .. } => {
+                Some(*index - self.hold.iter().count() - self.unread.iter().count())
+            }
+            TokenSource::Iterator(_) => None,
+        }
+    }
+
+    /// Saves the current position, so that reading can go back to it with [PlainDeserializer::rewind].
+    ///
+    /// This allows trying to deserialize a value one way, and then another way if that fails (i.e. for untagged data, or a `deserialize_any`-style probe).
+    /// Tokens read from an iterator are buffered until the checkpoint is rewound to or released ([PlainDeserializer::release]); reading from a slice needs no buffering.
+    ///
+    /// Checkpoints can be nested, but must be finished in the reverse of the order they were made.
+    /// If an error occurs between a checkpoint and rewinding to it, the error's path (see [PlainDeserializer::error_path]) is forgotten, so that later errors report theirs.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{datum_char_to_token_pipeline, IntoViaDatumPipe, serde::de::PlainDeserializer};
+    /// use serde::Deserialize;
+    /// let mut tokens = "(1 2) (\"a\" \"b\")".chars().via_datum_pipe(datum_char_to_token_pipeline());
+    /// let mut de = PlainDeserializer::from_iterator(&mut tokens);
+    /// let mut found = Vec::new();
+    /// while de.has_next_token().unwrap() {
+    ///     let checkpoint = de.checkpoint();
+    ///     match <(i32, i32)>::deserialize(&mut de) {
+    ///         Ok(v) => {
+    ///             de.release(checkpoint);
+    ///             found.push(format!("numbers {:?}", v));
+    ///         }
+    ///         Err(_) => {
+    ///             de.rewind(checkpoint);
+    ///             let v = <(String, String)>::deserialize(&mut de).unwrap();
+    ///             found.push(format!("strings {:?}", v));
+    ///         }
+    ///     }
+    /// }
+    /// assert_eq!(found, ["numbers (1, 2)", "strings (\"a\", \"b\")"]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn checkpoint(&mut self) -> PlainCheckpoint
+    where
+        B: Clone,
+    {
+        // tokens already read ahead are put back, so the checkpoint is before them
+        let ahead: Vec<DatumToken<B>> = self
+            .hold
+            .take()
+            .into_iter()
+            .chain(self.unread.take())
+            .collect();
+        self.put_back(ahead);
+        let position = match &mut self.source {
+            TokenSource::Slice { index, .. } => *index,
+            TokenSource::Iterator(_) => {
+                self.replay.clone = DatumToken::clone;
+                self.replay.checkpoints += 1;
+                self.replay.position
+            }
+        };
+        PlainCheckpoint {
+            position,
+            last_seen_offset: self.last_seen_offset,
+            had_error_path: self.error_path.is_some(),
+        }
+    }
+
+    /// Puts back tokens read by [PlainDeserializer::next_source_token] (in the order they were read), so they're read again.
+    #[cfg(feature = "alloc")]
+    fn put_back(&mut self, tokens: Vec<DatumToken<B>>) {
+        match &mut self.source {
+            TokenSource::Slice { index, .. } => *index -= tokens.len(),
+            // recorded, so they only need to be replayed again
+            TokenSource::Iterator(_) if self.replay.checkpoints != 0 => {
+                self.replay.position -= tokens.len()
+            }
+            // they go in front of anything left from an earlier rewind
+            TokenSource::Iterator(_) => {
+                let count = tokens.len();
+                self.replay.tokens.extend(tokens);
+                self.replay.tokens.rotate_right(count);
+            }
+        }
+    }
+
+    /// For `deserialize_any`: Looks ahead through the elements of a list (after the list start), or if `to_eof`, the rest of the document, to see if it looks like a map (see [PlainDeserializer::detect_maps]).
+    ///
+    /// The tokens looked at are put back. At most [MAP_LOOKAHEAD] are looked at, so nested lists don't each buffer all of their contents.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn looks_like_map(&mut self, to_eof: bool) -> error::Result<bool> {
+        if !self.detect_maps {
+            return Ok(false);
+        }
+        let mut tokens: Vec<DatumToken<B>> = self
+            .hold
+            .take()
+            .into_iter()
+            .chain(self.unread.take())
+            .collect();
+        self.put_back(core::mem::take(&mut tokens));
+        let mut depth: usize = 0;
+        // complete key/value pairs, and if there's a key waiting for its value
+        let mut pairs: usize = 0;
+        let mut key_read = false;
+        let res = loop {
+            if tokens.len() >= MAP_LOOKAHEAD {
+                // it's looked like a map so far
+                break pairs > 0 || key_read;
+            }
+            let token = match self.next_source_token() {
+                Some(Ok(token)) => token,
+                Some(Err(err)) => {
+                    self.put_back(tokens);
+                    return Err(error_from_datum(err));
+                }
+                // errors are left to be found when reading for real
+                None => break to_eof && depth == 0 && pairs > 0 && !key_read,
+            };
+            let depth_change: isize = match token {
+                DatumToken::ListStart(_) => 1,
+                DatumToken::ListEnd(_) => -1,
+                _ => 0,
+            };
+            let is_symbol = matches!(token, DatumToken::Symbol(_, _));
+            tokens.push(token);
+            if depth == 0 {
+                if depth_change < 0 {
+                    break !to_eof && pairs > 0 && !key_read;
+                } else if key_read {
+                    pairs += 1;
+                    key_read = false;
+                } else if is_symbol {
+                    key_read = true;
+                } else {
+                    break false;
+                }
+            }
+            depth = (depth as isize + depth_change) as usize;
+        };
+        self.put_back(tokens);
+        Ok(res)
+    }
+
+    /// Goes back to a checkpoint made by [PlainDeserializer::checkpoint], so the tokens after it are read again.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn rewind(&mut self, checkpoint: PlainCheckpoint) {
+        self.hold = None;
+        self.unread = None;
+        self.last_seen_offset = checkpoint.last_seen_offset;
+        if !checkpoint.had_error_path {
+            self.error_path = None;
+        }
+        match &mut self.source {
+            TokenSource::Slice { index, .. } => *index = checkpoint.position,
+            TokenSource::Iterator(_) => {
+                self.replay.position = checkpoint.position;
+                self.replay.finish();
+            }
+        }
+    }
+
+    /// Finishes with a checkpoint made by [PlainDeserializer::checkpoint] without going back to it, so the tokens buffered for it can be dropped.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn release(&mut self, checkpoint: PlainCheckpoint) {
+        let _ = checkpoint;
+        if let TokenSource::Iterator(_) = self.source {
+            self.replay.finish();
+        }
+    }
+
+    /// With [PlainDeserializer::lenient], consumes the next token if it's a string or symbol standing in for the expected kind of value, and returns that value.
+    fn coerce_next(&mut self, kind: Coerce) -> error::Result<Option<DatumAtom<B>>> {
+        if !self.lenient || !self.has_next_token()? {
+            return Ok(None);
+        }
+        let text = match &self.hold {
+            Some(DatumToken::String(_, v)) => v.deref(),
+            Some(DatumToken::Symbol(_, v)) if kind == Coerce::Boolean => v.deref(),
+            _ => return Ok(None),
+        };
+        let res = match kind {
+            Coerce::Boolean => {
+                let is = |words: &[&str]| words.iter().any(|w| text.eq_ignore_ascii_case(w));
+                if is(&["true", "yes", "on"]) {
+                    Some(DatumAtom::Boolean(true))
+                } else if is(&["false", "no", "off"]) {
+                    Some(DatumAtom::Boolean(false))
+                } else {
+                    None
+                }
+            }
+            Coerce::Number => {
+                let digits = text.strip_prefix(|c| c == '+' || c == '-').unwrap_or(text);
+                if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+                    None
+                } else if let Ok(v) = text.parse() {
+                    Some(DatumAtom::Integer(v))
+                } else if let Ok(v) = text.parse() {
+                    Some(DatumAtom::Float(v))
+                } else {
+                    None
+                }
+            }
+        };
+        if res.is_some() {
+            if let Some(token) = self.hold.take() {
+                self.last_seen_offset = token.offset();
+            }
+        }
+        Ok(res)
+    }
+
+    /// With [PlainDeserializer::newtype_names], consumes the name of a newtype struct if it's next.
+    /// If `wrapped`, this is the start of `(Name value)` (the list end is left to the caller), otherwise it's just `Name`.
+    pub(crate) fn take_newtype_name(&mut self, name: &str, wrapped: bool) -> error::Result<bool> {
+        if !self.newtype_names || !self.has_next_token()? {
+            return Ok(false);
+        }
+        let start = if wrapped {
+            if !matches!(self.hold, Some(DatumToken::ListStart(_))) {
+                return Ok(false);
+            }
+            let start = self.hold.take();
+            if !self.has_next_token()? {
+                self.hold = start;
+                return Ok(false);
+            }
+            start
+        } else {
+            None
+        };
+        if matches!(&self.hold, Some(DatumToken::Symbol(_, v)) if v.deref() == name) {
+            self.hold = None;
+            Ok(true)
+        } else {
+            if start.is_some() {
+                self.unread = self.hold.take();
+                self.hold = start;
+            }
+            Ok(false)
+        }
+    }
+
+    /// Reads a token from [PlainDeserializer::unread] or the source.
+    fn next_source_token(&mut self) -> Option<DatumResult<DatumToken<B>>> {
+        if let Some(token) = self.unread.take() {
+            return Some(Ok(token));
+        }
+        #[cfg(feature = "alloc")]
+        if let Some(token) = self.replay.next() {
+            return Some(Ok(token));
+        }
+        let token = self.source.next();
+        #[cfg(feature = "alloc")]
+        if let Some(Ok(token)) = &token {
+            self.replay.record(token);
+        }
+        token
+    }
+
     /// Checks if a next token exists.
     /// Errors indicate non-EOF errors.
     pub fn has_next_token(&mut self) -> error::Result<bool> {
         if self.hold.is_some() {
             Ok(true)
         } else {
-            let res = self.iterator.next();
+            let res = self.next_source_token();
             if let Some(v) = res {
-                self.hold = Some(v.map_err(error_from_datum)?);
+                let token = v.map_err(error_from_datum)?;
+                self.last_seen_offset = token.offset();
+                self.hold = Some(token);
                 Ok(true)
             } else {
                 Ok(false)
@@ -58,7 +767,7 @@ impl<'iterator, B: Default + Deref<Target = str>> PlainDeserializer<'iterator, B
         if let Some(token) = self.hold.take() {
             Ok(token)
         } else {
-            let res = self.iterator.next();
+            let res = self.next_source_token();
             if let Some(v) = res {
                 if let Ok(tkn) = &v {
                     self.last_seen_offset = tkn.offset();
@@ -69,6 +778,72 @@ impl<'iterator, B: Default + Deref<Target = str>> PlainDeserializer<'iterator, B
             }
         }
     }
+    /// Consumes one whole value without deserializing it. This is what `deserialize_ignored_any` does.
+    ///
+    /// Like [crate::datum_skip_value], atoms aren't checked for validity.
+    ///
+    /// _Added in 1.3.0._
+    pub fn skip_value(&mut self) -> error::Result<()> {
+        self.each_value_token(|_| Ok(()))
+    }
+    /// Consumes one whole value, giving each of its tokens to a function.
+    fn each_value_token(
+        &mut self,
+        mut f: impl FnMut(&DatumToken<B>) -> error::Result<()>,
+    ) -> error::Result<()> {
+        let mut depth: usize = 0;
+        loop {
+            let token = self.next_token(datum_error!(
+                Interrupted,
+                self.last_seen_offset,
+                "skip: unexpected EOF, expected value"
+            ))?;
+            match token {
+                DatumToken::ListStart(_) => depth += 1,
+                DatumToken::ListEnd(_) => {
+                    if depth == 0 {
+                        return Err(error_from_datum(datum_error!(
+                            BadData,
+                            self.last_seen_offset,
+                            "skip: unexpected list end"
+                        )));
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+            f(&token)?;
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+    /// Consumes one whole value, returning it as (canonical) text. This is how [crate::serde::DatumAny] is read.
+    #[cfg(feature = "alloc")]
+    fn value_text(&mut self) -> error::Result<String> {
+        let mut writer = crate::DatumWriter::default();
+        let mut text = String::new();
+        self.each_value_token(|token| {
+            writer
+                .write_token(&mut text, token)
+                .map_err(serde::de::Error::custom)
+        })?;
+        Ok(text)
+    }
+    /// The offset of the next token, without consuming it (or of the last token, at EOF). This is how [crate::serde::DatumSpanned] is read.
+    pub(crate) fn peek_offset(&mut self) -> error::Result<DatumOffset> {
+        if self.hold.is_none() {
+            if let Some(token) = self.next_source_token() {
+                let token = token.map_err(error_from_datum)?;
+                self.last_seen_offset = token.offset();
+                self.hold = Some(token);
+            }
+        }
+        Ok(self
+            .hold
+            .as_ref()
+            .map_or(self.last_seen_offset, |v| v.offset()))
+    }
     /// Expects a list end.
     fn expect_list_end(&mut self) -> error::Result<()> {
         if let DatumToken::ListEnd(_) = self.next_token(datum_error!(
@@ -87,31 +862,47 @@ impl<'iterator, B: Default + Deref<Target = str>> PlainDeserializer<'iterator, B
     }
 }
 
+impl<B: Default + Deref<Target = str>> SpannedSource for PlainDeserializer<'_, B> {
+    fn last_offset(&self) -> DatumOffset {
+        self.last_seen_offset
+    }
+}
+
 /// Hides access traits and also solves some weird lifetime problems.
 struct AccessWrapper<'a, 'iterator, B: Default + Deref<Target = str>>(
     &'a mut PlainDeserializer<'iterator, B>,
+    AccessState,
 );
 
+impl<B: Default + Deref<Target = str>> AccessWrapper<'_, '_, B> {
+    /// Checks for the end of the list, without consuming anything.
+    fn at_list_end(&mut self) -> error::Result<bool> {
+        let token = self.0.next_token(datum_error!(
+            Interrupted,
+            self.0.last_seen_offset,
+            "seq: unexpected EOF, expected next element or list end"
+        ))?;
+        // Ok, so, here's a sneaky thing that Serde does which is ?undocumented? outside of the JSON example?
+        // You CANNOT rely on the sequence accessor being fully consumed.
+        // You MUST have some check after calling [Visitor::visit_seq].
+        let res = matches!(token, DatumToken::ListEnd(_));
+        self.0.hold = Some(token);
+        Ok(res)
+    }
+}
+
 impl<'de, 'a, B: Default + Deref<Target = str>> SeqAccess<'de> for AccessWrapper<'a, '_, B> {
     type Error = error::Error;
     fn next_element_seed<T: serde::de::DeserializeSeed<'de>>(
         &mut self,
         seed: T,
     ) -> Result<Option<T::Value>, Self::Error> {
-        let token = self.0.next_token(datum_error!(
-            Interrupted,
-            self.0.last_seen_offset,
-            "seq: unexpected EOF, expected next element or list end"
-        ))?;
-        if let DatumToken::ListEnd(_) = token {
-            // Ok, so, here's a sneaky thing that Serde does which is ?undocumented? outside of the JSON example?
-            // You CANNOT rely on the sequence accessor being fully consumed.
-            // You MUST have some check after calling [Visitor::visit_seq].
-            self.0.hold = Some(token);
+        if self.at_list_end()? {
             Ok(None)
         } else {
-            self.0.hold = Some(token);
-            seed.deserialize(&mut *self.0).map(Some)
+            self.0
+                .at_index(&mut self.1, |d| seed.deserialize(d))
+                .map(Some)
         }
     }
 }
@@ -122,13 +913,18 @@ impl<'de, 'a, B: Default + Deref<Target = str>> MapAccess<'de> for AccessWrapper
         &mut self,
         seed: K,
     ) -> Result<Option<K::Value>, Self::Error> {
-        self.next_element_seed(seed)
+        self.0.check_key(&mut self.1)?;
+        if self.at_list_end()? {
+            Ok(None)
+        } else {
+            seed.deserialize(&mut *self.0).map(Some)
+        }
     }
     fn next_value_seed<V: serde::de::DeserializeSeed<'de>>(
         &mut self,
         seed: V,
     ) -> Result<V::Value, Self::Error> {
-        seed.deserialize(&mut *self.0)
+        self.0.at_key(&mut self.1, |d| seed.deserialize(d))
     }
 }
 
@@ -170,23 +966,33 @@ impl<'de, 'a, B: Default + Deref<Target = str>> VariantAccess<'de> for AccessWra
     }
 }
 
-impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
-    for &'a mut PlainDeserializer<'_, B>
-{
-    type Error = error::Error;
-    // deserialize_any itself
-    fn deserialize_any<V: serde::de::Visitor<'de>>(
-        self,
+impl<B: Default + Deref<Target = str>> PlainDeserializer<'_, B> {
+    /// What `deserialize_any` does; the other `deserialize_*` functions come here when they're given something else, so that the visitor can report what it was.
+    /// They don't [PlainDeserializer::detect_maps], so that sequences are always visited as such.
+    fn visit_any<'de, V: serde::de::Visitor<'de>>(
+        &mut self,
         visitor: V,
-    ) -> Result<V::Value, Self::Error> {
+        detect_maps: bool,
+    ) -> error::Result<V::Value> {
         let token = self.next_token(datum_error!(
             Interrupted,
             self.last_seen_offset,
             "any: Unexpected EOF, expected value"
         ))?;
         if let DatumToken::ListStart(_) = token {
-            // consume the list start and let the SeqAccess impl. take care of the rest
-            let res = visitor.visit_seq(AccessWrapper(self))?;
+            // consume the list start and let the SeqAccess/MapAccess impl. take care of the rest
+            #[cfg(feature = "alloc")]
+            let map = detect_maps && self.looks_like_map(false)?;
+            #[cfg(not(feature = "alloc"))]
+            let map = {
+                let _ = detect_maps;
+                false
+            };
+            let res = if map {
+                visitor.visit_map(AccessWrapper(self, AccessState::default()))?
+            } else {
+                visitor.visit_seq(AccessWrapper(self, AccessState::default()))?
+            };
             self.expect_list_end()?;
             Ok(res)
         } else if let DatumToken::ListEnd(_) = token {
@@ -211,10 +1017,28 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
             }
         }
     }
+}
+
+impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
+    for &'a mut PlainDeserializer<'_, B>
+{
+    type Error = error::Error;
+    // deserialize_any itself
+    fn deserialize_any<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.visit_any(visitor, true)
+    }
     fn deserialize_u64<V: serde::de::Visitor<'de>>(
         self,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
+        match self.coerce_next(Coerce::Number)? {
+            Some(DatumAtom::Integer(v)) => return visitor.visit_u64(v as u64),
+            Some(atom) => return visit_coerced(atom, visitor),
+            None => {}
+        }
         let token = self.next_token(datum_error!(
             Interrupted,
             self.last_seen_offset,
@@ -224,7 +1048,7 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
             DatumToken::Integer(_, v) => visitor.visit_u64(v as u64),
             _ => {
                 self.hold = Some(token);
-                self.deserialize_any(visitor)
+                self.visit_any(visitor, false)
             }
         }
     }
@@ -245,8 +1069,11 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
         ))?;
         match token {
             DatumToken::Symbol(_, text) => visitor.visit_enum(text.into_deserializer()),
+            DatumToken::String(_, text) if self.variant_strings => {
+                visitor.visit_enum(text.into_deserializer())
+            }
             DatumToken::ListStart(_) => {
-                let res = visitor.visit_enum(AccessWrapper(self))?;
+                let res = visitor.visit_enum(AccessWrapper(self, AccessState::default()))?;
                 self.expect_list_end()?;
                 Ok(res)
             }
@@ -293,7 +1120,7 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
             }
             _ => {
                 self.hold = Some(token);
-                self.deserialize_any(visitor)
+                self.visit_any(visitor, false)
             }
         }
     }
@@ -307,7 +1134,7 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
             "map: unexpected EOF, expected list"
         ))? {
             DatumToken::ListStart(_) => {
-                let res = visitor.visit_map(AccessWrapper(self))?;
+                let res = visitor.visit_map(AccessWrapper(self, AccessState::default()))?;
                 self.expect_list_end()?;
                 Ok(res)
             }
@@ -323,11 +1150,43 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
         _len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        self.deserialize_any(visitor)
+        self.visit_any(visitor, false)
+    }
+    fn deserialize_ignored_any<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.skip_value()?;
+        visitor.visit_unit()
+    }
+    fn deserialize_newtype_struct<V: serde::de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        #[cfg(feature = "alloc")]
+        if name == crate::serde::any::DATUM_ANY {
+            return visitor.visit_str(&self.value_text()?);
+        }
+        if name == crate::serde::spanned::DATUM_SPANNED {
+            let offset = self.peek_offset()?;
+            return visitor.visit_seq(SpannedAccess::new(offset, self));
+        }
+        if self.take_newtype_name(name, true)? {
+            let res = visitor.visit_newtype_struct(&mut *self)?;
+            self.expect_list_end()?;
+            Ok(res)
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
     }
     deserializer_invariants!();
-    forward_to_deserialize_any! {
-        seq
+    deserializer_scalars!(coerce);
+    fn deserialize_seq<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.visit_any(visitor, false)
     }
 }
 
@@ -338,6 +1197,12 @@ struct NewtypeVariantDeserializer<'base, 'iterator, B: Default + Deref<Target =
     &'base mut PlainDeserializer<'iterator, B>,
 );
 
+impl<B: Default + Deref<Target = str>> SpannedSource for NewtypeVariantDeserializer<'_, '_, B> {
+    fn last_offset(&self) -> DatumOffset {
+        self.0.last_seen_offset
+    }
+}
+
 impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
     for &'a mut NewtypeVariantDeserializer<'_, '_, B>
 {
@@ -377,20 +1242,20 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
         _variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_enum(AccessWrapper(&mut self.0))
+        visitor.visit_enum(AccessWrapper(self.0, AccessState::default()))
     }
     fn deserialize_map<V: serde::de::Visitor<'de>>(
         self,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_map(AccessWrapper(&mut self.0))
+        visitor.visit_map(AccessWrapper(self.0, AccessState::default()))
     }
     // -- forwarders/simple type aliases --
     fn deserialize_seq<V: serde::de::Visitor<'de>>(
         self,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_seq(AccessWrapper(&mut self.0))
+        visitor.visit_seq(AccessWrapper(self.0, AccessState::default()))
     }
     fn deserialize_tuple<V: serde::de::Visitor<'de>>(
         self,
@@ -399,5 +1264,34 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
     ) -> Result<V::Value, Self::Error> {
         self.deserialize_seq(visitor)
     }
+    fn deserialize_ignored_any<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_ignored_any(visitor)
+    }
+    fn deserialize_newtype_struct<V: serde::de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        // written by PlainSerializer, so read it with PlainDeserializer
+        #[cfg(feature = "alloc")]
+        if name == crate::serde::any::DATUM_ANY {
+            return self.0.deserialize_newtype_struct(name, visitor);
+        }
+        if name == crate::serde::spanned::DATUM_SPANNED {
+            let offset = self.0.peek_offset()?;
+            return visitor.visit_seq(SpannedAccess::new(offset, self));
+        }
+        if self.0.take_newtype_name(name, true)? {
+            let res = visitor.visit_newtype_struct(&mut *self.0)?;
+            self.0.expect_list_end()?;
+            Ok(res)
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
+    }
     deserializer_invariants!();
+    deserializer_scalars!(forward);
 }
@@ -13,18 +13,10 @@
 macro_rules! deserializer_invariants {
     () => {
         forward_to_deserialize_any! {
-            bool i8 i16 i32 i64 i128 u8 u16 u32 u128 f32 f64 char
+            char
             str string identifier
-            ignored_any
             bytes byte_buf
         }
-        fn deserialize_newtype_struct<V: serde::de::Visitor<'de>>(
-            self,
-            _name: &'static str,
-            visitor: V,
-        ) -> Result<V::Value, Self::Error> {
-            visitor.visit_newtype_struct(self)
-        }
         fn deserialize_struct<V: serde::de::Visitor<'de>>(
             self,
             _name: &'static str,
@@ -51,6 +43,45 @@ macro_rules! deserializer_invariants {
     };
 }
 
+/// Deserializer scalars, which [PlainDeserializer::lenient] may coerce.
+/// `coerce` implements them for [PlainDeserializer], and `forward` passes them to the [PlainDeserializer] in `self.0`.
+/// `deserialize_u64` is left to each deserializer.
+macro_rules! deserializer_scalars {
+    ($mode:ident) => {
+        deserializer_scalars!($mode, Boolean, deserialize_bool);
+        deserializer_scalars!(
+            $mode,
+            Number,
+            deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+            deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u128
+            deserialize_f32 deserialize_f64
+        );
+    };
+    (coerce, $kind:ident, $($name:ident)*) => {
+        $(
+            fn $name<V: serde::de::Visitor<'de>>(
+                self,
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                match self.coerce_next(Coerce::$kind)? {
+                    Some(atom) => visit_coerced(atom, visitor),
+                    None => self.visit_any(visitor, false),
+                }
+            }
+        )*
+    };
+    (forward, $kind:ident, $($name:ident)*) => {
+        $(
+            fn $name<V: serde::de::Visitor<'de>>(
+                self,
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                self.0.$name(visitor)
+            }
+        )*
+    };
+}
+
 mod deserializer;
 pub use deserializer::*;
 mod seqmaproot;
@@ -14,7 +14,8 @@ use serde::{
 
 use crate::{serde::error, DatumResult, DatumToken};
 
-use crate::serde::de::PlainDeserializer;
+use crate::serde::de::{AccessState, PlainDeserializer};
+use crate::serde::spanned::{SpannedAccess, SpannedSource};
 
 /// 'Document Root' deserializer.
 ///
@@ -25,6 +26,12 @@ pub struct RootDeserializer<'iterator, B: Default + Deref<Target = str>>(
     pub PlainDeserializer<'iterator, B>,
 );
 
+impl<B: Default + Deref<Target = str>> SpannedSource for RootDeserializer<'_, B> {
+    fn last_offset(&self) -> crate::DatumOffset {
+        self.0.last_offset()
+    }
+}
+
 impl<'iterator, B: Default + Deref<Target = str>> RootDeserializer<'iterator, B> {
     /// Creates the Deserializer from an iterator.
     ///
@@ -34,6 +41,69 @@ impl<'iterator, B: Default + Deref<Target = str>> RootDeserializer<'iterator, B>
     ) -> Self {
         Self(PlainDeserializer::from_iterator(iterator))
     }
+
+    /// See [PlainDeserializer::from_tokens].
+    ///
+    /// _Added in 1.3.0._
+    pub fn from_tokens(tokens: &'iterator [DatumToken<B>]) -> Self
+    where
+        B: Clone,
+    {
+        Self(PlainDeserializer::from_tokens(tokens))
+    }
+
+    /// See [PlainDeserializer::reject_duplicate_keys].
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn reject_duplicate_keys(self, reject: bool) -> Self {
+        Self(self.0.reject_duplicate_keys(reject))
+    }
+
+    /// See [PlainDeserializer::lenient].
+    ///
+    /// _Added in 1.3.0._
+    pub fn lenient(self, lenient: bool) -> Self {
+        Self(self.0.lenient(lenient))
+    }
+
+    /// See [PlainDeserializer::variant_strings].
+    ///
+    /// _Added in 1.3.0._
+    pub fn variant_strings(self, variant_strings: bool) -> Self {
+        Self(self.0.variant_strings(variant_strings))
+    }
+
+    /// See [PlainDeserializer::newtype_names]. At the root, the name isn't wrapped in a list: `Name value`.
+    ///
+    /// _Added in 1.3.0._
+    pub fn newtype_names(self, newtype_names: bool) -> Self {
+        Self(self.0.newtype_names(newtype_names))
+    }
+
+    /// See [PlainDeserializer::detect_maps]. At the root, the whole document is looked at.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn detect_maps(self, detect_maps: bool) -> Self {
+        Self(self.0.detect_maps(detect_maps))
+    }
+
+    /// See [PlainDeserializer::track_path].
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn track_path(self, track: bool) -> Self {
+        Self(self.0.track_path(track))
+    }
+
+    /// See [PlainDeserializer::error_path].
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn error_path(&self) -> Option<&crate::DatumPath> {
+        self.0.error_path()
+    }
 }
 
 impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
@@ -41,6 +111,10 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
 {
     type Error = error::Error;
     fn deserialize_any<V: serde::de::Visitor<'de>>(self, visitor: V) -> error::Result<V::Value> {
+        #[cfg(feature = "alloc")]
+        if self.0.looks_like_map(true)? {
+            return self.deserialize_map(visitor);
+        }
         self.0.deserialize_any(visitor)
     }
     fn deserialize_u64<V: serde::de::Visitor<'de>>(self, visitor: V) -> error::Result<V::Value> {
@@ -66,20 +140,20 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
         self,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_seq(AccessWrapper(self))
+        visitor.visit_seq(AccessWrapper(self, AccessState::default()))
     }
     fn deserialize_tuple<V: serde::de::Visitor<'de>>(
         self,
         len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_seq(TupleAccess(self, len))
+        visitor.visit_seq(TupleAccess(self, len, AccessState::default()))
     }
     fn deserialize_map<V: serde::de::Visitor<'de>>(
         self,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_map(AccessWrapper(self))
+        visitor.visit_map(AccessWrapper(self, AccessState::default()))
     }
     fn deserialize_enum<V: serde::de::Visitor<'de>>(
         self,
@@ -87,9 +161,34 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
         _variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_enum(AccessWrapper(self))
+        visitor.visit_enum(AccessWrapper(self, AccessState::default()))
+    }
+    fn deserialize_ignored_any<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_ignored_any(visitor)
+    }
+    fn deserialize_newtype_struct<V: serde::de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        #[cfg(feature = "alloc")]
+        if name == crate::serde::any::DATUM_ANY {
+            return Err(serde::de::Error::custom(
+                "DatumAny can't be the root of a document",
+            ));
+        }
+        if name == crate::serde::spanned::DATUM_SPANNED {
+            let offset = self.0.peek_offset()?;
+            return visitor.visit_seq(SpannedAccess::new(offset, self));
+        }
+        self.0.take_newtype_name(name, false)?;
+        visitor.visit_newtype_struct(self)
     }
     deserializer_invariants!();
+    deserializer_scalars!(forward);
     fn is_human_readable(&self) -> bool {
         true
     }
@@ -99,6 +198,7 @@ impl<'de, 'a, B: Default + Deref<Target = str>> Deserializer<'de>
 struct TupleAccess<'a, 'iterator, B: Default + Deref<Target = str>>(
     &'a mut RootDeserializer<'iterator, B>,
     usize,
+    AccessState,
 );
 
 impl<'de, 'a, B: Default + Deref<Target = str>> SeqAccess<'de> for TupleAccess<'a, '_, B> {
@@ -109,7 +209,10 @@ impl<'de, 'a, B: Default + Deref<Target = str>> SeqAccess<'de> for TupleAccess<'
     ) -> error::Result<Option<T::Value>> {
         if self.1 > 0 {
             self.1 -= 1;
-            seed.deserialize(&mut self.0 .0).map(Some)
+            self.0
+                 .0
+                .at_index(&mut self.2, |d| seed.deserialize(d))
+                .map(Some)
         } else {
             Ok(None)
         }
@@ -119,6 +222,7 @@ impl<'de, 'a, B: Default + Deref<Target = str>> SeqAccess<'de> for TupleAccess<'
 /// Hides access traits and also solves some weird lifetime problems.
 struct AccessWrapper<'a, 'iterator, B: Default + Deref<Target = str>>(
     &'a mut RootDeserializer<'iterator, B>,
+    AccessState,
 );
 
 impl<'de, 'a, B: Default + Deref<Target = str>> SeqAccess<'de> for AccessWrapper<'a, '_, B> {
@@ -128,7 +232,10 @@ impl<'de, 'a, B: Default + Deref<Target = str>> SeqAccess<'de> for AccessWrapper
         seed: T,
     ) -> error::Result<Option<T::Value>> {
         if self.0 .0.has_next_token()? {
-            seed.deserialize(&mut self.0 .0).map(Some)
+            self.0
+                 .0
+                .at_index(&mut self.1, |d| seed.deserialize(d))
+                .map(Some)
         } else {
             Ok(None)
         }
@@ -141,6 +248,7 @@ impl<'de, 'a, B: Default + Deref<Target = str>> MapAccess<'de> for AccessWrapper
         &mut self,
         seed: T,
     ) -> error::Result<Option<T::Value>> {
+        self.0 .0.check_key(&mut self.1)?;
         if self.0 .0.has_next_token()? {
             seed.deserialize(&mut self.0 .0).map(Some)
         } else {
@@ -151,7 +259,7 @@ impl<'de, 'a, B: Default + Deref<Target = str>> MapAccess<'de> for AccessWrapper
         &mut self,
         seed: V,
     ) -> error::Result<V::Value> {
-        seed.deserialize(&mut self.0 .0)
+        self.0 .0.at_key(&mut self.1, |d| seed.deserialize(d))
     }
 }
 
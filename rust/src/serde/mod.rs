@@ -41,11 +41,23 @@ pub mod error {
 pub mod de;
 pub mod ser;
 
+#[cfg(feature = "alloc")]
+mod any;
+#[cfg(feature = "alloc")]
+pub use any::*;
+mod spanned;
+pub use spanned::*;
+
+#[cfg(feature = "std")]
+mod config;
+#[cfg(feature = "std")]
+pub use config::*;
+
 /// Document layout descriptor.
 ///
 /// Added in 1.2.0.
 #[non_exhaustive]
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum DocLayout {
     Plain,
     Root,
@@ -57,6 +69,7 @@ impl DocLayout {
         &self,
         iterator: &mut dyn Iterator<Item = DatumResult<DatumToken<B>>>,
     ) -> error::Result<V> {
+        datum_span!(_span, "datum_deserialize", layout = ?self);
         match self {
             Self::Plain => {
                 let mut it = de::PlainDeserializer::from_iterator(iterator);
@@ -79,6 +92,7 @@ impl DocLayout {
         w: &mut dyn Write,
         style: ser::Style,
     ) -> error::Result<()> {
+        datum_span!(_span, "datum_serialize", layout = ?self);
         match self {
             Self::Plain => {
                 let mut it = ser::PlainSerializer::new(w, style);
@@ -99,10 +113,74 @@ impl DocLayout {
     ) -> error::Result<V> {
         use crate::{datum_char_to_token_pipeline, IntoViaDatumPipe};
 
+        datum_span!(_span, "datum_deserialize_str", bytes = text.len());
         let mut token_iterator = text.chars().via_datum_pipe(datum_char_to_token_pipeline());
         self.deserialize_tokens(&mut token_iterator)
     }
 
+    /// Deserialize from an already-parsed value, i.e. a subtree of a larger document.
+    ///
+    /// Error offsets are token indices within the value.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn deserialize_value<'a, V: Deserialize<'a>>(
+        &self,
+        value: &crate::DatumValue,
+    ) -> error::Result<V> {
+        self.deserialize_values(core::slice::from_ref(value))
+    }
+
+    /// Deserialize from a sequence of already-parsed values (i.e. a [crate::DatumDocument]).
+    ///
+    /// Error offsets are token indices within the values.
+    ///
+    /// _Added in 1.3.0._
+    #[cfg(feature = "alloc")]
+    pub fn deserialize_values<'a, V: Deserialize<'a>>(
+        &self,
+        values: &[crate::DatumValue],
+    ) -> error::Result<V> {
+        datum_span!(_span, "datum_deserialize_values", values = values.len());
+        let mut token_iterator = crate::DatumValueTokens::new(values).map(Ok);
+        self.deserialize_tokens(&mut token_iterator)
+    }
+
+    /// Deserialize from a document written by any supported version of a format, by first migrating it with a [crate::DatumMigrator].
+    /// The header isn't deserialized. The migrated document is left in `doc`, for writing back if wanted.
+    ///
+    /// Error offsets are token indices within the document body.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumAtom, DatumDocument, DatumMigrator, DatumValue};
+    /// use datum::serde::DocLayout;
+    /// use serde::Deserialize;
+    /// #[derive(Deserialize, PartialEq, Debug)]
+    /// struct Save {
+    ///     name: String,
+    ///     level: i32,
+    /// }
+    /// // version 1 had no level
+    /// let migrator = DatumMigrator::new("example-save", 1).step(|doc| {
+    ///     doc.push(DatumValue::Atom(DatumAtom::Symbol("level".to_string())));
+    ///     doc.push(DatumValue::Atom(DatumAtom::Integer(1)));
+    ///     Ok(())
+    /// });
+    /// let mut doc: DatumDocument = "(example-save 1) name \"old\"".parse().unwrap();
+    /// let save: Save = DocLayout::Root.deserialize_migrated(&mut doc, &migrator).unwrap();
+    /// assert_eq!(save, Save { name: "old".to_string(), level: 1 });
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn deserialize_migrated<'a, V: Deserialize<'a>>(
+        &self,
+        doc: &mut crate::DatumDocument,
+        migrator: &crate::DatumMigrator,
+    ) -> error::Result<V> {
+        migrator.migrate(doc).map_err(error::error_from_datum)?;
+        self.deserialize_values(&doc[1..])
+    }
+
     /// Serialize to a [alloc::string::String].
     #[cfg(feature = "alloc")]
     pub fn serialize_to_string<V: Serialize>(
@@ -110,20 +188,39 @@ impl DocLayout {
         v: &V,
         style: ser::Style,
     ) -> error::Result<alloc::string::String> {
+        datum_span!(
+            span,
+            "datum_serialize_to_string",
+            bytes = tracing::field::Empty
+        );
         let mut res = alloc::string::String::new();
         self.serialize_to(v, &mut res, style)?;
+        datum_span_record!(span, "bytes", res.len());
         Ok(res)
     }
 
     /// Deserialize from a file. _Beware: Allocates room for the whole file. Completely ignores trailing values._
+    ///
+    /// Since 1.3.0, error messages start with the file's path.
     #[cfg(feature = "std")]
     pub fn deserialize_file<'a, V: Deserialize<'a>, P: AsRef<std::path::Path>>(
         &self,
         path: P,
     ) -> error::Result<V> {
         use serde::de::Error;
-        let file = std::fs::read_to_string(path).map_err(|e| error::Error::custom(e))?;
-        self.deserialize_str(file)
+        let path = path.as_ref();
+        datum_span!(
+            span,
+            "datum_deserialize_file",
+            path = %path.display(),
+            bytes = tracing::field::Empty
+        );
+        let named = |e: &dyn core::fmt::Display| {
+            error::Error::custom(std::format!("{}: {}", path.display(), e))
+        };
+        let file = std::fs::read_to_string(path).map_err(|e| named(&e))?;
+        datum_span_record!(span, "bytes", file.len());
+        self.deserialize_str(file).map_err(|e| named(&e))
     }
 }
 
@@ -0,0 +1,202 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+use serde::de::Error;
+use serde::ser::{SerializeStruct, SerializeStructVariant};
+
+#[cfg(feature = "alloc")]
+use crate::DatumWriterState;
+use crate::{DatumAtom, DatumToken};
+
+use crate::serde::error;
+
+use super::PlainSerializer;
+
+/// The order struct fields are written in.
+///
+/// Reordering fields (anything but [FieldOrder::Declaration]) requires the `alloc` feature, as each field is written to a buffer first.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::serde::ser::{FieldOrder, PlainSerializer, Style};
+/// use serde::Serialize;
+/// #[derive(Serialize)]
+/// struct Server {
+///     port: u16,
+///     host: &'static str,
+///     debug: bool,
+/// }
+/// let server = Server { port: 80, host: "example", debug: false };
+/// let mut text = String::new();
+/// let mut ser = PlainSerializer::new(&mut text, Style::SpacingOnly).field_order(FieldOrder::Priority(&["host"]));
+/// server.serialize(&mut ser).unwrap();
+/// assert_eq!(text, "(host \"example\" port 80 debug #f)");
+/// ```
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FieldOrder {
+    /// The order the struct's `Serialize` implementation gives them in; for derived implementations, declaration order. This is the default.
+    Declaration,
+    /// Alphabetical order (by code point).
+    Alphabetical,
+    /// Fields named in the list come first, in the order of the list. The rest follow in declaration order.
+    Priority(&'static [&'static str]),
+}
+
+// `#[default]` on enum variants is newer than the MSRV.
+#[allow(clippy::derivable_impls)]
+impl Default for FieldOrder {
+    fn default() -> Self {
+        Self::Declaration
+    }
+}
+
+impl FieldOrder {
+    /// Sorts buffered fields into this order.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn sort<T>(&self, fields: &mut [(&'static str, T)]) {
+        match self {
+            Self::Declaration => {}
+            Self::Alphabetical => fields.sort_by(|a, b| a.0.cmp(b.0)),
+            Self::Priority(list) => fields.sort_by_key(|v| {
+                list.iter()
+                    .position(|name| *name == v.0)
+                    .unwrap_or(list.len())
+            }),
+        }
+    }
+}
+
+/// Which serializer a [StructSerializer] is writing for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum StructKind {
+    /// [PlainSerializer]; the struct is a list.
+    Plain,
+    /// Inside a newtype variant; the struct is flattened into the variant's list.
+    Newtype,
+    /// [super::RootSerializer]; the struct is the document.
+    Root,
+}
+
+/// Writes structs and struct variants for [PlainSerializer] and [super::RootSerializer], putting fields in [PlainSerializer::field_order].
+///
+/// _Added in 1.3.0._
+pub struct StructSerializer<'a, 'write> {
+    ser: &'a mut PlainSerializer<'write>,
+    kind: StructKind,
+    /// If the struct is written inline (see [PlainSerializer::inline_limit]).
+    inline: bool,
+    /// Buffered fields: Key, text, and writer state after the text.
+    #[cfg(feature = "alloc")]
+    fields: Vec<(&'static str, (String, DatumWriterState))>,
+}
+
+impl<'a, 'write> StructSerializer<'a, 'write> {
+    pub(crate) fn new(
+        ser: &'a mut PlainSerializer<'write>,
+        kind: StructKind,
+        inline: bool,
+    ) -> Self {
+        Self {
+            ser,
+            kind,
+            inline,
+            #[cfg(feature = "alloc")]
+            fields: Vec::new(),
+        }
+    }
+
+    fn write_field<T: serde::Serialize + ?Sized>(
+        ser: &mut PlainSerializer,
+        key: &'static str,
+        value: &T,
+    ) -> error::Result<()> {
+        let b: DatumAtom<&str> = DatumAtom::Symbol(key);
+        ser.write_atom(b)?;
+        value.serialize(&mut *ser)?;
+        ser.fmt_seq_newline()
+    }
+}
+
+impl SerializeStruct for StructSerializer<'_, '_> {
+    type Ok = ();
+    type Error = error::Error;
+    fn serialize_field<T: serde::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        if self.ser.field_order == FieldOrder::Declaration {
+            return Self::write_field(self.ser, key, value);
+        }
+        #[cfg(feature = "alloc")]
+        {
+            // written as if it were the first field, and spaced properly when the fields are put in order
+            let mut text = String::new();
+            let mut field = PlainSerializer::new(&mut text, self.ser.style);
+            field.writer = self.ser.writer;
+            field.writer.state = DatumWriterState::None;
+            field.variant_strings = self.ser.variant_strings;
+            field.newtype_names = self.ser.newtype_names;
+            field.field_order = self.ser.field_order;
+            field.inline_limit = self.ser.inline_limit;
+            Self::write_field(&mut field, key, value)?;
+            let state = field.writer.state;
+            self.fields.push((key, (text, state)));
+            Ok(())
+        }
+        #[cfg(not(feature = "alloc"))]
+        Err(error::Error::custom(
+            "reordering fields requires the alloc feature",
+        ))
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "alloc")]
+        {
+            let mut fields = self.fields;
+            self.ser.field_order.sort(&mut fields);
+            for (_, (text, state)) in fields {
+                self.ser
+                    .writer
+                    .emit_whitespace(self.ser.target, false)
+                    .map_err(error::Error::custom)?;
+                self.ser
+                    .target
+                    .write_str(&text)
+                    .map_err(error::Error::custom)?;
+                self.ser.writer.state = state;
+            }
+        }
+        match self.kind {
+            StructKind::Plain => {
+                self.ser.fmt_close_block(self.inline)?;
+                let b: DatumToken<&str> = DatumToken::ListEnd(0);
+                self.ser.write_token(b)
+            }
+            StructKind::Newtype => self.ser.fmt_close_block(self.inline),
+            StructKind::Root => Ok(()),
+        }
+    }
+}
+
+impl SerializeStructVariant for StructSerializer<'_, '_> {
+    type Ok = ();
+    type Error = error::Error;
+    fn serialize_field<T: serde::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        SerializeStruct::serialize_field(self, key, value)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeStruct::end(self)
+    }
+}
@@ -17,13 +17,6 @@ macro_rules! serializer_invariants {
                 "Byte arrays cannot be serialized at present.",
             ))
         }
-        fn serialize_newtype_struct<T: serde::Serialize + ?Sized>(
-            self,
-            _name: &'static str,
-            value: &T,
-        ) -> Result<Self::Ok, Self::Error> {
-            value.serialize(self)
-        }
         fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
             self.serialize_f64(v as f64)
         }
@@ -76,7 +69,7 @@ macro_rules! serializer_invariants {
             _variant_index: u32,
             variant: &'static str,
         ) -> Result<Self::Ok, Self::Error> {
-            self.write_atom(DatumAtom::Symbol(variant))
+            self.write_variant(variant)
         }
         fn serialize_some<T: serde::Serialize + ?Sized>(
             self,
@@ -97,5 +90,11 @@ macro_rules! serializer_invariants {
 
 mod serializer;
 pub use serializer::*;
+mod fields;
+pub use fields::*;
 mod seqmaproot;
 pub use seqmaproot::*;
+#[cfg(feature = "alloc")]
+mod tokens;
+#[cfg(feature = "alloc")]
+pub use tokens::*;
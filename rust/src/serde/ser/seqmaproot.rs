@@ -9,8 +9,7 @@ use core::fmt::Write;
 
 use serde::de::Error;
 use serde::ser::{
-    SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
-    SerializeTupleStruct, SerializeTupleVariant,
+    SerializeMap, SerializeSeq, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
 };
 use serde::Serializer;
 
@@ -18,7 +17,29 @@ use crate::DatumAtom;
 
 use crate::serde::error;
 
-use super::{PlainSerializer, Style};
+use super::{PlainSerializer, StructKind, StructSerializer, Style};
+
+/// What [RootSerializer] does with `None`, which has no unambiguous representation at the root.
+///
+/// _Added in 1.3.0._
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RootNone {
+    /// It's an error. This is the default.
+    Error,
+    /// Nothing is written. [crate::serde::de::RootDeserializer] reads an empty document (or the end of one) as `None`, so this round-trips.
+    Omit,
+    /// `#nil` is written. [crate::serde::de::RootDeserializer] reads this as `Some`, so this is only for other readers.
+    Nil,
+}
+
+// `#[default]` on enum variants is newer than the MSRV.
+#[allow(clippy::derivable_impls)]
+impl Default for RootNone {
+    fn default() -> Self {
+        Self::Error
+    }
+}
 
 /// [RootSerializer] serializes a document as a root-level sequence or map.
 /// This is intended to match [crate::serde::de::RootDeserializer].
@@ -34,21 +55,78 @@ impl<'write> RootSerializer<'write> {
         Self(PlainSerializer::new(target, style))
     }
 
+    /// Sets what happens to `None` at the root (see [RootNone]).
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::serde::ser::{RootNone, RootSerializer, Style};
+    /// use serde::Serialize;
+    /// let mut text = String::new();
+    /// let value: Option<(i32, i32)> = None;
+    /// value.serialize(&mut RootSerializer::new(&mut text, Style::SpacingOnly).root_none(RootNone::Omit)).unwrap();
+    /// assert_eq!(text, "");
+    /// ```
+    pub fn root_none(mut self, policy: RootNone) -> Self {
+        self.0.root_none = policy;
+        self
+    }
+
+    /// Sets the line ending, for [Style::Indented] (see [crate::DatumWriter::with_line_ending]).
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::DatumLineEnding;
+    /// use datum::serde::ser::{RootSerializer, Style};
+    /// use serde::Serialize;
+    /// let mut text = String::new();
+    /// let value = vec![(1, 2), (3, 4)];
+    /// value.serialize(&mut RootSerializer::new(&mut text, Style::Indented).line_ending(DatumLineEnding::CrLf)).unwrap();
+    /// assert_eq!(text, "(1 2)\r\n(3 4)\r\n");
+    /// ```
+    pub fn line_ending(mut self, line_ending: crate::DatumLineEnding) -> Self {
+        self.0.writer = self.0.writer.with_line_ending(line_ending);
+        self
+    }
+
+    /// Sets how small a seq, map or struct must be to be written on one line, for [Style::Indented] (see [PlainSerializer::inline_limit]).
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::serde::ser::{RootSerializer, Style};
+    /// use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct Point {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    /// let mut text = String::new();
+    /// let value = vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }];
+    /// value.serialize(&mut RootSerializer::new(&mut text, Style::Indented).inline_limit(2)).unwrap();
+    /// assert_eq!(text, "(x 1 y 2)\n(x 3 y 4)\n");
+    /// ```
+    pub fn inline_limit(self, inline_limit: usize) -> Self {
+        Self(self.0.inline_limit(inline_limit))
+    }
+
     fn write_atom(&mut self, atom: DatumAtom<&str>) -> error::Result<()> {
         self.0.write_atom(atom)?;
         self.0.fmt_seq_newline()
     }
+
+    fn write_variant(&mut self, variant: &str) -> error::Result<()> {
+        self.write_atom(self.0.variant_atom(variant))
+    }
 }
 
-impl<'a> Serializer for &'a mut RootSerializer<'_> {
+impl<'a, 'write> Serializer for &'a mut RootSerializer<'write> {
     type Ok = ();
     type Error = error::Error;
     type SerializeSeq = Self;
     type SerializeTuple = Self;
     type SerializeTupleVariant = Self;
     type SerializeMap = Self;
-    type SerializeStruct = Self;
-    type SerializeStructVariant = Self;
+    type SerializeStruct = StructSerializer<'a, 'write>;
+    type SerializeStructVariant = StructSerializer<'a, 'write>;
 
     // -- Trivial --
     fn collect_str<T: core::fmt::Display + ?Sized>(
@@ -64,9 +142,29 @@ impl<'a> Serializer for &'a mut RootSerializer<'_> {
         self.0.fmt_seq_newline()
     }
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
-        Err(error::Error::custom(
-            "not a supported type for datum's RootSerializer",
-        ))
+        match self.0.root_none {
+            RootNone::Omit => Ok(()),
+            RootNone::Nil => self.write_atom(DatumAtom::Nil),
+            _ => Err(error::Error::custom(
+                "not a supported type for datum's RootSerializer",
+            )),
+        }
+    }
+    fn serialize_newtype_struct<T: serde::Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "alloc")]
+        if name == crate::serde::any::DATUM_ANY {
+            return Err(error::Error::custom(
+                "not a supported type for datum's RootSerializer",
+            ));
+        }
+        if self.0.newtype_names {
+            self.write_atom(DatumAtom::Symbol(name))?;
+        }
+        value.serialize(self)
     }
     // -- Enum --
     fn serialize_newtype_variant<T: serde::Serialize + ?Sized>(
@@ -76,7 +174,7 @@ impl<'a> Serializer for &'a mut RootSerializer<'_> {
         variant: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
-        self.write_atom(DatumAtom::Symbol(variant))?;
+        self.write_variant(variant)?;
         value.serialize(self)
     }
     fn serialize_tuple_variant(
@@ -86,7 +184,7 @@ impl<'a> Serializer for &'a mut RootSerializer<'_> {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        self.write_atom(DatumAtom::Symbol(variant))?;
+        self.write_variant(variant)?;
         Ok(self)
     }
     fn serialize_struct_variant(
@@ -96,8 +194,8 @@ impl<'a> Serializer for &'a mut RootSerializer<'_> {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        self.write_atom(DatumAtom::Symbol(variant))?;
-        Ok(self)
+        self.write_variant(variant)?;
+        Ok(StructSerializer::new(&mut self.0, StructKind::Root, false))
     }
     // -- Struct --
     fn serialize_struct(
@@ -105,7 +203,7 @@ impl<'a> Serializer for &'a mut RootSerializer<'_> {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Ok(self)
+        Ok(StructSerializer::new(&mut self.0, StructKind::Root, false))
     }
     // -- Seq/Map --
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
@@ -200,36 +298,3 @@ impl<'a> SerializeMap for &'a mut RootSerializer<'_> {
         Ok(())
     }
 }
-
-impl<'a> SerializeStruct for &'a mut RootSerializer<'_> {
-    type Ok = ();
-    type Error = error::Error;
-    fn serialize_field<T: serde::Serialize + ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error> {
-        let b: DatumAtom<&str> = DatumAtom::Symbol(key);
-        self.0.write_atom(b)?;
-        value.serialize(&mut self.0)?;
-        self.0.fmt_seq_newline()
-    }
-    fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(())
-    }
-}
-
-impl<'a> SerializeStructVariant for &'a mut RootSerializer<'_> {
-    type Ok = ();
-    type Error = error::Error;
-    fn serialize_field<T: serde::Serialize + ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error> {
-        SerializeStruct::serialize_field(self, key, value)
-    }
-    fn end(self) -> Result<Self::Ok, Self::Error> {
-        SerializeStruct::end(self)
-    }
-}
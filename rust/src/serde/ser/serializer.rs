@@ -9,8 +9,7 @@ use core::fmt::Write;
 
 use serde::de::Error;
 use serde::ser::{
-    SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
-    SerializeTupleStruct, SerializeTupleVariant,
+    SerializeMap, SerializeSeq, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
 };
 use serde::Serializer;
 
@@ -21,6 +20,8 @@ use crate::{
 
 use crate::serde::error;
 
+use super::{StructKind, StructSerializer};
+
 /// Controls how the serializer does indentation and spacing.
 ///
 /// _Added in 1.1.0._
@@ -36,13 +37,24 @@ pub enum Style {
 }
 
 /// Similar to [crate::serde::de::PlainDeserializer], this represents a plain serializer without any funny business.
-/// Unlike that struct, full access is allowed, as there isn't much in the way of additional state.
+/// Unlike that struct, the target, style and writer are public, as there isn't much in the way of additional state.
+/// Other options are set with the builder methods (i.e. [PlainSerializer::field_order]), so start from [PlainSerializer::new].
 ///
 /// _Added in 1.1.0._
 pub struct PlainSerializer<'write> {
     pub target: &'write mut dyn Write,
     pub style: Style,
     pub writer: DatumWriter,
+    /// See [PlainSerializer::variant_strings].
+    pub(crate) variant_strings: bool,
+    /// See [PlainSerializer::newtype_names].
+    pub(crate) newtype_names: bool,
+    /// What [super::RootSerializer] does with `None` at the root; see [super::RootSerializer::root_none].
+    pub(crate) root_none: super::RootNone,
+    /// See [PlainSerializer::field_order].
+    pub(crate) field_order: super::FieldOrder,
+    /// See [PlainSerializer::inline_limit].
+    pub(crate) inline_limit: usize,
 }
 
 impl<'write> PlainSerializer<'write> {
@@ -52,8 +64,73 @@ impl<'write> PlainSerializer<'write> {
             target,
             style,
             writer: DatumWriter::default(),
+            variant_strings: false,
+            newtype_names: false,
+            root_none: super::RootNone::default(),
+            field_order: super::FieldOrder::default(),
+            inline_limit: 0,
         }
     }
+    /// Sets if enum variant names are written as strings, rather than symbols (they're symbols by default).
+    /// This is for documents read by other tools; see also [crate::serde::de::PlainDeserializer::variant_strings].
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::serde::ser::{PlainSerializer, Style};
+    /// use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// enum Mode {
+    ///     Fast,
+    /// }
+    /// let mut text = String::new();
+    /// Mode::Fast.serialize(&mut PlainSerializer::new(&mut text, Style::SpacingOnly).variant_strings(true)).unwrap();
+    /// assert_eq!(text, "\"Fast\"");
+    /// ```
+    pub fn variant_strings(mut self, variant_strings: bool) -> Self {
+        self.variant_strings = variant_strings;
+        self
+    }
+    /// Sets if newtype structs are written with their name, as `(Name value)`, rather than transparently (they're transparent by default).
+    /// At the root, [super::RootSerializer] writes the name without a list.
+    /// See also [crate::serde::de::PlainDeserializer::newtype_names].
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::serde::ser::{PlainSerializer, Style};
+    /// use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct Meters(i32);
+    /// let mut text = String::new();
+    /// (Meters(1), 2).serialize(&mut PlainSerializer::new(&mut text, Style::SpacingOnly).newtype_names(true)).unwrap();
+    /// assert_eq!(text, "((Meters 1) 2)");
+    /// ```
+    pub fn newtype_names(mut self, newtype_names: bool) -> Self {
+        self.newtype_names = newtype_names;
+        self
+    }
+    /// Sets the order struct fields are written in; see [super::FieldOrder].
+    ///
+    /// _Added in 1.3.0._
+    pub fn field_order(mut self, field_order: super::FieldOrder) -> Self {
+        self.field_order = field_order;
+        self
+    }
+    /// Sets how small a seq, map, struct or struct variant must be to be written on one line, for [Style::Indented].
+    /// Everything inside such a container is written on that line too; while it's being written, [PlainSerializer::style] is [Style::SpacingOnly].
+    /// Only containers with a known length count; this is `0` (off) by default.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::serde::ser::{PlainSerializer, Style};
+    /// use serde::Serialize;
+    /// let mut text = String::new();
+    /// vec![(1, 2)].serialize(&mut PlainSerializer::new(&mut text, Style::Indented).inline_limit(1)).unwrap();
+    /// assert_eq!(text, "((1 2))");
+    /// ```
+    pub fn inline_limit(mut self, inline_limit: usize) -> Self {
+        self.inline_limit = inline_limit;
+        self
+    }
     pub(crate) fn write_token(&mut self, token: DatumToken<&str>) -> error::Result<()> {
         if self.style == Style::Minified {
             let kind = token.token_type();
@@ -83,21 +160,43 @@ impl<'write> PlainSerializer<'write> {
         }
         Ok(())
     }
+    /// Writes an enum variant name.
+    pub(crate) fn write_variant(&mut self, variant: &str) -> error::Result<()> {
+        self.write_atom(self.variant_atom(variant))
+    }
+    /// The atom an enum variant name is written as.
+    pub(crate) fn variant_atom<'a>(&self, variant: &'a str) -> DatumAtom<&'a str> {
+        if self.variant_strings {
+            DatumAtom::String(variant)
+        } else {
+            DatumAtom::Symbol(variant)
+        }
+    }
     /// Indent control: Opened block
-    /// Run after starting a list.
-    pub(crate) fn fmt_open_block(&mut self) -> error::Result<()> {
+    /// Run after starting a list, with the number of elements if known.
+    /// Returns if the block is written inline, which is passed to [PlainSerializer::fmt_close_block].
+    pub(crate) fn fmt_open_block(&mut self, len: Option<usize>) -> error::Result<bool> {
         if self.style == Style::Indented {
+            if let Some(len) = len {
+                if self.inline_limit != 0 && len <= self.inline_limit {
+                    // everything inside an inline block is inline too
+                    self.style = Style::SpacingOnly;
+                    return Ok(true);
+                }
+            }
             self.writer.indent += 1;
             self.writer
                 .write_newline(self.target)
                 .map_err(|e| error::Error::custom(e))?;
         }
-        Ok(())
+        Ok(false)
     }
     /// Indent control: Close block
     /// Run before ending a list.
-    pub(crate) fn fmt_close_block(&mut self) -> error::Result<()> {
-        if self.style == Style::Indented {
+    pub(crate) fn fmt_close_block(&mut self, inline: bool) -> error::Result<()> {
+        if inline {
+            self.style = Style::Indented;
+        } else if self.style == Style::Indented {
             self.writer.indent -= 1;
         }
         Ok(())
@@ -114,15 +213,15 @@ impl<'write> PlainSerializer<'write> {
     }
 }
 
-impl<'a> Serializer for &'a mut PlainSerializer<'_> {
+impl<'a, 'write> Serializer for &'a mut PlainSerializer<'write> {
     type Ok = ();
     type Error = error::Error;
-    type SerializeSeq = Self;
+    type SerializeSeq = BlockSerializer<'a, 'write>;
     type SerializeTuple = Self;
     type SerializeTupleVariant = Self;
-    type SerializeMap = Self;
-    type SerializeStruct = Self;
-    type SerializeStructVariant = Self;
+    type SerializeMap = BlockSerializer<'a, 'write>;
+    type SerializeStruct = StructSerializer<'a, 'write>;
+    type SerializeStructVariant = StructSerializer<'a, 'write>;
 
     // -- Option/Unit --
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
@@ -135,6 +234,28 @@ impl<'a> Serializer for &'a mut PlainSerializer<'_> {
         b = DatumToken::ListEnd(0);
         self.write_token(b)
     }
+    fn serialize_newtype_struct<T: serde::Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "alloc")]
+        if name == crate::serde::any::DATUM_ANY {
+            for token in crate::serde::any::datum_any_tokens(value)? {
+                self.write_token(token.as_borrowed())?;
+            }
+            return Ok(());
+        }
+        if !self.newtype_names {
+            return value.serialize(self);
+        }
+        let mut b: DatumToken<&str> = DatumToken::ListStart(0);
+        self.write_token(b)?;
+        self.write_atom(DatumAtom::Symbol(name))?;
+        value.serialize(&mut *self)?;
+        b = DatumToken::ListEnd(0);
+        self.write_token(b)
+    }
     // -- Enum --
     fn serialize_newtype_variant<T: serde::Serialize + ?Sized>(
         self,
@@ -145,7 +266,7 @@ impl<'a> Serializer for &'a mut PlainSerializer<'_> {
     ) -> Result<Self::Ok, Self::Error> {
         let mut b: DatumToken<&str> = DatumToken::ListStart(0);
         self.write_token(b)?;
-        self.write_atom(DatumAtom::Symbol(variant))?;
+        self.write_variant(variant)?;
         value.serialize(&mut NewtypeVariantSerializer(self))?;
         b = DatumToken::ListEnd(0);
         self.write_token(b)
@@ -159,7 +280,7 @@ impl<'a> Serializer for &'a mut PlainSerializer<'_> {
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
         let b: DatumToken<&str> = DatumToken::ListStart(0);
         self.write_token(b)?;
-        self.write_atom(DatumAtom::Symbol(variant))?;
+        self.write_variant(variant)?;
         Ok(self)
     }
     fn serialize_struct_variant(
@@ -167,42 +288,42 @@ impl<'a> Serializer for &'a mut PlainSerializer<'_> {
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
         let b: DatumToken<&str> = DatumToken::ListStart(0);
         self.write_token(b)?;
-        self.write_atom(DatumAtom::Symbol(variant))?;
-        self.fmt_open_block()?;
-        Ok(self)
+        self.write_variant(variant)?;
+        let inline = self.fmt_open_block(Some(len))?;
+        Ok(StructSerializer::new(self, StructKind::Plain, inline))
     }
     // -- Struct --
     fn serialize_struct(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
         let b: DatumToken<&str> = DatumToken::ListStart(0);
         self.write_token(b)?;
-        self.fmt_open_block()?;
-        Ok(self)
+        let inline = self.fmt_open_block(Some(len))?;
+        Ok(StructSerializer::new(self, StructKind::Plain, inline))
     }
     // -- Seq/Map --
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let b: DatumToken<&str> = DatumToken::ListStart(0);
         self.write_token(b)?;
-        self.fmt_open_block()?;
-        Ok(self)
+        let inline = self.fmt_open_block(len)?;
+        Ok(BlockSerializer::new(self, StructKind::Plain, inline))
     }
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
         let b: DatumToken<&str> = DatumToken::ListStart(0);
         self.write_token(b)?;
         Ok(self)
     }
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         let b: DatumToken<&str> = DatumToken::ListStart(0);
         self.write_token(b)?;
-        self.fmt_open_block()?;
-        Ok(self)
+        let inline = self.fmt_open_block(len)?;
+        Ok(BlockSerializer::new(self, StructKind::Plain, inline))
     }
     // -- String --
     fn collect_str<T: core::fmt::Display + ?Sized>(
@@ -223,25 +344,6 @@ impl<'a> Serializer for &'a mut PlainSerializer<'_> {
     serializer_invariants!();
 }
 
-// -- Seqlikes --
-
-impl<'a> SerializeSeq for &'a mut PlainSerializer<'_> {
-    type Ok = ();
-    type Error = error::Error;
-    fn serialize_element<T: serde::Serialize + ?Sized>(
-        &mut self,
-        value: &T,
-    ) -> Result<(), Self::Error> {
-        value.serialize(&mut **self)?;
-        self.fmt_seq_newline()
-    }
-    fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.fmt_close_block()?;
-        let b: DatumToken<&str> = DatumToken::ListEnd(0);
-        self.write_token(b)
-    }
-}
-
 // Tuples don't get indentation and per-element newlines.
 
 impl<'a> SerializeTuple for &'a mut PlainSerializer<'_> {
@@ -287,83 +389,31 @@ impl<'a> SerializeTupleVariant for &'a mut PlainSerializer<'_> {
     }
 }
 
-// -- Maplikes --
-
-impl<'a> SerializeMap for &'a mut PlainSerializer<'_> {
-    type Ok = ();
-    type Error = error::Error;
-    fn serialize_key<T: serde::Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
-        key.serialize(&mut **self)
-    }
-    fn serialize_value<T: serde::Serialize + ?Sized>(
-        &mut self,
-        value: &T,
-    ) -> Result<(), Self::Error> {
-        value.serialize(&mut **self)?;
-        self.fmt_seq_newline()
-    }
-    fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.fmt_close_block()?;
-        let b: DatumToken<&str> = DatumToken::ListEnd(0);
-        self.write_token(b)
-    }
-}
-
-impl<'a> SerializeStruct for &'a mut PlainSerializer<'_> {
-    type Ok = ();
-    type Error = error::Error;
-    fn serialize_field<T: serde::Serialize + ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error> {
-        let b: DatumAtom<&str> = DatumAtom::Symbol(key);
-        self.write_atom(b)?;
-        value.serialize(&mut **self)?;
-        self.fmt_seq_newline()
-    }
-    fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.fmt_close_block()?;
-        let b: DatumToken<&str> = DatumToken::ListEnd(0);
-        self.write_token(b)
-    }
-}
-
-impl<'a> SerializeStructVariant for &'a mut PlainSerializer<'_> {
-    type Ok = ();
-    type Error = error::Error;
-    fn serialize_field<T: serde::Serialize + ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error> {
-        SerializeStruct::serialize_field(self, key, value)
-    }
-    fn end(self) -> Result<Self::Ok, Self::Error> {
-        SerializeStruct::end(self)
-    }
-}
-
 // -- Newtype Variant --
 
 /// NewtypeVariantSerializer writes the value inside a newtype variant.
-struct NewtypeVariantSerializer<'ser, 'write>(&'ser mut PlainSerializer<'write>);
+pub(crate) struct NewtypeVariantSerializer<'ser, 'write>(
+    pub(crate) &'ser mut PlainSerializer<'write>,
+);
 
 impl NewtypeVariantSerializer<'_, '_> {
     fn write_atom(&mut self, token: DatumAtom<&str>) -> error::Result<()> {
         self.0.write_atom(token)
     }
+    fn write_variant(&mut self, variant: &str) -> error::Result<()> {
+        self.0.write_variant(variant)
+    }
 }
 
-impl<'a> Serializer for &'a mut NewtypeVariantSerializer<'_, '_> {
+impl<'a, 'write> Serializer for &'a mut NewtypeVariantSerializer<'_, 'write> {
     type Ok = ();
     type Error = error::Error;
-    type SerializeSeq = Self;
+    type SerializeSeq = BlockSerializer<'a, 'write>;
     type SerializeTuple = Self;
     type SerializeTupleVariant = Self;
-    type SerializeMap = Self;
-    type SerializeStruct = Self;
-    type SerializeStructVariant = Self;
+    type SerializeMap = BlockSerializer<'a, 'write>;
+    type SerializeStruct = StructSerializer<'a, 'write>;
+    type SerializeStructVariant = StructSerializer<'a, 'write>;
 
     // -- Forward --
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
@@ -378,6 +428,22 @@ impl<'a> Serializer for &'a mut NewtypeVariantSerializer<'_, '_> {
     ) -> Result<Self::Ok, Self::Error> {
         self.0.collect_str(value)
     }
+    fn serialize_newtype_struct<T: serde::Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        #[cfg(feature = "alloc")]
+        if name == crate::serde::any::DATUM_ANY {
+            return self.0.serialize_newtype_struct(name, value);
+        }
+        if self.0.newtype_names {
+            // the name needs its own list to be distinguishable
+            self.0.serialize_newtype_struct(name, value)
+        } else {
+            value.serialize(self)
+        }
+    }
     // -- Enum --
     fn serialize_newtype_variant<T: serde::Serialize + ?Sized>(
         self,
@@ -386,7 +452,7 @@ impl<'a> Serializer for &'a mut NewtypeVariantSerializer<'_, '_> {
         variant: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
-        self.write_atom(DatumAtom::Symbol(variant))?;
+        self.write_variant(variant)?;
         // this itself is a newtype variant, so keep the chain going
         value.serialize(self)
     }
@@ -397,7 +463,7 @@ impl<'a> Serializer for &'a mut NewtypeVariantSerializer<'_, '_> {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        self.write_atom(DatumAtom::Symbol(variant))?;
+        self.write_variant(variant)?;
         Ok(self)
     }
     fn serialize_struct_variant(
@@ -405,53 +471,36 @@ impl<'a> Serializer for &'a mut NewtypeVariantSerializer<'_, '_> {
         _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        self.write_atom(DatumAtom::Symbol(variant))?;
-        self.0.fmt_open_block()?;
-        Ok(self)
+        self.write_variant(variant)?;
+        let inline = self.0.fmt_open_block(Some(len))?;
+        Ok(StructSerializer::new(self.0, StructKind::Newtype, inline))
     }
     // -- Struct --
     fn serialize_struct(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        self.0.fmt_open_block()?;
-        Ok(self)
+        let inline = self.0.fmt_open_block(Some(len))?;
+        Ok(StructSerializer::new(self.0, StructKind::Newtype, inline))
     }
     // -- Seq/Map --
-    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        self.0.fmt_open_block()?;
-        Ok(self)
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let inline = self.0.fmt_open_block(len)?;
+        Ok(BlockSerializer::new(self.0, StructKind::Newtype, inline))
     }
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
         Ok(self)
     }
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        self.0.fmt_open_block()?;
-        Ok(self)
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let inline = self.0.fmt_open_block(len)?;
+        Ok(BlockSerializer::new(self.0, StructKind::Newtype, inline))
     }
     serializer_invariants!();
 }
 
-// -- Seqlikes --
-
-impl<'a> SerializeSeq for &'a mut NewtypeVariantSerializer<'_, '_> {
-    type Ok = ();
-    type Error = error::Error;
-    fn serialize_element<T: serde::Serialize + ?Sized>(
-        &mut self,
-        value: &T,
-    ) -> Result<(), Self::Error> {
-        value.serialize(&mut **self)?;
-        self.0.fmt_seq_newline()
-    }
-    fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.0.fmt_close_block()
-    }
-}
-
 // Tuples don't get indentation and per-element newlines.
 
 impl<'a> SerializeTuple for &'a mut NewtypeVariantSerializer<'_, '_> {
@@ -461,7 +510,7 @@ impl<'a> SerializeTuple for &'a mut NewtypeVariantSerializer<'_, '_> {
         &mut self,
         value: &T,
     ) -> Result<(), Self::Error> {
-        value.serialize(&mut **self)
+        value.serialize(&mut *self.0)
     }
     fn end(self) -> Result<Self::Ok, Self::Error> {
         Ok(())
@@ -496,55 +545,65 @@ impl<'a> SerializeTupleVariant for &'a mut NewtypeVariantSerializer<'_, '_> {
     }
 }
 
-// -- Maplikes --
+// -- Seq/Map --
 
-impl<'a> SerializeMap for &'a mut NewtypeVariantSerializer<'_, '_> {
-    type Ok = ();
-    type Error = error::Error;
-    fn serialize_key<T: serde::Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
-        key.serialize(&mut **self)
-    }
-    fn serialize_value<T: serde::Serialize + ?Sized>(
-        &mut self,
-        value: &T,
-    ) -> Result<(), Self::Error> {
-        value.serialize(&mut **self)?;
-        self.0.fmt_seq_newline()
-    }
-    fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.0.fmt_close_block()
+/// Writes seqs and maps for [PlainSerializer], remembering if they're written inline (see [PlainSerializer::inline_limit]).
+///
+/// _Added in 1.3.0._
+pub struct BlockSerializer<'a, 'write> {
+    ser: &'a mut PlainSerializer<'write>,
+    kind: StructKind,
+    inline: bool,
+}
+
+impl<'a, 'write> BlockSerializer<'a, 'write> {
+    pub(crate) fn new(
+        ser: &'a mut PlainSerializer<'write>,
+        kind: StructKind,
+        inline: bool,
+    ) -> Self {
+        Self { ser, kind, inline }
+    }
+    fn end_block(self) -> error::Result<()> {
+        self.ser.fmt_close_block(self.inline)?;
+        if self.kind == StructKind::Newtype {
+            // flattened into the variant's list
+            return Ok(());
+        }
+        let b: DatumToken<&str> = DatumToken::ListEnd(0);
+        self.ser.write_token(b)
     }
 }
 
-impl<'a> SerializeStruct for &'a mut NewtypeVariantSerializer<'_, '_> {
+impl SerializeSeq for BlockSerializer<'_, '_> {
     type Ok = ();
     type Error = error::Error;
-    fn serialize_field<T: serde::Serialize + ?Sized>(
+    fn serialize_element<T: serde::Serialize + ?Sized>(
         &mut self,
-        key: &'static str,
         value: &T,
     ) -> Result<(), Self::Error> {
-        let b: DatumAtom<&str> = DatumAtom::Symbol(key);
-        self.0.write_atom(b)?;
-        value.serialize(&mut **self)?;
-        self.0.fmt_seq_newline()
+        value.serialize(&mut *self.ser)?;
+        self.ser.fmt_seq_newline()
     }
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        self.0.fmt_close_block()
+        self.end_block()
     }
 }
 
-impl<'a> SerializeStructVariant for &'a mut NewtypeVariantSerializer<'_, '_> {
+impl SerializeMap for BlockSerializer<'_, '_> {
     type Ok = ();
     type Error = error::Error;
-    fn serialize_field<T: serde::Serialize + ?Sized>(
+    fn serialize_key<T: serde::Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        key.serialize(&mut *self.ser)
+    }
+    fn serialize_value<T: serde::Serialize + ?Sized>(
         &mut self,
-        key: &'static str,
         value: &T,
     ) -> Result<(), Self::Error> {
-        SerializeStruct::serialize_field(self, key, value)
+        value.serialize(&mut *self.ser)?;
+        self.ser.fmt_seq_newline()
     }
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        SerializeStruct::end(self)
+        self.end_block()
     }
 }
@@ -0,0 +1,464 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::de::Error;
+use serde::ser::{
+    SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::Serializer;
+
+use crate::{DatumAtom, DatumOffset, DatumResult, DatumToken};
+
+use crate::serde::error::{self, error_from_datum};
+
+use super::FieldOrder;
+
+/// A struct field's key and the tokens of it and its value, waiting to be put in order.
+type PendingField = (&'static str, Vec<DatumToken<String>>);
+
+/// How the current value is being written; mirrors which of the text serializers would be writing it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TokenSerializerMode {
+    /// [super::PlainSerializer]
+    Plain,
+    /// Inside a newtype variant; containers are flattened into the variant's list.
+    Newtype,
+    /// [super::RootSerializer]
+    Root,
+}
+
+/// [TokenSerializer] emits the tokens [super::PlainSerializer] (or [super::RootSerializer]) would write, rather than text.
+///
+/// This allows serialized data to be fed into token transforms or hashed without a round-trip through text.
+///
+/// Token offsets are token indices, as with [crate::DatumValueTokens].
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::DatumToken;
+/// use datum::serde::ser::TokenSerializer;
+/// use serde::Serialize;
+/// let mut tokens = Vec::new();
+/// let mut ser = TokenSerializer::new(|t: DatumToken<String>| {
+///     tokens.push(t.to_string());
+///     Ok(())
+/// });
+/// (1, "a").serialize(&mut ser).unwrap();
+/// assert_eq!(tokens, ["(", "1", "\"a\"", ")"]);
+/// ```
+pub struct TokenSerializer<F: FnMut(DatumToken<String>) -> DatumResult<()>> {
+    output: F,
+    offset: DatumOffset,
+    mode: TokenSerializerMode,
+    /// For each open container: The mode to return to, and if it has a list end to write.
+    stack: Vec<(TokenSerializerMode, bool)>,
+    /// For each open container: Fields waiting to be put in order.
+    fields: Vec<Vec<PendingField>>,
+    /// Tokens of fields being written, innermost last.
+    captures: Vec<Vec<DatumToken<String>>>,
+    variant_strings: bool,
+    newtype_names: bool,
+    root_none: super::RootNone,
+    field_order: FieldOrder,
+}
+
+impl<F: FnMut(DatumToken<String>) -> DatumResult<()>> TokenSerializer<F> {
+    /// Creates a serializer matching [super::PlainSerializer].
+    pub fn new(output: F) -> Self {
+        Self {
+            output,
+            offset: 0,
+            mode: TokenSerializerMode::Plain,
+            stack: Vec::new(),
+            fields: Vec::new(),
+            captures: Vec::new(),
+            variant_strings: false,
+            newtype_names: false,
+            root_none: super::RootNone::default(),
+            field_order: FieldOrder::default(),
+        }
+    }
+
+    /// Creates a serializer matching [super::RootSerializer].
+    pub fn new_root(output: F) -> Self {
+        Self {
+            mode: TokenSerializerMode::Root,
+            ..Self::new(output)
+        }
+    }
+
+    /// Sets if enum variant names are written as strings; see [super::PlainSerializer::variant_strings].
+    ///
+    /// _Added in 1.3.0._
+    pub fn variant_strings(mut self, variant_strings: bool) -> Self {
+        self.variant_strings = variant_strings;
+        self
+    }
+
+    /// Sets if newtype structs are written with their name; see [super::PlainSerializer::newtype_names].
+    ///
+    /// _Added in 1.3.0._
+    pub fn newtype_names(mut self, newtype_names: bool) -> Self {
+        self.newtype_names = newtype_names;
+        self
+    }
+
+    /// Sets what happens to `None` at the root; see [super::RootNone].
+    ///
+    /// _Added in 1.3.0._
+    pub fn root_none(mut self, policy: super::RootNone) -> Self {
+        self.root_none = policy;
+        self
+    }
+
+    /// Sets the order struct fields are written in; see [super::PlainSerializer::field_order].
+    ///
+    /// _Added in 1.3.0._
+    pub fn field_order(mut self, field_order: FieldOrder) -> Self {
+        self.field_order = field_order;
+        self
+    }
+
+    /// Returns the output callback.
+    pub fn into_inner(self) -> F {
+        self.output
+    }
+
+    fn write_token(&mut self, token: DatumToken<String>) -> error::Result<()> {
+        if let Some(capture) = self.captures.last_mut() {
+            capture.push(token);
+            return Ok(());
+        }
+        // captured tokens are written later, so the offset is set here
+        let at = self.offset;
+        let token = match token {
+            DatumToken::String(_, v) => DatumToken::String(at, v),
+            DatumToken::Symbol(_, v) => DatumToken::Symbol(at, v),
+            DatumToken::SpecialID(_, v) => DatumToken::SpecialID(at, v),
+            DatumToken::Integer(_, v) => DatumToken::Integer(at, v),
+            DatumToken::Float(_, v) => DatumToken::Float(at, v),
+            DatumToken::ListStart(_) => DatumToken::ListStart(at),
+            DatumToken::ListEnd(_) => DatumToken::ListEnd(at),
+        };
+        self.offset += 1;
+        (self.output)(token).map_err(error_from_datum)
+    }
+
+    fn write_atom(&mut self, atom: DatumAtom<&str>) -> error::Result<()> {
+        let at = self.offset;
+        let token = match atom.to_token(at) {
+            DatumToken::String(at, v) => DatumToken::String(at, v.into()),
+            DatumToken::Symbol(at, v) => DatumToken::Symbol(at, v.into()),
+            DatumToken::SpecialID(at, v) => DatumToken::SpecialID(at, v.into()),
+            DatumToken::Integer(at, v) => DatumToken::Integer(at, v),
+            DatumToken::Float(at, v) => DatumToken::Float(at, v),
+            DatumToken::ListStart(at) => DatumToken::ListStart(at),
+            DatumToken::ListEnd(at) => DatumToken::ListEnd(at),
+        };
+        self.write_token(token)
+    }
+
+    fn write_variant(&mut self, variant: &str) -> error::Result<()> {
+        if self.variant_strings {
+            self.write_atom(DatumAtom::String(variant))
+        } else {
+            self.write_atom(DatumAtom::Symbol(variant))
+        }
+    }
+
+    /// Starts a container. Its contents are written in `child` mode.
+    fn open(&mut self, child: TokenSerializerMode, variant: Option<&str>) -> error::Result<()> {
+        let close = self.mode == TokenSerializerMode::Plain;
+        if close {
+            self.write_token(DatumToken::ListStart(self.offset))?;
+        }
+        if let Some(variant) = variant {
+            self.write_variant(variant)?;
+        }
+        self.stack.push((self.mode, close));
+        self.fields.push(Vec::new());
+        self.mode = child;
+        Ok(())
+    }
+
+    fn close(&mut self) -> error::Result<()> {
+        // serde guarantees end is only called on an open container
+        let (mode, close) = self.stack.pop().expect("container end without start");
+        let mut fields = self.fields.pop().unwrap_or_default();
+        self.mode = mode;
+        self.field_order.sort(&mut fields);
+        for (_, tokens) in fields {
+            for token in tokens {
+                self.write_token(token)?;
+            }
+        }
+        if close {
+            self.write_token(DatumToken::ListEnd(self.offset))?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: FnMut(DatumToken<String>) -> DatumResult<()>> Serializer for &mut TokenSerializer<F> {
+    type Ok = ();
+    type Error = error::Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    // -- Option/Unit --
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        if self.mode == TokenSerializerMode::Root {
+            match self.root_none {
+                super::RootNone::Omit => return Ok(()),
+                super::RootNone::Nil => {}
+                _ => {
+                    return Err(error::Error::custom(
+                        "not a supported type for datum's RootSerializer",
+                    ))
+                }
+            }
+        }
+        self.write_atom(DatumAtom::Nil)
+    }
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.write_token(DatumToken::ListStart(self.offset))?;
+        self.write_token(DatumToken::ListEnd(self.offset))
+    }
+    fn serialize_newtype_struct<T: serde::Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        if name == crate::serde::any::DATUM_ANY {
+            if self.mode == TokenSerializerMode::Root {
+                return Err(error::Error::custom(
+                    "not a supported type for datum's RootSerializer",
+                ));
+            }
+            for token in crate::serde::any::datum_any_tokens(value)? {
+                self.write_token(token)?;
+            }
+            return Ok(());
+        }
+        if !self.newtype_names {
+            return value.serialize(self);
+        }
+        if self.mode == TokenSerializerMode::Root {
+            self.write_atom(DatumAtom::Symbol(name))?;
+            return value.serialize(self);
+        }
+        // always a list of its own, even inside a newtype variant
+        let mode = self.mode;
+        self.mode = TokenSerializerMode::Plain;
+        self.open(TokenSerializerMode::Plain, None)?;
+        self.write_atom(DatumAtom::Symbol(name))?;
+        value.serialize(&mut *self)?;
+        self.close()?;
+        self.mode = mode;
+        Ok(())
+    }
+    // -- Enum --
+    fn serialize_newtype_variant<T: serde::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let child = match self.mode {
+            TokenSerializerMode::Root => TokenSerializerMode::Root,
+            _ => TokenSerializerMode::Newtype,
+        };
+        self.open(child, Some(variant))?;
+        value.serialize(&mut *self)?;
+        self.close()
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.open(TokenSerializerMode::Plain, Some(variant))?;
+        Ok(self)
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.open(TokenSerializerMode::Plain, Some(variant))?;
+        Ok(self)
+    }
+    // -- Struct --
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.open(TokenSerializerMode::Plain, None)?;
+        Ok(self)
+    }
+    // -- Seq/Map --
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        self.open(TokenSerializerMode::Plain, None)?;
+        Ok(self)
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.open(TokenSerializerMode::Plain, None)?;
+        Ok(self)
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        self.open(TokenSerializerMode::Plain, None)?;
+        Ok(self)
+    }
+    // -- String --
+    fn collect_str<T: core::fmt::Display + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.write_token(DatumToken::String(self.offset, value.to_string()))
+    }
+    serializer_invariants!();
+}
+
+// -- Seqlikes --
+
+impl<F: FnMut(DatumToken<String>) -> DatumResult<()>> SerializeSeq for &mut TokenSerializer<F> {
+    type Ok = ();
+    type Error = error::Error;
+    fn serialize_element<T: serde::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.close()
+    }
+}
+
+impl<F: FnMut(DatumToken<String>) -> DatumResult<()>> SerializeTuple for &mut TokenSerializer<F> {
+    type Ok = ();
+    type Error = error::Error;
+    fn serialize_element<T: serde::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.close()
+    }
+}
+
+impl<F: FnMut(DatumToken<String>) -> DatumResult<()>> SerializeTupleStruct
+    for &mut TokenSerializer<F>
+{
+    type Ok = ();
+    type Error = error::Error;
+    fn serialize_field<T: serde::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        SerializeTuple::serialize_element(self, value)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeTuple::end(self)
+    }
+}
+
+impl<F: FnMut(DatumToken<String>) -> DatumResult<()>> SerializeTupleVariant
+    for &mut TokenSerializer<F>
+{
+    type Ok = ();
+    type Error = error::Error;
+    fn serialize_field<T: serde::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        SerializeTuple::serialize_element(self, value)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeTuple::end(self)
+    }
+}
+
+// -- Maplikes --
+
+impl<F: FnMut(DatumToken<String>) -> DatumResult<()>> SerializeMap for &mut TokenSerializer<F> {
+    type Ok = ();
+    type Error = error::Error;
+    fn serialize_key<T: serde::Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        key.serialize(&mut **self)
+    }
+    fn serialize_value<T: serde::Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.close()
+    }
+}
+
+impl<F: FnMut(DatumToken<String>) -> DatumResult<()>> SerializeStruct for &mut TokenSerializer<F> {
+    type Ok = ();
+    type Error = error::Error;
+    fn serialize_field<T: serde::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        if self.field_order == FieldOrder::Declaration {
+            self.write_atom(DatumAtom::Symbol(key))?;
+            return value.serialize(&mut **self);
+        }
+        self.captures.push(Vec::new());
+        let res = self
+            .write_atom(DatumAtom::Symbol(key))
+            .and_then(|_| value.serialize(&mut **self));
+        let tokens = self.captures.pop().unwrap_or_default();
+        res?;
+        if let Some(fields) = self.fields.last_mut() {
+            fields.push((key, tokens));
+        }
+        Ok(())
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.close()
+    }
+}
+
+impl<F: FnMut(DatumToken<String>) -> DatumResult<()>> SerializeStructVariant
+    for &mut TokenSerializer<F>
+{
+    type Ok = ();
+    type Error = error::Error;
+    fn serialize_field<T: serde::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        SerializeStruct::serialize_field(self, key, value)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeStruct::end(self)
+    }
+}
@@ -0,0 +1,186 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::serde::error;
+use crate::DatumOffset;
+
+/// Newtype struct name that the Datum deserializers recognize as [DatumSpanned].
+/// They visit a sequence of the start offset, the value, and the end offset.
+pub(crate) const DATUM_SPANNED: &str = "$datum::DatumSpanned";
+
+/// A value, along with the offsets of its first and last tokens, so that checks made after deserializing can say where in the document a problem is.
+/// This is like `toml::Spanned`; it's also available as [crate::Spanned].
+///
+/// Offsets are in whatever units the tokens had (i.e. characters, for [crate::serde::DocLayout::deserialize_str]), and are as the tokenizer gives them: where a list starts or ends, or just after an atom ends (as with errors).
+/// With other deserializers, the value is read as if it were a newtype struct's, and the offsets are 0.
+///
+/// This is written as the value alone. Likewise, comparisons and hashing only look at the value, so that the same value read from different places is still equal.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::serde::{DatumSpanned, DocLayout};
+/// use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct Config {
+///     name: String,
+///     workers: DatumSpanned<u32>,
+/// }
+/// let config: Config = DocLayout::Root.deserialize_str("name \"main\"\nworkers 0").unwrap();
+/// assert_eq!(config.workers.value, 0);
+/// // i.e. to report that there must be at least one worker
+/// assert_eq!((config.workers.start, config.workers.end), (21, 21));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DatumSpanned<T> {
+    /// Offset of the first token of the value.
+    pub start: DatumOffset,
+    /// Offset of the last token of the value (the same as the start for atoms).
+    pub end: DatumOffset,
+    /// The value.
+    pub value: T,
+}
+
+impl<T> DatumSpanned<T> {
+    /// Creates a spanned value.
+    pub fn new(start: DatumOffset, end: DatumOffset, value: T) -> Self {
+        Self { start, end, value }
+    }
+
+    /// Returns the value, discarding the offsets.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: PartialEq> PartialEq for DatumSpanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for DatumSpanned<T> {}
+
+impl<T: PartialOrd> PartialOrd for DatumSpanned<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Ord> Ord for DatumSpanned<T> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: core::hash::Hash> core::hash::Hash for DatumSpanned<T> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl<T> core::ops::Deref for DatumSpanned<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> core::ops::DerefMut for DatumSpanned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: Serialize> Serialize for DatumSpanned<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+struct DatumSpannedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for DatumSpannedVisitor<T> {
+    type Value = DatumSpanned<T>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a value")
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let start = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::custom("DatumSpanned: no start"))?;
+        let value = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::custom("DatumSpanned: no value"))?;
+        let end = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::custom("DatumSpanned: no end"))?;
+        Ok(DatumSpanned { start, end, value })
+    }
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        // not a Datum deserializer
+        T::deserialize(d).map(|value| DatumSpanned::new(0, 0, value))
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for DatumSpanned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(DATUM_SPANNED, DatumSpannedVisitor(PhantomData))
+    }
+}
+
+/// For the deserializers: Where the last token they read was.
+pub(crate) trait SpannedSource {
+    fn last_offset(&self) -> DatumOffset;
+}
+
+/// For the deserializers: What a [DatumSpanned] visits, given the start offset and the deserializer for the value.
+pub(crate) struct SpannedAccess<'a, X> {
+    start: Option<DatumOffset>,
+    value: Option<&'a mut X>,
+    end: Option<DatumOffset>,
+}
+
+impl<'a, X> SpannedAccess<'a, X> {
+    pub(crate) fn new(start: DatumOffset, value: &'a mut X) -> Self {
+        Self {
+            start: Some(start),
+            value: Some(value),
+            end: None,
+        }
+    }
+}
+
+impl<'de, 'a, X: SpannedSource> SeqAccess<'de> for SpannedAccess<'a, X>
+where
+    for<'b> &'b mut X: Deserializer<'de, Error = error::Error>,
+{
+    type Error = error::Error;
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Self::Error> {
+        let offset = if let Some(start) = self.start.take() {
+            start
+        } else if let Some(value) = self.value.take() {
+            let res = seed.deserialize(&mut *value)?;
+            self.end = Some(value.last_offset());
+            return Ok(Some(res));
+        } else if let Some(end) = self.end.take() {
+            end
+        } else {
+            return Ok(None);
+        };
+        let offset: de::value::U64Deserializer<error::Error> = offset.into_deserializer();
+        seed.deserialize(offset).map(Some)
+    }
+}
@@ -17,7 +17,7 @@ use crate::{
     DatumAtom, IntoViaDatumPipe,
 };
 
-use crate::serde::ser::{RootSerializer, Style};
+use crate::serde::ser::{FieldOrder, RootNone, RootSerializer, Style, TokenSerializer};
 
 use super::ser::PlainSerializer;
 
@@ -59,18 +59,39 @@ enum Doc {
     NewtypeMap(HashMap<String, String>),
 }
 
+/// Tokenizes text, for comparison with [TokenSerializer] output.
+fn tokenize(text: &str) -> std::vec::Vec<String> {
+    text.chars()
+        .via_datum_pipe(datum_char_to_token_pipeline())
+        .map(|t| t.unwrap().to_string())
+        .collect()
+}
+
 fn test_serializes_to<'a, V: Debug + PartialEq + Serialize + Deserialize<'a>>(text: &str, v: &V) {
     let mut out = String::new();
     v.serialize(&mut PlainSerializer::new(&mut out, Style::SpacingOnly))
         .unwrap();
     assert_eq!(&out, text);
+    let mut tokens = std::vec::Vec::new();
+    v.serialize(&mut TokenSerializer::new(|t| {
+        tokens.push(t.to_string());
+        Ok(())
+    }))
+    .unwrap();
+    assert_eq!(tokens, tokenize(text));
     // verify no panics/etc.
     let mut delme = String::new();
     v.serialize(&mut PlainSerializer::new(&mut delme, Style::Indented))
         .unwrap();
+    let mut crlf = String::new();
+    let mut ser = PlainSerializer::new(&mut crlf, Style::Indented);
+    ser.writer = ser.writer.with_line_ending(crate::DatumLineEnding::CrLf);
+    v.serialize(&mut ser).unwrap();
+    assert_eq!(crlf, delme.replace('\n', "\r\n"));
     // check these deserialize properly
     test_deserializes_to(&out, v);
     test_deserializes_to(&delme, v);
+    test_deserializes_to(&crlf, v);
 }
 fn test_root_serializes_to<'a, V: Debug + PartialEq + Serialize + Deserialize<'a>>(
     text: &str,
@@ -83,6 +104,13 @@ fn test_root_serializes_to<'a, V: Debug + PartialEq + Serialize + Deserialize<'a
     )))
     .unwrap();
     assert_eq!(&out, text);
+    let mut tokens = std::vec::Vec::new();
+    v.serialize(&mut TokenSerializer::new_root(|t| {
+        tokens.push(t.to_string());
+        Ok(())
+    }))
+    .unwrap();
+    assert_eq!(tokens, tokenize(text));
     // verify no panics/etc.
     let mut delme = String::new();
     v.serialize(&mut RootSerializer(PlainSerializer::new(
@@ -115,7 +143,8 @@ fn test_deserializes_to<'a, V: Deserialize<'a> + Debug + PartialEq>(source: &str
     let mut it = source
         .chars()
         .via_datum_pipe(datum_char_to_token_pipeline());
-    let mut pd = PlainDeserializer::from_iterator(&mut it);
+    // flattened fields and untagged enums need maps detected; everything else reads the same either way
+    let mut pd = PlainDeserializer::from_iterator(&mut it).detect_maps(true);
     assert!(pd.has_next_token().unwrap());
     let v2 = V::deserialize(&mut pd).unwrap();
     assert!(!pd.has_next_token().unwrap());
@@ -126,7 +155,7 @@ fn test_root_deserializes_to<'a, V: Deserialize<'a> + Debug + PartialEq>(source:
     let mut it = source
         .chars()
         .via_datum_pipe(datum_char_to_token_pipeline());
-    let mut pd = RootDeserializer(PlainDeserializer::from_iterator(&mut it));
+    let mut pd = RootDeserializer::from_iterator(&mut it).detect_maps(true);
     assert!(pd.0.has_next_token().unwrap());
     let v2 = V::deserialize(&mut pd).unwrap();
     assert!(!pd.0.has_next_token().unwrap());
@@ -265,6 +294,628 @@ fn test_deserializing() {
     test_deserialization_fails("\"\"", 0 as u64);
 }
 
+#[test]
+fn test_deserializing_values() {
+    use crate::{serde::DocLayout, DatumDocument, DatumMayContainAtom};
+    let doc: DatumDocument = "header 1 (section (test1 \"hi\")) (section (test1 \"bye\"))"
+        .parse()
+        .unwrap();
+    let mut structs: Vec<MyExampleStruct> = Vec::new();
+    for v in doc.iter() {
+        if let Some(list) = v.as_list() {
+            assert_eq!(list[0].as_sym().unwrap(), "section");
+            structs.push(DocLayout::Plain.deserialize_value(&list[1]).unwrap());
+        }
+    }
+    assert_eq!(
+        structs,
+        vec![
+            MyExampleStruct {
+                test1: "hi".to_string()
+            },
+            MyExampleStruct {
+                test1: "bye".to_string()
+            }
+        ]
+    );
+    // atoms which become special IDs
+    let doc: DatumDocument = "#t #nil #{}# #i+inf.0".parse().unwrap();
+    let res: (bool, Option<i32>, String, f64) = DocLayout::Root.deserialize_values(&doc).unwrap();
+    assert_eq!(res, (true, None, "".to_string(), f64::INFINITY));
+    // error offsets are token indices
+    let err = DocLayout::Plain
+        .deserialize_value::<MyExampleStruct>(&doc[0])
+        .unwrap_err();
+    assert!(err.to_string().contains("@ 0"));
+}
+
+#[test]
+fn test_deserializing_token_slices() {
+    let tokens: Vec<crate::DatumToken<String>> = "(test1 \"hi\") NewtypeU64 4 (1 2)"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline())
+        .map(|t| t.unwrap())
+        .collect();
+    // consecutive values from one buffer
+    let mut pd = PlainDeserializer::from_tokens(&tokens);
+    let a = MyExampleStruct::deserialize(&mut pd).unwrap();
+    assert_eq!(a.test1, "hi");
+    assert_eq!(pd.token_index(), Some(4));
+    let mut rd = RootDeserializer::from_tokens(&tokens[4..6]);
+    assert_eq!(Doc::deserialize(&mut rd).unwrap(), Doc::NewtypeU64(4));
+    // the same buffer, as different types
+    let mut pd = PlainDeserializer::from_tokens(&tokens[6..]);
+    assert_eq!(
+        MyExampleTupleStruct::deserialize(&mut pd).unwrap(),
+        MyExampleTupleStruct(1, 2)
+    );
+    assert!(!pd.has_next_token().unwrap());
+    let mut pd = PlainDeserializer::from_tokens(&tokens[6..]);
+    assert_eq!(Vec::<i64>::deserialize(&mut pd).unwrap(), vec![1, 2]);
+    // errors use the token offsets
+    let mut pd = PlainDeserializer::from_tokens(&tokens[4..]);
+    let err = MyExampleStruct::deserialize(&mut pd).unwrap_err();
+    let at = std::format!("@ {}:", tokens[4].offset());
+    assert!(err.to_string().contains(&at), "{}", err);
+}
+
+#[test]
+fn test_skipping() {
+    use crate::serde::DocLayout;
+    // unknown fields are skipped without being looked at, so even invalid special IDs are fine
+    let res: MyExampleStruct = DocLayout::Plain
+        .deserialize_str("(extra (#bogus (\"deep\")) test1 \"kept\" more 1)")
+        .unwrap();
+    assert_eq!(res.test1, "kept");
+    // but it's still an error to run out of data
+    assert!(DocLayout::Plain
+        .deserialize_str::<MyExampleStruct, _>("(extra (1 2")
+        .is_err());
+}
+
+#[test]
+fn test_duplicate_keys() {
+    use crate::serde::de::{PlainDeserializer, RootDeserializer};
+    use crate::{datum_char_to_token_pipeline, IntoViaDatumPipe};
+    use std::collections::HashMap;
+    let source = "(test1 \"a\" \"test1\" \"b\")";
+    // by default, the map decides (and the derived struct impl. refuses)
+    let mut tokens = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let map: HashMap<String, String> =
+        HashMap::deserialize(&mut PlainDeserializer::from_iterator(&mut tokens)).unwrap();
+    assert_eq!(map["test1"], "b");
+    // when rejected, the error points at the second key
+    let mut tokens = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let err = HashMap::<String, String>::deserialize(
+        &mut PlainDeserializer::from_iterator(&mut tokens).reject_duplicate_keys(true),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("@ 17: map: duplicate key"));
+    // also at root, and keys are per-map
+    let source = "a (a 1) b (a 2) a ()";
+    let mut tokens = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let err = HashMap::<String, HashMap<String, i32>>::deserialize(
+        &mut RootDeserializer::from_iterator(&mut tokens).reject_duplicate_keys(true),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("@ 17: map: duplicate key"));
+    // keys of different types don't clash, except symbols and strings
+    #[derive(Deserialize, PartialEq, Eq, Hash, Debug)]
+    #[serde(untagged)]
+    enum Key {
+        Int(i64),
+        Text(String),
+    }
+    let mut tokens = "1 a \"1\" b c d \"c\" e"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let err = HashMap::<Key, String>::deserialize(
+        &mut RootDeserializer::from_iterator(&mut tokens).reject_duplicate_keys(true),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("@ 16: map: duplicate key"));
+}
+
+#[test]
+fn test_lenient() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Config {
+        port: u16,
+        scale: f64,
+        offset: i32,
+        enabled: bool,
+        verbose: bool,
+        name: String,
+        mode: Option<bool>,
+    }
+    let source =
+        "port \"8080\" scale \"1.5\" offset \"-3\" enabled yes verbose \"OFF\" name \"42\" mode on";
+    let expected = Config {
+        port: 8080,
+        scale: 1.5,
+        offset: -3,
+        enabled: true,
+        verbose: false,
+        name: "42".to_string(),
+        mode: Some(true),
+    };
+    let mut tokens = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let res = Config::deserialize(&mut RootDeserializer::from_iterator(&mut tokens).lenient(true));
+    assert_eq!(res.unwrap(), expected);
+    // not without asking
+    let mut tokens = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    assert!(Config::deserialize(&mut RootDeserializer::from_iterator(&mut tokens)).is_err());
+    // inside newtype variants
+    let mut tokens = "(NewtypeU64 \"7\")"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let res = Doc::deserialize(&mut PlainDeserializer::from_iterator(&mut tokens).lenient(true));
+    assert_eq!(res.unwrap(), Doc::NewtypeU64(7));
+    // things which don't look like numbers/booleans are still errors
+    for source in ["\"inf\"", "\"1 2\"", "\"\"", "\"x1\""] {
+        let mut tokens = source
+            .chars()
+            .via_datum_pipe(datum_char_to_token_pipeline());
+        let mut pd = PlainDeserializer::from_iterator(&mut tokens).lenient(true);
+        assert!(f64::deserialize(&mut pd).is_err());
+    }
+    let mut tokens = "maybe"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut pd = PlainDeserializer::from_iterator(&mut tokens).lenient(true);
+    assert!(bool::deserialize(&mut pd).is_err());
+}
+
+#[test]
+fn test_variant_strings() {
+    let values = vec![
+        VeryDetailedEnum::UnitVariant,
+        VeryDetailedEnum::NewtypeVariant(1),
+        VeryDetailedEnum::TupleVariant(2, 3),
+    ];
+    let mut out = String::new();
+    let mut ps = PlainSerializer::new(&mut out, Style::SpacingOnly).variant_strings(true);
+    values.serialize(&mut ps).unwrap();
+    let text = "(\"UnitVariant\" (\"NewtypeVariant\" 1) (\"TupleVariant\" 2 3))";
+    assert_eq!(out, text);
+    let mut tokens = std::vec::Vec::new();
+    values
+        .serialize(
+            &mut TokenSerializer::new(|t| {
+                tokens.push(t.to_string());
+                Ok(())
+            })
+            .variant_strings(true),
+        )
+        .unwrap();
+    assert_eq!(tokens, tokenize(text));
+    // strings at the start of lists are always fine, but unit variants need the option
+    let mut it = text.chars().via_datum_pipe(datum_char_to_token_pipeline());
+    assert!(
+        Vec::<VeryDetailedEnum>::deserialize(&mut PlainDeserializer::from_iterator(&mut it))
+            .is_err()
+    );
+    let mut it = text.chars().via_datum_pipe(datum_char_to_token_pipeline());
+    let mut pd = PlainDeserializer::from_iterator(&mut it).variant_strings(true);
+    assert_eq!(
+        Vec::<VeryDetailedEnum>::deserialize(&mut pd).unwrap(),
+        values
+    );
+    // symbols are still accepted
+    test_deserializes_to(
+        "(UnitVariant (\"NewtypeVariant\" 1))",
+        &values[..2].to_vec(),
+    );
+}
+
+#[test]
+fn test_newtype_names() {
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Meters(i32);
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Path(Vec<Meters>);
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    enum Shape {
+        Line(Path),
+        Square(Meters),
+    }
+    let value = (
+        Path(vec![Meters(1), Meters(2)]),
+        Shape::Line(Path(vec![Meters(3)])),
+        Shape::Square(Meters(4)),
+    );
+    let text = "((Path ((Meters 1) (Meters 2))) (Line (Path ((Meters 3)))) (Square (Meters 4)))";
+    let mut out = String::new();
+    let mut ps = PlainSerializer::new(&mut out, Style::SpacingOnly).newtype_names(true);
+    value.serialize(&mut ps).unwrap();
+    assert_eq!(out, text);
+    let mut tokens = std::vec::Vec::new();
+    value
+        .serialize(
+            &mut TokenSerializer::new(|t| {
+                tokens.push(t.to_string());
+                Ok(())
+            })
+            .newtype_names(true),
+        )
+        .unwrap();
+    assert_eq!(tokens, tokenize(text));
+    let mut it = text.chars().via_datum_pipe(datum_char_to_token_pipeline());
+    let mut pd = PlainDeserializer::from_iterator(&mut it).newtype_names(true);
+    assert_eq!(<(Path, Shape, Shape)>::deserialize(&mut pd).unwrap(), value);
+    // the transparent form is still accepted
+    let mut it = "((1 2) (Line 3) (Square 4))"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut pd = PlainDeserializer::from_iterator(&mut it).newtype_names(true);
+    assert_eq!(<(Path, Shape, Shape)>::deserialize(&mut pd).unwrap(), value);
+    // at the root, the name isn't in a list
+    let mut out = String::new();
+    let mut rs =
+        RootSerializer(PlainSerializer::new(&mut out, Style::SpacingOnly).newtype_names(true));
+    Path(vec![Meters(5)]).serialize(&mut rs).unwrap();
+    assert_eq!(out, "Path (Meters 5)");
+    let mut it = out.chars().via_datum_pipe(datum_char_to_token_pipeline());
+    let mut rd = RootDeserializer::from_iterator(&mut it).newtype_names(true);
+    assert_eq!(Path::deserialize(&mut rd).unwrap(), Path(vec![Meters(5)]));
+}
+
+#[test]
+fn test_root_none() {
+    fn root_text<V: Serialize>(v: &V, policy: RootNone) -> Option<String> {
+        let mut out = String::new();
+        let res =
+            v.serialize(&mut RootSerializer::new(&mut out, Style::SpacingOnly).root_none(policy));
+        let mut tokens = std::vec::Vec::new();
+        let token_res = v.serialize(
+            &mut TokenSerializer::new_root(|t| {
+                tokens.push(t.to_string());
+                Ok(())
+            })
+            .root_none(policy),
+        );
+        assert_eq!(res.is_ok(), token_res.is_ok());
+        res.ok().map(|_| {
+            assert_eq!(tokens, tokenize(&out));
+            out
+        })
+    }
+    let none: Option<(i32, i32)> = None;
+    assert_eq!(root_text(&none, RootNone::Error), None);
+    assert_eq!(root_text(&none, RootNone::Omit).unwrap(), "");
+    assert_eq!(root_text(&none, RootNone::Nil).unwrap(), "#nil");
+    assert_eq!(root_text(&Some((1, 2)), RootNone::Omit).unwrap(), "1 2");
+    // omitting round-trips, including inside newtype variants
+    let res: Option<(i32, i32)> = crate::serde::DocLayout::Root.deserialize_str("").unwrap();
+    assert_eq!(res, none);
+    let text = root_text(&Doc::NewtypeOption(None), RootNone::Omit).unwrap();
+    assert_eq!(text, "NewtypeOption");
+    test_root_deserializes_to(&text, &Doc::NewtypeOption(None));
+    // not at the root, None is #nil as usual
+    assert_eq!(root_text(&(1, none), RootNone::Omit).unwrap(), "1 #nil");
+}
+
+#[test]
+fn test_field_order() {
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        port: u16,
+        host: String,
+        inner: Substruct,
+        debug: bool,
+    }
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    enum Config {
+        Server { port: u16, host: String },
+        Wrapped(Server),
+    }
+    fn text<V: Serialize>(v: &V, order: FieldOrder, root: bool, style: Style) -> String {
+        let mut out = String::new();
+        let mut ser = PlainSerializer::new(&mut out, style).field_order(order);
+        if root {
+            v.serialize(&mut RootSerializer(ser)).unwrap();
+        } else {
+            v.serialize(&mut ser).unwrap();
+        }
+        let mut tokens = std::vec::Vec::new();
+        let mut offsets = std::vec::Vec::new();
+        let output = |t: crate::DatumToken<String>| {
+            offsets.push(t.offset());
+            tokens.push(t.to_string());
+            Ok(())
+        };
+        let mut ts = if root {
+            TokenSerializer::new_root(output)
+        } else {
+            TokenSerializer::new(output)
+        }
+        .field_order(order);
+        v.serialize(&mut ts).unwrap();
+        assert_eq!(tokens, tokenize(&out));
+        // offsets stay token indices despite reordering
+        assert!(offsets.iter().enumerate().all(|(i, v)| i as u64 == *v));
+        out
+    }
+    let server = Server {
+        port: 80,
+        host: "example".to_string(),
+        inner: Substruct { a: 1 },
+        debug: false,
+    };
+    let alpha = FieldOrder::Alphabetical;
+    let priority = FieldOrder::Priority(&["inner", "host"]);
+    // declaration order is the default
+    test_serializes_to("(port 80 host \"example\" inner (a 1) debug #f)", &server);
+    assert_eq!(
+        text(&server, alpha, false, Style::SpacingOnly),
+        "(debug #f host \"example\" inner (a 1) port 80)"
+    );
+    assert_eq!(
+        text(&server, priority, false, Style::SpacingOnly),
+        "(inner (a 1) host \"example\" port 80 debug #f)"
+    );
+    assert_eq!(
+        text(&server, alpha, true, Style::Indented),
+        "debug #f\nhost \"example\"\ninner (\n\ta 1\n)\nport 80\n"
+    );
+    assert_eq!(
+        text(&server, alpha, false, Style::Indented),
+        "(\n\tdebug #f\n\thost \"example\"\n\tinner (\n\t\ta 1\n\t)\n\tport 80\n)"
+    );
+    let variant = Config::Server {
+        port: 80,
+        host: "example".to_string(),
+    };
+    assert_eq!(
+        text(&variant, alpha, false, Style::SpacingOnly),
+        "(Server host \"example\" port 80)"
+    );
+    assert_eq!(
+        text(&variant, alpha, true, Style::SpacingOnly),
+        "Server host \"example\" port 80"
+    );
+    let wrapped = Config::Wrapped(server);
+    let out = text(&wrapped, priority, false, Style::SpacingOnly);
+    assert_eq!(
+        out,
+        "(Wrapped inner (a 1) host \"example\" port 80 debug #f)"
+    );
+    test_deserializes_to(&out, &wrapped);
+    // any order reads back
+    for order in [alpha, priority] {
+        let out = text(&variant, order, false, Style::Minified);
+        test_deserializes_to(&out, &variant);
+        let out = text(&variant, order, true, Style::Indented);
+        test_root_deserializes_to(&out, &variant);
+    }
+}
+
+#[test]
+fn test_inline_limit() {
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        name: String,
+        limits: Substruct,
+        ports: Vec<u16>,
+        hosts: Vec<Vec<String>>,
+    }
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    enum Config {
+        Server(Server),
+        Tags(Vec<String>),
+    }
+    fn text<V: Serialize>(v: &V, limit: usize, order: FieldOrder) -> String {
+        let mut out = String::new();
+        let mut ser = PlainSerializer::new(&mut out, Style::Indented)
+            .inline_limit(limit)
+            .field_order(order);
+        v.serialize(&mut ser).unwrap();
+        // inline blocks put the style back when they end
+        assert!(ser.style == Style::Indented);
+        out
+    }
+    let server = Server {
+        name: "main".to_string(),
+        limits: Substruct { a: 1 },
+        ports: vec![80, 443, 8080],
+        hosts: vec![vec!["a".to_string(), "b".to_string(), "c".to_string()]],
+    };
+    // off by default
+    assert_eq!(
+        text(&server, 0, FieldOrder::Declaration),
+        "(\n\tname \"main\"\n\tlimits (\n\t\ta 1\n\t)\n\tports (\n\t\t80\n\t\t443\n\t\t8080\n\t)\n\thosts (\n\t\t(\n\t\t\t\"a\"\n\t\t\t\"b\"\n\t\t\t\"c\"\n\t\t)\n\t)\n)"
+    );
+    // small containers are inline, along with everything in them
+    let out = text(&server, 2, FieldOrder::Declaration);
+    assert_eq!(
+        out,
+        "(\n\tname \"main\"\n\tlimits (a 1)\n\tports (\n\t\t80\n\t\t443\n\t\t8080\n\t)\n\thosts ((\"a\" \"b\" \"c\"))\n)"
+    );
+    test_deserializes_to(&out, &server);
+    assert_eq!(
+        text(&server, 2, FieldOrder::Alphabetical),
+        "(\n\thosts ((\"a\" \"b\" \"c\"))\n\tlimits (a 1)\n\tname \"main\"\n\tports (\n\t\t80\n\t\t443\n\t\t8080\n\t)\n)"
+    );
+    assert_eq!(
+        text(&server, 4, FieldOrder::Declaration),
+        "(name \"main\" limits (a 1) ports (80 443 8080) hosts ((\"a\" \"b\" \"c\")))"
+    );
+    // newtype variants, and empty containers
+    let tags = Config::Tags(vec![]);
+    assert_eq!(text(&tags, 1, FieldOrder::Declaration), "(Tags)");
+    let config = Config::Server(server);
+    let out = text(&config, 3, FieldOrder::Declaration);
+    assert_eq!(
+        out,
+        "(Server\n\tname \"main\"\n\tlimits (a 1)\n\tports (80 443 8080)\n\thosts ((\"a\" \"b\" \"c\"))\n)"
+    );
+    test_deserializes_to(&out, &config);
+}
+
+#[test]
+fn test_map_keys() {
+    use std::collections::BTreeMap;
+    #[derive(Deserialize, Serialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    enum Key {
+        Unit,
+        Pair(i32, i32),
+    }
+    let mut ints: BTreeMap<i64, String> = BTreeMap::new();
+    ints.insert(-2, "b".to_string());
+    ints.insert(1, "a".to_string());
+    test_serializes_to("(-2 \"b\" 1 \"a\")", &ints);
+    test_root_serializes_to("-2 \"b\" 1 \"a\"", &ints);
+    let mut enums: BTreeMap<Key, i32> = BTreeMap::new();
+    enums.insert(Key::Unit, 1);
+    enums.insert(Key::Pair(2, 3), 4);
+    test_serializes_to("(Unit 1 (Pair 2 3) 4)", &enums);
+    test_root_serializes_to("Unit 1 (Pair 2 3) 4", &enums);
+    let mut others: BTreeMap<(bool, char), Option<i32>> = BTreeMap::new();
+    others.insert((false, 'x'), None);
+    others.insert((true, 'y'), Some(1));
+    test_serializes_to("((#f \"x\") #nil (#t \"y\") 1)", &others);
+    // duplicate atom keys are still caught, with the key in the path
+    let mut tokens = "(1 a 1 b)"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let res = BTreeMap::<i64, String>::deserialize(
+        &mut PlainDeserializer::from_iterator(&mut tokens).reject_duplicate_keys(true),
+    );
+    assert!(res.unwrap_err().to_string().contains("duplicate key"));
+    let mut tokens = "(1 a 2 #t)"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut pd = PlainDeserializer::from_iterator(&mut tokens).track_path(true);
+    assert!(BTreeMap::<i64, String>::deserialize(&mut pd).is_err());
+    assert_eq!(pd.error_path().unwrap().to_string(), "2");
+}
+
+#[test]
+fn test_error_paths() {
+    use crate::{DatumPath, DatumPathSegment};
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Listener {
+        port: u16,
+    }
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Server {
+        listeners: Vec<Listener>,
+    }
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        server: Server,
+    }
+    let source = "server (listeners ((port 1) (port 2) (port \"three\")))";
+    let mut tokens = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = RootDeserializer::from_iterator(&mut tokens).track_path(true);
+    let err = Config::deserialize(&mut de).unwrap_err();
+    assert!(err.to_string().starts_with("at server.listeners[2].port: "));
+    let expected = DatumPath(vec![
+        DatumPathSegment::Key("server".to_string()),
+        DatumPathSegment::Key("listeners".to_string()),
+        DatumPathSegment::Index(2),
+        DatumPathSegment::Key("port".to_string()),
+    ]);
+    assert_eq!(de.error_path(), Some(&expected));
+    // tracking is off by default, leaving messages as they were
+    let mut tokens = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = RootDeserializer::from_iterator(&mut tokens);
+    let err = Config::deserialize(&mut de).unwrap_err();
+    assert!(!err.to_string().starts_with("at "));
+    assert_eq!(de.error_path(), None);
+    // successful deserialization leaves no path behind
+    let mut tokens = "(1 2) (3)"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = PlainDeserializer::from_iterator(&mut tokens).track_path(true);
+    assert_eq!(Vec::<i32>::deserialize(&mut de).unwrap(), vec![1, 2]);
+    assert_eq!(de.error_path(), None);
+}
+
+#[test]
+fn test_config_watcher() {
+    use crate::serde::{DatumConfigWatcher, DocLayout};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+    let path = std::env::temp_dir().join(format!("datum-config-test-{}.scm", std::process::id()));
+    std::fs::write(&path, "test1 \"first\"").unwrap();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let errors_cb = errors.clone();
+    let mut watcher: DatumConfigWatcher<MyExampleStruct> =
+        DatumConfigWatcher::new(&path, DocLayout::Root, move |e| {
+            errors_cb.lock().unwrap().push(e.to_string());
+        })
+        .unwrap();
+    assert_eq!(watcher.get().test1, "first");
+    assert!(!watcher.poll());
+    // bad data keeps the old value
+    std::fs::write(&path, "test1").unwrap();
+    assert!(!watcher.reload());
+    {
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        // errors say which file they came from
+        assert!(errors[0].starts_with(&format!("{}: ", path.display())));
+    }
+    assert_eq!(watcher.get().test1, "first");
+    // explicitly move the modification time so the test isn't timing-dependent
+    std::fs::write(&path, "test1 \"second\"").unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+    assert!(watcher.poll());
+    assert_eq!(watcher.get().test1, "second");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "notify")]
+#[test]
+fn test_config_watcher_notify() {
+    use crate::serde::{DatumConfigWatcher, DocLayout};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    let dir = std::env::temp_dir().join(format!("datum-notify-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.scm");
+    std::fs::write(&path, "test1 \"first\"").unwrap();
+    let watcher: DatumConfigWatcher<MyExampleStruct> =
+        DatumConfigWatcher::new(&path, DocLayout::Root, |_| {}).unwrap();
+    let watcher = Arc::new(Mutex::new(watcher));
+    let handle = DatumConfigWatcher::watch(&watcher).unwrap();
+    // other files in the directory are ignored
+    std::fs::write(dir.join("other.scm"), "test1").unwrap();
+    // save by replacing the file, as editors do
+    std::fs::write(dir.join("config.scm.tmp"), "test1 \"second\"").unwrap();
+    std::fs::rename(dir.join("config.scm.tmp"), &path).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while watcher.lock().unwrap().get().test1 != "second" {
+        assert!(Instant::now() < deadline, "no reload");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    drop(handle);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
 #[test]
 fn test_serializing() {
     // primitives
@@ -348,3 +999,484 @@ fn test_serializing() {
         ),
     );
 }
+
+#[test]
+fn test_token_serializer() {
+    // offsets are token indices
+    let mut offsets = std::vec::Vec::new();
+    Doc::NewtypeEnum(VeryDetailedEnum::StructVariant { a: 1 })
+        .serialize(&mut TokenSerializer::new(|t| {
+            offsets.push(t.offset());
+            Ok(())
+        }))
+        .unwrap();
+    assert_eq!(offsets, [0, 1, 2, 3, 4, 5]);
+    // callback errors are passed through
+    let res = (1, 2).serialize(&mut TokenSerializer::new(|t| match t {
+        crate::DatumToken::Integer(at, 2) => Err(crate::datum_error!(BadData, at, "no twos")),
+        _ => Ok(()),
+    }));
+    assert!(res.is_err());
+    // root none is refused
+    assert!(Option::<i32>::None
+        .serialize(&mut TokenSerializer::new_root(|_| Ok(())))
+        .is_err());
+}
+
+#[test]
+fn test_datum_any() {
+    use crate::serde::DatumAny;
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Config {
+        name: String,
+        plugin: DatumAny,
+        extra: Option<Vec<DatumAny>>,
+    }
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    enum Section {
+        Plugin(DatumAny),
+    }
+    let config = Config {
+        name: "main".to_string(),
+        plugin: DatumAny::from_text("(mode fast \"fast\" (ratio 1.5) #t)").unwrap(),
+        extra: Some(vec![
+            DatumAny::from_text("sym").unwrap(),
+            DatumAny::from_text("()").unwrap(),
+        ]),
+    };
+    test_serializes_to(
+        "(name \"main\" plugin (mode fast \"fast\" (ratio 1.5) #t) extra (sym ()))",
+        &config,
+    );
+    test_root_serializes_to(
+        "name \"main\" plugin (mode fast \"fast\" (ratio 1.5) #t) extra (sym ())",
+        &config,
+    );
+    test_serializes_to(
+        "(Plugin #f)",
+        &Section::Plugin(DatumAny::from_text("#f").unwrap()),
+    );
+    // not allowed at the root
+    let any = DatumAny::from_text("1").unwrap();
+    assert!(any
+        .serialize(&mut RootSerializer::new(
+            &mut String::new(),
+            Style::SpacingOnly
+        ))
+        .is_err());
+    assert!(any
+        .serialize(&mut TokenSerializer::new_root(|_| Ok(())))
+        .is_err());
+    let tokens: std::vec::Vec<_> = "1"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline())
+        .map(|t| t.unwrap())
+        .collect();
+    assert!(DatumAny::deserialize(&mut RootDeserializer::from_tokens(&tokens)).is_err());
+    assert!(DatumAny::from_text("1 2").is_err());
+}
+
+#[test]
+fn test_spanned() {
+    use crate::serde::{DatumSpanned, DocLayout};
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Worker {
+        name: DatumSpanned<String>,
+        ports: DatumSpanned<Vec<u16>>,
+        limit: Option<DatumSpanned<u32>>,
+    }
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    enum Entry {
+        Worker(DatumSpanned<Worker>),
+        Port(DatumSpanned<u16>),
+    }
+    let text = "(name \"a\" ports (80 443) limit 5)";
+    let worker = Worker {
+        name: DatumSpanned::new(8, 8, "a".to_string()),
+        ports: DatumSpanned::new(16, 23, vec![80, 443]),
+        limit: Some(DatumSpanned::new(32, 32, 5)),
+    };
+    // written as the values alone
+    test_serializes_to(text, &worker);
+    let worker: Worker = DocLayout::Plain.deserialize_str(text).unwrap();
+    assert_eq!((worker.ports.start, worker.ports.end), (16, 23));
+    // atoms start and end at the offset they're finished at, as with errors
+    assert_eq!((worker.name.start, worker.name.end), (8, 8));
+    let limit = worker.limit.unwrap();
+    assert_eq!((limit.start, limit.end), (32, 32));
+    // at the root, and inside newtype variants
+    let root: crate::Spanned<Worker> = DocLayout::Root
+        .deserialize_str("\n name \"b\" ports ()")
+        .unwrap();
+    assert_eq!((root.start, root.end), (6, 18));
+    assert_eq!((root.ports.start, root.ports.end), (17, 18));
+    assert_eq!(root.limit, None);
+    let entries: Vec<Entry> = DocLayout::Root
+        .deserialize_str("(Port 80) (Worker name \"c\" ports (1))")
+        .unwrap();
+    match (&entries[0], &entries[1]) {
+        (Entry::Port(port), Entry::Worker(worker)) => {
+            assert_eq!((port.start, port.end, port.value), (8, 8, 80));
+            // the fields are inline in the variant's list, so they end with it
+            assert_eq!((worker.start, worker.end), (22, 36));
+            assert_eq!((worker.name.start, worker.name.end), (25, 25));
+            assert_eq!((worker.ports.start, worker.ports.end), (33, 35));
+        }
+        _ => panic!("wrong entries"),
+    }
+    // spans don't depend on path tracking
+    let spans = |track: bool| {
+        let mut tokens = "(Worker name \"c\" ports (1))"
+            .chars()
+            .via_datum_pipe(datum_char_to_token_pipeline());
+        let mut de = RootDeserializer::from_iterator(&mut tokens).track_path(track);
+        match Vec::<Entry>::deserialize(&mut de).unwrap().remove(0) {
+            Entry::Worker(worker) => (worker.start, worker.end, worker.ports.end),
+            _ => panic!("wrong entry"),
+        }
+    };
+    assert_eq!(spans(false), (12, 26, 25));
+    assert_eq!(spans(true), spans(false));
+}
+
+#[test]
+fn test_checkpoints() {
+    let text = "(a 1) (b 2) 3";
+    let tokens: std::vec::Vec<_> = text
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline())
+        .map(|t| t.unwrap())
+        .collect();
+    let mut iterator = tokens.iter().cloned().map(Ok);
+    let mut from_iterator = PlainDeserializer::from_iterator(&mut iterator);
+    for de in [
+        &mut from_iterator,
+        &mut PlainDeserializer::from_tokens(&tokens),
+    ] {
+        // read ahead before the checkpoint
+        assert!(de.has_next_token().unwrap());
+        let outer = de.checkpoint();
+        assert!(u32::deserialize(&mut *de).is_err());
+        de.rewind(outer);
+        let outer = de.checkpoint();
+        assert_eq!(
+            <(String, i32)>::deserialize(&mut *de).unwrap(),
+            ("a".to_string(), 1)
+        );
+        let inner = de.checkpoint();
+        assert_eq!(
+            <(String, i32)>::deserialize(&mut *de).unwrap(),
+            ("b".to_string(), 2)
+        );
+        de.rewind(inner);
+        assert_eq!(
+            <(String, i32)>::deserialize(&mut *de).unwrap(),
+            ("b".to_string(), 2)
+        );
+        de.rewind(outer);
+        for _ in 0..2 {
+            <(String, i32)>::deserialize(&mut *de).unwrap();
+        }
+        assert_eq!(i32::deserialize(&mut *de).unwrap(), 3);
+        assert!(!de.has_next_token().unwrap());
+    }
+    // newtype names read two tokens ahead
+    let mut tokens = "(Meters 1.5)"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = PlainDeserializer::from_iterator(&mut tokens).newtype_names(true);
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Meters(f64);
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Feet(f64);
+    let checkpoint = de.checkpoint();
+    assert!(Feet::deserialize(&mut de).is_err());
+    de.rewind(checkpoint);
+    let checkpoint = de.checkpoint();
+    assert_eq!(Meters::deserialize(&mut de).unwrap(), Meters(1.5));
+    de.release(checkpoint);
+    // errors while probing don't hide the path of later errors
+    let mut tokens = "((x 1)) ((x \"a\"))"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = PlainDeserializer::from_iterator(&mut tokens).track_path(true);
+    #[derive(Deserialize, Debug)]
+    struct Strings {
+        #[allow(dead_code)]
+        x: String,
+    }
+    #[derive(Deserialize, Debug)]
+    struct Numbers {
+        #[allow(dead_code)]
+        x: i32,
+    }
+    let checkpoint = de.checkpoint();
+    assert!(<(Strings,)>::deserialize(&mut de).is_err());
+    assert!(de.error_path().is_some());
+    de.rewind(checkpoint);
+    assert!(de.error_path().is_none());
+    <(Numbers,)>::deserialize(&mut de).unwrap();
+    let err = <(Numbers,)>::deserialize(&mut de).unwrap_err();
+    assert_eq!(de.error_path().unwrap().to_string(), "[0].x");
+    assert!(err.to_string().starts_with("at [0].x: "));
+}
+
+#[test]
+fn test_untagged_and_internally_tagged() {
+    use crate::serde::DocLayout;
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    #[serde(tag = "type")]
+    enum Source {
+        File { path: String },
+        Network { host: String, ports: Vec<Port> },
+    }
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Port {
+        Number(u16),
+        Range(u16, u16),
+        Named {
+            service: String,
+            fallback: Option<u16>,
+        },
+        Names(Vec<String>),
+    }
+    let source = Source::Network {
+        host: "example.com".to_string(),
+        ports: vec![
+            Port::Number(80),
+            Port::Range(8000, 8080),
+            Port::Named {
+                service: "https".to_string(),
+                fallback: Some(443),
+            },
+            Port::Names(vec!["a".to_string(), "b".to_string()]),
+        ],
+    };
+    let text = "(type \"Network\" host \"example.com\" ports (80 (8000 8080) (service \"https\" fallback 443) (\"a\" \"b\")))";
+    test_serializes_to(text, &source);
+    // at the root, the whole document is looked at
+    let mut tokens = "type File path \"/etc/x.scm\""
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = RootDeserializer::from_iterator(&mut tokens).detect_maps(true);
+    assert_eq!(
+        Source::deserialize(&mut de).unwrap(),
+        Source::File {
+            path: "/etc/x.scm".to_string()
+        }
+    );
+    // looking ahead works with checkpoints and lookahead of its own
+    let mut tokens = text.chars().via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = PlainDeserializer::from_iterator(&mut tokens).detect_maps(true);
+    assert!(de.has_next_token().unwrap());
+    let checkpoint = de.checkpoint();
+    assert_eq!(Source::deserialize(&mut de).unwrap(), source);
+    de.rewind(checkpoint);
+    assert_eq!(Source::deserialize(&mut de).unwrap(), source);
+    // off by default, so lists are always sequences
+    let mut tokens = "(service \"https\" fallback 443)"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = PlainDeserializer::from_iterator(&mut tokens);
+    assert!(Port::deserialize(&mut de).is_err());
+    assert!(DocLayout::Root
+        .deserialize_str::<Source, _>("type File path \"/etc/x.scm\"")
+        .is_err());
+    // long lists are taken to be maps if they look like one as far as the lookahead goes
+    let mut long = "(service \"https\" fallback 443 extra (".to_string();
+    long.push_str(&"1 ".repeat(1000));
+    long.push_str("))");
+    let mut tokens = long.chars().via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = PlainDeserializer::from_iterator(&mut tokens).detect_maps(true);
+    assert!(matches!(
+        Port::deserialize(&mut de).unwrap(),
+        Port::Named { .. }
+    ));
+}
+
+#[test]
+fn test_flatten() {
+    use crate::serde::DocLayout;
+    use std::collections::BTreeMap;
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Limits {
+        max: u32,
+        burst: Option<u32>,
+    }
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Common {
+        name: String,
+        #[serde(flatten)]
+        limits: Limits,
+    }
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        #[serde(flatten)]
+        common: Common,
+        listen: Vec<u16>,
+    }
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Plugin {
+        name: String,
+        #[serde(flatten)]
+        settings: BTreeMap<String, Substruct>,
+    }
+    let server = Server {
+        common: Common {
+            name: "main".to_string(),
+            limits: Limits {
+                max: 10,
+                burst: None,
+            },
+        },
+        listen: vec![80, 443],
+    };
+    test_serializes_to(
+        "(\"name\" \"main\" \"max\" 10 \"burst\" #nil \"listen\" (80 443))",
+        &server,
+    );
+    test_root_serializes_to(
+        "\"name\" \"main\" \"max\" 10 \"burst\" #nil \"listen\" (80 443)",
+        &server,
+    );
+    // optional fields can be left out, and fields can be in any order
+    let read: Server = DocLayout::Root
+        .deserialize_str("listen (80 443) max 10 name \"main\"")
+        .unwrap();
+    assert_eq!(read, server);
+    // flattened maps collect the rest
+    let mut settings = BTreeMap::new();
+    settings.insert("cache".to_string(), Substruct { a: 1 });
+    settings.insert("tls".to_string(), Substruct { a: 2 });
+    let plugin = Plugin {
+        name: "web".to_string(),
+        settings,
+    };
+    test_serializes_to("(\"name\" \"web\" \"cache\" (a 1) \"tls\" (a 2))", &plugin);
+    test_root_serializes_to("\"name\" \"web\" \"cache\" (a 1) \"tls\" (a 2)", &plugin);
+    // structs inside them need maps detected
+    let mut tokens = "tls (a 2) name \"web\" \"cache\" (a 1)"
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let mut de = RootDeserializer::from_iterator(&mut tokens).detect_maps(true);
+    assert_eq!(Plugin::deserialize(&mut de).unwrap(), plugin);
+    // and can be inside enums
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    enum Entry {
+        Server(Server),
+        Plugin {
+            version: u32,
+            #[serde(flatten)]
+            plugin: Plugin,
+        },
+        Groups(Vec<Vec<u16>>),
+    }
+    test_serializes_to(
+        "(Server \"name\" \"main\" \"max\" 10 \"burst\" #nil \"listen\" (80 443))",
+        &Entry::Server(server),
+    );
+    test_serializes_to(
+        "(Plugin \"version\" 1 \"name\" \"web\" \"cache\" (a 1) \"tls\" (a 2))",
+        &Entry::Plugin { version: 1, plugin },
+    );
+    // only the variant's own list is shared; lists inside the value are kept
+    test_serializes_to(
+        "(Groups (80 443) (8080))",
+        &Entry::Groups(vec![vec![80, 443], vec![8080]]),
+    );
+}
+
+#[test]
+fn test_log_writer() {
+    use crate::DatumLogWriter;
+    let path = std::env::temp_dir().join(format!("datum-log-test-{}.scm", std::process::id()));
+    // simulate a previous writer crashing mid-entry
+    std::fs::write(&path, "(broken").unwrap();
+    let mut log = DatumLogWriter::open(&path).unwrap().sync_each_entry(true);
+    log.append(&MyExampleStruct {
+        test1: "a".to_string(),
+    })
+    .unwrap();
+    log.append_separator().unwrap();
+    log.append(&MyExampleTupleStruct(1, 2)).unwrap();
+    drop(log);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "(broken\n(test1 \"a\")\n#---\n(1 2)\n"
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[cfg(all(feature = "chrono", feature = "time"))]
+fn test_time_interop() {
+    use crate::interop::time::{Span, Timestamp};
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Event {
+        #[serde(with = "crate::interop::time::chrono_datetime")]
+        at: chrono::DateTime<chrono::Utc>,
+        #[serde(with = "crate::interop::time::chrono_duration")]
+        took: chrono::TimeDelta,
+    }
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TimeEvent {
+        #[serde(with = "crate::interop::time::time_datetime")]
+        at: ::time::OffsetDateTime,
+        #[serde(with = "crate::interop::time::time_duration")]
+        took: ::time::Duration,
+        #[serde(with = "crate::interop::time::duration")]
+        timeout: core::time::Duration,
+    }
+    let at = chrono::DateTime::from_timestamp(1704067200, 0).unwrap();
+    let event = Event {
+        at,
+        took: chrono::TimeDelta::milliseconds(-1500),
+    };
+    let text = "(at (instant \"2024-01-01T00:00:00Z\") took (duration -2 500000000))";
+    test_serializes_to(text, &event);
+    test_root_serializes_to(
+        "at (instant \"2024-01-01T00:00:00Z\") took (duration -2 500000000)",
+        &event,
+    );
+    // both crates agree
+    let time_event = TimeEvent {
+        at: ::time::OffsetDateTime::from_unix_timestamp(1704067200).unwrap(),
+        took: ::time::Duration::milliseconds(-1500),
+        timeout: core::time::Duration::from_secs(30),
+    };
+    test_serializes_to(
+        "(at (instant \"2024-01-01T00:00:00Z\") took (duration -2 500000000) timeout (duration 30))",
+        &time_event,
+    );
+    // other offsets and Unix time are accepted; the offset is normalized on write
+    let text = "(at (instant \"2024-01-01T02:00:00+02:00\") took (duration 0))";
+    let res: Event = crate::serde::DocLayout::Plain
+        .deserialize_str(text)
+        .unwrap();
+    assert_eq!(res.at, at);
+    let text = "(at (instant 1704067200) took (duration 0) timeout (duration 0))";
+    let res: TimeEvent = crate::serde::DocLayout::Plain
+        .deserialize_str(text)
+        .unwrap();
+    assert_eq!(res.at, time_event.at);
+    // the representations on their own
+    test_serializes_to("(instant 5)", &Timestamp::Unix(5));
+    test_serializes_to("(duration 5 1)", &Span { secs: 5, nanos: 1 });
+    assert_eq!(
+        Span::from_signed(-1, -1),
+        Span {
+            secs: -2,
+            nanos: 999_999_999
+        }
+    );
+    // bad values
+    let res: Result<Span, _> =
+        crate::serde::DocLayout::Plain.deserialize_str("(duration 1 1000000000)");
+    assert!(res.is_err());
+    let res: Result<Span, _> = crate::serde::DocLayout::Plain.deserialize_str("(instant 1)");
+    assert!(res.is_err());
+    let res: Result<Event, _> = crate::serde::DocLayout::Plain
+        .deserialize_str("(at (instant \"yesterday\") took (duration 0))");
+    assert!(res.is_err());
+}
@@ -0,0 +1,121 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+use crate::token::char_to_byte;
+use crate::{
+    unary, DatumBoundedPipe, DatumChar, DatumOffset, DatumPipe, DatumResult, DatumSpan,
+    DatumTokenType, DatumTokenizer, DatumTokenizerAction,
+};
+
+#[cfg(feature = "alloc")]
+use crate::{datum_error, DatumCharToTokenPipeline, DatumToken};
+
+/// Token which only records where it is in the source (in characters, as when reading from [str::chars]), rather than its contents.
+///
+/// Made by [DatumSpanTokenizer]. Nothing is unescaped, parsed, or allocated until [DatumSpanToken::resolve] is called, so scanning a document and only looking at a few tokens is cheap.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DatumSpanToken {
+    /// Type of the token.
+    pub token_type: DatumTokenType,
+    /// Where the token is (end inclusive). This includes the quotes of strings and the `#` of special IDs.
+    pub span: DatumSpan,
+}
+
+impl DatumSpanToken {
+    /// The token's text in the source, as written (escapes and all).
+    pub fn source_text<'a>(&self, source: &'a str) -> &'a str {
+        let start = char_to_byte(source, self.span.start);
+        let end = start + char_to_byte(&source[start..], self.span.end + 1 - self.span.start);
+        &source[start..end]
+    }
+
+    /// Unescapes and parses the token, giving the same [DatumToken] (including offset) as the usual tokenizer would.
+    ///
+    /// The source must be the text the token was read from.
+    #[cfg(feature = "alloc")]
+    pub fn resolve(&self, source: &str) -> DatumResult<DatumToken<String>> {
+        let mut pipe = DatumCharToTokenPipeline::<String>::default();
+        let mut token = None;
+        let mut output = |at, v| match token {
+            None => {
+                token = Some(v);
+                Ok(())
+            }
+            Some(_) => Err(datum_error!(BadData, at, "span token: more than one token")),
+        };
+        let mut at = self.span.start;
+        for c in self.source_text(source).chars() {
+            pipe.feed(at, Some(c), &mut output)?;
+            at += 1;
+        }
+        pipe.feed(at, None, &mut output)?;
+        token.ok_or(datum_error!(
+            BadData,
+            self.span.start,
+            "span token: no token"
+        ))
+    }
+}
+
+/// Tokenizer which outputs [DatumSpanToken]s; see there.
+///
+/// Input offsets must be character offsets into the source (as with [crate::datum_char_to_span_token_pipeline]) for the spans to be usable.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_char_to_span_token_pipeline, DatumToken, DatumTokenType, IntoViaDatumPipe};
+/// let source = "(name \"caf\\xE9;\" notes \"ignored\")";
+/// let tokens: Vec<_> = source.chars().via_datum_pipe(datum_char_to_span_token_pipeline()).map(|v| v.unwrap()).collect();
+/// let strings: Vec<&str> = tokens
+///     .iter()
+///     .filter(|v| v.token_type == DatumTokenType::String)
+///     .map(|v| v.source_text(source))
+///     .collect();
+/// assert_eq!(strings, ["\"caf\\xE9;\"", "\"ignored\""]);
+/// // only the value that's wanted is unescaped
+/// assert_eq!(tokens[2].resolve(source).unwrap(), DatumToken::String(15, "café".to_string()));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DatumSpanTokenizer(DatumTokenizer);
+
+// Same as the tokenizer itself.
+impl DatumBoundedPipe for DatumSpanTokenizer {
+    type OutputQueueSize = unary::C2;
+}
+
+impl DatumPipe for DatumSpanTokenizer {
+    type Input = DatumChar;
+    type Output = DatumSpanToken;
+
+    fn feed<F: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut F,
+    ) -> DatumResult<()> {
+        self.0.feed(at, i, &mut |start, action| match action {
+            DatumTokenizerAction::Push(_) => Ok(()),
+            DatumTokenizerAction::Token(token_type) => {
+                let end = match token_type {
+                    // finished by the token's last character
+                    DatumTokenType::String
+                    | DatumTokenType::ListStart
+                    | DatumTokenType::ListEnd => at,
+                    // finished by whatever follows the token
+                    _ => at.saturating_sub(1),
+                };
+                let span = DatumSpan { start, end };
+                f(at, DatumSpanToken { token_type, span })
+            }
+        })
+    }
+}
@@ -0,0 +1,196 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use crate::{DatumCompression, DatumDirLoader, DatumDocument, DatumDocumentStyle};
+
+/// Returns true if a name matches a glob pattern.
+///
+/// `*` matches any run of characters other than `/`, `?` matches any one character other than `/`, and `**` matches any run of characters (including `/`).
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern {
+        [] => name.is_empty(),
+        ['*', '*', rest @ ..] => (0..=name.len()).any(|i| glob_match(rest, &name[i..])),
+        ['*', rest @ ..] => {
+            let run = name.iter().take_while(|c| **c != '/').count();
+            (0..=run).any(|i| glob_match(rest, &name[i..]))
+        }
+        ['?', rest @ ..] => match name {
+            [c, name @ ..] if *c != '/' => glob_match(rest, name),
+            _ => false,
+        },
+        [p, rest @ ..] => match name {
+            [c, name @ ..] if c == p => glob_match(rest, name),
+            _ => false,
+        },
+    }
+}
+
+/// Somewhere documents live, addressed by name, i.e. a directory or (for tests) memory.
+///
+/// Names are relative paths separated by `/`, including the extension, i.e. `config/main.scm`.
+///
+/// Errors are [io::Error]s; a document that doesn't exist is [ErrorKind::NotFound].
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumDocument, DatumMemoryStore, DatumStore};
+/// fn save_defaults(store: &mut dyn DatumStore) -> std::io::Result<()> {
+///     let doc: DatumDocument = "(volume 80)".parse().unwrap();
+///     store.save("settings/audio.scm", &doc)?;
+///     store.save("settings/video.scm", &DatumDocument::default())?;
+///     store.save("saves/1.scm", &DatumDocument::default())
+/// }
+/// let mut store = DatumMemoryStore::default();
+/// save_defaults(&mut store).unwrap();
+/// assert_eq!(store.list("settings/*.scm").unwrap(), ["settings/audio.scm", "settings/video.scm"]);
+/// assert_eq!(store.load("settings/audio.scm").unwrap().to_string(), "(volume 80)\n");
+/// ```
+pub trait DatumStore {
+    /// Loads a document.
+    fn load(&self, name: &str) -> io::Result<DatumDocument>;
+
+    /// Saves a document, replacing any document of the same name.
+    fn save(&mut self, name: &str, doc: &DatumDocument) -> io::Result<()>;
+
+    /// Names of every document, in order.
+    fn names(&self) -> io::Result<Vec<String>>;
+
+    /// Names of documents matching a glob pattern, in order.
+    ///
+    /// `*` matches any run of characters other than `/`, `?` matches any one character other than `/`, and `**` matches any run of characters (including `/`).
+    fn list(&self, pattern: &str) -> io::Result<Vec<String>> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let mut names = self.names()?;
+        names.retain(|name| glob_match(&pattern, &name.chars().collect::<Vec<_>>()));
+        Ok(names)
+    }
+}
+
+/// [DatumStore] keeping documents in memory, for tests and such.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DatumMemoryStore {
+    /// The documents, by name.
+    pub documents: BTreeMap<String, DatumDocument>,
+}
+
+impl DatumStore for DatumMemoryStore {
+    fn load(&self, name: &str) -> io::Result<DatumDocument> {
+        self.documents
+            .get(name)
+            .cloned()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "store: no such document"))
+    }
+
+    fn save(&mut self, name: &str, doc: &DatumDocument) -> io::Result<()> {
+        self.documents.insert(name.to_string(), doc.clone());
+        Ok(())
+    }
+
+    fn names(&self) -> io::Result<Vec<String>> {
+        Ok(self.documents.keys().cloned().collect())
+    }
+}
+
+/// [DatumStore] keeping documents as files in a directory.
+///
+/// Only `*.scm` and `*.datum` files (that aren't hidden) are listed, as with [DatumDirLoader]. Names that would escape the directory (i.e. containing `..`) are rejected.
+///
+/// Documents are read with [DatumDocument::read_from] (so compressed files can be loaded), and saved by writing a temporary file and renaming it over the old one, so a crash doesn't leave a half-written document.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumDocument, DatumFileStore, DatumStore};
+/// let dir = std::env::temp_dir().join(format!("datum-store-doctest-{}", std::process::id()));
+/// let mut store = DatumFileStore::new(&dir);
+/// let doc: DatumDocument = "(volume 80)".parse().unwrap();
+/// store.save("settings/audio.scm", &doc).unwrap();
+/// assert_eq!(std::fs::read_to_string(dir.join("settings").join("audio.scm")).unwrap(), "(volume 80)\n");
+/// assert_eq!(store.list("**.scm").unwrap(), ["settings/audio.scm"]);
+/// assert!(store.load("../audio.scm").is_err());
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DatumFileStore {
+    /// The directory.
+    pub root: PathBuf,
+    /// Style documents are saved with.
+    pub style: DatumDocumentStyle,
+}
+
+impl DatumFileStore {
+    /// Creates a store for a directory (which is created when something is saved, if it doesn't exist).
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            style: DatumDocumentStyle::default(),
+        }
+    }
+
+    /// Path of a document.
+    pub fn path(&self, name: &str) -> io::Result<PathBuf> {
+        let mut path = self.root.clone();
+        for segment in name.split('/') {
+            if segment.is_empty() || segment == "." || segment == ".." || segment.contains('\\') {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "store: bad document name",
+                ));
+            }
+            path.push(segment);
+        }
+        Ok(path)
+    }
+}
+
+impl DatumStore for DatumFileStore {
+    fn load(&self, name: &str) -> io::Result<DatumDocument> {
+        datum_span!(_span, "datum_store_load", name);
+        DatumDocument::read_from(std::fs::File::open(self.path(name)?)?)
+    }
+
+    fn save(&mut self, name: &str, doc: &DatumDocument) -> io::Result<()> {
+        datum_span!(_span, "datum_store_save", name);
+        let path = self.path(name)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut temporary = path.clone().into_os_string();
+        temporary.push(".tmp");
+        let file = std::fs::File::create(&temporary)?;
+        doc.write_to(file, DatumCompression::None, &self.style)?
+            .sync_all()?;
+        std::fs::rename(&temporary, &path)
+    }
+
+    fn names(&self) -> io::Result<Vec<String>> {
+        let paths = match DatumDirLoader::new().recursive(true).list(&self.root) {
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            other => other?,
+        };
+        let mut names: Vec<String> = paths
+            .iter()
+            .filter_map(|path| {
+                let relative = path.strip_prefix(&self.root).ok()?;
+                let segments: Option<Vec<&str>> = relative
+                    .components()
+                    .map(|v| v.as_os_str().to_str())
+                    .collect();
+                Some(segments?.join("/"))
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+}
@@ -0,0 +1,202 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::{fmt::Write, ops::Deref};
+
+use crate::{datum_error, DatumAtom, DatumError, DatumResult, DatumToken, DatumWriter};
+
+/// Push-style writer for emitting documents without building values first.
+///
+/// Keeps track of list nesting, so unbalanced lists are caught (as [crate::DatumErrorKind::BadData]).
+/// Failures of the underlying [Write] become [crate::DatumErrorKind::OutOfRoom].
+/// Error offsets are the amount of tokens written so far.
+///
+/// Each top-level value is followed by a newline.
+/// If indentation is enabled, so is every list element, and list contents are indented.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumAtom, DatumStreamWriter};
+/// let mut out = String::new();
+/// let mut writer = DatumStreamWriter::new(&mut out, false);
+/// writer.comment("generated").unwrap();
+/// writer.begin_list().unwrap();
+/// writer.atom(&DatumAtom::Symbol("entry")).unwrap();
+/// writer.atom::<&str>(&DatumAtom::Integer(1)).unwrap();
+/// writer.end_list().unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!(out, "; generated\n(entry 1)\n");
+/// ```
+pub struct DatumStreamWriter<'write> {
+    target: &'write mut dyn Write,
+    writer: DatumWriter,
+    indented: bool,
+    depth: usize,
+    tokens: u64,
+}
+
+impl<'write> DatumStreamWriter<'write> {
+    /// Creates a new stream writer, optionally with indentation.
+    pub fn new(target: &'write mut dyn Write, indented: bool) -> Self {
+        Self {
+            target,
+            writer: DatumWriter::default(),
+            indented,
+            depth: 0,
+            tokens: 0,
+        }
+    }
+
+    /// Sets the line ending.
+    ///
+    /// _Added in 1.3.0._
+    pub fn line_ending(mut self, line_ending: crate::DatumLineEnding) -> Self {
+        self.writer = self.writer.with_line_ending(line_ending);
+        self
+    }
+
+    /// Current list nesting depth.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn write_error(&self) -> DatumError {
+        datum_error!(OutOfRoom, self.tokens, "stream writer: failed to write")
+    }
+
+    /// Writes the newline that follows a value, if any.
+    fn after_value(&mut self) -> DatumResult<()> {
+        self.tokens += 1;
+        if self.depth == 0 || self.indented {
+            self.newline()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes a newline.
+    pub fn newline(&mut self) -> DatumResult<()> {
+        self.writer
+            .write_newline(self.target)
+            .map_err(|_| self.write_error())
+    }
+
+    /// Writes a line comment. Newlines in the text become more line comments.
+    pub fn comment(&mut self, text: &str) -> DatumResult<()> {
+        self.writer
+            .write_comment(self.target, text)
+            .map_err(|_| self.write_error())
+    }
+
+    /// Writes a line comment with the given options; see [crate::DatumWriter::write_comment_with].
+    ///
+    /// _Added in 1.3.0._
+    pub fn comment_with(
+        &mut self,
+        text: &str,
+        style: &crate::DatumCommentStyle,
+    ) -> DatumResult<()> {
+        self.writer
+            .write_comment_with(self.target, text, style)
+            .map_err(|_| self.write_error())
+    }
+
+    /// Writes an atom.
+    pub fn atom<B: Deref<Target = str>>(&mut self, atom: &DatumAtom<B>) -> DatumResult<()> {
+        self.writer
+            .write_atom(self.target, atom)
+            .map_err(|_| self.write_error())?;
+        self.after_value()
+    }
+
+    /// Writes a token. List tokens are handled as per [DatumStreamWriter::begin_list] and [DatumStreamWriter::end_list].
+    pub fn token<B: Deref<Target = str>>(&mut self, token: &DatumToken<B>) -> DatumResult<()> {
+        match token {
+            DatumToken::ListStart(_) => self.begin_list(),
+            DatumToken::ListEnd(_) => self.end_list(),
+            _ => {
+                self.writer
+                    .write_token(self.target, token)
+                    .map_err(|_| self.write_error())?;
+                self.after_value()
+            }
+        }
+    }
+
+    /// Starts a list.
+    pub fn begin_list(&mut self) -> DatumResult<()> {
+        let token: DatumToken<&str> = DatumToken::ListStart(0);
+        self.writer
+            .write_token(self.target, &token)
+            .map_err(|_| self.write_error())?;
+        self.tokens += 1;
+        self.depth += 1;
+        if self.indented {
+            self.writer.indent += 1;
+            self.newline()?;
+        }
+        Ok(())
+    }
+
+    /// Ends a list. Fails if there is no list to end.
+    pub fn end_list(&mut self) -> DatumResult<()> {
+        if self.depth == 0 {
+            return Err(datum_error!(
+                BadData,
+                self.tokens,
+                "stream writer: end of list while not in list"
+            ));
+        }
+        self.depth -= 1;
+        if self.indented {
+            self.writer.indent -= 1;
+        }
+        let token: DatumToken<&str> = DatumToken::ListEnd(0);
+        self.writer
+            .write_token(self.target, &token)
+            .map_err(|_| self.write_error())?;
+        self.after_value()
+    }
+
+    /// Writes a document separator (see [crate::DatumDocumentFramer]). Fails if inside a list.
+    pub fn separator(&mut self) -> DatumResult<()> {
+        if self.depth != 0 {
+            return Err(datum_error!(
+                BadData,
+                self.tokens,
+                "stream writer: document separator inside list"
+            ));
+        }
+        let token = DatumToken::SpecialID(0, crate::DATUM_DOCUMENT_SEPARATOR);
+        self.writer
+            .write_token(self.target, &token)
+            .map_err(|_| self.write_error())?;
+        self.after_value()
+    }
+
+    /// Writes a value from AST.
+    #[cfg(feature = "alloc")]
+    pub fn value<V: crate::DatumValueNode>(&mut self, value: &V) -> DatumResult<()> {
+        for token in crate::DatumValueTokens::new(core::slice::from_ref(value)) {
+            self.token(&token)?;
+        }
+        Ok(())
+    }
+
+    /// Finishes writing, checking that all lists have been ended.
+    pub fn finish(self) -> DatumResult<()> {
+        if self.depth != 0 {
+            Err(datum_error!(
+                Interrupted,
+                self.tokens,
+                "stream writer: unterminated list"
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
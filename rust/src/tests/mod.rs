@@ -14,17 +14,29 @@ use std::convert::TryFrom;
 use std::hash::{DefaultHasher, Hasher};
 
 use crate::{
-    DatumByteToTokenPipeline, DatumChar, DatumCharClass, DatumDecoder, DatumParser,
-    DatumUTF8Decoder, IntoViaDatumBufPipe,
+    DatumBlankCommentLines, DatumByteToTokenPipeline, DatumChar, DatumCharClass,
+    DatumCharToTokenPipeline, DatumCommentControl, DatumCommentStyle, DatumContextError,
+    DatumDecoder, DatumDiagnostic, DatumDocument, DatumDocumentStyle, DatumDuplicateKeyPipe,
+    DatumErrorKind, DatumFormatConfig, DatumGenerator, DatumGeneratorConfig, DatumHeader,
+    DatumHeaderCheck, DatumIndex, DatumLineEnding, DatumMigrator, DatumOffset, DatumParser,
+    DatumSeverity, DatumSpan, DatumStreamWriter, DatumUTF8Decoder, DatumValue, DatumValueMetrics,
+    DatumWarning, DatumWarningKind, DatumWriteError, IntoViaDatumBufPipe, DATUM_BYTE_CLASS_TABLE,
 };
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
 use crate::{
-    datum_byte_to_value_pipeline, datum_char_to_token_pipeline, datum_char_to_value_pipeline,
-    DatumAtom, DatumPipe, DatumToken, DatumWriter, IntoViaDatumPipe,
+    datum_byte_to_framed_value_pipeline, datum_byte_to_value_pipeline,
+    datum_char_to_linted_token_pipeline, datum_char_to_span_token_pipeline,
+    datum_char_to_token_pipeline, datum_char_to_value_pipeline, datum_escape_string_content,
+    datum_format, datum_format_with, datum_is_formatted, datum_unescape_string_content, DatumAtom,
+    DatumFrame, DatumPipe, DatumResult, DatumSpanToken, DatumToken, DatumWriter, DatumWriterState,
+    IntoViaDatumPipe,
 };
 
+#[cfg(feature = "std")]
+mod vectors;
+
 fn do_roundtrip_test(input: &str, output: &str) {
     let mut dectok1 = datum_char_to_token_pipeline();
     let mut tokenization = Vec::new();
@@ -90,7 +102,16 @@ fn do_roundtrip_test(input: &str, output: &str) {
     assert_eq!(
         out_str
             .bytes()
-            .via_datum_buf_pipe(i2pipeline)
+            .via_datum_buf_pipe(i2pipeline.clone())
+            .map(|v| v.unwrap())
+            .count(),
+        out.len()
+    );
+    // --- iterator test 3 ---
+    assert_eq!(
+        out_str
+            .bytes()
+            .via_datum_array_pipe(i2pipeline)
             .map(|v| v.unwrap())
             .count(),
         out.len()
@@ -176,6 +197,73 @@ fn roundtrip_tests() {
     assert_eq!(writer_fmt, "\n\t; lof\n\t; lif\n\t; idk?\n");
 }
 
+#[test]
+fn writer_line_ending() {
+    let mut writer_fmt = String::new();
+    let mut writer_fmt_test = DatumWriter::default().with_line_ending(DatumLineEnding::CrLf);
+    writer_fmt_test
+        .write_comment(&mut writer_fmt, "lof\nlif")
+        .unwrap();
+    writer_fmt_test.write_newline(&mut writer_fmt).unwrap();
+    assert_eq!(writer_fmt, "; lof\r\n; lif\r\n\r\n");
+}
+
+/// Writes a comment at the start of a line with the given style.
+fn styled_comment(indent: usize, text: &str, style: &DatumCommentStyle) -> String {
+    let mut out = String::new();
+    let mut writer = DatumWriter::default();
+    writer.indent = indent;
+    writer.state = DatumWriterState::QueuedIndent;
+    writer.write_comment_with(&mut out, text, style).unwrap();
+    out
+}
+
+#[test]
+fn comment_styles() {
+    let style = DatumCommentStyle::default();
+    assert_eq!(styled_comment(0, "a\n\n  b", &style), "; a\n; \n;   b\n");
+    let style = DatumCommentStyle {
+        wrap_column: Some(14),
+        blank_lines: DatumBlankCommentLines::Skip,
+        ..style
+    };
+    assert_eq!(
+        styled_comment(1, "one two  three\n\nextraordinarily x", &style),
+        "\t; one two\n\t; three\n\t; extraordinarily\n\t; x\n"
+    );
+    // short lines aren't touched
+    assert_eq!(styled_comment(1, "a  b", &style), "\t; a  b\n");
+    let style = DatumCommentStyle {
+        blank_lines: DatumBlankCommentLines::Trimmed,
+        ..style
+    };
+    assert_eq!(styled_comment(0, "", &style), ";\n");
+}
+
+#[test]
+fn comment_escaping() {
+    use alloc::format;
+    // hostile text can't break or spoof the document
+    let hostile = "C:\\dir\\\r\nevil\x1b[2J\x00\u{202E}\x7F\u{85}\u{2028}\tok";
+    let style = DatumCommentStyle::default();
+    let escaped = styled_comment(0, hostile, &style);
+    assert_eq!(
+        escaped,
+        "; C:\\\\dir\\\\\n; evil\\x1b;[2J\\x0;\\x202e;\\x7f;\\x85;\\x2028;\tok\n"
+    );
+    let style = DatumCommentStyle {
+        control: DatumCommentControl::Strip,
+        ..style
+    };
+    let stripped = styled_comment(0, hostile, &style);
+    assert_eq!(stripped, "; C:\\\\dir\\\\\n; evil[2J\tok\n");
+    for text in [escaped, stripped].iter() {
+        let doc = format!("{}(a)", text);
+        let value: DatumDocument = doc.parse().unwrap();
+        assert_eq!(value.to_string(), "(a)\n");
+    }
+}
+
 fn decoder_test(input: &str, output: &str, out_class: DatumCharClass) {
     let mut decoder = DatumDecoder::default();
     let mut output_iterator = output.chars();
@@ -233,6 +321,115 @@ fn decoder_should_not_allow_eof(input: &str) {
     assert!(decoder.feed(0, None, &mut |_, _| { Ok(()) }).is_err());
 }
 
+#[test]
+fn writer_list_guards() {
+    let mut text = String::new();
+    let mut writer = DatumWriter::default();
+    writer
+        .write_token(&mut text, &DatumToken::<&str>::ListStart(0))
+        .unwrap();
+    assert_eq!(writer.in_list_depth(), 1);
+    {
+        let mut list = writer.begin_list(&mut text).unwrap();
+        assert_eq!(list.in_list_depth(), 2);
+        list.write_atom(&DatumAtom::Symbol("a")).unwrap();
+        list.write_newline().unwrap();
+        list.write_comment("b").unwrap();
+        list.begin_list().unwrap().end().unwrap();
+        list.end().unwrap();
+    }
+    writer
+        .write_token(&mut text, &DatumToken::<&str>::ListEnd(0))
+        .unwrap();
+    assert_eq!(writer.in_list_depth(), 0);
+    assert_eq!(text, "((a\n; b\n()))");
+    // a failed write means the guard can't (and isn't expected to) balance anything
+    struct Full(usize);
+    impl core::fmt::Write for Full {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            if s.len() > self.0 {
+                return Err(core::fmt::Error);
+            }
+            self.0 -= s.len();
+            Ok(())
+        }
+    }
+    let mut full = Full(2);
+    let mut writer = DatumWriter::default();
+    let mut list = writer.begin_list(&mut full).unwrap();
+    assert!(list.write_atom(&DatumAtom::Symbol("long")).is_err());
+    drop(list);
+    // errors which say what was being written
+    let mut full = Full(1);
+    let mut writer = DatumWriter::default();
+    writer
+        .try_write_token(&mut full, &DatumToken::<&str>::ListStart(0))
+        .unwrap();
+    let error = writer
+        .try_write_atom(&mut full, &DatumAtom::<&str>::Integer(10))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        DatumWriteError {
+            token_type: Some(crate::DatumTokenType::Numeric),
+            depth: 1
+        }
+    );
+    let error = writer.try_write_newline(&mut full).unwrap_err();
+    assert_eq!(error.token_type, None);
+    assert_eq!(
+        error.to_string(),
+        "failed writing whitespace at list depth 1"
+    );
+}
+
+#[test]
+fn writer_raw_atoms() {
+    let samples = [
+        "a", "", "+", "-", "+a", "-a", "-1", "1x", "a b", "a;b", "a\"b", "\\", "é", "#t", "a#b",
+        "(", "line\n", "tab\t",
+    ];
+    for sample in samples {
+        let mut escaped = String::new();
+        DatumToken::Symbol(0, sample).write(&mut escaped).unwrap();
+        let mut raw = String::new();
+        let ok = DatumWriter::default()
+            .write_raw_symbol(&mut raw, sample)
+            .is_ok();
+        assert_eq!(ok, escaped == sample, "symbol {:?}", sample);
+        assert_eq!(raw, if ok { sample } else { "" });
+        let mut escaped = String::new();
+        DatumToken::String(0, sample).write(&mut escaped).unwrap();
+        let mut raw = String::new();
+        let ok = DatumWriter::default()
+            .write_raw_string(&mut raw, sample)
+            .is_ok();
+        assert_eq!(
+            ok,
+            escaped[1..escaped.len() - 1] == *sample,
+            "string {:?}",
+            sample
+        );
+        if ok {
+            assert_eq!(raw, escaped);
+        }
+    }
+    let error = DatumWriter::default()
+        .write_raw_string(&mut String::new(), "ab\"c")
+        .unwrap_err();
+    assert_eq!(error.offset, 2);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "list guard dropped without end()")]
+fn writer_list_guard_leak() {
+    let mut text = String::new();
+    let mut writer = DatumWriter::default();
+    let list = writer.begin_list(&mut text).unwrap();
+    drop(list);
+}
+
 #[test]
 fn decoder_results_test() {
     let mut decoder = DatumDecoder::default();
@@ -268,6 +465,62 @@ fn decoder_results_test() {
     assert_eq!(tmp, out);
 }
 
+#[test]
+fn decoder_feed_str() {
+    let run = |text: &str, plus_sign: bool, split: usize| {
+        let mut decoder = DatumDecoder::default().plus_sign(plus_sign);
+        let mut out = Vec::new();
+        let mut f = |at, c| {
+            out.push((at, c));
+            Ok(())
+        };
+        let (a, b) = text.split_at(split);
+        let next = decoder.feed_str(0, a, &mut f)?;
+        let next = decoder.feed_str(next, b, &mut f)?;
+        decoder.feed(next, None, &mut f)?;
+        Ok(out)
+    };
+    for text in [
+        "(a \"b c\" 1.5 -2 +3)",
+        "caf\u{E9} \\x41;\\n\\(",
+        "a\r\nb\t;c",
+        "\\x10FFFF;\\x",
+        "bad\u{1}",
+    ] {
+        for plus_sign in [false, true] {
+            let mut decoder = DatumDecoder::default().plus_sign(plus_sign);
+            let mut expected = Vec::new();
+            let expected = text
+                .chars()
+                .enumerate()
+                .try_for_each(|(at, c)| {
+                    decoder.feed(at as DatumOffset, Some(c), &mut |at, c| {
+                        expected.push((at, c));
+                        Ok(())
+                    })
+                })
+                .and_then(|_| {
+                    decoder.feed(
+                        text.chars().count() as DatumOffset,
+                        None,
+                        &mut |_, _| Ok(()),
+                    )
+                })
+                .map(|_| expected);
+            for split in 0..=text.len() {
+                if text.is_char_boundary(split) {
+                    let result: DatumResult<Vec<(DatumOffset, DatumChar)>> =
+                        run(text, plus_sign, split);
+                    assert_eq!(result.is_ok(), expected.is_ok(), "{:?}", text);
+                    if let (Ok(result), Ok(expected)) = (result, &expected) {
+                        assert_eq!(&result, expected);
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[test]
 fn all_decoder_test_cases() {
     // -- also see byte_decoder.rs:byte_decoder_tests
@@ -345,6 +598,2208 @@ fn byte_decoder_tests() {
     byte_decoder_should_fail(&[0xC2, 0xC2]);
 }
 
+#[test]
+fn byte_decoder_feed_bytes() {
+    type Decoded = DatumResult<Vec<(DatumOffset, char)>>;
+    let one_at_a_time = |input: &[u8]| -> Decoded {
+        let mut decoder = DatumUTF8Decoder::default();
+        let mut out = Vec::new();
+        for (at, v) in input.iter().enumerate() {
+            decoder.feed(at as DatumOffset, Some(*v), &mut |at, c| {
+                out.push((at, c));
+                Ok(())
+            })?;
+        }
+        decoder.feed(input.len() as DatumOffset, None, &mut |_, _| Ok(()))?;
+        Ok(out)
+    };
+    let chunked = |input: &[u8], split: usize| -> Decoded {
+        let mut decoder = DatumUTF8Decoder::default();
+        let mut out = Vec::new();
+        let mut f = |at, c| {
+            out.push((at, c));
+            Ok(())
+        };
+        let next = decoder.feed_bytes(0, &input[..split], &mut f)?;
+        let next = decoder.feed_bytes(next, &input[split..], &mut f)?;
+        decoder.feed(next, None, &mut f)?;
+        Ok(out)
+    };
+    let inputs: [&[u8]; 6] = [
+        b"(plain ascii \"text\")",
+        "caf\u{E9} \u{1F600} \u{FFFF}".as_bytes(),
+        &[b'a', 0x80, b'b'],
+        &[b'a', 0xC2, 0xC2],
+        &[b'a', 0xE2, 0x82],
+        &[0xC2, 0x80, 0x80, 0x80, 0x80],
+    ];
+    for input in inputs {
+        let expected = one_at_a_time(input);
+        for split in 0..=input.len() {
+            match (chunked(input, split), &expected) {
+                (Ok(result), Ok(expected)) => assert_eq!(&result, expected),
+                (Err(result), Err(expected)) => {
+                    assert_eq!(
+                        (result.kind, result.offset),
+                        (expected.kind, expected.offset)
+                    )
+                }
+                (result, expected) => panic!("{:?} vs {:?} for {:?}", result, expected, input),
+            }
+        }
+    }
+}
+
+/// Minimal fixed-size string buffer, to check no-alloc pipelines work end to end.
+#[derive(Default)]
+struct FixedTokenBuffer([u8; 16], usize);
+
+impl core::fmt::Write for FixedTokenBuffer {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.1 + s.len();
+        if end > self.0.len() {
+            return Err(core::fmt::Error);
+        }
+        self.0[self.1..end].copy_from_slice(s.as_bytes());
+        self.1 = end;
+        Ok(())
+    }
+}
+
+impl core::ops::Deref for FixedTokenBuffer {
+    type Target = str;
+    fn deref(&self) -> &str {
+        core::str::from_utf8(&self.0[..self.1]).unwrap()
+    }
+}
+
+#[test]
+fn bounded_token_pipelines() {
+    // this is the worst case for the tokenizer: symbol ended by list end
+    let source = "(a)(b c)\"str\" 12 #t x)";
+    let pipeline: DatumByteToTokenPipeline<FixedTokenBuffer> = Default::default();
+    let tokens: Vec<String> = source
+        .bytes()
+        .via_datum_buf_pipe(pipeline)
+        .map(|v| v.unwrap().to_string())
+        .collect();
+    assert_eq!(
+        tokens,
+        ["(", "a", ")", "(", "b", "c", ")", "\"str\"", "12", "#t", "x", ")"]
+    );
+    let pipeline: DatumCharToTokenPipeline<FixedTokenBuffer> = Default::default();
+    assert_eq!(source.chars().via_datum_buf_pipe(pipeline).count(), 12);
+    // buffer overflow must error rather than panic
+    let pipeline: DatumCharToTokenPipeline<FixedTokenBuffer> = Default::default();
+    let err = "a_symbol_that_is_too_long"
+        .chars()
+        .via_datum_buf_pipe(pipeline)
+        .find_map(|v| v.err())
+        .unwrap();
+    assert_eq!(err.kind, DatumErrorKind::OutOfRoom);
+}
+
+#[test]
+fn feed_all_to_vec() {
+    let source = "(a 1) \"b\"";
+    let mut expected = Vec::new();
+    datum_char_to_token_pipeline()
+        .feed_iter_to_vec(&mut expected, source.chars(), true)
+        .unwrap();
+    let tokens = datum_char_to_token_pipeline()
+        .feed_all_to_vec(source.chars(), true)
+        .unwrap();
+    assert_eq!(tokens, expected);
+    // anything iterable will do
+    let chars: Vec<char> = source.chars().collect();
+    let tokens = datum_char_to_token_pipeline()
+        .feed_all_to_vec(chars.iter().copied(), true)
+        .unwrap();
+    assert_eq!(tokens, expected);
+    // errors replace the results
+    assert!(datum_char_to_value_pipeline()
+        .feed_all_to_vec("(a".chars(), true)
+        .is_err());
+    // without EOF, unfinished values stay in the pipeline
+    let mut pipeline = datum_char_to_value_pipeline();
+    let first = pipeline.feed_all_to_vec("1 (2".chars(), false).unwrap();
+    assert_eq!(first, [DatumValue::Atom(DatumAtom::Integer(1))]);
+    let rest = pipeline.feed_all_to_vec(") 3".chars(), true).unwrap();
+    assert_eq!(rest.len(), 2);
+}
+
+#[test]
+fn fused_iterators() {
+    // the decoder complains about each bad character, so this produces a value between errors
+    let source = "\x01 1 \x01";
+    let unfused: Vec<_> = source
+        .chars()
+        .via_datum_pipe(datum_char_to_value_pipeline())
+        .collect();
+    assert_eq!(unfused.len(), 3);
+    assert!(unfused[1].is_ok());
+    let fused: Vec<_> = source
+        .chars()
+        .via_datum_pipe(datum_char_to_value_pipeline())
+        .fuse_on_error()
+        .collect();
+    assert_eq!(fused.len(), 1);
+    assert!(fused[0].is_err());
+    let pipeline: DatumCharToTokenPipeline<String> = Default::default();
+    let fused: Vec<_> = source
+        .chars()
+        .via_datum_buf_pipe(pipeline)
+        .errors_are_fatal(true)
+        .collect();
+    assert_eq!(fused.len(), 1);
+}
+
+#[test]
+fn cancellation_is_fatal() {
+    use core::cell::Cell;
+    let cancel = Cell::new(false);
+    let pipeline = datum_char_to_value_pipeline().with_cancel(|| cancel.get());
+    let mut iterator = "1 2 3".chars().via_datum_pipe(pipeline);
+    assert_eq!(
+        iterator.next().unwrap().unwrap(),
+        DatumValue::Atom(DatumAtom::Integer(1))
+    );
+    cancel.set(true);
+    assert_eq!(
+        iterator.next().unwrap().unwrap_err().kind,
+        DatumErrorKind::Cancelled
+    );
+    cancel.set(false);
+    assert!(iterator.next().is_none());
+    let cancel = Cell::new(true);
+    let pipeline: DatumCharToTokenPipeline<String> = Default::default();
+    let mut iterator = "1 2 3"
+        .chars()
+        .via_datum_buf_pipe(pipeline.with_cancel(|| cancel.get()));
+    assert_eq!(
+        iterator.next().unwrap().unwrap_err().kind,
+        DatumErrorKind::Cancelled
+    );
+    assert!(iterator.next().is_none());
+}
+
+#[test]
+fn pipeline_metrics() {
+    use crate::DatumMetric;
+    let mut counters: Vec<(DatumMetric, u64)> = Vec::new();
+    let mut count = |metric, amount| match counters.iter_mut().find(|(m, _)| *m == metric) {
+        Some((_, total)) => *total += amount,
+        None => counters.push((metric, amount)),
+    };
+    let mut pipeline = datum_char_to_value_pipeline().with_metrics(&mut count);
+    pipeline.feed_all_to_vec("1 (2) 3".chars(), true).unwrap();
+    // errors from later on aren't this pipeline's
+    let mut pipeline = datum_char_to_value_pipeline().with_metrics(&mut count);
+    pipeline.feed(0, Some('4'), &mut |_, _| Ok(())).unwrap();
+    let res = pipeline.feed(1, Some(' '), &mut |at, _| {
+        Err(crate::datum_error!(Custom, at, "full"))
+    });
+    assert!(res.is_err());
+    let mut pipeline = datum_char_to_value_pipeline().with_metrics(&mut count);
+    assert!(pipeline.feed_all_to_vec("(".chars(), true).is_err());
+    let total = |metric| {
+        counters
+            .iter()
+            .find(|(m, _)| *m == metric)
+            .map(|(_, total)| *total)
+    };
+    assert_eq!(total(DatumMetric::Consumed), Some(10));
+    assert_eq!(total(DatumMetric::Emitted), Some(4));
+    assert_eq!(total(DatumMetric::Completed), Some(1));
+    assert_eq!(
+        total(DatumMetric::Error(DatumErrorKind::Interrupted)),
+        Some(1)
+    );
+    assert_eq!(counters.len(), 4);
+    assert_eq!(
+        DatumMetric::Error(DatumErrorKind::BadData).name(),
+        "error-bad-data"
+    );
+}
+
+#[test]
+fn pipe_writers() {
+    use crate::DatumPipeWriter;
+    use core::fmt::Write;
+    struct Point(i64, i64);
+    impl core::fmt::Display for Point {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "(point {} {})", self.0, self.1)
+        }
+    }
+    let mut values = Vec::new();
+    let mut writer = DatumPipeWriter::new(datum_char_to_value_pipeline(), |at, v| {
+        values.push((at, v));
+        Ok(())
+    });
+    write!(writer, "{} {}", Point(1, -2), Point(3, 4)).unwrap();
+    assert_eq!(writer.offset(), 24);
+    writer.finish().unwrap();
+    assert_eq!(values.len(), 2);
+    assert_eq!(values[1].0, 13);
+    assert_eq!(values[1].1.to_string(), "(point 3 4)");
+    // errors stop writing, and are kept
+    let mut writer = DatumPipeWriter::new(datum_char_to_value_pipeline(), |_, _| Ok(()));
+    assert!(writer.write_str("(a))").is_err());
+    assert_eq!(writer.offset(), 3);
+    assert!(writer.write_str("b").is_err());
+    let error = writer.error().unwrap();
+    assert_eq!(writer.finish().err().unwrap().offset, error.offset);
+    // including those from the output function
+    let mut writer = DatumPipeWriter::new(datum_char_to_value_pipeline(), |at, _| {
+        Err(crate::datum_error!(Custom, at, "full"))
+    });
+    assert!(write!(writer, "1 2").is_err());
+    assert_eq!(writer.error().unwrap().kind, DatumErrorKind::Custom);
+    // extending, into anything that can be extended
+    let mut values = std::collections::VecDeque::new();
+    let mut writer = DatumPipeWriter::new(
+        datum_byte_to_value_pipeline(),
+        crate::datum_extend_output(&mut values),
+    );
+    writer.extend("1 \u{1F600} (".bytes());
+    writer.extend(") ) 2".bytes());
+    // stopped at the stray list end
+    assert_eq!(writer.offset(), 10);
+    writer.extend("3".bytes());
+    assert_eq!(writer.offset(), 10);
+    assert!(writer.finish().is_err());
+    assert_eq!(values.len(), 3);
+    // incomplete input is found at EOF
+    let mut writer = DatumPipeWriter::new(datum_char_to_value_pipeline(), |_, _| Ok(()));
+    writer.write_str("(a").unwrap();
+    assert_eq!(
+        writer.finish().err().unwrap().kind,
+        DatumErrorKind::Interrupted
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn tracing_spans() {
+    use crate::DatumCompression;
+    use std::sync::Mutex;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    // `fields` of each span by ID, written as `name=value`
+    static SPANS: Mutex<Vec<(&str, Vec<String>)>> = Mutex::new(Vec::new());
+    struct Fields<'a>(&'a mut Vec<String>);
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+            self.0.push(std::format!("{}={:?}", field.name(), value));
+        }
+    }
+    struct Collector;
+    impl tracing::Subscriber for Collector {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut spans = SPANS.lock().unwrap();
+            let mut fields = Vec::new();
+            span.record(&mut Fields(&mut fields));
+            spans.push((span.metadata().name(), fields));
+            Id::from_u64(spans.len() as u64)
+        }
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = SPANS.lock().unwrap();
+            values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1].1));
+        }
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &tracing::Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+    // this is global, so other tests' spans may show up too
+    tracing::subscriber::set_global_default(Collector).unwrap();
+    let mut pipeline = datum_char_to_value_pipeline().with_tracing("tracing-test");
+    pipeline.feed_all_to_vec("(a) (b)".chars(), true).unwrap();
+    let doc: DatumDocument = "(a) (b) (c)".parse().unwrap();
+    let file = doc
+        .write_to(Vec::new(), DatumCompression::None, &Default::default())
+        .unwrap();
+    DatumDocument::read_from(&file[..]).unwrap();
+    let spans = SPANS.lock().unwrap();
+    let find = |name: &str, field: &str| {
+        spans
+            .iter()
+            .any(|(n, fields)| *n == name && fields.iter().any(|f| f == field))
+    };
+    assert!(find("datum_pipe", "stage=\"tracing-test\""));
+    assert!(spans.iter().any(|(n, fields)| *n == "datum_pipe"
+        && fields.iter().any(|f| f == "inputs=7")
+        && fields.iter().any(|f| f == "outputs=2")));
+    assert!(find("datum_write_document", "bytes=12"));
+    assert!(find("datum_read_document", "values=3"));
+    assert!(find("datum_read_document", "compression=None"));
+}
+
+#[test]
+fn document_conversions() {
+    let text = "(a \"b\") 1 #t\n";
+    let doc = DatumDocument::try_from(text).unwrap();
+    assert_eq!(doc.len(), 3);
+    assert_eq!(DatumDocument::try_from(text.as_bytes()).unwrap(), doc);
+    assert_eq!(text.parse::<DatumDocument>().unwrap(), doc);
+    assert_eq!(doc.to_string(), "(a \"b\")\n1\n#t\n");
+    let values: Vec<DatumValue> = doc.into();
+    assert_eq!(values.len(), 3);
+    // offsets are in the units of the input
+    let err = DatumDocument::try_from("£)").unwrap_err();
+    assert_eq!(err.offset, 1);
+    let err = DatumDocument::try_from("£)".as_bytes()).unwrap_err();
+    assert_eq!(err.offset, 2);
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn canonical_digests() {
+    let a: DatumDocument = "(a\t#x10) ; comment\n#T".parse().unwrap();
+    let b: DatumDocument = "(a 16)\n#t\n".parse().unwrap();
+    assert_eq!(a.canonical_sha256(), b.canonical_sha256());
+    assert_eq!(a[0].canonical_sha256(), b[0].canonical_sha256());
+    assert_ne!(a[0].canonical_sha256(), b[1].canonical_sha256());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn compressed_documents() {
+    use crate::DatumCompression;
+    let doc: DatumDocument = "(save (level 3) (name \"\u{1F600}\"))".parse().unwrap();
+    #[cfg_attr(not(any(feature = "flate2", feature = "zstd")), allow(unused_mut))]
+    let mut kinds = std::vec![DatumCompression::None];
+    #[cfg(feature = "flate2")]
+    kinds.push(DatumCompression::Gzip);
+    #[cfg(feature = "zstd")]
+    kinds.push(DatumCompression::Zstd);
+    for kind in kinds {
+        let file = doc
+            .write_to(Vec::new(), kind, &DatumDocumentStyle::default())
+            .unwrap();
+        assert_eq!(DatumCompression::detect(&file), kind);
+        assert_eq!(DatumDocument::read_from(&file[..]).unwrap(), doc);
+        // truncated files are errors, whether in the compression or the document
+        assert!(DatumDocument::read_from(&file[..file.len() - 4]).is_err());
+    }
+    assert!(DatumDocument::read_from(&b"(a"[..]).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn directory_loading() {
+    use crate::{DatumDirLoader, DatumFileError};
+    let dir = std::env::temp_dir().join(std::format!("datum-dir-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::create_dir_all(dir.join(".git")).unwrap();
+    std::fs::write(dir.join("b.scm"), "b").unwrap();
+    std::fs::write(dir.join("a.datum"), "a").unwrap();
+    std::fs::write(dir.join(".a.scm"), "hidden").unwrap();
+    std::fs::write(dir.join(".git").join("c.scm"), "hidden").unwrap();
+    std::fs::write(dir.join("sub").join("c.scm"), "\"c").unwrap();
+    std::fs::write(dir.join("sub").join("d.scm"), "d").unwrap();
+    let names = |report: &crate::DatumDirReport| -> Vec<String> {
+        report
+            .files
+            .iter()
+            .map(|file| {
+                let path = file.path.strip_prefix(&dir).unwrap();
+                path.to_string_lossy().replace('\\', "/")
+            })
+            .collect()
+    };
+    let report = DatumDirLoader::new().load(&dir).unwrap();
+    assert_eq!(names(&report), ["a.datum", "b.scm"]);
+    assert!(report.is_ok());
+    assert_eq!(report.to_string(), "");
+    let report = DatumDirLoader::new().recursive(true).load(&dir).unwrap();
+    assert_eq!(
+        names(&report),
+        ["a.datum", "b.scm", "sub/c.scm", "sub/d.scm"]
+    );
+    assert_eq!(report.documents().count(), 3);
+    let errors: Vec<_> = report.errors().collect();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].0.ends_with("c.scm"));
+    match errors[0].1 {
+        DatumFileError::Parse(e) => assert!(e.source_name.as_ref().unwrap().ends_with("c.scm")),
+        DatumFileError::Io(e) => panic!("{}", e),
+    }
+    let diagnostics: Vec<_> = report.diagnostics().collect();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].1.code, "interrupted");
+    assert!(report.to_string().ends_with('\n'));
+    // missing directories are errors in themselves
+    assert!(DatumDirLoader::new().load(dir.join("missing")).is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn document_stores() {
+    use crate::{DatumFileStore, DatumMemoryStore, DatumStore};
+    use std::io::ErrorKind;
+    let dir = std::env::temp_dir().join(std::format!("datum-store-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut memory = DatumMemoryStore::default();
+    let mut files = DatumFileStore::new(&dir);
+    let doc: DatumDocument = "(a 1)".parse().unwrap();
+    // the same behaviour either way
+    let stores: [&mut dyn DatumStore; 2] = [&mut memory, &mut files];
+    for store in stores {
+        assert!(store.names().unwrap().is_empty());
+        assert_eq!(store.load("a.scm").unwrap_err().kind(), ErrorKind::NotFound);
+        for name in &["a.scm", "b.datum", "x/a.scm", "x/y/a.scm", "x/ab.scm"] {
+            store.save(name, &doc).unwrap();
+        }
+        assert_eq!(store.load("x/y/a.scm").unwrap(), doc);
+        assert_eq!(store.list("*.scm").unwrap(), ["a.scm"]);
+        assert_eq!(store.list("x/?.scm").unwrap(), ["x/a.scm"]);
+        assert_eq!(store.list("x/*").unwrap(), ["x/a.scm", "x/ab.scm"]);
+        assert_eq!(store.list("**/a.scm").unwrap(), ["x/a.scm", "x/y/a.scm"]);
+        assert_eq!(store.list("**").unwrap().len(), 5);
+        assert_eq!(store.list("b").unwrap().len(), 0);
+    }
+    for name in &[
+        "",
+        "/a.scm",
+        "x//a.scm",
+        "./a.scm",
+        "x/../a.scm",
+        "x\\a.scm",
+    ] {
+        assert_eq!(
+            files.load(name).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+    }
+    // no temporary files left behind
+    assert!(!dir.join("a.scm.tmp").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "tokio_codec")]
+#[test]
+fn codec_framing() {
+    use crate::{DatumCodec, DatumCodecFraming};
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+    let doc: DatumDocument = "(a \"multi\nline\") 1 ()".parse().unwrap();
+    for framing in [
+        DatumCodecFraming::LengthPrefixed,
+        DatumCodecFraming::NewlineDelimited,
+    ]
+    .iter()
+    {
+        let mut codec = DatumCodec::new(*framing);
+        let mut wire = BytesMut::new();
+        for value in doc.iter() {
+            codec.encode(value, &mut wire).unwrap();
+        }
+        // fed a byte at a time
+        let mut buffer = BytesMut::new();
+        let mut received = Vec::new();
+        for byte in wire.iter() {
+            buffer.extend_from_slice(&[*byte]);
+            if let Some(value) = codec.decode(&mut buffer).unwrap() {
+                received.push(value);
+            }
+        }
+        assert_eq!(received, doc.0);
+        assert_eq!(codec.decode_eof(&mut buffer).unwrap(), None);
+        // truncated
+        let mut buffer = BytesMut::from(&wire[..wire.len() - 2]);
+        while codec.decode(&mut buffer).unwrap().is_some() {}
+        assert!(codec.decode_eof(&mut buffer).is_err());
+        // too long
+        codec.max_length = 2;
+        assert!(codec.encode(&doc[0], &mut BytesMut::new()).is_err());
+        assert!(codec.decode(&mut wire.clone()).is_err());
+    }
+    let mut codec = DatumCodec::new(DatumCodecFraming::NewlineDelimited);
+    let mut buffer = BytesMut::from(&b"\n; comment\r\n(a) (b)\n1"[..]);
+    assert!(codec.decode(&mut buffer).is_err());
+    assert_eq!(
+        codec.decode_eof(&mut buffer).unwrap(),
+        Some(DatumValue::Atom(DatumAtom::Integer(1)))
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn newline_delimited() {
+    use crate::{DatumLineReader, DatumLineWriter};
+    let doc: DatumDocument = "(a \"multi\nline\" (b)) 1 () \"\"".parse().unwrap();
+    let mut writer = DatumLineWriter::new(Vec::new());
+    for value in doc.iter() {
+        writer.write_value(value).unwrap();
+    }
+    let mut log = writer.into_inner();
+    assert_eq!(log.iter().filter(|b| **b == b'\n').count(), doc.len());
+    // damage: a partial line, a line with two values, a bad escape, CRLF
+    log.extend_from_slice(b"(partial\n1 2\n\"\\xzz;\"\r\n(after)\r\n");
+    let lines: Vec<_> = DatumLineReader::new(&log[..])
+        .map(|line| line.unwrap())
+        .collect();
+    let values: Vec<_> = lines
+        .iter()
+        .filter_map(|line| line.value.clone().ok())
+        .collect();
+    assert_eq!(values[..doc.len()], doc[..]);
+    assert_eq!(values[doc.len()].to_string(), "(after)");
+    let errors: Vec<_> = lines
+        .iter()
+        .filter(|line| line.value.is_err())
+        .map(|line| line.number)
+        .collect();
+    assert_eq!(errors, [5, 6, 7]);
+}
+
+#[test]
+fn token_traces() {
+    use crate::{DatumTrace, DatumTraceRecorder};
+    use core::cell::RefCell;
+    let record = |source: &str| {
+        let trace = RefCell::new(DatumTrace::default());
+        let mut pipeline = datum_char_to_token_pipeline().compose(DatumTraceRecorder::new(&trace));
+        let _ = pipeline.feed_all_to_vec(source.chars(), true);
+        trace.into_inner()
+    };
+    let source = "(a \"b\" #t 1 -2.5 #i+inf.0 (())) sym";
+    let trace = record(source);
+    assert_eq!(trace.tokens.len(), 13);
+    let loaded: DatumTrace = trace.to_string().parse().unwrap();
+    assert_eq!(loaded, trace);
+    let expected = DatumDocument::try_from(source).unwrap();
+    assert_eq!(loaded.replay(DatumParser::default()).unwrap(), expected.0);
+    // redaction keeps structure
+    let mut redacted = loaded.clone();
+    redacted.redact(|token| !matches!(token, DatumToken::SpecialID(_, _)));
+    let values = redacted.replay(DatumParser::default()).unwrap();
+    assert_eq!(values[0].to_string(), "(x \"x\" #t 0 0.0 #i+inf.0 (()))");
+    // parser errors reproduce at the same offset
+    let broken = record("(a)) b");
+    let direct = DatumDocument::try_from("(a)) b").unwrap_err();
+    let replayed = broken.replay(DatumParser::default()).unwrap_err();
+    assert_eq!(replayed.offset, direct.offset);
+    for bad in [
+        "1",
+        "(0 bogus)",
+        "(0 integer \"1\")",
+        "(0 eof) (1 eof)",
+        "(-1 list-end)",
+    ]
+    .iter()
+    {
+        assert!(bad.parse::<DatumTrace>().is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn redaction() {
+    use crate::datum_redact;
+    let redact = |source: &str, path: &str| {
+        datum_redact(source, &DatumAtom::Nil, |p| p.to_string() == path).unwrap()
+    };
+    let source = "a (b 1 c (2 3)) d ((\"x\\\"y\" 1) secret\\ sym)";
+    assert_eq!(
+        redact(source, "a.b"),
+        "a (b #nil c (2 3)) d ((\"x\\\"y\" 1) secret\\ sym)"
+    );
+    // whole lists, and lists that aren't maps
+    assert_eq!(
+        redact(source, "a.c"),
+        "a (b 1 c #nil) d ((\"x\\\"y\" 1) secret\\ sym)"
+    );
+    assert_eq!(
+        redact(source, "a.c[1]"),
+        "a (b 1 c (2 #nil)) d ((\"x\\\"y\" 1) secret\\ sym)"
+    );
+    assert_eq!(
+        redact(source, "d[1]"),
+        "a (b 1 c (2 3)) d ((\"x\\\"y\" 1) #nil)"
+    );
+    assert_eq!(
+        redact(source, "d[0].x\"y"),
+        "a (b 1 c (2 3)) d ((\"x\\\"y\" #nil) secret\\ sym)"
+    );
+    assert_eq!(redact(source, "d"), "a (b 1 c (2 3)) d #nil");
+    assert_eq!(redact(source, "nothing"), source);
+    // documents that aren't key/value pairs
+    assert_eq!(redact("1 2 3", "[1]"), "1 #nil 3");
+    assert!(datum_redact("(a", &DatumAtom::Nil, |_| true).is_err());
+}
+
+#[test]
+fn symbol_renaming() {
+    use crate::{datum_rename_symbol, datum_rename_symbol_values};
+    // escaped spellings, symbols at the end of the text, and non-ASCII before them
+    let source = "(é a\\ b) ;a b\n\"a b\" a\\ b";
+    let (renamed, count) = datum_rename_symbol(source, "a b", "c").unwrap();
+    assert_eq!(renamed, "(é c) ;a b\n\"a b\" c");
+    assert_eq!(count, 2);
+    // names that need escaping or look like other tokens
+    let (renamed, _) = datum_rename_symbol("x (x)", "x", "1").unwrap();
+    assert_eq!(renamed, "\\1 (\\1)");
+    assert_eq!(
+        datum_rename_symbol("xy x", "y", "z").unwrap(),
+        ("xy x".to_string(), 0)
+    );
+    assert!(datum_rename_symbol("(x", "x", "y").is_err());
+    // both ways agree
+    let source = "(define (f x) (g x \"x\" #t))";
+    let mut doc: DatumDocument = source.parse().unwrap();
+    assert_eq!(datum_rename_symbol_values(&mut doc, "x", "y z"), 2);
+    let renamed: DatumDocument = datum_rename_symbol(source, "x", "y z")
+        .unwrap()
+        .0
+        .parse()
+        .unwrap();
+    assert_eq!(doc, renamed);
+}
+
+#[test]
+fn pattern_matching() {
+    use crate::{DatumBinding, DatumPattern};
+    let doc: DatumDocument = "(f) (f 1) (f 1 2 3) (g 1 1) (g 1 2) ($x (a b)) (h 1 . 9)"
+        .parse()
+        .unwrap();
+    let matching = |pattern: &DatumPattern| -> Vec<usize> {
+        (0..doc.len())
+            .filter(|i| pattern.matches(&doc[*i]).is_some())
+            .collect()
+    };
+    // rest variables can be empty, and can go anywhere
+    let p: DatumPattern = "(f $rest...)".parse().unwrap();
+    assert_eq!(matching(&p), [0, 1, 2]);
+    let p: DatumPattern = "($_ $_... 3)".parse().unwrap();
+    assert_eq!(matching(&p), [2]);
+    let p: DatumPattern = "($_... $last)".parse().unwrap();
+    let bindings = p.matches(&doc[6]).unwrap();
+    assert_eq!(bindings.one("last"), Some(&doc[6].as_list().unwrap()[3]));
+    assert_eq!(bindings.0.len(), 1);
+    // repeated variables must be equal
+    let p: DatumPattern = "(g $a $a)".parse().unwrap();
+    assert_eq!(matching(&p), [3]);
+    // escaped variables, and nested lists
+    let p: DatumPattern = "($$x ($a $b))".parse().unwrap();
+    let bindings = p.matches(&doc[5]).unwrap();
+    assert_eq!(bindings.one("a").unwrap().to_string(), "a");
+    assert_eq!(bindings.many("a"), None);
+    // built from values
+    let p = DatumPattern::new(DatumValue::List(alloc::vec![
+        DatumValue::Atom(DatumAtom::Symbol("f".to_string())),
+        DatumPattern::var("first"),
+        DatumPattern::rest("more"),
+    ]))
+    .unwrap();
+    assert_eq!(p.value().to_string(), "(f $first $more...)");
+    let bindings = p.matches(&doc[2]).unwrap();
+    assert_eq!(
+        bindings.0["more"],
+        DatumBinding::Many(&doc[2].as_list().unwrap()[2..])
+    );
+    // bad patterns
+    assert!("($a... $b...)".parse::<DatumPattern>().is_err());
+    assert!("(a) (b)".parse::<DatumPattern>().is_err());
+    assert!("".parse::<DatumPattern>().is_err());
+}
+
+#[test]
+fn rewriting() {
+    use crate::{DatumRewriteRule, DatumRewriter};
+    let parse = |text: &str| text.parse::<DatumDocument>().unwrap().0.remove(0);
+    // a cond macro defined in terms of itself, with literal `$` symbols
+    let rewriter = DatumRewriter::new(alloc::vec![
+        DatumRewriteRule::parse("(cond (else $x))", "$x").unwrap(),
+        DatumRewriteRule::parse("(cond ($c $x) $more...)", "(if $c $x (cond $more...))").unwrap(),
+        DatumRewriteRule::parse("(var $name)", "($$get $name)").unwrap(),
+    ]);
+    let value = parse("(f (cond (a (var p)) (b 2) (else 3)))");
+    assert_eq!(
+        rewriter.rewrite(value).unwrap().to_string(),
+        "(f (if a ($get p) (if b 2 3)))"
+    );
+    // a rewritten element can make its list match
+    let rewriter = DatumRewriter::new(alloc::vec![
+        DatumRewriteRule::parse("(zero)", "0").unwrap(),
+        DatumRewriteRule::parse("(+ 0 $x)", "$x").unwrap(),
+    ]);
+    assert_eq!(
+        rewriter.rewrite(parse("(+ (zero) (+ (zero) 1))")).unwrap(),
+        parse("1")
+    );
+    // limits
+    let rewriter = DatumRewriter::new(alloc::vec![DatumRewriteRule::parse("x", "(x)").unwrap()]);
+    assert_eq!(
+        rewriter
+            .clone()
+            .max_steps(5)
+            .rewrite(parse("x"))
+            .unwrap_err()
+            .kind,
+        DatumErrorKind::OutOfRoom
+    );
+    assert_eq!(
+        rewriter.max_depth(5).rewrite(parse("x")).unwrap_err().kind,
+        DatumErrorKind::OutOfRoom
+    );
+    // bad rules
+    assert!(DatumRewriteRule::parse("(a $x...)", "$x...").is_err());
+    assert!(DatumRewriteRule::parse("(a $x...)", "(b $x)").is_err());
+    assert!(DatumRewriteRule::parse("(a $x)", "(b $x...)").is_err());
+    assert!(DatumRewriteRule::parse("(a $_)", "(b $_)").is_err());
+    assert!(DatumRewriteRule::parse("(a)", "b c").is_err());
+}
+
+#[test]
+fn evaluator_scaffold() {
+    use crate::{DatumEnvironment, DatumError, DatumEvaluator};
+    /// Strings, with `let` as a special form and scoped variables.
+    struct Strings(DatumEnvironment<String>);
+    impl DatumEvaluator for Strings {
+        type Output = String;
+        type Error = DatumError;
+        fn eval_atom(&mut self, atom: &DatumAtom<String>) -> Result<String, DatumError> {
+            match atom {
+                DatumAtom::String(v) => Ok(v.clone()),
+                DatumAtom::Symbol(v) => self
+                    .0
+                    .get(v)
+                    .cloned()
+                    .ok_or(crate::datum_error!(BadData, 0, "unbound")),
+                _ => Ok(atom.to_string()),
+            }
+        }
+        fn eval_special_form(
+            &mut self,
+            head: &str,
+            args: &[DatumValue],
+        ) -> Option<Result<String, DatumError>> {
+            let (name, value, body) = match (head, args) {
+                ("let", [DatumValue::Atom(DatumAtom::Symbol(name)), value, body]) => {
+                    (name, value, body)
+                }
+                _ => return None,
+            };
+            Some(self.eval(value).and_then(|value| {
+                self.0.push_scope();
+                self.0.define(name, value);
+                let result = self.eval(body);
+                self.0.pop_scope();
+                result
+            }))
+        }
+        fn eval_call(&mut self, head: &str, args: Vec<String>) -> Result<String, DatumError> {
+            match head {
+                "cat" => Ok(args.concat()),
+                _ => Err(crate::datum_error!(BadData, 0, "no such function")),
+            }
+        }
+    }
+    let mut strings = Strings(DatumEnvironment::new());
+    assert_eq!(
+        strings
+            .eval_str("(let x \"a\" (let y (cat x 1) (cat x y))) (cat)")
+            .unwrap(),
+        ["aa1", ""]
+    );
+    assert_eq!(strings.0.depth(), 1);
+    // evaluation errors are at the start of the failing form, parse errors where the parser says
+    let error = strings
+        .eval_str("(cat) \"é\" (cat \"a\" (nope))")
+        .unwrap_err();
+    // (messages are empty without detailed_errors)
+    assert_eq!(
+        (error.offset, error.error.message),
+        (
+            10,
+            crate::datum_error!(BadData, 0, "no such function").message
+        )
+    );
+    assert_eq!(strings.eval_str("x").unwrap_err().offset, 0);
+    assert_eq!(
+        strings.eval_str("(cat) ()").unwrap_err().error.kind,
+        DatumErrorKind::BadData
+    );
+    let error = strings.eval_str("\"a\" (cat").unwrap_err();
+    assert_eq!(
+        (error.offset, error.error.kind),
+        (8, DatumErrorKind::Interrupted)
+    );
+}
+
+#[test]
+fn repl_buffer() {
+    use crate::repl::DatumRepl;
+    use crate::DatumBalance;
+    let mut repl = DatumRepl::new("$ ", "| ");
+    // strings continue over lines; comments end with them
+    assert_eq!(repl.push_line("(a \"b"), DatumBalance::Incomplete);
+    assert_eq!(repl.push_line(")\" c\\;"), DatumBalance::Incomplete);
+    assert_eq!(repl.prompt(), "| ");
+    assert_eq!(repl.push_line("d ; )\r\n"), DatumBalance::Incomplete);
+    assert_eq!(repl.depth(), 1);
+    assert_eq!(repl.push_line(")"), DatumBalance::Complete);
+    assert_eq!(repl.pending(), "(a \"b\n)\" c\\;\nd ; )\n)\n");
+    let doc: DatumDocument = repl.take().parse().unwrap();
+    assert_eq!(doc.len(), 1);
+    assert!(repl.is_empty());
+    assert_eq!(repl.prompt(), "$ ");
+    // invalid input stays invalid until taken
+    assert_eq!(repl.push_line("a) (b"), DatumBalance::Invalid);
+    assert_eq!(repl.push_line(")"), DatumBalance::Invalid);
+    repl.take();
+    // escapes can't continue over lines
+    assert_eq!(repl.push_line("(a\\"), DatumBalance::Invalid);
+    repl.take();
+    assert_eq!(repl.push_line("(b)"), DatumBalance::Complete);
+}
+
+#[test]
+fn completion_context() {
+    use crate::datum_completion_context;
+    let context = |source: &str| {
+        let c = datum_completion_context(source, source.chars().count()).unwrap();
+        let forms: Vec<(u64, Option<String>, usize)> = c
+            .forms
+            .iter()
+            .map(|f| (f.start, f.head.clone(), f.index))
+            .collect();
+        (forms, c.partial, c.partial_start, c.in_string, c.in_comment)
+    };
+    // closed lists are single elements, and heads have to be symbols
+    assert_eq!(
+        context("é (a (b c) ((d) x\\ y"),
+        (
+            alloc::vec![(2, Some("a".to_string()), 2), (11, None, 1)],
+            "x y".to_string(),
+            16,
+            false,
+            false
+        )
+    );
+    assert_eq!(context("a b").0, []);
+    assert_eq!(context("(1 ").0, [(0, None, 1)]);
+    // strings and comments
+    let (_, partial, _, in_string, _) = context("(a \"b c");
+    assert_eq!((partial.as_str(), in_string), ("", true));
+    let (_, partial, _, _, in_comment) = context("(a ; b");
+    assert_eq!((partial.as_str(), in_comment), ("", true));
+    assert_eq!(context("(a ; b\n c").1, "c");
+    // the cursor is clamped, and only what's before it matters
+    let c = datum_completion_context("(ab)) )", 2).unwrap();
+    assert_eq!((c.forms.len(), c.partial.as_str()), (1, "a"));
+    assert_eq!(datum_completion_context("x", 100).unwrap().partial, "x");
+    assert!(datum_completion_context("a) (b", 5).is_err());
+}
+
+#[test]
+fn highlight_spans() {
+    use crate::{DatumHighlightKind as K, DatumHighlighter};
+    let spans = |source: &str| -> Vec<(K, String, usize)> {
+        let spans: Vec<_> = DatumHighlighter::new(source).collect();
+        // spans cover everything, in order
+        let text: String = spans.iter().map(|s| s.source_text(source)).collect();
+        assert_eq!(text, source);
+        spans
+            .iter()
+            .map(|s| (s.kind, s.source_text(source).to_string(), s.depth))
+            .collect()
+    };
+    assert_eq!(
+        spans("é\\ x) #t\t;(\n\"a\\\"b\"-1"),
+        [
+            (K::Symbol, "é\\ x".to_string(), 0),
+            (K::ListEnd, ")".to_string(), 0),
+            (K::Whitespace, " ".to_string(), 0),
+            (K::SpecialID, "#t".to_string(), 0),
+            (K::Whitespace, "\t".to_string(), 0),
+            (K::Comment, ";(".to_string(), 0),
+            (K::Whitespace, "\n".to_string(), 0),
+            (K::String, "\"a\\\"b\"".to_string(), 0),
+            (K::Numeric, "-1".to_string(), 0),
+        ]
+    );
+    // bad escapes make the rest invalid
+    assert_eq!(
+        spans("((a) b\\xZ; c)"),
+        [
+            (K::ListStart, "(".to_string(), 0),
+            (K::ListStart, "(".to_string(), 1),
+            (K::Symbol, "a".to_string(), 2),
+            (K::ListEnd, ")".to_string(), 1),
+            (K::Whitespace, " ".to_string(), 1),
+            (K::Invalid, "b\\xZ; c)".to_string(), 1),
+        ]
+    );
+    assert_eq!(spans(""), []);
+    assert_eq!(spans(";"), [(K::Comment, ";".to_string(), 0)]);
+}
+
+#[test]
+fn folding_ranges() {
+    use crate::datum_folding_ranges;
+    let ranges = |source: &str| -> Vec<(u64, u64, usize, usize, usize)> {
+        datum_folding_ranges(source)
+            .iter()
+            .map(|r| (r.span.start, r.span.end, r.start_line, r.end_line, r.depth))
+            .collect()
+    };
+    // newlines in strings and comments count, and offsets are in characters
+    assert_eq!(
+        ranges("(é \"a\nb\") ; (\n(x ; )\n)"),
+        [(0, 8, 0, 1, 0), (14, 21, 2, 3, 0)]
+    );
+    // stray list ends and unclosed lists are ignored
+    assert_eq!(ranges(")\n(a\n(b\n)"), [(5, 8, 2, 3, 1)]);
+    assert_eq!(ranges("(a b)\n(c)"), []);
+}
+
+#[test]
+fn document_outline() {
+    use crate::{datum_outline, DatumOutlineEntry};
+    let source = "; é\n(a\\ b \"n\\\"m\") (1 #t) ((x) (y z)) (def ((f)) 2)\n(open x";
+    let outline = datum_outline(source);
+    let entry =
+        |head: Option<&str>, name: Option<(&str, u64, u64)>, span: (u64, u64)| DatumOutlineEntry {
+            head: head.map(String::from),
+            name: name.map(|n| n.0.to_string()),
+            name_span: name.map(|n| DatumSpan {
+                start: n.1,
+                end: n.2,
+            }),
+            span: DatumSpan {
+                start: span.0,
+                end: span.1,
+            },
+            start_line: 1,
+            end_line: 1,
+        };
+    assert_eq!(
+        outline,
+        [
+            entry(Some("a b"), Some(("n\"m", 10, 15)), (4, 16)),
+            entry(None, Some(("#t", 21, 22)), (18, 23)),
+            entry(None, Some(("y", 31, 31)), (25, 35)),
+            entry(Some("def"), None, (37, 49)),
+        ]
+    );
+    let text: String = source.chars().skip(37).take(13).collect();
+    assert_eq!(text, "(def ((f)) 2)");
+}
+
+#[test]
+fn definition_index() {
+    use crate::DatumDefinitionIndex;
+    let mut index = DatumDefinitionIndex::new()
+        .rule("define", 1)
+        .rule("let-var", 2);
+    let source = "; é\n(define ((curried a) b)\n  (define inner 1)\n  (let-var x y\\ z))\n(f define g) (define \"s\" 1) (define h";
+    index.add_document("a", source);
+    let found = |index: &DatumDefinitionIndex, symbol: &str| -> Vec<(u64, u64, usize, u64, u64)> {
+        index
+            .get(symbol)
+            .iter()
+            .map(|d| (d.span.start, d.span.end, d.line, d.form.start, d.form.end))
+            .collect()
+    };
+    // heads of lists in defining positions, at any depth
+    assert_eq!(found(&index, "curried"), [(14, 20, 1, 4, 65)]);
+    assert_eq!(found(&index, "inner"), [(38, 42, 2, 30, 45)]);
+    // escaped symbols, and positions other than 1
+    assert_eq!(found(&index, "y z"), [(60, 63, 3, 49, 64)]);
+    assert!(index.get("x").is_empty());
+    // only heads count, strings aren't symbols, and open forms run to the end
+    assert!(index.get("g").is_empty());
+    assert!(index.get("s").is_empty());
+    assert_eq!(found(&index, "h"), [(103, 103, 4, 95, 103)]);
+    assert_eq!(index.definitions().len(), 4);
+    index.add_document("b", "(define inner 2)");
+    assert_eq!(index.get("inner").len(), 2);
+    index.remove_document("a");
+    assert_eq!(index.definitions().len(), 1);
+    assert_eq!(index.get("inner")[0].document, "b");
+}
+
+#[test]
+fn binary_encoding() {
+    use crate::{datum_binary_decode, datum_binary_encode};
+    let doc: DatumDocument =
+        "(a \"b\\x0;\" (c (d)) ()) 0 23 24 -1 -24 -25 65536 9223372036854775807 -9223372036854775808 1.5 -inf #t #f #nil"
+            .parse()
+            .unwrap();
+    let cache = doc.to_binary();
+    assert_eq!(DatumDocument::from_binary(&cache).unwrap(), doc);
+    // heads are minimal
+    let ints: DatumDocument = "23 24 -24 -25 256".parse().unwrap();
+    assert_eq!(
+        ints.to_binary()[4..],
+        [0x17, 0x18, 0x18, 0x37, 0x38, 0x18, 0x19, 0x01, 0x00]
+    );
+    // non-minimal heads and 32-bit floats (from other CBOR encoders) are accepted
+    assert_eq!(
+        datum_binary_decode(&[
+            0xD9, 0xD9, 0xF7, 0x98, 0x02, 0x19, 0x00, 0x05, 0xFA, 0x3F, 0xC0, 0x00, 0x00
+        ])
+        .unwrap(),
+        DatumDocument::try_from("5 1.5").unwrap().0
+    );
+    // truncation anywhere is Interrupted, at the end of the data
+    for len in 0..cache.len() {
+        let err = datum_binary_decode(&cache[..len]).unwrap_err();
+        assert_eq!(err.kind, DatumErrorKind::Interrupted);
+        assert_eq!(err.offset, len as DatumOffset);
+    }
+    let bad = |bytes: &[u8], offset: DatumOffset| {
+        let err = datum_binary_decode(bytes).unwrap_err();
+        assert_eq!(err.kind, DatumErrorKind::BadData);
+        assert_eq!(err.offset, offset);
+    };
+    // not the magic
+    bad(b"(a)", 0);
+    // top level isn't an array
+    bad(&[0xD9, 0xD9, 0xF7, 0x01], 3);
+    // trailing data
+    bad(&[0xD9, 0xD9, 0xF7, 0x80, 0x00], 4);
+    // invalid UTF-8, in a string and a symbol
+    bad(&[0xD9, 0xD9, 0xF7, 0x81, 0x61, 0xFF], 4);
+    bad(&[0xD9, 0xD9, 0xF7, 0x81, 0xD8, 0x27, 0x61, 0xFF], 6);
+    // tagged non-text, unknown tags, byte strings, indefinite lengths, out of range integers
+    bad(&[0xD9, 0xD9, 0xF7, 0x81, 0xD8, 0x27, 0x01], 6);
+    bad(&[0xD9, 0xD9, 0xF7, 0x81, 0xC1, 0x01], 4);
+    bad(&[0xD9, 0xD9, 0xF7, 0x81, 0x41, 0x00], 4);
+    bad(&[0xD9, 0xD9, 0xF7, 0x81, 0x9F, 0xFF], 4);
+    bad(
+        &[0xD9, 0xD9, 0xF7, 0x81, 0x1B, 0x80, 0, 0, 0, 0, 0, 0, 0],
+        4,
+    );
+    // huge claimed lengths don't allocate
+    assert_eq!(
+        datum_binary_decode(&[
+            0xD9, 0xD9, 0xF7, 0x9B, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF
+        ])
+        .unwrap_err()
+        .kind,
+        DatumErrorKind::Interrupted
+    );
+    // ...even when nested, where each open list would reserve its own
+    let mut nested = alloc::vec![0xD9, 0xD9, 0xF7];
+    for _ in 0..20000 {
+        nested.extend_from_slice(&[0x9B, 0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+    }
+    assert_eq!(
+        datum_binary_decode(&nested).unwrap_err().kind,
+        DatumErrorKind::Interrupted
+    );
+    // deep nesting doesn't recurse
+    let mut deep = DatumValue::Atom(DatumAtom::Nil);
+    for _ in 0..100000 {
+        deep = DatumValue::List(alloc::vec![deep]);
+    }
+    let mut cache = Vec::new();
+    datum_binary_encode(core::slice::from_ref(&deep), &mut cache);
+    deep.drop_iteratively();
+    let decoded = datum_binary_decode(&cache).unwrap();
+    assert_eq!(DatumValueMetrics::measure(&decoded).depth, 100000);
+    for v in decoded {
+        v.drop_iteratively();
+    }
+}
+
+#[test]
+fn stream_writer_tests() {
+    let doc: DatumDocument = "(a (b \"c\") ()) 1".parse().unwrap();
+    let mut out = String::new();
+    let mut writer = DatumStreamWriter::new(&mut out, true);
+    for v in doc.iter() {
+        writer.value(v).unwrap();
+    }
+    writer.finish().unwrap();
+    assert_eq!(out, "(\n\ta\n\t(\n\t\tb\n\t\t\"c\"\n\t)\n\t(\n\t)\n)\n1\n");
+    assert_eq!(DatumDocument::try_from(out.as_str()).unwrap(), doc);
+    // balance checking
+    let mut out = String::new();
+    let mut writer = DatumStreamWriter::new(&mut out, false);
+    assert_eq!(writer.end_list().unwrap_err().kind, DatumErrorKind::BadData);
+    writer.begin_list().unwrap();
+    assert_eq!(writer.depth(), 1);
+    assert_eq!(
+        writer.finish().err().unwrap().kind,
+        DatumErrorKind::Interrupted
+    );
+}
+
+#[test]
+fn document_framing() {
+    let mut out = String::new();
+    let mut writer = DatumStreamWriter::new(&mut out, false);
+    writer.atom::<&str>(&DatumAtom::Integer(1)).unwrap();
+    writer.begin_list().unwrap();
+    assert!(writer.separator().is_err());
+    writer.end_list().unwrap();
+    writer.separator().unwrap();
+    writer.finish().unwrap();
+    assert_eq!(out, "1\n()\n#---\n");
+    // a truncated document doesn't affect the next one
+    let res: Vec<DatumFrame<DatumValue>> = "1 (2 #--- 3 #---"
+        .bytes()
+        .via_datum_pipe(datum_byte_to_framed_value_pipeline())
+        .map(|v| v.unwrap())
+        .collect();
+    assert_eq!(
+        res,
+        [
+            DatumFrame::Value(DatumValue::Atom(DatumAtom::Integer(1))),
+            DatumFrame::Separator { complete: false },
+            DatumFrame::Value(DatumValue::Atom(DatumAtom::Integer(3))),
+            DatumFrame::Separator { complete: true },
+        ]
+    );
+}
+
+#[test]
+fn field_extraction() {
+    let source = "a (1 2) b #bogus c \"see\" b 2";
+    let mut tokens = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline());
+    let fields = crate::datum_extract_fields(&mut tokens, &["c"]).unwrap();
+    assert_eq!(
+        fields,
+        [Some(DatumValue::Atom(DatumAtom::String("see".to_string())))]
+    );
+    // skipped values aren't checked, but extracted ones are
+    assert_eq!(
+        crate::datum_extract_fields(
+            &mut source
+                .chars()
+                .via_datum_pipe(datum_char_to_token_pipeline()),
+            &["b"]
+        )
+        .unwrap_err()
+        .kind,
+        DatumErrorKind::BadData
+    );
+    // reading stopped after 'c', so the duplicate 'b' is still there
+    assert_eq!(
+        crate::datum_extract_fields(&mut tokens, &["b"]).unwrap(),
+        [Some(DatumValue::Atom(DatumAtom::Integer(2)))]
+    );
+    // missing values
+    let mut tokens = "a".chars().via_datum_pipe(datum_char_to_token_pipeline());
+    assert_eq!(
+        crate::datum_extract_fields(&mut tokens, &["a"])
+            .unwrap_err()
+            .kind,
+        DatumErrorKind::Interrupted
+    );
+}
+
+#[test]
+fn strict_special_ids() {
+    let canonical = "#t #f #nil #{}# #i+nan.0 #i+inf.0 #i-inf.0 #x1F";
+    let mut pipeline = datum_char_to_token_pipeline().compose(DatumParser::default().strict(true));
+    assert_eq!(
+        pipeline
+            .feed_all_to_vec(canonical.chars(), true)
+            .unwrap()
+            .len(),
+        8
+    );
+    for v in [
+        "#T", "#F", "#NIL", "#Nil", "#I+NAN.0", "#I+inf.0", "#i-INF.0", "#X1F",
+    ] {
+        let mut pipeline =
+            datum_char_to_token_pipeline().compose(DatumParser::default().strict(true));
+        let err = pipeline.feed_all_to_vec(v.chars(), true).unwrap_err();
+        assert_eq!(err.kind, DatumErrorKind::BadData);
+        // ...but they're all fine normally
+        let mut pipeline = datum_char_to_value_pipeline();
+        assert_eq!(pipeline.feed_all_to_vec(v.chars(), true).unwrap().len(), 1);
+    }
+}
+
+#[test]
+fn plus_sign() {
+    fn parse(source: &str, plus_sign: bool) -> DatumResult<Vec<DatumValue>> {
+        DatumDecoder::default()
+            .plus_sign(plus_sign)
+            .compose(crate::DatumStringTokenizer::default())
+            .compose(DatumParser::default())
+            .feed_all_to_vec(source.chars(), true)
+    }
+    fn write_for_plus_sign(value: &DatumValue) -> String {
+        let mut text = String::new();
+        let mut writer = DatumWriter::default().plus_sign(true);
+        value.write_to(&mut text, &mut writer).unwrap();
+        text
+    }
+    let source = "(+ +1 +2.5 -3 a+b +a \\+1 #i+inf.0)";
+    // normally, + is just content, and the writer leaves it alone
+    let value = &parse(source, false).unwrap()[0];
+    assert_eq!(value.to_string(), "(+ +1 +2.5 -3 a+b +a +1 #i+inf.0)");
+    // the plus_sign writer output means the same thing with plus_sign enabled...
+    let text = write_for_plus_sign(value);
+    // (escaped as a - would be)
+    assert_eq!(text, "(+ \\+\\1 \\+\\2.5 -3 a+b \\+a \\+\\1 #i+inf.0)");
+    assert_eq!(
+        DatumValue::Atom(DatumAtom::Symbol("-1".into())).to_string(),
+        "\\-\\1"
+    );
+    assert_eq!(&parse(&text, true).unwrap()[0], value);
+    assert_eq!(&parse(&text, false).unwrap()[0], value);
+    // ...but the original doesn't, as +a needs escaping
+    assert!(parse(source, true).is_err());
+    let source = "(+ +1 +2.5 -3 a+b \\+a \\+1 #i+inf.0)";
+    let value = &parse(source, true).unwrap()[0];
+    assert_eq!(value.to_string(), "(+ 1 2.5 -3 a+b +a +1 #i+inf.0)");
+    assert_eq!(
+        write_for_plus_sign(value),
+        "(+ 1 2.5 -3 a+b \\+a \\+\\1 #i+inf.0)"
+    );
+}
+
+#[test]
+fn parser_list_capacity() {
+    fn check(value: &DatumValue) {
+        if let DatumValue::List(list) = value {
+            assert_eq!(list.capacity(), list.len());
+            list.iter().for_each(check);
+        }
+    }
+    let values = datum_char_to_value_pipeline()
+        .feed_all_to_vec("(a (1 2 3 4 5) () (b (c d)) e) (f)".chars(), true)
+        .unwrap();
+    assert_eq!(values.len(), 2);
+    values.iter().for_each(check);
+}
+
+#[test]
+fn parser_memory_budget() {
+    let value_size = core::mem::size_of::<DatumValue>();
+    let mut parser = DatumParser::default().memory_budget(Some(value_size * 4));
+    let mut count = 0;
+    for token in datum_char_to_token_pipeline()
+        .feed_all_to_vec("(1 2) 3".chars(), true)
+        .unwrap()
+    {
+        parser
+            .feed(0, Some(token), &mut |_, _| {
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+    }
+    assert_eq!(count, 2);
+    assert_eq!(parser.memory_used(), value_size * 4);
+    // one more value goes over
+    let err = parser
+        .feed(7, Some(DatumToken::Integer(7, 4)), &mut |_, _| Ok(()))
+        .unwrap_err();
+    assert_eq!(err.kind, DatumErrorKind::OutOfRoom);
+    assert_eq!(err.offset, 7);
+    parser.reset_memory_used();
+    assert!(parser
+        .feed(8, Some(DatumToken::Integer(8, 4)), &mut |_, _| Ok(()))
+        .is_ok());
+}
+
+#[test]
+fn parser_max_list_len() {
+    let parse = |text: &str| {
+        datum_char_to_token_pipeline()
+            .compose(DatumParser::default().max_list_len(Some(3)))
+            .feed_all_to_vec(text.chars(), true)
+    };
+    assert_eq!(parse("(1 2 3) (() () ()) 4 5 6 7").unwrap().len(), 6);
+    let err = parse("(1 (2 3 4 5))").unwrap_err();
+    assert_eq!(err.kind, DatumErrorKind::BadData);
+    assert_eq!(err.offset, 10);
+    // nested lists count as one element of their parent
+    assert!(parse("((1 2 3) (4 5 6) (7 8 9) ())").is_err());
+    assert!(parse("((1 2 3) (4 5 6) (7 8 9))").is_ok());
+}
+
+#[test]
+fn deep_nesting() {
+    let depth = 100000;
+    let source = "(".repeat(depth) + "a" + &")".repeat(depth);
+    let values = datum_char_to_value_pipeline()
+        .feed_all_to_vec(source.chars(), true)
+        .unwrap();
+    let value = &values[0];
+    assert_eq!(value.to_string(), source);
+    let copy = value.clone();
+    assert_eq!(&copy, value);
+    assert_eq!(copy.partial_cmp(value), Some(core::cmp::Ordering::Equal));
+    let mut stream = String::new();
+    DatumStreamWriter::new(&mut stream, false)
+        .value(value)
+        .unwrap();
+    assert_eq!(stream, source.clone() + "\n");
+    let mut ha = DefaultHasher::new();
+    value.hash(&mut ha);
+    let mut hb = DefaultHasher::new();
+    copy.hash(&mut hb);
+    assert_eq!(ha.finish(), hb.finish());
+    // hashing still matches the old recursive implementation
+    let atom: DatumAtom<String> = DatumAtom::Integer(1);
+    let mut ha = DefaultHasher::new();
+    DatumValue::List(alloc::vec![DatumValue::Atom(atom.clone())]).hash(&mut ha);
+    let mut hb = DefaultHasher::new();
+    hb.write_u8(6);
+    alloc::vec![atom].hash(&mut hb);
+    assert_eq!(ha.finish(), hb.finish());
+    let cow = value.to_cow();
+    assert_eq!(cow.to_string(), source);
+    let owned = cow.into_owned();
+    assert_eq!(&owned, value);
+    owned.drop_iteratively();
+    copy.drop_iteratively();
+    values.into_iter().for_each(DatumValue::drop_iteratively);
+}
+
+#[test]
+fn cow_values() {
+    use alloc::borrow::Cow;
+    let doc = DatumDocument::try_from("(a \"b\" 1) c").unwrap();
+    let cow: Vec<crate::DatumCowValue> = doc.iter().map(|v| v.to_cow()).collect();
+    assert_eq!(cow[0].to_string(), doc[0].to_string());
+    assert!(matches!(
+        cow[1],
+        crate::DatumValueOf::Atom(DatumAtom::Symbol(Cow::Borrowed("c")))
+    ));
+    // replace one atom
+    let mut cow = cow;
+    if let crate::DatumValueOf::List(list) = &mut cow[0] {
+        list[1] = crate::DatumValueOf::Atom(DatumAtom::String(Cow::Owned(String::from("x"))));
+        assert!(matches!(
+            list[0],
+            crate::DatumValueOf::Atom(DatumAtom::Symbol(Cow::Borrowed(_)))
+        ));
+    }
+    let owned: Vec<DatumValue> = cow.into_iter().map(|v| v.into_owned()).collect();
+    assert_eq!(owned, DatumDocument::try_from("(a \"x\" 1) c").unwrap().0);
+}
+
+#[test]
+fn linting() {
+    use core::cell::RefCell;
+    fn lint(source: &str) -> Vec<(DatumWarningKind, DatumOffset)> {
+        let warnings = RefCell::new(Vec::new());
+        let warn = |w: DatumWarning| warnings.borrow_mut().push((w.kind, w.offset));
+        datum_char_to_linted_token_pipeline(&warn)
+            .feed_all_to_vec(source.chars(), true)
+            .unwrap();
+        warnings.into_inner()
+    }
+    // none of these should warn
+    assert_eq!(
+        lint("(a b)\r\n\\x41; \"\\n \\t  \n\" + a+1 \\+1 #t #i+inf.0"),
+        []
+    );
+    assert_eq!(
+        lint("\\A ; comment \n+a (+1)\t\r\n#NiL"),
+        [
+            (DatumWarningKind::UnknownEscape, 1),
+            (DatumWarningKind::TrailingWhitespace, 12),
+            (DatumWarningKind::PlusSign, 14),
+            (DatumWarningKind::PlusSign, 18),
+            (DatumWarningKind::TrailingWhitespace, 21),
+            // token offsets are where the tokenizer finished the token
+            (DatumWarningKind::NonCanonicalSpecialID, 28),
+        ]
+    );
+}
+
+#[test]
+fn diagnostics() {
+    let diagnostic = DatumDiagnostic {
+        severity: DatumSeverity::Warning,
+        code: "test",
+        span: DatumSpan { start: 2, end: 5 },
+        message: "a \"quoted\"\\\tmessage\x01",
+    };
+    assert_eq!(
+        diagnostic.to_string(),
+        "warning[test] @ 2-5: a \"quoted\"\\\tmessage\x01"
+    );
+    let mut json = String::new();
+    diagnostic.write_json(&mut json).unwrap();
+    assert_eq!(
+        json,
+        "{\"severity\":\"warning\",\"code\":\"test\",\"start\":2,\"end\":5,\"message\":\"a \\\"quoted\\\"\\\\\\tmessage\\u0001\"}"
+    );
+    // the Datum form parses back into the same fields
+    let mut text = String::new();
+    diagnostic.write_datum(&mut text).unwrap();
+    let values = datum_char_to_value_pipeline()
+        .feed_all_to_vec(text.chars(), true)
+        .unwrap();
+    let fields = if let DatumValue::List(fields) = &values[0] {
+        fields
+    } else {
+        panic!("not a list")
+    };
+    assert_eq!(fields.len(), 10);
+    assert_eq!(
+        fields[1],
+        DatumValue::Atom(DatumAtom::Symbol("warning".into()))
+    );
+    assert_eq!(fields[7], DatumValue::Atom(DatumAtom::Integer(5)));
+    assert_eq!(
+        fields[9],
+        DatumValue::Atom(DatumAtom::String(diagnostic.message.into()))
+    );
+    // conversions
+    let warning = DatumWarning {
+        kind: DatumWarningKind::TrailingWhitespace,
+        offset: 3,
+        message: "trailing whitespace",
+    };
+    let diagnostic = DatumDiagnostic::from(warning);
+    assert_eq!(diagnostic.code, "trailing-whitespace");
+    assert_eq!(diagnostic.span, DatumSpan { start: 3, end: 3 });
+    let err = datum_char_to_value_pipeline()
+        .feed_all_to_vec("(".chars(), true)
+        .unwrap_err();
+    let diagnostic = DatumDiagnostic::from(err);
+    assert_eq!(diagnostic.severity, DatumSeverity::Error);
+    assert_eq!(diagnostic.code, "interrupted");
+}
+
+#[test]
+fn formatting() {
+    let config = DatumFormatConfig {
+        max_width: 16,
+        tab_width: 4,
+        ..Default::default()
+    };
+    let source = "; header\r\n\n\n(short list)(a-longer-list (with a nested list) ; comment\n  \"string\" 1.50)\n\n\n\n#t ;end";
+    let formatted = datum_format_with(source, &config).unwrap();
+    assert_eq!(
+        formatted,
+        "; header\n\n(short list)\n(\n\ta-longer-list\n\t(\n\t\twith\n\t\ta\n\t\tnested\n\t\tlist\n\t) ; comment\n\t\"string\"\n\t1.5\n)\n\n#t ;end\n"
+    );
+    assert!(datum_is_formatted(&formatted, &config));
+    assert!(!datum_is_formatted(source, &config));
+    // idempotence
+    for source in [
+        "",
+        "a",
+        ";",
+        "(\n\n)",
+        "( ; first\n a\n\n\n b ; last\n)",
+        "((((((((((((((((((((((a))))))))))))))))))))))",
+        "(a (b (c (d (e (f (g (h (i (j (k (l))))))))))))",
+        "\"multi\nline ; not a comment\" ; comment",
+    ] {
+        let formatted = datum_format_with(source, &config).unwrap();
+        assert_eq!(datum_format_with(&formatted, &config).unwrap(), formatted);
+        let default = datum_format(source).unwrap();
+        assert!(datum_is_formatted(&default, &DatumFormatConfig::default()));
+    }
+    assert_eq!(datum_format(")").unwrap_err().kind, DatumErrorKind::BadData);
+    assert_eq!(
+        datum_format("(").unwrap_err().kind,
+        DatumErrorKind::Interrupted
+    );
+}
+
+#[test]
+fn formatting_document_style() {
+    let config = DatumFormatConfig {
+        max_width: 16,
+        tab_width: 4,
+        ..Default::default()
+    };
+    let crlf = DatumFormatConfig {
+        document: DatumDocumentStyle {
+            line_ending: DatumLineEnding::CrLf,
+            trailing_newline: false,
+            newline_if_empty: true,
+        },
+        ..config
+    };
+    let source = "; header\r\n\n\n(short list)(a-longer-list (with a nested list) ; comment\n  \"string\" 1.50)\n\n\n\n#t ;end";
+    let formatted = datum_format_with(source, &config).unwrap();
+    assert_eq!(
+        datum_format_with(source, &crlf).unwrap(),
+        formatted.trim_end().replace('\n', "\r\n")
+    );
+    assert_eq!(datum_format_with("", &crlf).unwrap(), "\r\n");
+    // idempotence
+    for source in ["", "a", ";", "(\n\n)", "( ; first\n a\n\n\n b ; last\n)"] {
+        let formatted = datum_format_with(source, &crlf).unwrap();
+        assert_eq!(datum_format_with(&formatted, &crlf).unwrap(), formatted);
+        assert!(!formatted.replace("\r\n", "").contains('\n'));
+    }
+}
+
+#[test]
+fn document_style() {
+    let doc = DatumDocument::try_from("a (b c)").unwrap();
+    let empty = DatumDocument::default();
+    let write = |doc: &DatumDocument, style: DatumDocumentStyle| {
+        let mut text = String::new();
+        doc.write_with(&mut text, &style).unwrap();
+        text
+    };
+    let style = DatumDocumentStyle::default();
+    assert_eq!(write(&doc, style), doc.to_string());
+    assert_eq!(write(&doc, style), "a\n(b c)\n");
+    assert_eq!(write(&empty, style), "");
+    let style = DatumDocumentStyle {
+        line_ending: DatumLineEnding::CrLf,
+        trailing_newline: false,
+        newline_if_empty: true,
+    };
+    assert_eq!(write(&doc, style), "a\r\n(b c)");
+    assert_eq!(write(&empty, style), "\r\n");
+}
+
+#[test]
+fn string_content_escaping() {
+    for text in [
+        "",
+        "plain",
+        "\"\\\r\n\t",
+        "\x00\x7f ;()#",
+        "\u{1F600} \u{FFFF}",
+    ] {
+        let mut escaped = String::new();
+        datum_escape_string_content(text, &mut escaped).unwrap();
+        // escaped content is exactly what a string token holds
+        let mut token = String::new();
+        DatumToken::String(0, text).write(&mut token).unwrap();
+        assert_eq!(token, "\"".to_string() + &escaped + "\"");
+        assert_eq!(datum_unescape_string_content(&escaped).unwrap(), text);
+    }
+    let err = datum_unescape_string_content("ab\"c").unwrap_err();
+    assert_eq!(err.kind, DatumErrorKind::BadData);
+    assert_eq!(err.offset, 2);
+    let err = datum_unescape_string_content("\\x4").unwrap_err();
+    assert_eq!(err.kind, DatumErrorKind::Interrupted);
+    // the bulk writer matches escaping one character at a time
+    let text: String = (0..0x800u32)
+        .chain(0xFFF0..0x10010)
+        .filter_map(core::char::from_u32)
+        .flat_map(|c| [c, 'a', 'b', c, c])
+        .collect();
+    let mut one_at_a_time = String::new();
+    for c in text.chars() {
+        DatumChar::string_content(c)
+            .write(&mut one_at_a_time)
+            .unwrap();
+    }
+    let mut escaped = String::new();
+    datum_escape_string_content(&text, &mut escaped).unwrap();
+    assert_eq!(escaped, one_at_a_time);
+}
+
+#[test]
+fn byte_class_table() {
+    for b in 0..=255u8 {
+        let class = DatumCharClass::from_u8(DATUM_BYTE_CLASS_TABLE[b as usize]);
+        assert_eq!(class, DatumCharClass::identify_byte(b));
+        if b < 0x80 {
+            assert_eq!(class, DatumCharClass::identify(b as char));
+        }
+        if let Some(class) = DatumCharClass::from_u8(b) {
+            assert_eq!(class as u8, b);
+        }
+    }
+    // multi-byte characters are content, and so is each byte
+    let mut buf = [0u8; 4];
+    for c in ['\u{80}', '\u{A0}', '\u{2028}', '\u{FEFF}', '\u{10FFFF}'] {
+        assert_eq!(DatumCharClass::identify(c), Some(DatumCharClass::Content));
+        for b in c.encode_utf8(&mut buf).bytes() {
+            assert_eq!(
+                DATUM_BYTE_CLASS_TABLE[b as usize],
+                DatumCharClass::Content as u8
+            );
+        }
+    }
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_roundtrip() {
+    use crate::ffi::*;
+    extern "C" fn copy_event(user: *mut core::ffi::c_void, event: *const DatumFfiEvent) -> i32 {
+        // SAFETY: user is the writer, and the event is valid for the callback
+        #[allow(unsafe_code)]
+        unsafe {
+            let event = &*event;
+            if event.kind == DATUM_FFI_EVENT_INTEGER && event.integer == 13 {
+                return 1;
+            }
+            datum_ffi_writer_write(user as *mut DatumFfiWriter, event)
+        }
+    }
+    #[allow(unsafe_code)]
+    unsafe {
+        let parser = datum_ffi_parser_new();
+        let writer = datum_ffi_writer_new();
+        let user = writer as *mut core::ffi::c_void;
+        // split mid-token to check the parser keeps state between calls
+        let source = b"(a \"b\\n\" 1.5 #t) -1";
+        assert_eq!(
+            datum_ffi_parser_feed(parser, source.as_ptr(), 5, 0, copy_event, user),
+            0
+        );
+        let rest = &source[5..];
+        assert_eq!(
+            datum_ffi_parser_feed(parser, rest.as_ptr(), rest.len(), 1, copy_event, user),
+            0
+        );
+        let mut len = 0;
+        let data = datum_ffi_writer_buffer(writer, &mut len);
+        let text = core::str::from_utf8(core::slice::from_raw_parts(data, len)).unwrap();
+        assert_eq!(text, "(a \"b\\n\" 1.5 #t) -1");
+        // cancellation
+        let source = b"1 13 2";
+        assert_eq!(
+            datum_ffi_parser_feed(parser, source.as_ptr(), source.len(), 1, copy_event, user),
+            DATUM_FFI_ERROR_CANCELLED
+        );
+        // bad data
+        datum_ffi_parser_free(parser);
+        let parser = datum_ffi_parser_new();
+        let source = b"ab\"";
+        assert_eq!(
+            datum_ffi_parser_feed(parser, source.as_ptr(), source.len(), 1, copy_event, user),
+            DATUM_FFI_ERROR_INTERRUPTED
+        );
+        assert_eq!(datum_ffi_parser_error_offset(parser), 3);
+        // writer rejects invalid UTF-8
+        let bad = [0xFFu8];
+        let event = DatumFfiEvent {
+            kind: DATUM_FFI_EVENT_SYMBOL,
+            offset: 0,
+            text: bad.as_ptr(),
+            text_len: 1,
+            integer: 0,
+            float: 0.0,
+        };
+        assert_eq!(
+            datum_ffi_writer_write(writer, &event),
+            DATUM_FFI_ERROR_BAD_DATA
+        );
+        datum_ffi_writer_clear(writer);
+        datum_ffi_writer_buffer(writer, &mut len);
+        assert_eq!(len, 0);
+        datum_ffi_parser_free(parser);
+        datum_ffi_writer_free(writer);
+    }
+}
+
+#[cfg(feature = "python")]
+#[test]
+fn python_bindings() {
+    use crate::python::*;
+    use pyo3::prelude::*;
+    Python::initialize();
+    Python::attach(|py| {
+        let source = "(a \"b\" 1 1.5 #t #nil) -2";
+        let values = datum_loads_py(py, source).unwrap();
+        assert_eq!(
+            values.repr().unwrap().to_str().unwrap(),
+            "[[Symbol(\"a\"), 'b', 1, 1.5, True, None], -2]"
+        );
+        assert_eq!(
+            datum_dumps_py(&values).unwrap(),
+            "(a \"b\" 1 1.5 #t #nil)\n-2\n"
+        );
+        // tuples work as lists, bytes don't
+        let tuple = (1, (2, 3)).into_pyobject(py).unwrap();
+        assert_eq!(datum_dumps_py(&tuple).unwrap(), "1\n(2 3)\n");
+        let bytes = pyo3::types::PyBytes::new(py, b"a");
+        let list = pyo3::types::PyList::new(py, [bytes]).unwrap();
+        assert!(datum_dumps_py(&list).is_err());
+        assert!(datum_loads_py(py, "(a").is_err());
+        // reader
+        let io = py.import("io").unwrap();
+        let file = io.call_method1("BytesIO", (source.as_bytes(),)).unwrap();
+        let reader = Bound::new(py, DatumPyReader::new(file.unbind())).unwrap();
+        let mut count = 0;
+        for v in reader.try_iter().unwrap() {
+            v.unwrap();
+            count += 1;
+        }
+        assert_eq!(count, 2);
+        let file = io.call_method1("StringIO", ("1 (",)).unwrap();
+        let reader = Bound::new(py, DatumPyReader::new(file.unbind())).unwrap();
+        let results: Vec<PyResult<_>> = reader.try_iter().unwrap().collect();
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    });
+}
+
+#[test]
+fn context_errors() {
+    let source = "(ok) ab\u{1}cd ef";
+    let error = DatumDocument::try_from(source).unwrap_err();
+    assert_eq!(error.kind, DatumErrorKind::BadData);
+    let context = DatumContextError::from_chars(error, source);
+    assert_eq!(context.context, "at '\\u{1}' in \"ab\\u{1}cd\"");
+    assert!(context
+        .to_string()
+        .ends_with(&alloc::format!("({})", context.context)));
+    // same again, but in bytes with a multi-byte character before the error
+    let source = "é(ab\u{1}";
+    let error = DatumDocument::try_from(source.as_bytes()).unwrap_err();
+    let context = DatumContextError::from_bytes(error, source.as_bytes());
+    assert_eq!(context.context, "at '\\u{1}' in \"ab\\u{1}\"");
+    // no context
+    let context = DatumContextError::from(error);
+    assert_eq!(context.to_string(), error.to_string());
+    let context = DatumContextError::from_chars(error, "");
+    assert_eq!(context.context, "at end of input");
+    // named sources
+    let error = DatumDocument::parse_named("(a\n(b", "include/b.scm").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        alloc::format!(
+            "include/b.scm: {} (at end of input, after \"b\")",
+            error.error
+        )
+    );
+    assert!(DatumDocument::parse_named("(a)", "a.scm").is_ok());
+}
+
+#[cfg(feature = "core_error")]
+#[test]
+fn core_error() {
+    use alloc::boxed::Box;
+    let error = DatumDocument::try_from("(").unwrap_err();
+    let boxed: Box<dyn core::error::Error> = Box::new(DatumContextError::from_chars(error, "("));
+    let source = boxed.source().unwrap();
+    assert_eq!(source.to_string(), error.to_string());
+    assert!(source.source().is_none());
+}
+
+#[test]
+fn token_source_spans() {
+    let source = "é\\ b \"x\\\"y\\\\\";c\n#t\t-1.5";
+    let tokens: Vec<DatumToken<String>> = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline())
+        .map(|v| v.unwrap())
+        .collect();
+    let spans: Vec<(u64, u64)> = tokens
+        .iter()
+        .map(|v| {
+            let span = v.source_span(source);
+            (span.start, span.end)
+        })
+        .collect();
+    assert_eq!(spans, [(0, 3), (5, 12), (16, 17), (19, 22)]);
+    let descriptions: Vec<String> = tokens
+        .iter()
+        .map(|v| v.describe(source).to_string())
+        .collect();
+    assert_eq!(descriptions[0], "Symbol é\\ b @ 1:1 (0-3): \"é\\\\ b\"");
+    assert_eq!(descriptions[2], "SpecialID #t @ 2:1 (16-17): \"#t\"");
+    assert_eq!(descriptions[3], "Numeric -1.5 @ 2:4 (19-22): \"-1.5\"");
+}
+
+#[test]
+fn span_tokens() {
+    let source = "(é\\ b \"x\\\"y\\\\\";c\n#t\t-1.5 \\x41;bc 12)";
+    let tokens: Vec<DatumToken<String>> = source
+        .chars()
+        .via_datum_pipe(datum_char_to_token_pipeline())
+        .map(|v| v.unwrap())
+        .collect();
+    let span_tokens: Vec<DatumSpanToken> = source
+        .chars()
+        .via_datum_pipe(datum_char_to_span_token_pipeline())
+        .map(|v| v.unwrap())
+        .collect();
+    assert_eq!(tokens.len(), span_tokens.len());
+    for (token, span_token) in tokens.iter().zip(span_tokens.iter()) {
+        assert_eq!(token.token_type(), span_token.token_type);
+        assert_eq!(&span_token.resolve(source).unwrap(), token);
+    }
+    let texts: Vec<&str> = span_tokens.iter().map(|v| v.source_text(source)).collect();
+    assert_eq!(
+        texts,
+        [
+            "(",
+            "é\\ b",
+            "\"x\\\"y\\\\\"",
+            "#t",
+            "-1.5",
+            "\\x41;bc",
+            "12",
+            ")"
+        ]
+    );
+    // errors are reported as usual
+    assert_eq!(
+        "(\"abc"
+            .chars()
+            .via_datum_pipe(datum_char_to_span_token_pipeline())
+            .last()
+            .unwrap()
+            .unwrap_err()
+            .kind,
+        DatumErrorKind::Interrupted
+    );
+}
+
+#[test]
+fn document_index() {
+    let source = "; fruit\né (a (b) \"c)\") #t\n(d\n  ((e)))".as_bytes();
+    let index = DatumIndex::build_with_lists(source).unwrap();
+    let texts: Vec<&[u8]> = (0..index.len())
+        .map(|n| index.text(n, source).unwrap())
+        .collect();
+    assert_eq!(
+        texts,
+        ["é".as_bytes(), b"(a (b) \"c)\")", b"#t", b"(d\n  ((e)))"]
+    );
+    let lists: Vec<(u64, u64, usize)> = index
+        .lists
+        .iter()
+        .map(|v| (v.span.start, v.span.end, v.depth))
+        .collect();
+    assert_eq!(
+        lists,
+        [
+            (11, 22, 0),
+            (14, 16, 1),
+            (27, 37, 0),
+            (32, 36, 1),
+            (33, 35, 2)
+        ]
+    );
+    let doc = DatumDocument::try_from(source).unwrap();
+    for (n, value) in doc.iter().enumerate() {
+        assert_eq!(&index.value(n, source).unwrap().unwrap(), value);
+    }
+    assert!(index.value(4, source).is_none());
+    assert!(DatumIndex::build(source).unwrap().lists.is_empty());
+    // bad atoms are found when parsed, with offsets in the document
+    let source = b"1 #nope 2";
+    let index = DatumIndex::build(source).unwrap();
+    assert_eq!(index.value(1, source).unwrap().unwrap_err().offset, 7);
+    // unbalanced lists are found when indexing
+    assert_eq!(
+        DatumIndex::build(b"(a").unwrap_err().kind,
+        DatumErrorKind::Interrupted
+    );
+    assert!(DatumIndex::build(b"a)").is_err());
+}
+
+#[test]
+fn duplicate_keys() {
+    let source = "(é 1) 2 (() 3) (\"é\" 4) (b) (é 5)";
+    let mut duplicates = Vec::new();
+    let mut pipeline = datum_byte_to_value_pipeline().compose(DatumDuplicateKeyPipe::new(|dup| {
+        duplicates.push(dup);
+        Ok(())
+    }));
+    let values = pipeline.feed_all_to_vec(source.bytes(), true).unwrap();
+    assert_eq!(values.len(), 6);
+    assert_eq!(pipeline.1.seen().len(), 2);
+    assert_eq!(pipeline.1.seen()["b"], 25);
+    drop(pipeline);
+    // offsets are in bytes here; strings and symbols are the same name
+    let pairs: Vec<(&str, DatumOffset, DatumOffset)> = duplicates
+        .iter()
+        .map(|d| (d.key.as_str(), d.first, d.second))
+        .collect();
+    assert_eq!(pairs, [("é", 0, 16), ("é", 0, 29)]);
+    assert_eq!(
+        duplicates[0].to_string(),
+        "duplicate key \"é\" @ 16 (first @ 0)"
+    );
+}
+
+#[test]
+fn value_metrics() {
+    let doc: DatumDocument = "\"é\" (a (()) #t)".parse().unwrap();
+    assert_eq!(
+        doc.metrics(),
+        DatumValueMetrics {
+            depth: 3,
+            nodes: 6,
+            string_bytes: 2,
+        }
+    );
+    assert_eq!(doc[0].metrics().depth, 0);
+    assert_eq!(
+        DatumDocument::default().metrics(),
+        DatumValueMetrics::default()
+    );
+    // deep values don't recurse
+    let mut deep = DatumValue::List(Vec::new());
+    for _ in 0..100000 {
+        deep = DatumValue::List(alloc::vec![deep]);
+    }
+    assert_eq!(deep.metrics().depth, 100001);
+    deep.drop_iteratively();
+}
+
+#[test]
+fn generated_documents_roundtrip() {
+    let config = DatumGeneratorConfig {
+        special_char_chance: 128,
+        ..Default::default()
+    };
+    let mut generator = DatumGenerator::new(0, config);
+    for _ in 0..200 {
+        let doc = generator.document();
+        let text = doc.to_string();
+        assert_eq!(text.parse::<DatumDocument>().unwrap(), doc, "{}", text);
+        assert_eq!(DatumDocument::try_from(text.as_bytes()).unwrap(), doc);
+    }
+    // depth limits are respected
+    generator.config = DatumGeneratorConfig {
+        max_depth: 2,
+        list_chance: 255,
+        ..Default::default()
+    };
+    assert!(generator.document().metrics().depth <= 2);
+}
+
+#[test]
+fn static_documents() {
+    use crate::{static_datum, DatumMayContainAtom, DatumValueRef};
+    static DOC: &[DatumValueRef] = &[
+        static_datum!((a "b\"c" -1 2.5 -0.5 #t #f true #nil ())),
+        static_datum!(symbol),
+        static_datum!(-7),
+        static_datum!(((nested)(lists(here)))),
+    ];
+    let text = "(a \"b\\\"c\" -1 2.5 -0.5 #t #f #t #nil ())\nsymbol\n-7\n((nested) (lists (here)))";
+    let expected = DatumDocument::try_from(text).unwrap();
+    assert_eq!(DOC.len(), expected.len());
+    for (a, b) in DOC.iter().zip(expected.iter()) {
+        assert_eq!(a, b);
+        assert_eq!(&a.to_value(), b);
+        assert_eq!(a.to_string(), b.to_string());
+    }
+    assert_ne!(DOC[1], DOC[2].to_value());
+    assert_eq!(DOC[2].as_i64(), Some(-7));
+    assert_eq!(DOC[0].as_list().unwrap()[3].as_f64(), Some(2.5));
+    assert!(DOC[1].as_list().is_none());
+}
+
+#[test]
+fn concrete_values() {
+    use crate::DatumValue::{Atom, List};
+    use crate::DatumValueOf;
+    let doc = DatumDocument::try_from("(a 1) b").unwrap();
+    match &doc[0] {
+        List(list) => assert_eq!(list[1], Atom(DatumAtom::Integer(1))),
+        Atom(_) => panic!("expected a list"),
+    }
+    let generic: DatumValueOf<String> = doc[0].clone().into();
+    assert_eq!(generic.to_string(), doc[0].to_string());
+    assert_eq!(DatumValue::from(generic), doc[0]);
+}
+
+#[test]
+fn generic_values() {
+    use crate::{DatumParserOf, DatumValueOf, DatumValueTokens};
+    let doc = DatumDocument::try_from("(a \"b\" (1 2.5)) #t c").unwrap();
+    // re-parse the document's own tokens, borrowing from it
+    let mut parser: DatumParserOf<&str> = DatumParserOf::default();
+    let mut values: Vec<DatumValueOf<&str>> = Vec::new();
+    for (at, token) in DatumValueTokens::new(&doc).enumerate() {
+        parser
+            .feed(at as DatumOffset, Some(token), &mut |_, v| {
+                values.push(v);
+                Ok(())
+            })
+            .unwrap();
+    }
+    assert_eq!(values.len(), doc.len());
+    for (a, b) in values.iter().zip(doc.iter()) {
+        assert_eq!(a.to_string(), b.to_string());
+        assert_eq!(a.metrics(), b.metrics());
+        assert_eq!(
+            &DatumValue::from(a.map_atoms(|v| v.map_buffer(String::from))),
+            b
+        );
+        assert!(a.tokens().eq(b.tokens()));
+    }
+}
+
+#[test]
+fn list_storage() {
+    use crate::{DatumSliceStorage, DatumValueOf, DatumValueRef, DatumValueTokens};
+    use alloc::format;
+    type Arena<'a> = DatumValueOf<&'a str, DatumSliceStorage<'a>>;
+    let doc = DatumDocument::try_from("(a (\"b\" 1) ()) 2.5").unwrap();
+    let empty: [Arena; 0] = [];
+    let inner = [
+        Arena::Atom(DatumAtom::String("b")),
+        Arena::Atom(DatumAtom::Integer(1)),
+    ];
+    let outer = [
+        Arena::Atom(DatumAtom::Symbol("a")),
+        Arena::List(&inner),
+        Arena::List(&empty),
+    ];
+    let values = [Arena::List(&outer), Arena::Atom(DatumAtom::Float(2.5))];
+    let copy = values;
+    assert_eq!(values, copy);
+    assert!(values[0] > values[1]);
+    for (a, b) in values.iter().zip(doc.iter()) {
+        assert_eq!(a.to_string(), b.to_string());
+        assert_eq!(a.metrics(), b.metrics());
+        assert_eq!(
+            &DatumValue::from(a.map_atoms(|v| v.map_buffer(String::from))),
+            b
+        );
+        let mut ha = DefaultHasher::new();
+        a.hash(&mut ha);
+        let mut hb = DefaultHasher::new();
+        b.hash(&mut hb);
+        assert_eq!(ha.finish(), hb.finish());
+    }
+    assert!(DatumValueTokens::new(&values).eq(DatumValueTokens::new(&doc)));
+    let mut nodes = [DatumValueRef::Atom(DatumAtom::Nil); 16];
+    let mut buf = [0u8; 16];
+    let parsed = DatumValueRef::parse_into("(a (\"b\" 1) ())", &mut nodes, &mut buf).unwrap();
+    assert_eq!(parsed[0], values[0]);
+    assert_eq!(
+        format!("{:?}", doc[0]),
+        "List([Atom(Symbol(\"a\")), List([Atom(String(\"b\")), Atom(Integer(1))]), List([])])"
+    );
+}
+
+#[test]
+fn borrowed_parse() {
+    use crate::{DatumErrorKind, DatumValueRef};
+    let text = "(a \"b\\\"c\" -1 2.5 #t #F #nil #{}# #x10 ()) ; comment\nsymbol \"\" ((nested) (lists (here)))";
+    let expected = DatumDocument::try_from(text).unwrap();
+    let mut nodes = [DatumValueRef::Atom(DatumAtom::Nil); 32];
+    let mut buf = [0u8; 64];
+    let doc = DatumValueRef::parse_into(text, &mut nodes, &mut buf).unwrap();
+    assert_eq!(doc.len(), expected.len());
+    for (a, b) in doc.iter().zip(expected.iter()) {
+        assert_eq!(a, b);
+    }
+    // errors
+    let mut nodes = [DatumValueRef::Atom(DatumAtom::Nil); 4];
+    let mut buf = [0u8; 4];
+    let res = DatumValueRef::parse_into("(a b c d)", &mut nodes, &mut buf);
+    assert_eq!(res.unwrap_err().kind, DatumErrorKind::OutOfRoom);
+    let mut nodes = [DatumValueRef::Atom(DatumAtom::Nil); 4];
+    let mut buf = [0u8; 4];
+    let res = DatumValueRef::parse_into("abcde", &mut nodes, &mut buf);
+    assert_eq!(res.unwrap_err().kind, DatumErrorKind::OutOfRoom);
+    let mut nodes = [DatumValueRef::Atom(DatumAtom::Nil); 4];
+    let mut buf = [0u8; 4];
+    let res = DatumValueRef::parse_into("(a", &mut nodes, &mut buf);
+    assert_eq!(res.unwrap_err().kind, DatumErrorKind::Interrupted);
+    let mut nodes = [DatumValueRef::Atom(DatumAtom::Nil); 4];
+    let mut buf = [0u8; 4];
+    let res = DatumValueRef::parse_into("a)", &mut nodes, &mut buf);
+    assert_eq!(res.unwrap_err().kind, DatumErrorKind::BadData);
+}
+
+#[test]
+fn headers() {
+    let mut text = String::new();
+    let mut writer = DatumWriter::default();
+    let header = DatumHeader::new("example-config", 3);
+    header.write_magic_comment(&mut writer, &mut text).unwrap();
+    header.write(&mut writer, &mut text).unwrap();
+    writer
+        .write_atom::<&str>(&mut text, &DatumAtom::Integer(1))
+        .unwrap();
+    assert_eq!(text, "; example-config 3\n(example-config 3)\n1");
+    assert_eq!(DatumHeader::from_magic_comment(&text), Some(header));
+    let doc = DatumDocument::try_from(text.as_str()).unwrap();
+    assert_eq!(DatumHeader::from_value(&doc[0]), Some(header));
+    assert_eq!(DatumHeader::from_value(&doc[1]), None);
+    let mut tokens = text.chars().via_datum_pipe(datum_char_to_token_pipeline());
+    let read = DatumHeader::read(&mut tokens).unwrap();
+    assert_eq!(read.as_borrowed(), header);
+    assert_eq!(
+        read.check("example-config", 3..=3),
+        DatumHeaderCheck::Supported(3)
+    );
+    assert_eq!(
+        read.check("example-config", 1..=2),
+        DatumHeaderCheck::TooNew
+    );
+    assert_eq!(
+        read.check("example-config", 4..=5),
+        DatumHeaderCheck::TooOld
+    );
+    assert_eq!(read.check("other", 3..=3), DatumHeaderCheck::WrongFormat);
+    assert!(matches!(tokens.next(), Some(Ok(DatumToken::Integer(_, 1)))));
+    // not headers
+    for bad in ["1", "(1 2)", "(a b)", "(a 1 2)", "(a 1"].iter() {
+        let mut tokens = bad.chars().via_datum_pipe(datum_char_to_token_pipeline());
+        assert!(DatumHeader::read(&mut tokens).is_err());
+    }
+    assert_eq!(DatumHeader::from_magic_comment("(a 1)"), None);
+    assert_eq!(DatumHeader::from_magic_comment("; a 1 2"), None);
+}
+
+#[test]
+fn migrations() {
+    let migrator = DatumMigrator::new("example", 1)
+        .step(|doc| {
+            doc.push(DatumValue::Atom(DatumAtom::Integer(2)));
+            Ok(())
+        })
+        .step(|doc| {
+            if doc.is_empty() {
+                return Err(crate::datum_error!(BadData, 0, "test: empty"));
+            }
+            doc.push(DatumValue::Atom(DatumAtom::Integer(3)));
+            Ok(())
+        })
+        .headerless_version(Some(1));
+    assert_eq!(migrator.current_version(), 3);
+    let migrate = |text: &str| {
+        let mut doc = DatumDocument::try_from(text).unwrap();
+        migrator
+            .migrate(&mut doc)
+            .map(|version| (version, doc.to_string()))
+    };
+    assert_eq!(
+        migrate("(example 1) 1").unwrap(),
+        (1, "(example 3)\n1\n2\n3\n".to_string())
+    );
+    assert_eq!(
+        migrate("(example 2) 1").unwrap(),
+        (2, "(example 3)\n1\n3\n".to_string())
+    );
+    assert_eq!(
+        migrate("(example 3)").unwrap(),
+        (3, "(example 3)\n".to_string())
+    );
+    assert_eq!(
+        migrate("1").unwrap(),
+        (1, "(example 3)\n1\n2\n3\n".to_string())
+    );
+    assert!(migrate("(example 0)").is_err());
+    assert!(migrate("(example 4)").is_err());
+    assert!(migrate("(other 1)").is_err());
+    // failed steps leave the document as it was before that step
+    let mut doc = DatumDocument::try_from("(example 2)").unwrap();
+    assert!(migrator.migrate(&mut doc).is_err());
+    assert_eq!(doc.to_string(), "(example 2)\n");
+    let strict = DatumMigrator::new("example", 1);
+    assert!(strict.migrate(&mut DatumDocument::default()).is_err());
+}
+
 #[test]
 fn these_cannot_be_atomized() {
     let tkn: DatumToken<&str> = DatumToken::ListStart(0);
@@ -0,0 +1,206 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+//! Checks the shared test vectors in doc/vectors; the format is described in the README.md there.
+
+use core::fmt::{Display, Write};
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::{
+    datum_byte_to_token_pipeline, datum_byte_to_value_pipeline, DatumPipe, DatumResult, DatumToken,
+    DatumTokenType, DatumWriter,
+};
+
+/// Marks the point at which a dump stopped due to an error.
+const ERROR_LINE: &str = "error\n";
+
+/// Feeds all bytes (and the EOF) to a pipeline, stopping at the first error.
+fn feed_all<P: DatumPipe<Input = u8>>(
+    mut pipe: P,
+    input: &[u8],
+    mut f: impl FnMut(P::Output),
+) -> DatumResult<()> {
+    for (at, b) in input.iter().enumerate() {
+        pipe.feed(at as u64, Some(*b), &mut |_, v| {
+            f(v);
+            Ok(())
+        })?;
+    }
+    pipe.feed(input.len() as u64, None, &mut |_, v| {
+        f(v);
+        Ok(())
+    })
+}
+
+/// Dumps the tokens of a UTF-8 document in the test vector token dump format.
+///
+/// Each token is written on its own line as its type (`string`, `symbol`, `special-id`, `integer`, `float`, `list-start` or `list-end`), a space, and the token as [DatumToken::write] writes it.
+/// If tokenization fails, the dump ends with the line `error`.
+fn dump_tokens(input: &[u8]) -> String {
+    let mut out = String::new();
+    let res = feed_all(datum_byte_to_token_pipeline(), input, |token| {
+        let kind = match &token {
+            DatumToken::Integer(_, _) => "integer",
+            DatumToken::Float(_, _) => "float",
+            _ => match token.token_type() {
+                DatumTokenType::String => "string",
+                DatumTokenType::Symbol => "symbol",
+                DatumTokenType::SpecialID => "special-id",
+                DatumTokenType::ListStart => "list-start",
+                _ => "list-end",
+            },
+        };
+        // writing to a String can't fail
+        let _ = writeln!(out, "{} {}", kind, token);
+    });
+    if res.is_err() {
+        out.push_str(ERROR_LINE);
+    }
+    out
+}
+
+/// Dumps the values of a UTF-8 document in the test vector value dump format.
+///
+/// This is the canonical form of each value (see [crate::DatumDocument::write_canonical]), each followed by a newline.
+/// If parsing fails, the dump ends with the line `error`.
+fn dump_values(input: &[u8]) -> String {
+    let mut out = String::new();
+    let mut writer = DatumWriter::default();
+    let res = feed_all(datum_byte_to_value_pipeline(), input, |value| {
+        let _ = value.write_to(&mut out, &mut writer);
+        let _ = writer.write_newline(&mut out);
+    });
+    if res.is_err() {
+        out.push_str(ERROR_LINE);
+    }
+    out
+}
+
+/// A test vector: an input document and the expected dumps of it.
+///
+/// These are loaded from a directory of files: `NAME.in` is the input, and `NAME.tokens` and `NAME.values` (both optional) are the expected output of [dump_tokens] and [dump_values].
+/// Line endings in expected dumps are normalized to `\n`.
+struct TestVector {
+    /// Name (the file name, without extension).
+    name: String,
+    /// Input document.
+    input: Vec<u8>,
+    /// Expected token dump, if any.
+    tokens: Option<String>,
+    /// Expected value dump, if any.
+    values: Option<String>,
+}
+
+/// A [TestVector] whose expected output didn't match.
+struct TestMismatch {
+    /// Name of the test vector.
+    name: String,
+    /// Which dump differed: `tokens` or `values`.
+    dump: &'static str,
+    /// Expected dump.
+    expected: String,
+    /// What this implementation produced.
+    actual: String,
+}
+
+impl Display for TestMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} ({}): expected:\n{}actual:\n{}",
+            self.name, self.dump, self.expected, self.actual
+        )
+    }
+}
+
+fn read_expected(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text.replace("\r\n", "\n"))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+impl TestVector {
+    /// Loads all test vectors in a directory, sorted by name.
+    fn load_dir(dir: &Path) -> io::Result<Vec<Self>> {
+        let mut vectors = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension() != Some(OsStr::new("in")) {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|v| v.to_str()) {
+                Some(v) => v.into(),
+                None => continue,
+            };
+            vectors.push(Self {
+                name,
+                input: fs::read(&path)?,
+                tokens: read_expected(&path.with_extension("tokens"))?,
+                values: read_expected(&path.with_extension("values"))?,
+            });
+        }
+        vectors.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(vectors)
+    }
+
+    /// Checks this implementation's output against the expected dumps.
+    fn check(&self) -> Result<(), TestMismatch> {
+        self.check_dump("tokens", &self.tokens, dump_tokens)?;
+        self.check_dump("values", &self.values, dump_values)
+    }
+
+    fn check_dump(
+        &self,
+        dump: &'static str,
+        expected: &Option<String>,
+        f: fn(&[u8]) -> String,
+    ) -> Result<(), TestMismatch> {
+        if let Some(expected) = expected {
+            let actual = f(&self.input);
+            if &actual != expected {
+                return Err(TestMismatch {
+                    name: self.name.clone(),
+                    dump,
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn dumps() {
+    assert_eq!(
+        dump_tokens(b"(a #T 1.0"),
+        "list-start (\nsymbol a\nspecial-id #T\nfloat 1.0\n"
+    );
+    assert_eq!(dump_tokens(b"a \"b"), "symbol a\nerror\n");
+    assert_eq!(dump_values(b"(a   #x10) #t"), "(a 16)\n#t\n");
+    assert_eq!(dump_values(b"1 (2"), "1\nerror\n");
+}
+
+#[test]
+fn test_vectors() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../doc/vectors");
+    let vectors = TestVector::load_dir(&dir).unwrap();
+    assert!(vectors.len() >= 10);
+    for v in vectors {
+        assert!(v.tokens.is_some() && v.values.is_some(), "{}", v.name);
+        if let Err(mismatch) = v.check() {
+            panic!("{}", mismatch);
+        }
+    }
+}
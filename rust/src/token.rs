@@ -16,7 +16,7 @@ use alloc::string::String;
 
 use crate::{
     datum_error, unary, DatumBoundedPipe, DatumChar, DatumCharClass, DatumError, DatumOffset,
-    DatumPipe, DatumResult, DatumTokenType, DatumTokenizer, DatumTokenizerAction,
+    DatumPipe, DatumResult, DatumSpan, DatumTokenType, DatumTokenizer, DatumTokenizerAction,
 };
 
 /// Datum token with integrated string.
@@ -57,6 +57,7 @@ impl<B: Deref<Target = str>> TryFrom<(DatumTokenType, DatumOffset, B)> for Datum
                 // Originally, this was offloaded to DatumAtom, but this bloated the spec and caused all sorts of problems.
                 // Having to figure out how to make it reasonably safe if someone tries to make "ABCD" a numeric token did not end well.
                 // Besides, the quicker we get rid of these things the saner the memory use is for people who use [char;16] etc...
+                // Rust's parsing is locale-independent ('.' is always the decimal point) and accepts a leading '+'.
                 if let Ok(v) = v.parse() {
                     Ok(DatumToken::Integer(at, v))
                 } else if let Ok(v) = v.parse() {
@@ -111,25 +112,141 @@ impl<B: Deref<Target = str>> DatumToken<B> {
         }
     }
 
+    /// Borrows the buffer, if any.
+    ///
+    /// _Added in 1.3.0._
+    pub fn as_borrowed(&self) -> DatumToken<&str> {
+        match self {
+            Self::String(at, b) => DatumToken::String(*at, b),
+            Self::Symbol(at, b) => DatumToken::Symbol(*at, b),
+            Self::SpecialID(at, b) => DatumToken::SpecialID(*at, b),
+            Self::Integer(at, v) => DatumToken::Integer(*at, *v),
+            Self::Float(at, v) => DatumToken::Float(*at, *v),
+            Self::ListStart(at) => DatumToken::ListStart(*at),
+            Self::ListEnd(at) => DatumToken::ListEnd(*at),
+        }
+    }
+
+    /// Converts the buffer, if any, with `f`.
+    ///
+    /// _Added in 1.3.0._
+    pub fn map_buffer<C: Deref<Target = str>, F: FnOnce(B) -> C>(self, f: F) -> DatumToken<C> {
+        match self {
+            Self::String(at, b) => DatumToken::String(at, f(b)),
+            Self::Symbol(at, b) => DatumToken::Symbol(at, f(b)),
+            Self::SpecialID(at, b) => DatumToken::SpecialID(at, f(b)),
+            Self::Integer(at, v) => DatumToken::Integer(at, v),
+            Self::Float(at, v) => DatumToken::Float(at, v),
+            Self::ListStart(at) => DatumToken::ListStart(at),
+            Self::ListEnd(at) => DatumToken::ListEnd(at),
+        }
+    }
+
+    /// Finds where this token is in the source it was read from, assuming offsets are in characters (as when reading from [str::chars]).
+    ///
+    /// Tokens only record a single offset (where the tokenizer finished them), so this scans backwards through the source to find the start.
+    ///
+    /// _Added in 1.3.0._
+    pub fn source_span(&self, source: &str) -> DatumSpan {
+        let at = self.offset();
+        let (before, string) = match self {
+            Self::ListStart(_) | Self::ListEnd(_) => {
+                return DatumSpan { start: at, end: at };
+            }
+            // the offset is the closing quote
+            Self::String(_, _) => (&source[..char_to_byte(source, at)], true),
+            // the offset is whatever ended the token
+            _ => (&source[..char_to_byte(source, at)], false),
+        };
+        let mut start = 0;
+        for (i, c) in before.char_indices().rev() {
+            let boundary = if string {
+                c == '"'
+            } else {
+                match DatumCharClass::identify(c) {
+                    Some(class) => matches!(
+                        class,
+                        DatumCharClass::Whitespace
+                            | DatumCharClass::Newline
+                            | DatumCharClass::ListStart
+                            | DatumCharClass::ListEnd
+                            | DatumCharClass::String
+                            | DatumCharClass::LineComment
+                    ),
+                    None => c != '\\',
+                }
+            };
+            // boundaries can be escaped
+            if boundary && before[..i].chars().rev().take_while(|v| *v == '\\').count() % 2 == 0 {
+                start = if string { i } else { i + c.len_utf8() };
+                break;
+            }
+        }
+        let start = at - before[start..].chars().count() as DatumOffset;
+        let end = if string { at } else { at.saturating_sub(1) };
+        DatumSpan {
+            start,
+            end: end.max(start),
+        }
+    }
+
+    /// Describes this token for debugging, with its position and the source text it came from.
+    /// See [DatumToken::source_span] for how the source text is found.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumToken, IntoViaDatumPipe, datum_char_to_token_pipeline};
+    /// let source = "(a\n  \"b c\")";
+    /// let tokens: Vec<DatumToken<String>> = source.chars()
+    ///     .via_datum_pipe(datum_char_to_token_pipeline())
+    ///     .map(|v| v.unwrap())
+    ///     .collect();
+    /// assert_eq!(tokens[1].describe(source).to_string(), "Symbol a @ 1:2 (1-1): \"a\"");
+    /// assert_eq!(tokens[2].describe(source).to_string(), "String \"b c\" @ 2:3 (5-9): \"\\\"b c\\\"\"");
+    /// ```
+    pub fn describe<'a>(&'a self, source: &'a str) -> DatumTokenDescription<'a, B> {
+        DatumTokenDescription {
+            token: self,
+            source,
+        }
+    }
+
     /// Writes this value as a valid, parsable Datum token.
     pub fn write(&self, f: &mut dyn Write) -> core::fmt::Result {
+        self.write_with_plus_sign(f, false)
+    }
+
+    /// Writes this value like [DatumToken::write], but so that it also reads back the same with [crate::DatumDecoder::plus_sign] set.
+    /// This escapes a leading `+` in symbols other than `+` itself, which the default decoder doesn't need.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::DatumToken;
+    /// let mut text = String::new();
+    /// DatumToken::Symbol(0, "+a").write(&mut text).unwrap();
+    /// text.push(' ');
+    /// DatumToken::Symbol(0, "+a").write_with_plus_sign(&mut text, true).unwrap();
+    /// assert_eq!(text, "+a \\+a");
+    /// ```
+    pub fn write_with_plus_sign(&self, f: &mut dyn Write, plus_sign: bool) -> core::fmt::Result {
         match self {
             Self::String(_, b) => {
                 f.write_char('\"')?;
-                for v in b.deref().chars() {
-                    DatumChar::string_content(v).write(f)?;
-                }
+                DatumStringContentWriter(f).write_str(b)?;
                 f.write_char('\"')
             }
             Self::Symbol(_, b) => {
                 let mut chars = b.chars();
                 match chars.next() {
                     Some(v) => {
-                        if DatumCharClass::identify(v) == Some(DatumCharClass::Sign) {
+                        // with [crate::DatumDecoder::plus_sign], + is a sign like -
+                        let class = DatumCharClass::identify_with_plus_sign(v, plus_sign);
+                        if class == Some(DatumCharClass::Sign) {
                             match chars.next() {
                                 Some(v2) => {
-                                    // business as usual
-                                    DatumChar::content(v).write(f)?;
+                                    // business as usual (+ isn't escaped by DatumChar, as it's normally content)
+                                    f.write_char('\\')?;
+                                    f.write_char(v)?;
                                     DatumChar::content(v2).write(f)?;
                                 }
                                 None => {
@@ -190,6 +307,47 @@ impl<B: Deref<Target = str>> Display for DatumToken<B> {
     }
 }
 
+/// Byte index of a character offset (clamped to the end).
+pub(crate) fn char_to_byte(source: &str, at: DatumOffset) -> usize {
+    match source.char_indices().nth(at as usize) {
+        Some((i, _)) => i,
+        None => source.len(),
+    }
+}
+
+/// Displays a token with its position and source text; see [DatumToken::describe].
+///
+/// The format is `Type token @ line:column (start-end): "source"`. Lines and columns start at 1.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, Debug)]
+pub struct DatumTokenDescription<'a, B: Deref<Target = str>> {
+    token: &'a DatumToken<B>,
+    source: &'a str,
+}
+
+impl<B: Deref<Target = str>> Display for DatumTokenDescription<'_, B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let span = self.token.source_span(self.source);
+        let start = char_to_byte(self.source, span.start);
+        let end = char_to_byte(self.source, span.end + 1);
+        let before = &self.source[..start];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        write!(
+            f,
+            "{:?} {} @ {}:{} ({}-{}): {:?}",
+            self.token.token_type(),
+            self.token,
+            line,
+            column,
+            span.start,
+            span.end,
+            &self.source[start..end]
+        )
+    }
+}
+
 /// Writes a Datum string where the contents come from a [core::fmt::Display] type.
 ///
 /// _Added in 1.1.0._
@@ -202,12 +360,69 @@ pub fn datum_write_display_as_string<T: core::fmt::Display + ?Sized>(
     f.write_char('"')
 }
 
+/// Writes escaped string contents, without the surrounding quotes.
+/// This is the same escaping as used when writing [DatumToken::String].
+///
+/// This and [datum_unescape_string_content] are meant for tooling that works on document text directly.
+///
+/// _Added in 1.3.0._
+/// ```
+/// let mut out = String::new();
+/// datum::datum_escape_string_content("say \"hi\"\n", &mut out).unwrap();
+/// assert_eq!(out, "say \\\"hi\\\"\\n");
+/// ```
+pub fn datum_escape_string_content(text: &str, f: &mut dyn Write) -> core::fmt::Result {
+    DatumStringContentWriter(f).write_str(text)
+}
+
+/// Unescapes string contents (without the surrounding quotes), i.e. the inverse of [datum_escape_string_content].
+///
+/// Any valid escape is accepted, not just those [datum_escape_string_content] writes.
+/// An unescaped `"` is an error, as it would have ended the string.
+/// Error offsets are byte offsets into the text.
+///
+/// _Added in 1.3.0._
+/// ```
+/// assert_eq!(datum::datum_unescape_string_content("a\\x41;\\\"").unwrap(), "aA\"");
+/// assert!(datum::datum_unescape_string_content("a\"").is_err());
+/// assert!(datum::datum_unescape_string_content("a\\").is_err());
+/// ```
+#[cfg(feature = "alloc")]
+pub fn datum_unescape_string_content(text: &str) -> DatumResult<String> {
+    let mut decoder = crate::DatumDecoder::default();
+    let mut res = String::with_capacity(text.len());
+    let mut output = |at: DatumOffset, c: DatumChar| {
+        if c.class() == DatumCharClass::String {
+            Err(datum_error!(BadData, at, "unescape: unescaped quote"))
+        } else {
+            res.push(c.char());
+            Ok(())
+        }
+    };
+    for (at, c) in text.char_indices() {
+        decoder.feed(at as DatumOffset, Some(c), &mut output)?;
+    }
+    decoder.feed(text.len() as DatumOffset, None, &mut output)?;
+    Ok(res)
+}
+
 /// Used by [datum_write_display_as_string].
 struct DatumStringContentWriter<'writer>(&'writer mut dyn Write);
 impl<'writer> Write for DatumStringContentWriter<'writer> {
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
-        for v in s.chars() {
-            self.write_char(v)?;
+        // runs of characters that are written as themselves are written in one go
+        let mut run = 0;
+        for (at, v) in s.char_indices() {
+            if !string_char_is_verbatim(v) {
+                if run < at {
+                    self.0.write_str(&s[run..at])?;
+                }
+                self.write_char(v)?;
+                run = at + v.len_utf8();
+            }
+        }
+        if run < s.len() {
+            self.0.write_str(&s[run..])?;
         }
         Ok(())
     }
@@ -216,6 +431,15 @@ impl<'writer> Write for DatumStringContentWriter<'writer> {
     }
 }
 
+/// If [DatumChar::string_content] writes the character as itself.
+#[inline]
+fn string_char_is_verbatim(v: char) -> bool {
+    if DatumChar::string_content(v).class() != DatumCharClass::Content {
+        return true;
+    }
+    v >= ' ' && v != '\x7F' && DatumCharClass::identify(v) == Some(DatumCharClass::Content)
+}
+
 /// Internal structure to determine if Rust didn't write any indicator this number is intended to be a float.
 struct DatumFloatObserver<'a>(&'a mut dyn Write, bool);
 
@@ -241,6 +465,8 @@ impl Write for DatumFloatObserver<'_> {
 #[derive(Clone, Default, Debug)]
 pub struct DatumPipeTokenizer<B: Write + Deref<Target = str> + Default>(B, DatumTokenizer);
 
+// Pushes are absorbed into the buffer, so this is bounded by the tokens out of [DatumTokenizer].
+// That is, two tokens: i.e. `a)` outputs the symbol and then the list end on receiving `)`.
 impl<B: Write + Deref<Target = str> + Default> DatumBoundedPipe for DatumPipeTokenizer<B> {
     type OutputQueueSize = unary::C2;
 }
@@ -270,3 +496,52 @@ impl<B: Write + Deref<Target = str> + Default> DatumPipe for DatumPipeTokenizer<
         })
     }
 }
+
+/// Consumes one whole value (an atom, or a list and everything in it) from a token iterator, without building anything.
+///
+/// Returns false if the iterator ended before the value started.
+/// This doesn't check atoms for validity (i.e. invalid special IDs are skipped like anything else).
+///
+/// Note that the tokens themselves still have to be produced; this avoids the cost of [crate::DatumValue] and such.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{datum_char_to_token_pipeline, datum_skip_value, DatumToken, IntoViaDatumPipe};
+/// let mut tokens = "(skip (these) \"please\") keep".chars().via_datum_pipe(datum_char_to_token_pipeline());
+/// assert!(datum_skip_value(&mut tokens).unwrap());
+/// assert!(matches!(tokens.next().unwrap().unwrap(), DatumToken::Symbol(_, s) if s == "keep"));
+/// assert!(!datum_skip_value(&mut tokens).unwrap());
+/// ```
+pub fn datum_skip_value<B: Deref<Target = str>>(
+    iterator: &mut dyn Iterator<Item = DatumResult<DatumToken<B>>>,
+) -> DatumResult<bool> {
+    let mut depth: usize = 0;
+    let mut last_seen_offset = 0;
+    loop {
+        let token = match iterator.next() {
+            Some(token) => token?,
+            None if depth == 0 => return Ok(false),
+            None => {
+                return Err(datum_error!(
+                    Interrupted,
+                    last_seen_offset,
+                    "skip: unexpected EOF inside list"
+                ))
+            }
+        };
+        last_seen_offset = token.offset();
+        match token {
+            DatumToken::ListStart(_) => depth += 1,
+            DatumToken::ListEnd(at) => {
+                if depth == 0 {
+                    return Err(datum_error!(BadData, at, "skip: unexpected list end"));
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+        if depth == 0 {
+            return Ok(true);
+        }
+    }
+}
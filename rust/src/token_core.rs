@@ -12,7 +12,7 @@ use crate::{
 
 /// Datum token type.
 /// This is paired with the token contents, if any.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub enum DatumTokenType {
     /// String. Buffer contents are the unescaped string contents.
     String,
@@ -114,6 +114,9 @@ impl Default for DatumTokenizer {
     }
 }
 
+// The worst case is ending a potential identifier with a character that itself does something.
+// That is, the token for the potential identifier, followed by either a push or another token (list start/end).
+// EOF outputs at most one token.
 impl DatumBoundedPipe for DatumTokenizer {
     type OutputQueueSize = unary::C2;
 }
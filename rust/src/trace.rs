@@ -0,0 +1,240 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::{cell::RefCell, fmt::Display, marker::PhantomData, ops::Deref, str::FromStr};
+
+use crate::{
+    datum_error, unary, DatumAtom, DatumBoundedPipe, DatumDocument, DatumError, DatumOffset,
+    DatumPipe, DatumResult, DatumToken, DatumValue,
+};
+
+/// A recording of a token stream, made by [DatumTraceRecorder].
+///
+/// Traces can be saved (as a Datum document; see [DatumTrace::to_document]), redacted, loaded, and replayed into a pipeline.
+/// This is meant for reproducing parser problems with documents that can't be shared as they are.
+///
+/// Each token is written as `(OFFSET KIND)` or `(OFFSET KIND VALUE)`, i.e. `(0 list-start)` or `(4 symbol "abc")`; a recorded EOF is `(OFFSET eof)`.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use core::cell::RefCell;
+/// use datum::{datum_char_to_token_pipeline, DatumParser, DatumPipe, DatumTrace, DatumTraceRecorder};
+/// let trace = RefCell::new(DatumTrace::default());
+/// let mut pipeline = datum_char_to_token_pipeline().compose(DatumTraceRecorder::new(&trace));
+/// pipeline.feed_all_to_vec("(secret \"password\") 12".chars(), true).unwrap();
+/// let mut trace = trace.into_inner();
+/// trace.redact(|token| token.buffer().is_some());
+/// let saved = trace.to_string();
+/// assert_eq!(saved, "(0 list-start)\n(7 symbol \"xxxxxx\")\n(17 string \"xxxxxxxx\")\n(18 list-end)\n(22 integer 12)\n(22 eof)\n");
+/// // the saved trace can be attached to a bug report, and then...
+/// let trace: DatumTrace = saved.parse().unwrap();
+/// let values = trace.replay(DatumParser::default()).unwrap();
+/// assert_eq!(values.len(), 2);
+/// ```
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DatumTrace {
+    /// The recorded tokens, in order.
+    pub tokens: Vec<DatumToken<String>>,
+    /// If EOF was recorded, its offset.
+    pub eof: Option<DatumOffset>,
+}
+
+impl DatumTrace {
+    /// Replaces the contents of tokens matching the filter with placeholders.
+    ///
+    /// Buffers are replaced with `x`s (keeping the length in characters), and numbers with zero. The structure of the stream is unchanged.
+    pub fn redact<F: FnMut(&DatumToken<String>) -> bool>(&mut self, mut filter: F) {
+        for token in self.tokens.iter_mut() {
+            if !filter(token) {
+                continue;
+            }
+            let redacted = match &*token {
+                DatumToken::Integer(at, _) => DatumToken::Integer(*at, 0),
+                DatumToken::Float(at, _) => DatumToken::Float(*at, 0.0),
+                other => other
+                    .as_borrowed()
+                    .map_buffer(|b| "x".repeat(b.chars().count())),
+            };
+            *token = redacted;
+        }
+    }
+
+    /// Feeds the trace into a pipeline (with the recorded offsets), returning the output.
+    pub fn replay<P: DatumPipe<Input = DatumToken<String>>>(
+        &self,
+        mut pipe: P,
+    ) -> DatumResult<Vec<P::Output>> {
+        let mut output = Vec::new();
+        for token in &self.tokens {
+            pipe.feed(token.offset(), Some(token.clone()), &mut |_, v| {
+                output.push(v);
+                Ok(())
+            })?;
+        }
+        if let Some(at) = self.eof {
+            pipe.feed(at, None, &mut |_, v| {
+                output.push(v);
+                Ok(())
+            })?;
+        }
+        Ok(output)
+    }
+
+    /// Converts the trace into a document, for saving.
+    pub fn to_document(&self) -> DatumDocument {
+        let entry = |at: DatumOffset, kind: &str, value: Option<DatumAtom<String>>| {
+            let mut list = vec![
+                DatumValue::Atom(DatumAtom::Integer(at as i64)),
+                DatumValue::Atom(DatumAtom::Symbol(kind.to_string())),
+            ];
+            list.extend(value.map(DatumValue::Atom));
+            DatumValue::List(list)
+        };
+        let mut doc: DatumDocument = self
+            .tokens
+            .iter()
+            .map(|token| {
+                let at = token.offset();
+                match token {
+                    DatumToken::String(_, b) => {
+                        entry(at, "string", Some(DatumAtom::String(b.clone())))
+                    }
+                    DatumToken::Symbol(_, b) => {
+                        entry(at, "symbol", Some(DatumAtom::String(b.clone())))
+                    }
+                    DatumToken::SpecialID(_, b) => {
+                        entry(at, "special-id", Some(DatumAtom::String(b.clone())))
+                    }
+                    DatumToken::Integer(_, v) => entry(at, "integer", Some(DatumAtom::Integer(*v))),
+                    DatumToken::Float(_, v) => entry(at, "float", Some(DatumAtom::Float(*v))),
+                    DatumToken::ListStart(_) => entry(at, "list-start", None),
+                    DatumToken::ListEnd(_) => entry(at, "list-end", None),
+                }
+            })
+            .collect::<Vec<_>>()
+            .into();
+        if let Some(at) = self.eof {
+            doc.push(entry(at, "eof", None));
+        }
+        doc
+    }
+
+    /// Reads a trace from a document written by [DatumTrace::to_document].
+    pub fn from_document(doc: &[DatumValue]) -> DatumResult<Self> {
+        let mut trace = Self::default();
+        for (index, entry) in doc.iter().enumerate() {
+            let bad_entry = datum_error!(BadData, index as DatumOffset, "trace: bad entry");
+            if trace.eof.is_some() {
+                return Err(datum_error!(
+                    BadData,
+                    index as DatumOffset,
+                    "trace: entry after eof"
+                ));
+            }
+            let (at, kind, value) = match entry {
+                DatumValue::List(list) => match &list[..] {
+                    [DatumValue::Atom(DatumAtom::Integer(at)), DatumValue::Atom(DatumAtom::Symbol(kind)), rest @ ..]
+                        if *at >= 0 && rest.len() <= 1 =>
+                    {
+                        (*at as DatumOffset, kind.as_str(), rest.first())
+                    }
+                    _ => return Err(bad_entry),
+                },
+                _ => return Err(bad_entry),
+            };
+            let text = |value: Option<&DatumValue>| match value {
+                Some(DatumValue::Atom(DatumAtom::String(b))) => Ok(b.clone()),
+                _ => Err(bad_entry),
+            };
+            let token = match (kind, value) {
+                ("string", _) => DatumToken::String(at, text(value)?),
+                ("symbol", _) => DatumToken::Symbol(at, text(value)?),
+                ("special-id", _) => DatumToken::SpecialID(at, text(value)?),
+                ("integer", Some(DatumValue::Atom(DatumAtom::Integer(v)))) => {
+                    DatumToken::Integer(at, *v)
+                }
+                ("float", Some(DatumValue::Atom(DatumAtom::Float(v)))) => DatumToken::Float(at, *v),
+                ("list-start", None) => DatumToken::ListStart(at),
+                ("list-end", None) => DatumToken::ListEnd(at),
+                ("eof", None) => {
+                    trace.eof = Some(at);
+                    continue;
+                }
+                _ => return Err(bad_entry),
+            };
+            trace.tokens.push(token);
+        }
+        Ok(trace)
+    }
+}
+
+impl Display for DatumTrace {
+    /// Writes the trace as a document, one entry per line.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.to_document().write_canonical(f)
+    }
+}
+
+impl FromStr for DatumTrace {
+    type Err = DatumError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_document(&s.parse::<DatumDocument>()?)
+    }
+}
+
+/// 'Tracking' pipeline stage which records passing tokens into a [DatumTrace] by reference.
+///
+/// Like [crate::DatumStatsPipe], this is transparent and can be 'lost in' pipelines.
+///
+/// _Added in 1.3.0._
+pub struct DatumTraceRecorder<'trace, B: Deref<Target = str>> {
+    trace: &'trace RefCell<DatumTrace>,
+    _buffer: PhantomData<B>,
+}
+
+impl<'trace, B: Deref<Target = str>> DatumTraceRecorder<'trace, B> {
+    /// Creates a new DatumTraceRecorder recording into the given trace.
+    pub fn new(trace: &'trace RefCell<DatumTrace>) -> Self {
+        Self {
+            trace,
+            _buffer: PhantomData,
+        }
+    }
+}
+
+impl<B: Deref<Target = str>> DatumPipe for DatumTraceRecorder<'_, B> {
+    type Input = DatumToken<B>;
+    type Output = DatumToken<B>;
+    fn feed<F: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut F,
+    ) -> DatumResult<()> {
+        let mut trace = self.trace.borrow_mut();
+        match i {
+            Some(token) => {
+                trace
+                    .tokens
+                    .push(token.as_borrowed().map_buffer(String::from));
+                drop(trace);
+                f(at, token)
+            }
+            None => {
+                trace.eof = Some(at);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<B: Deref<Target = str>> DatumBoundedPipe for DatumTraceRecorder<'_, B> {
+    type OutputQueueSize = unary::C1;
+}
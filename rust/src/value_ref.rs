@@ -0,0 +1,375 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::convert::TryFrom;
+use core::fmt::{Display, Write};
+
+use crate::{
+    datum_error, DatumAtom, DatumComposePipe, DatumDecoder, DatumMayContainAtom, DatumOffset,
+    DatumPipe, DatumResult, DatumToken, DatumTokenType, DatumTokenizer, DatumTokenizerAction,
+    DatumWriter,
+};
+
+/// Borrowed Datum value: Like `DatumValue`, but the text and lists are borrowed rather than owned.
+///
+/// As this needs no allocation, fully static documents can be built at compile time with [crate::static_datum], and live in read-only memory without any startup parsing (even without `alloc`).
+///
+/// Unlike `DatumValue`, writing is recursive; this is meant for hand-written data rather than untrusted input.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{static_datum, DatumMayContainAtom, DatumValueRef};
+/// static CONFIG: DatumValueRef = static_datum!((window (title "Demo") (size 640 480) (fullscreen #f)));
+/// let size = CONFIG.as_list().unwrap()[2].as_list().unwrap();
+/// assert_eq!(size[1].as_i64(), Some(640));
+/// assert_eq!(CONFIG.to_string(), "(window (title \"Demo\") (size 640 480) (fullscreen #f))");
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DatumValueRef<'a> {
+    Atom(DatumAtom<&'a str>),
+    List(&'a [DatumValueRef<'a>]),
+}
+
+impl<'a> DatumValueRef<'a> {
+    /// Writes the value.
+    pub fn write_to(&self, f: &mut dyn Write, writer: &mut DatumWriter) -> core::fmt::Result {
+        match self {
+            DatumValueRef::Atom(v) => writer.write_atom(f, v),
+            DatumValueRef::List(list) => {
+                let ls: DatumToken<&str> = DatumToken::ListStart(0);
+                let le: DatumToken<&str> = DatumToken::ListEnd(0);
+                writer.write_token(f, &ls)?;
+                for v in list.iter() {
+                    v.write_to(f, writer)?;
+                }
+                writer.write_token(f, &le)
+            }
+        }
+    }
+
+    /// If this value is a list, returns it, otherwise [None].
+    pub fn as_list(&self) -> Option<&'a [DatumValueRef<'a>]> {
+        match self {
+            DatumValueRef::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Copies this value into an owned `DatumValue`.
+    #[cfg(feature = "alloc")]
+    pub fn to_value(&self) -> crate::DatumValue {
+        match self {
+            DatumValueRef::Atom(v) => crate::DatumValue::Atom(match *v {
+                DatumAtom::String(v) => DatumAtom::String(v.into()),
+                DatumAtom::Symbol(v) => DatumAtom::Symbol(v.into()),
+                DatumAtom::Integer(v) => DatumAtom::Integer(v),
+                DatumAtom::Float(v) => DatumAtom::Float(v),
+                DatumAtom::Boolean(v) => DatumAtom::Boolean(v),
+                DatumAtom::Nil => DatumAtom::Nil,
+            }),
+            DatumValueRef::List(list) => {
+                crate::DatumValue::List(list.iter().map(|v| v.to_value()).collect())
+            }
+        }
+    }
+}
+
+impl<'a> DatumValueRef<'a> {
+    /// Parses a document without allocating, into caller-provided space, returning the top-level values.
+    ///
+    /// `nodes` holds the values: One node per value, plus one per level of list nesting while parsing.
+    /// `text` holds the contents of strings and symbols (as UTF-8).
+    /// Running out of either is [crate::DatumErrorKind::OutOfRoom].
+    ///
+    /// Parsing isn't recursive, but the result is; see the notes on [DatumValueRef].
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumAtom, DatumValueRef};
+    /// let mut nodes = [DatumValueRef::Atom(DatumAtom::Nil); 16];
+    /// let mut text = [0u8; 64];
+    /// let doc = DatumValueRef::parse_into("(size 640 480) title \"Demo\"", &mut nodes, &mut text).unwrap();
+    /// assert_eq!(doc.len(), 3);
+    /// assert_eq!(doc[0].to_string(), "(size 640 480)");
+    /// assert_eq!(doc[2], DatumValueRef::Atom(DatumAtom::String("Demo")));
+    /// ```
+    pub fn parse_into(
+        source: &str,
+        nodes: &'a mut [DatumValueRef<'a>],
+        text: &'a mut [u8],
+    ) -> DatumResult<&'a [DatumValueRef<'a>]> {
+        let mut arena = DatumRefArena {
+            nodes,
+            stack_len: 0,
+            count: 0,
+            depth: 0,
+            text,
+            text_len: 0,
+        };
+        let mut pipe: DatumComposePipe<DatumDecoder, DatumTokenizer> = DatumComposePipe::default();
+        let mut at: DatumOffset = 0;
+        for c in source.chars() {
+            pipe.feed(at, Some(c), &mut |at, action| arena.action(at, action))?;
+            at += 1;
+        }
+        pipe.feed(at, None, &mut |at, action| arena.action(at, action))?;
+        if arena.depth != 0 {
+            return Err(datum_error!(Interrupted, at, "eof inside list"));
+        }
+        Ok(arena.take_list())
+    }
+}
+
+/// Parser state for [DatumValueRef::parse_into].
+///
+/// Unfinished values are kept in a stack at the back of `nodes`, with the previous `count` stored as an integer atom under each open list.
+/// When a list ends, its values are moved to the front of `nodes`, which is then split off to become the list.
+struct DatumRefArena<'a> {
+    nodes: &'a mut [DatumValueRef<'a>],
+    stack_len: usize,
+    /// Values in the innermost open list, or at top level.
+    count: usize,
+    depth: usize,
+    text: &'a mut [u8],
+    text_len: usize,
+}
+
+impl<'a> DatumRefArena<'a> {
+    fn push(&mut self, at: DatumOffset, v: DatumValueRef<'a>) -> DatumResult<()> {
+        if self.stack_len == self.nodes.len() {
+            return Err(datum_error!(OutOfRoom, at, "out of nodes"));
+        }
+        self.stack_len += 1;
+        self.nodes[self.nodes.len() - self.stack_len] = v;
+        Ok(())
+    }
+
+    /// Pops the innermost `count` values into a finished list.
+    fn take_list(&mut self) -> &'a [DatumValueRef<'a>] {
+        let len = self.count;
+        let start = self.nodes.len() - self.stack_len;
+        self.nodes[start..start + len].reverse();
+        self.nodes.copy_within(start..start + len, 0);
+        self.stack_len -= len;
+        let (list, rest) = core::mem::take(&mut self.nodes).split_at_mut(len);
+        self.nodes = rest;
+        list
+    }
+
+    /// Takes the buffered text.
+    fn take_text(&mut self, at: DatumOffset) -> DatumResult<&'a str> {
+        let (text, rest) = core::mem::take(&mut self.text).split_at_mut(self.text_len);
+        self.text = rest;
+        self.text_len = 0;
+        core::str::from_utf8(text).map_err(|_| datum_error!(BadData, at, "invalid UTF-8"))
+    }
+
+    fn action(&mut self, at: DatumOffset, action: DatumTokenizerAction) -> DatumResult<()> {
+        match action {
+            DatumTokenizerAction::Push(c) => {
+                let len = c.len_utf8();
+                if self.text.len() - self.text_len < len {
+                    return Err(datum_error!(OutOfRoom, at, "out of text space"));
+                }
+                c.encode_utf8(&mut self.text[self.text_len..]);
+                self.text_len += len;
+                Ok(())
+            }
+            DatumTokenizerAction::Token(DatumTokenType::ListStart) => {
+                self.push(
+                    at,
+                    DatumValueRef::Atom(DatumAtom::Integer(self.count as i64)),
+                )?;
+                self.count = 0;
+                self.depth += 1;
+                Ok(())
+            }
+            DatumTokenizerAction::Token(DatumTokenType::ListEnd) => {
+                if self.depth == 0 {
+                    return Err(datum_error!(BadData, at, "end of list while not in list"));
+                }
+                self.depth -= 1;
+                let list = self.take_list();
+                // the marker
+                let marker = self.nodes[self.nodes.len() - self.stack_len];
+                self.stack_len -= 1;
+                if let DatumValueRef::Atom(DatumAtom::Integer(count)) = marker {
+                    self.count = count as usize;
+                }
+                self.push(at, DatumValueRef::List(list))?;
+                self.count += 1;
+                Ok(())
+            }
+            DatumTokenizerAction::Token(tt) => {
+                // atoms are parsed while the text is still in the buffer, so only strings and symbols keep it
+                let atom = {
+                    let text = core::str::from_utf8(&self.text[..self.text_len])
+                        .map_err(|_| datum_error!(BadData, at, "invalid UTF-8"))?;
+                    match DatumAtom::from_token(DatumToken::try_from((tt, at, text))?, false)? {
+                        DatumAtom::String(_) => None,
+                        DatumAtom::Symbol(v) => {
+                            if v.is_empty() {
+                                Some(DatumAtom::Symbol(""))
+                            } else {
+                                None
+                            }
+                        }
+                        DatumAtom::Integer(v) => Some(DatumAtom::Integer(v)),
+                        DatumAtom::Float(v) => Some(DatumAtom::Float(v)),
+                        DatumAtom::Boolean(v) => Some(DatumAtom::Boolean(v)),
+                        DatumAtom::Nil => Some(DatumAtom::Nil),
+                    }
+                };
+                let atom = match atom {
+                    Some(atom) => {
+                        self.text_len = 0;
+                        atom
+                    }
+                    None if tt == DatumTokenType::String => DatumAtom::String(self.take_text(at)?),
+                    None => DatumAtom::Symbol(self.take_text(at)?),
+                };
+                self.push(at, DatumValueRef::Atom(atom))?;
+                self.count += 1;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for DatumValueRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.write_to(f, &mut DatumWriter::default())
+    }
+}
+
+impl<'a> DatumMayContainAtom<&'a str> for DatumValueRef<'a> {
+    fn as_atom(&self) -> Option<&DatumAtom<&'a str>> {
+        if let DatumValueRef::Atom(a) = self {
+            Some(a)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DatumValueRef<'_> {
+    fn eq_node<V: crate::DatumValueNode>(&self, other: &V) -> bool {
+        match (self, other.node()) {
+            // tokens, as they're both borrowed
+            (DatumValueRef::Atom(a), crate::DatumNode::Atom(b)) => a.to_token(0) == b.to_token(0),
+            (DatumValueRef::List(a), crate::DatumNode::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.eq_node(b))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<crate::DatumValue> for DatumValueRef<'_> {
+    fn eq(&self, other: &crate::DatumValue) -> bool {
+        self.eq_node(other)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B: core::ops::Deref<Target = str>, S: crate::DatumListStorage<B>>
+    PartialEq<crate::DatumValueOf<B, S>> for DatumValueRef<'_>
+{
+    fn eq(&self, other: &crate::DatumValueOf<B, S>) -> bool {
+        self.eq_node(other)
+    }
+}
+
+/// Rust literal to atom conversion for [crate::static_datum].
+/// Inherent methods on concrete types are used so that the literal's type is inferred, and the conversion stays `const`.
+#[doc(hidden)]
+pub struct DatumStaticLiteral<T>(pub T);
+
+impl DatumStaticLiteral<&'static str> {
+    pub const fn atom(self) -> DatumAtom<&'static str> {
+        DatumAtom::String(self.0)
+    }
+}
+
+impl DatumStaticLiteral<i64> {
+    pub const fn atom(self) -> DatumAtom<&'static str> {
+        DatumAtom::Integer(self.0)
+    }
+}
+
+impl DatumStaticLiteral<f64> {
+    pub const fn atom(self) -> DatumAtom<&'static str> {
+        DatumAtom::Float(self.0)
+    }
+}
+
+impl DatumStaticLiteral<bool> {
+    pub const fn atom(self) -> DatumAtom<&'static str> {
+        DatumAtom::Boolean(self.0)
+    }
+}
+
+/// Builds a [DatumValueRef] at compile time, from Datum-like syntax.
+///
+/// * `(...)` is a list.
+/// * Identifiers are symbols. (Symbols which aren't Rust identifiers can't be written.)
+/// * String, integer and float literals are strings, integers and floats. Rust escapes apply in strings.
+/// * `#t`, `#f` and `#nil` are themselves; `true` and `false` also work.
+///
+/// Each list element is one step of macro recursion, so very long lists may need `#![recursion_limit]` raised.
+///
+/// A whole document can be written as a slice, i.e. `&[static_datum!(a), static_datum!((b))]`.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{static_datum, DatumAtom, DatumValueRef};
+/// static ITEMS: &[DatumValueRef] = &[
+///     static_datum!((sword (damage 5) (weight 1.5))),
+///     static_datum!((potion (heal -2) (name "Red\nPotion") #nil)),
+/// ];
+/// assert_eq!(ITEMS[1].to_string(), "(potion (heal -2) (name \"Red\\nPotion\") #nil)");
+/// ```
+#[macro_export]
+macro_rules! static_datum {
+    // List contents, one element at a time.
+    (@list [$($done:expr,)*]) => {
+        $crate::DatumValueRef::List(&[$($done,)*])
+    };
+    (@list [$($done:expr,)*] # $id:ident $($rest:tt)*) => {
+        $crate::static_datum!(@list [$($done,)* $crate::static_datum!(# $id),] $($rest)*)
+    };
+    (@list [$($done:expr,)*] - $v:literal $($rest:tt)*) => {
+        $crate::static_datum!(@list [$($done,)* $crate::static_datum!(- $v),] $($rest)*)
+    };
+    (@list [$($done:expr,)*] $v:tt $($rest:tt)*) => {
+        $crate::static_datum!(@list [$($done,)* $crate::static_datum!($v),] $($rest)*)
+    };
+    // Values.
+    (($($v:tt)*)) => {
+        $crate::static_datum!(@list [] $($v)*)
+    };
+    (# t) => {
+        $crate::DatumValueRef::Atom($crate::DatumAtom::Boolean(true))
+    };
+    (# f) => {
+        $crate::DatumValueRef::Atom($crate::DatumAtom::Boolean(false))
+    };
+    (# nil) => {
+        $crate::DatumValueRef::Atom($crate::DatumAtom::Nil)
+    };
+    (- $v:literal) => {
+        $crate::DatumValueRef::Atom($crate::DatumStaticLiteral(-$v).atom())
+    };
+    ($v:literal) => {
+        $crate::DatumValueRef::Atom($crate::DatumStaticLiteral($v).atom())
+    };
+    ($v:ident) => {
+        $crate::DatumValueRef::Atom($crate::DatumAtom::Symbol(stringify!($v)))
+    };
+}
@@ -0,0 +1,330 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+use core::{fmt::Display, marker::PhantomData, ops::Deref};
+
+use crate::{
+    atom::special_id_is_canonical, unary, DatumBoundedPipe, DatumCharClass, DatumOffset, DatumPipe,
+    DatumResult, DatumToken,
+};
+
+#[cfg(feature = "alloc")]
+use crate::{DatumComposePipe, DatumDecoder, DatumPipeTokenizer};
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+/// Kind of [DatumWarning].
+///
+/// _Added in 1.3.0._
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DatumWarningKind {
+    /// An escape of a letter with no escape meaning, i.e. `\a`. This is valid (it's just the letter), but probably not what was meant.
+    UnknownEscape,
+    /// Spaces or tabs at the end of a line (outside of strings).
+    TrailingWhitespace,
+    /// A special ID not spelled as [crate::DatumWriter] would write it, i.e. `#T` or `#NIL`. See [crate::DatumAtom::from_token].
+    NonCanonicalSpecialID,
+    /// A token starting with an unescaped `+` followed by more characters, i.e. `+1`.
+    /// This means something different depending on [crate::DatumDecoder::plus_sign], so it should be written as `\+1` or `1`.
+    PlusSign,
+}
+
+impl Display for DatumWarningKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Something suspicious that doesn't stop parsing.
+/// Unlike [crate::DatumError], warnings are reported to a callback and parsing carries on.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DatumWarning {
+    /// Kind of warning.
+    pub kind: DatumWarningKind,
+    /// Warning occurred at this offset (as supplied to the pipeline stage that found it).
+    pub offset: DatumOffset,
+    /// Message.
+    pub message: &'static str,
+}
+
+impl Display for DatumWarning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} @ {}: {}", self.kind, self.offset, self.message)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum DatumCharLinterState {
+    Normal,
+    String,
+    Comment,
+    Escape(bool),
+    HexEscape(bool),
+}
+
+/// Transparent pipeline stage over raw `char`s (so it goes before a [crate::DatumDecoder]), reporting [DatumWarning]s about escapes and whitespace.
+///
+/// Reports [DatumWarningKind::UnknownEscape], [DatumWarningKind::TrailingWhitespace] and [DatumWarningKind::PlusSign].
+///
+/// The callback is `Fn` so that a reference to it can be shared with a [DatumTokenLinter]; collect warnings with a [core::cell::RefCell] or similar.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumPipe, DatumCharLinter, DatumWarningKind};
+/// use core::cell::RefCell;
+/// let warnings = RefCell::new(Vec::new());
+/// let mut linter = DatumCharLinter::new(|w| warnings.borrow_mut().push(w));
+/// let mut chars = Vec::new();
+/// for (i, c) in "\\a \"x  \n\" b  \n".chars().enumerate() {
+///     linter.feed(i as u64, Some(c), &mut |_, c| {
+///         chars.push(c);
+///         Ok(())
+///     }).unwrap();
+/// }
+/// assert_eq!(chars.len(), 14);
+/// let warnings = warnings.into_inner();
+/// assert_eq!(warnings.len(), 2);
+/// assert_eq!(warnings[0].kind, DatumWarningKind::UnknownEscape);
+/// assert_eq!(warnings[1].kind, DatumWarningKind::TrailingWhitespace);
+/// assert_eq!(warnings[1].offset, 11);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DatumCharLinter<W: Fn(DatumWarning)> {
+    state: DatumCharLinterState,
+    whitespace_start: Option<DatumOffset>,
+    token_start: bool,
+    plus_at: Option<DatumOffset>,
+    warn: W,
+}
+
+impl<W: Fn(DatumWarning)> DatumCharLinter<W> {
+    /// Creates a new DatumCharLinter reporting to the given callback.
+    pub fn new(warn: W) -> Self {
+        Self {
+            state: DatumCharLinterState::Normal,
+            whitespace_start: None,
+            token_start: true,
+            plus_at: None,
+            warn,
+        }
+    }
+
+    fn warn(&self, kind: DatumWarningKind, offset: DatumOffset, message: &'static str) {
+        (self.warn)(DatumWarning {
+            kind,
+            offset,
+            message,
+        });
+    }
+
+    fn check(&mut self, at: DatumOffset, c: char) {
+        // ignored, as in the decoder
+        if c == '\r' {
+            return;
+        }
+        let class = DatumCharClass::identify(c);
+        if matches!(
+            self.state,
+            DatumCharLinterState::Normal | DatumCharLinterState::Comment
+        ) {
+            match class {
+                Some(DatumCharClass::Whitespace) => {
+                    if self.whitespace_start.is_none() {
+                        self.whitespace_start = Some(at);
+                    }
+                }
+                Some(DatumCharClass::Newline) => {
+                    if let Some(start) = self.whitespace_start.take() {
+                        self.warn(
+                            DatumWarningKind::TrailingWhitespace,
+                            start,
+                            "trailing whitespace",
+                        );
+                    }
+                }
+                _ => self.whitespace_start = None,
+            }
+        }
+        if let Some(plus) = self.plus_at.take() {
+            if c == '\\' || matches!(class, Some(v) if v.potential_identifier()) {
+                self.warn(
+                    DatumWarningKind::PlusSign,
+                    plus,
+                    "unescaped leading '+' (meaning depends on decoder options)",
+                );
+            }
+        }
+        self.state = match self.state {
+            DatumCharLinterState::Normal => {
+                if self.token_start && c == '+' {
+                    self.plus_at = Some(at);
+                }
+                self.token_start = matches!(
+                    class,
+                    Some(DatumCharClass::Whitespace)
+                        | Some(DatumCharClass::Newline)
+                        | Some(DatumCharClass::ListStart)
+                        | Some(DatumCharClass::ListEnd)
+                );
+                match class {
+                    Some(DatumCharClass::LineComment) => DatumCharLinterState::Comment,
+                    Some(DatumCharClass::String) => DatumCharLinterState::String,
+                    None if c == '\\' => DatumCharLinterState::Escape(false),
+                    _ => DatumCharLinterState::Normal,
+                }
+            }
+            DatumCharLinterState::Comment => {
+                if class == Some(DatumCharClass::Newline) {
+                    self.token_start = true;
+                    DatumCharLinterState::Normal
+                } else {
+                    DatumCharLinterState::Comment
+                }
+            }
+            DatumCharLinterState::String => match c {
+                '\\' => DatumCharLinterState::Escape(true),
+                '"' => {
+                    self.token_start = true;
+                    DatumCharLinterState::Normal
+                }
+                _ => DatumCharLinterState::String,
+            },
+            DatumCharLinterState::Escape(in_string) => {
+                if c == 'x' {
+                    DatumCharLinterState::HexEscape(in_string)
+                } else {
+                    if c.is_ascii_alphabetic() && !matches!(c, 'r' | 'n' | 't') {
+                        self.warn(
+                            DatumWarningKind::UnknownEscape,
+                            at,
+                            "escaped letter has no special meaning",
+                        );
+                    }
+                    if in_string {
+                        DatumCharLinterState::String
+                    } else {
+                        DatumCharLinterState::Normal
+                    }
+                }
+            }
+            DatumCharLinterState::HexEscape(in_string) => match c {
+                ';' if in_string => DatumCharLinterState::String,
+                ';' => DatumCharLinterState::Normal,
+                _ => DatumCharLinterState::HexEscape(in_string),
+            },
+        };
+    }
+}
+
+impl<W: Fn(DatumWarning)> DatumPipe for DatumCharLinter<W> {
+    type Input = char;
+    type Output = char;
+    fn feed<F: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut F,
+    ) -> DatumResult<()> {
+        if let Some(c) = i {
+            self.check(at, c);
+            f(at, c)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<W: Fn(DatumWarning)> DatumBoundedPipe for DatumCharLinter<W> {
+    type OutputQueueSize = unary::C1;
+}
+
+/// Transparent pipeline stage over tokens, reporting [DatumWarning]s about accepted-but-unusual syntax.
+///
+/// Reports [DatumWarningKind::NonCanonicalSpecialID].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, Debug)]
+pub struct DatumTokenLinter<B: Deref<Target = str>, W: Fn(DatumWarning)> {
+    warn: W,
+    _buffer: PhantomData<B>,
+}
+
+impl<B: Deref<Target = str>, W: Fn(DatumWarning)> DatumTokenLinter<B, W> {
+    /// Creates a new DatumTokenLinter reporting to the given callback.
+    pub fn new(warn: W) -> Self {
+        Self {
+            warn,
+            _buffer: PhantomData,
+        }
+    }
+}
+
+impl<B: Deref<Target = str>, W: Fn(DatumWarning)> DatumPipe for DatumTokenLinter<B, W> {
+    type Input = DatumToken<B>;
+    type Output = DatumToken<B>;
+    fn feed<F: FnMut(DatumOffset, Self::Output) -> DatumResult<()>>(
+        &mut self,
+        at: DatumOffset,
+        i: Option<Self::Input>,
+        f: &mut F,
+    ) -> DatumResult<()> {
+        if let Some(token) = i {
+            match &token {
+                DatumToken::SpecialID(offset, id) if !special_id_is_canonical(id) => {
+                    (self.warn)(DatumWarning {
+                        kind: DatumWarningKind::NonCanonicalSpecialID,
+                        offset: *offset,
+                        message: "non-canonical special ID",
+                    })
+                }
+                _ => {}
+            }
+            f(at, token)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<B: Deref<Target = str>, W: Fn(DatumWarning)> DatumBoundedPipe for DatumTokenLinter<B, W> {
+    type OutputQueueSize = unary::C1;
+}
+
+/// Character to token parsing pipeline, with a [DatumCharLinter] and [DatumTokenLinter] reporting to the given callback.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumWarningKind, IntoViaDatumPipe, datum_char_to_linted_token_pipeline};
+/// use core::cell::RefCell;
+/// let warnings = RefCell::new(Vec::new());
+/// let warn = |w| warnings.borrow_mut().push(w);
+/// let tokens = "#T \\q +1".chars()
+///     .via_datum_pipe(datum_char_to_linted_token_pipeline(&warn))
+///     .count();
+/// assert_eq!(tokens, 3);
+/// let kinds: Vec<DatumWarningKind> = warnings.into_inner().iter().map(|w| w.kind).collect();
+/// assert_eq!(kinds, [
+///     DatumWarningKind::NonCanonicalSpecialID,
+///     DatumWarningKind::UnknownEscape,
+///     DatumWarningKind::PlusSign,
+/// ]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn datum_char_to_linted_token_pipeline<W: Fn(DatumWarning) + Copy>(
+    warn: W,
+) -> impl DatumPipe<Input = char, Output = DatumToken<String>> {
+    DatumComposePipe(
+        DatumComposePipe(
+            DatumCharLinter::new(warn),
+            DatumComposePipe(DatumDecoder::default(), DatumPipeTokenizer::default()),
+        ),
+        DatumTokenLinter::new(warn),
+    )
+}
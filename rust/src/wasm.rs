@@ -0,0 +1,129 @@
+/*
+ * datum-rs - Quick to implement S-expression format
+ * Written starting in 2024 by contributors (see CREDITS.txt at repository's root)
+ * To the extent possible under law, the author(s) have dedicated all copyright and related and neighboring rights to this software to the public domain worldwide. This software is distributed without any warranty.
+ * A copy of the Unlicense should have been supplied as COPYING.txt in this repository. Alternatively, you can find it at <https://unlicense.org/>.
+ */
+
+//! JavaScript interface via `wasm-bindgen`, for use in the browser (i.e. editors and playgrounds).
+//!
+//! To use this, make a crate with `crate-type = ["cdylib"]` which depends on this one with the `wasm` feature, and `pub use datum::wasm::*;`, then build it with `wasm-pack` or `wasm-bindgen`.
+//!
+//! Values map to JavaScript as follows:
+//!
+//! | Datum | JavaScript |
+//! |-|-|
+//! | String | `string` |
+//! | Symbol | `{ symbol: string }` |
+//! | Integer | `bigint` |
+//! | Float | `number` |
+//! | Boolean | `boolean` |
+//! | Nil | `null` (`undefined` is also accepted) |
+//! | List | `Array` |
+//!
+//! Integers are `bigint` so that they survive the round-trip exactly, and remain distinct from floats.
+//!
+//! _Added in 1.3.0._
+
+// The glue wasm-bindgen generates for wasm32 targets uses `unsafe`.
+#![allow(unsafe_code)]
+
+use std::convert::TryFrom;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::{DatumAtom, DatumDocument, DatumError, DatumValue};
+
+fn js_error(message: &str) -> JsValue {
+    js_sys::Error::new(message).into()
+}
+
+fn js_datum_error(e: DatumError) -> JsValue {
+    let error = js_sys::Error::new(&e.to_string());
+    // failure here would mean the error object is frozen, which it isn't
+    let _ = Reflect::set(
+        &error,
+        &"offset".into(),
+        &JsValue::from_f64(e.offset as f64),
+    );
+    error.into()
+}
+
+/// Converts a [DatumValue] to the JavaScript representation described in the module documentation.
+///
+/// _Added in 1.3.0._
+pub fn datum_value_to_js(value: &DatumValue) -> JsValue {
+    match value {
+        DatumValue::Atom(DatumAtom::String(v)) => JsValue::from_str(v),
+        DatumValue::Atom(DatumAtom::Symbol(v)) => {
+            let object = Object::new();
+            let _ = Reflect::set(&object, &"symbol".into(), &JsValue::from_str(v));
+            object.into()
+        }
+        DatumValue::Atom(DatumAtom::Integer(v)) => js_sys::BigInt::from(*v).into(),
+        DatumValue::Atom(DatumAtom::Float(v)) => JsValue::from_f64(*v),
+        DatumValue::Atom(DatumAtom::Boolean(v)) => JsValue::from_bool(*v),
+        DatumValue::Atom(DatumAtom::Nil) => JsValue::NULL,
+        DatumValue::List(list) => list.iter().map(datum_value_to_js).collect::<Array>().into(),
+    }
+}
+
+/// Converts from the JavaScript representation described in the module documentation to a [DatumValue].
+///
+/// _Added in 1.3.0._
+pub fn datum_value_from_js(value: &JsValue) -> Result<DatumValue, JsValue> {
+    if let Some(v) = value.as_string() {
+        Ok(DatumValue::Atom(DatumAtom::String(v)))
+    } else if let Some(v) = value.as_f64() {
+        Ok(DatumValue::Atom(DatumAtom::Float(v)))
+    } else if let Some(v) = value.as_bool() {
+        Ok(DatumValue::Atom(DatumAtom::Boolean(v)))
+    } else if value.is_null() || value.is_undefined() {
+        Ok(DatumValue::Atom(DatumAtom::Nil))
+    } else if value.is_bigint() {
+        match i64::try_from(value.clone()) {
+            Ok(v) => Ok(DatumValue::Atom(DatumAtom::Integer(v))),
+            Err(_) => Err(js_error("datum: bigint out of range for an integer")),
+        }
+    } else if let Some(array) = value.dyn_ref::<Array>() {
+        let mut list = Vec::new();
+        for v in array.iter() {
+            list.push(datum_value_from_js(&v)?);
+        }
+        Ok(DatumValue::List(list))
+    } else if value.is_object() {
+        match Reflect::get(value, &"symbol".into())?.as_string() {
+            Some(v) => Ok(DatumValue::Atom(DatumAtom::Symbol(v))),
+            None => Err(js_error("datum: object is not a symbol")),
+        }
+    } else {
+        Err(js_error("datum: value has no Datum equivalent"))
+    }
+}
+
+/// Parses a document, returning an `Array` of its top-level values.
+///
+/// Errors are thrown as `Error`s with an additional `offset` property (in characters).
+///
+/// _Added in 1.3.0._
+#[wasm_bindgen(js_name = parse)]
+pub fn datum_parse_js(source: &str) -> Result<Array, JsValue> {
+    let document = DatumDocument::try_from(source).map_err(js_datum_error)?;
+    Ok(document.iter().map(datum_value_to_js).collect())
+}
+
+/// Writes an `Array` of top-level values as a document, each value on its own line.
+///
+/// _Added in 1.3.0._
+#[wasm_bindgen(js_name = stringify)]
+pub fn datum_stringify_js(values: &Array) -> Result<String, JsValue> {
+    let mut document = Vec::new();
+    for v in values.iter() {
+        document.push(datum_value_from_js(&v)?);
+    }
+    Ok(DatumDocument::from(document).to_string())
+}
@@ -7,7 +7,10 @@
 
 use core::{fmt::Write, ops::Deref};
 
-use crate::{DatumAtom, DatumToken, DatumTokenType};
+use crate::{
+    datum_error, datum_escape_string_content, DatumAtom, DatumOffset, DatumResult, DatumToken,
+    DatumTokenType,
+};
 
 /// The states a Datum writer can be in.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -26,14 +29,215 @@ impl Default for DatumWriterState {
     }
 }
 
+/// Line ending for written documents.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DatumLineEnding {
+    /// `\n`, the default.
+    Lf,
+    /// `\r\n`.
+    CrLf,
+}
+
+// `#[default]` on enum variants is newer than the MSRV.
+#[allow(clippy::derivable_impls)]
+impl Default for DatumLineEnding {
+    fn default() -> Self {
+        Self::Lf
+    }
+}
+
+impl DatumLineEnding {
+    /// The line ending's text.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How whole documents are laid out: line endings and how the document ends.
+///
+/// Used by [crate::DatumDocument::write_with] and [crate::DatumFormatConfig].
+/// The default matches what was written before this existed.
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumDocument, DatumDocumentStyle, DatumLineEnding};
+/// let doc: DatumDocument = "a (b)".parse().unwrap();
+/// let style = DatumDocumentStyle {
+///     line_ending: DatumLineEnding::CrLf,
+///     trailing_newline: false,
+///     ..Default::default()
+/// };
+/// let mut text = String::new();
+/// doc.write_with(&mut text, &style).unwrap();
+/// assert_eq!(text, "a\r\n(b)");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DatumDocumentStyle {
+    /// Line ending to use.
+    pub line_ending: DatumLineEnding,
+    /// If the last line of a non-empty document ends with a line ending. Defaults to true.
+    pub trailing_newline: bool,
+    /// If an empty document is written as a single line ending, rather than nothing. Defaults to false.
+    pub newline_if_empty: bool,
+}
+
+impl Default for DatumDocumentStyle {
+    fn default() -> Self {
+        Self {
+            line_ending: DatumLineEnding::Lf,
+            trailing_newline: true,
+            newline_if_empty: false,
+        }
+    }
+}
+
+impl DatumDocumentStyle {
+    /// Writes the end of a document, given if anything was written.
+    pub fn write_end(&self, f: &mut dyn Write, empty: bool) -> core::fmt::Result {
+        if (empty && self.newline_if_empty) || (!empty && self.trailing_newline) {
+            f.write_str(self.line_ending.as_str())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// How blank lines in comment text are written; see [DatumCommentStyle].
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DatumBlankCommentLines {
+    /// As the whole prefix, i.e. `; `. This is the default.
+    Prefix,
+    /// As the prefix without trailing whitespace, i.e. `;`.
+    Trimmed,
+    /// Not at all.
+    Skip,
+}
+
+// `#[default]` on enum variants is newer than the MSRV.
+#[allow(clippy::derivable_impls)]
+impl Default for DatumBlankCommentLines {
+    fn default() -> Self {
+        Self::Prefix
+    }
+}
+
+/// What happens to characters in comment text that would make the document unreadable or misleading; see [DatumCommentStyle].
+///
+/// These are control characters other than tab (which are forbidden in Datum documents, except for `\r`, which the decoder drops), Unicode line separators, and bidirectional formatting characters.
+/// Backslashes are always escaped, as they would otherwise start an escape sequence.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DatumCommentControl {
+    /// Written as hex escapes, i.e. `\x1b;`. This is the default.
+    Escape,
+    /// Removed.
+    Strip,
+}
+
+// `#[default]` on enum variants is newer than the MSRV.
+#[allow(clippy::derivable_impls)]
+impl Default for DatumCommentControl {
+    fn default() -> Self {
+        Self::Escape
+    }
+}
+
+impl DatumCommentControl {
+    /// Returns true if the character is handled by this policy rather than written as-is.
+    pub const fn applies_to(c: char) -> bool {
+        matches!(
+            c,
+            '\0'..='\x08'
+                | '\x0A'..='\x1F'
+                | '\x7F'..='\u{9F}'
+                | '\u{2028}'..='\u{202E}'
+                | '\u{2066}'..='\u{2069}'
+        )
+    }
+
+    /// Writes comment text (which should not contain newlines) with this policy applied.
+    pub fn write_text(&self, f: &mut dyn Write, text: &str) -> core::fmt::Result {
+        for c in text.chars() {
+            if c == '\\' {
+                f.write_str("\\\\")?;
+            } else if !Self::applies_to(c) {
+                f.write_char(c)?;
+            } else if *self == Self::Escape {
+                write!(f, "\\x{:x};", c as u32)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Options for [DatumWriter::write_comment_with].
+///
+/// The default matches [DatumWriter::write_comment].
+///
+/// _Added in 1.3.0._
+/// ```
+/// use datum::{DatumBlankCommentLines, DatumCommentStyle, DatumWriter};
+/// let style = DatumCommentStyle {
+///     prefix: ";; ",
+///     wrap_column: Some(24),
+///     blank_lines: DatumBlankCommentLines::Trimmed,
+///     ..Default::default()
+/// };
+/// let mut text = String::new();
+/// DatumWriter::default().write_comment_with(&mut text, "Generated file. Do not edit by hand.\n\nSee README.", &style).unwrap();
+/// assert_eq!(text, ";; Generated file. Do\n;; not edit by hand.\n;;\n;; See README.\n");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DatumCommentStyle {
+    /// Written at the start of each line, after indentation. This must start with `;` and not contain newlines.
+    pub prefix: &'static str,
+    /// If set, lines longer than this many columns (including indentation and the prefix) are wrapped at spaces, where possible.
+    /// Wrapped lines have their runs of spaces collapsed; other lines are written as they are.
+    pub wrap_column: Option<usize>,
+    /// Width of an indentation tab, for the purposes of [DatumCommentStyle::wrap_column].
+    pub tab_width: usize,
+    /// How blank lines are written.
+    pub blank_lines: DatumBlankCommentLines,
+    /// What happens to control characters and the like.
+    pub control: DatumCommentControl,
+}
+
+impl Default for DatumCommentStyle {
+    fn default() -> Self {
+        Self {
+            prefix: "; ",
+            wrap_column: None,
+            tab_width: 4,
+            blank_lines: DatumBlankCommentLines::Prefix,
+            control: DatumCommentControl::Escape,
+        }
+    }
+}
+
 /// General interface for formatting/printing Datum content.
 /// Note that if the passed writer returns an error at any point, the state of the writer is indeterminate, as it can be mid-token or part-way through a comment.
+///
+/// Start from [Default::default] and set options with the builder methods (i.e. [DatumWriter::with_line_ending]).
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
 pub struct DatumWriter {
     /// Indentation level (in tabs).
     pub indent: usize,
     /// Writer's state. Beware: Editing this improperly can create unreadable output.
     pub state: DatumWriterState,
+    /// See [DatumWriter::with_line_ending].
+    line_ending: DatumLineEnding,
+    /// How many lists [DatumWriter::write_token] has started and not yet ended; see [DatumWriter::in_list_depth].
+    list_depth: usize,
+    /// See [DatumWriter::plus_sign].
+    plus_sign: bool,
 }
 
 impl DatumWriter {
@@ -60,28 +264,168 @@ impl DatumWriter {
         Ok(())
     }
 
-    /// Writes a newline and prepares for it.
+    /// Writes a newline (as [DatumWriter::with_line_ending]) and prepares for it.
     pub fn write_newline(&mut self, f: &mut dyn Write) -> core::fmt::Result {
         self.state = DatumWriterState::QueuedIndent;
-        f.write_char('\n')
+        f.write_str(self.line_ending.as_str())
     }
 
-    /// Writes a line comment. Newlines are converted into more line comments.
+    /// Writes a line comment. Newlines (including CRLF) are converted into more line comments.
+    ///
+    /// Backslashes and control characters are escaped, so the comment can't break or spoof the document; see [DatumCommentControl].
     pub fn write_comment(&mut self, f: &mut dyn Write, text: &str) -> core::fmt::Result {
-        self.emit_whitespace(f, false)?;
-        f.write_str("; ")?;
-        for v in text.chars() {
-            if v == '\n' {
+        self.write_comment_with(f, text, &DatumCommentStyle::default())
+    }
+
+    /// Writes a line comment, with the given options. Newlines (including CRLF) are converted into more line comments.
+    ///
+    /// _Added in 1.3.0._
+    pub fn write_comment_with(
+        &mut self,
+        f: &mut dyn Write,
+        text: &str,
+        style: &DatumCommentStyle,
+    ) -> core::fmt::Result {
+        let prefix_width = self.indent * style.tab_width + style.prefix.chars().count();
+        for line in text.split('\n') {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let wrap_width = match style.wrap_column {
+                Some(column) if prefix_width + line.chars().count() > column => {
+                    column.saturating_sub(prefix_width)
+                }
+                _ => {
+                    self.write_comment_line(f, style, line)?;
+                    continue;
+                }
+            };
+            let mut width = 0;
+            let mut line_open = false;
+            for word in line.split(' ').filter(|word| !word.is_empty()) {
+                let word_width = word.chars().count();
+                if line_open && width + 1 + word_width > wrap_width {
+                    self.write_newline(f)?;
+                    line_open = false;
+                }
+                if line_open {
+                    f.write_char(' ')?;
+                    width += 1 + word_width;
+                } else {
+                    self.emit_whitespace(f, false)?;
+                    f.write_str(style.prefix)?;
+                    line_open = true;
+                    width = word_width;
+                }
+                style.control.write_text(f, word)?;
+            }
+            if line_open {
                 self.write_newline(f)?;
-                self.emit_whitespace(f, false)?;
-                f.write_str("; ")?;
             } else {
-                f.write_char(v)?;
+                // only spaces
+                self.write_comment_line(f, style, "")?;
             }
         }
+        Ok(())
+    }
+
+    /// Writes a single comment line, unwrapped (or as a blank line).
+    fn write_comment_line(
+        &mut self,
+        f: &mut dyn Write,
+        style: &DatumCommentStyle,
+        line: &str,
+    ) -> core::fmt::Result {
+        let prefix = if !line.is_empty() {
+            style.prefix
+        } else {
+            match style.blank_lines {
+                DatumBlankCommentLines::Prefix => style.prefix,
+                DatumBlankCommentLines::Trimmed => style.prefix.trim_end(),
+                DatumBlankCommentLines::Skip => return Ok(()),
+            }
+        };
+        self.emit_whitespace(f, false)?;
+        f.write_str(prefix)?;
+        style.control.write_text(f, line)?;
         self.write_newline(f)
     }
 
+    /// Sets the line ending written by [DatumWriter::write_newline] (and so after comments).
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumLineEnding, DatumWriter};
+    /// let mut text = String::new();
+    /// let mut writer = DatumWriter::default().with_line_ending(DatumLineEnding::CrLf);
+    /// writer.write_comment(&mut text, "a").unwrap();
+    /// assert_eq!(text, "; a\r\n");
+    /// ```
+    pub fn with_line_ending(mut self, line_ending: DatumLineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// The line ending written by [DatumWriter::write_newline]; see [DatumWriter::with_line_ending].
+    ///
+    /// _Added in 1.3.0._
+    pub fn line_ending(&self) -> DatumLineEnding {
+        self.line_ending
+    }
+
+    /// Sets if symbols are written so that they also read back the same with [crate::DatumDecoder::plus_sign] set (by default, they aren't).
+    /// See [DatumToken::write_with_plus_sign].
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumAtom, DatumWriter};
+    /// let mut text = String::new();
+    /// let mut writer = DatumWriter::default().plus_sign(true);
+    /// writer.write_atom(&mut text, &DatumAtom::Symbol("+a")).unwrap();
+    /// assert_eq!(text, "\\+a");
+    /// ```
+    pub fn plus_sign(mut self, plus_sign: bool) -> Self {
+        self.plus_sign = plus_sign;
+        self
+    }
+
+    /// How many lists deep the writer is (0 at the top level of a document).
+    ///
+    /// _Added in 1.3.0._
+    pub fn in_list_depth(&self) -> usize {
+        self.list_depth
+    }
+
+    /// Starts a list, returning a guard to write its contents through, which ends it.
+    ///
+    /// The list is ended by [DatumListGuard::end], or when the guard is dropped (which in debug builds is treated as a bug, unless something being written failed).
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumAtom, DatumWriter};
+    /// let mut text = String::new();
+    /// let mut writer = DatumWriter::default();
+    /// let mut list = writer.begin_list(&mut text).unwrap();
+    /// list.write_atom(&DatumAtom::Symbol("point")).unwrap();
+    /// let mut inner = list.begin_list().unwrap();
+    /// inner.write_atom(&DatumAtom::<&str>::Integer(1)).unwrap();
+    /// inner.write_atom(&DatumAtom::<&str>::Integer(2)).unwrap();
+    /// assert_eq!(inner.in_list_depth(), 2);
+    /// inner.end().unwrap();
+    /// list.end().unwrap();
+    /// assert_eq!(writer.in_list_depth(), 0);
+    /// assert_eq!(text, "(point (1 2))");
+    /// ```
+    pub fn begin_list<'a>(
+        &'a mut self,
+        f: &'a mut dyn Write,
+    ) -> Result<DatumListGuard<'a>, core::fmt::Error> {
+        self.write_token(f, &DatumToken::<&str>::ListStart(0))?;
+        Ok(DatumListGuard {
+            writer: self,
+            f,
+            done: false,
+        })
+    }
+
     /// Writes a token.
     pub fn write_token<B: Deref<Target = str>>(
         &mut self,
@@ -90,7 +434,12 @@ impl DatumWriter {
     ) -> core::fmt::Result {
         let token_type = token.token_type();
         self.emit_whitespace(f, token_type == DatumTokenType::ListEnd)?;
-        token.write(f)?;
+        token.write_with_plus_sign(f, self.plus_sign)?;
+        match token_type {
+            DatumTokenType::ListStart => self.list_depth += 1,
+            DatumTokenType::ListEnd => self.list_depth = self.list_depth.saturating_sub(1),
+            _ => {}
+        }
         if token_type != DatumTokenType::ListStart {
             self.state = DatumWriterState::AfterToken;
         } else {
@@ -99,6 +448,150 @@ impl DatumWriter {
         Ok(())
     }
 
+    /// Writes a token, like [DatumWriter::write_token], but with a [DatumWriteError] saying what was being written if it fails.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumToken, DatumTokenType, DatumWriter};
+    /// // a sink with room for three bytes
+    /// struct Limited(String);
+    /// impl std::fmt::Write for Limited {
+    ///     fn write_str(&mut self, s: &str) -> std::fmt::Result {
+    ///         if self.0.len() + s.len() > 3 {
+    ///             return Err(std::fmt::Error);
+    ///         }
+    ///         self.0.push_str(s);
+    ///         Ok(())
+    ///     }
+    /// }
+    /// let mut sink = Limited(String::new());
+    /// let mut writer = DatumWriter::default();
+    /// writer.try_write_token(&mut sink, &DatumToken::<&str>::ListStart(0)).unwrap();
+    /// writer.try_write_token(&mut sink, &DatumToken::Symbol(0, "a")).unwrap();
+    /// let error = writer.try_write_token(&mut sink, &DatumToken::Symbol(0, "long")).unwrap_err();
+    /// assert_eq!(error.token_type, Some(DatumTokenType::Symbol));
+    /// assert_eq!(error.depth, 1);
+    /// assert_eq!(error.to_string(), "failed writing Symbol at list depth 1");
+    /// ```
+    pub fn try_write_token<B: Deref<Target = str>>(
+        &mut self,
+        f: &mut dyn Write,
+        token: &DatumToken<B>,
+    ) -> Result<(), DatumWriteError> {
+        let error = DatumWriteError {
+            token_type: Some(token.token_type()),
+            depth: self.list_depth,
+        };
+        self.write_token(f, token).map_err(|_| error)
+    }
+
+    /// Writes a value from AST atom, like [DatumWriter::write_atom], but with a [DatumWriteError] saying what was being written if it fails.
+    ///
+    /// _Added in 1.3.0._
+    pub fn try_write_atom<B: Deref<Target = str>>(
+        &mut self,
+        f: &mut dyn Write,
+        value: &DatumAtom<B>,
+    ) -> Result<(), DatumWriteError> {
+        let error = DatumWriteError {
+            token_type: Some(value.to_token(0).token_type()),
+            depth: self.list_depth,
+        };
+        self.write_atom(f, value).map_err(|_| error)
+    }
+
+    /// Writes a newline, like [DatumWriter::write_newline], but with a [DatumWriteError] if it fails.
+    ///
+    /// _Added in 1.3.0._
+    pub fn try_write_newline(&mut self, f: &mut dyn Write) -> Result<(), DatumWriteError> {
+        let error = DatumWriteError {
+            token_type: None,
+            depth: self.list_depth,
+        };
+        self.write_newline(f).map_err(|_| error)
+    }
+
+    /// Writes a symbol as-is, without checking if any of it needs escaping.
+    /// This skips classifying each character, for hot paths whose identifiers are known to be clean; see [DatumWriter::write_raw_symbol].
+    ///
+    /// If the symbol does need escaping (or is empty), the output won't read back as the same symbol, or may not be readable at all. Debug builds check this.
+    ///
+    /// _Added in 1.3.0._
+    pub fn write_raw_symbol_unchecked(
+        &mut self,
+        f: &mut dyn Write,
+        text: &str,
+    ) -> core::fmt::Result {
+        debug_assert!(
+            verbatim_mismatch(text, |w| {
+                DatumToken::Symbol(0, text).write_with_plus_sign(w, self.plus_sign)
+            })
+            .is_none(),
+            "write_raw_symbol_unchecked: symbol needs escaping"
+        );
+        self.emit_whitespace(f, false)?;
+        f.write_str(text)?;
+        self.state = DatumWriterState::AfterToken;
+        Ok(())
+    }
+
+    /// Writes a string as-is (between quotes), without checking if any of it needs escaping; as [DatumWriter::write_raw_symbol_unchecked], but for strings.
+    ///
+    /// _Added in 1.3.0._
+    pub fn write_raw_string_unchecked(
+        &mut self,
+        f: &mut dyn Write,
+        text: &str,
+    ) -> core::fmt::Result {
+        debug_assert!(
+            verbatim_mismatch(text, |w| datum_escape_string_content(text, w)).is_none(),
+            "write_raw_string_unchecked: string needs escaping"
+        );
+        self.emit_whitespace(f, false)?;
+        f.write_char('"')?;
+        f.write_str(text)?;
+        f.write_char('"')?;
+        self.state = DatumWriterState::AfterToken;
+        Ok(())
+    }
+
+    /// Writes a symbol as-is, or returns a `BadData` error (offset: the byte offset of the first character that needs escaping) without writing anything.
+    ///
+    /// Failures of the sink are `OutOfRoom` errors.
+    ///
+    /// _Added in 1.3.0._
+    /// ```
+    /// use datum::{DatumErrorKind, DatumWriter};
+    /// let mut text = String::new();
+    /// let mut writer = DatumWriter::default();
+    /// writer.write_raw_symbol(&mut text, "player-speed").unwrap();
+    /// let error = writer.write_raw_symbol(&mut text, "two words").unwrap_err();
+    /// assert_eq!((error.kind, error.offset), (DatumErrorKind::BadData, 3));
+    /// writer.write_raw_string(&mut text, "fast").unwrap();
+    /// assert!(writer.write_raw_string(&mut text, "line\n").is_err());
+    /// assert_eq!(text, "player-speed \"fast\"");
+    /// ```
+    pub fn write_raw_symbol(&mut self, f: &mut dyn Write, text: &str) -> DatumResult<()> {
+        if let Some(at) = verbatim_mismatch(text, |w| {
+            DatumToken::Symbol(0, text).write_with_plus_sign(w, self.plus_sign)
+        }) {
+            return Err(datum_error!(BadData, at, "writer: symbol needs escaping"));
+        }
+        self.write_raw_symbol_unchecked(f, text)
+            .map_err(|_| datum_error!(OutOfRoom, 0, "writer: write failed"))
+    }
+
+    /// Writes a string as-is (between quotes), or returns an error without writing anything; as [DatumWriter::write_raw_symbol], but for strings.
+    ///
+    /// _Added in 1.3.0._
+    pub fn write_raw_string(&mut self, f: &mut dyn Write, text: &str) -> DatumResult<()> {
+        if let Some(at) = verbatim_mismatch(text, |w| datum_escape_string_content(text, w)) {
+            return Err(datum_error!(BadData, at, "writer: string needs escaping"));
+        }
+        self.write_raw_string_unchecked(f, text)
+            .map_err(|_| datum_error!(OutOfRoom, 0, "writer: write failed"))
+    }
+
     /// Writes a value from AST atom.
     pub fn write_atom<B: Deref<Target = str>>(
         &mut self,
@@ -106,8 +599,173 @@ impl DatumWriter {
         value: &DatumAtom<B>,
     ) -> core::fmt::Result {
         self.emit_whitespace(f, false)?;
-        value.write(f)?;
+        match value {
+            DatumAtom::Symbol(v) => {
+                DatumToken::Symbol(0, v.deref()).write_with_plus_sign(f, self.plus_sign)?
+            }
+            _ => value.write(f)?,
+        }
         self.state = DatumWriterState::AfterToken;
         Ok(())
     }
 }
+
+/// Runs the escaping writer `write` for `text`, returning where (in bytes) its output first differs from `text`, if it does.
+fn verbatim_mismatch<F: FnOnce(&mut dyn Write) -> core::fmt::Result>(
+    text: &str,
+    write: F,
+) -> Option<DatumOffset> {
+    /// Compares what's written with the expected text.
+    struct Compare<'a> {
+        rest: &'a str,
+        matched: usize,
+    }
+    impl Write for Compare<'_> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            match self.rest.strip_prefix(s) {
+                Some(rest) => {
+                    self.rest = rest;
+                    self.matched += s.len();
+                    Ok(())
+                }
+                None => {
+                    // find the part of s that did match
+                    let common = self
+                        .rest
+                        .char_indices()
+                        .zip(s.chars())
+                        .find(|((_, a), b)| a != b)
+                        .map_or(self.rest.len().min(s.len()), |((at, _), _)| at);
+                    self.matched += common;
+                    Err(core::fmt::Error)
+                }
+            }
+        }
+    }
+    let mut compare = Compare {
+        rest: text,
+        matched: 0,
+    };
+    if write(&mut compare).is_err() || !compare.rest.is_empty() {
+        return Some(compare.matched as DatumOffset);
+    }
+    None
+}
+
+/// A failure to write, from [DatumWriter::try_write_token] and similar, recording what was being written.
+///
+/// [core::fmt::Error] carries no information, so when the sink is fallible (i.e. a size-limited buffer), this is what says where writing stopped.
+///
+/// _Added in 1.3.0._
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DatumWriteError {
+    /// Type of the token being written, or `None` for whitespace.
+    pub token_type: Option<DatumTokenType>,
+    /// How many lists deep the writer was (see [DatumWriter::in_list_depth]).
+    pub depth: usize,
+}
+
+impl core::fmt::Display for DatumWriteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.token_type {
+            Some(token_type) => write!(f, "failed writing {:?}", token_type)?,
+            None => f.write_str("failed writing whitespace")?,
+        }
+        write!(f, " at list depth {}", self.depth)
+    }
+}
+
+impl From<DatumWriteError> for core::fmt::Error {
+    fn from(_: DatumWriteError) -> Self {
+        core::fmt::Error
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "core_error")))]
+impl std::error::Error for DatumWriteError {}
+
+#[cfg(feature = "core_error")]
+impl core::error::Error for DatumWriteError {}
+
+/// An open list, from [DatumWriter::begin_list]; see there.
+///
+/// Writing through the guard can't leave the list unbalanced: Lists started inside it get their own guards, and the list end is written when the guard ends.
+///
+/// _Added in 1.3.0._
+pub struct DatumListGuard<'a> {
+    writer: &'a mut DatumWriter,
+    f: &'a mut dyn Write,
+    /// If the list end has been written, or writing failed (so the output is already broken).
+    done: bool,
+}
+
+impl<'a> DatumListGuard<'a> {
+    /// Runs a write, noting if it failed.
+    fn track(&mut self, result: core::fmt::Result) -> core::fmt::Result {
+        if result.is_err() {
+            self.done = true;
+        }
+        result
+    }
+
+    /// How many lists deep the writer is (1 or more).
+    pub fn in_list_depth(&self) -> usize {
+        self.writer.in_list_depth()
+    }
+
+    /// Writes an atom into the list.
+    pub fn write_atom<B: Deref<Target = str>>(
+        &mut self,
+        value: &DatumAtom<B>,
+    ) -> core::fmt::Result {
+        let result = self.writer.write_atom(self.f, value);
+        self.track(result)
+    }
+
+    /// Writes a newline into the list (see [DatumWriter::write_newline]).
+    pub fn write_newline(&mut self) -> core::fmt::Result {
+        let result = self.writer.write_newline(self.f);
+        self.track(result)
+    }
+
+    /// Writes a line comment into the list (see [DatumWriter::write_comment]).
+    pub fn write_comment(&mut self, text: &str) -> core::fmt::Result {
+        let result = self.writer.write_comment(self.f, text);
+        self.track(result)
+    }
+
+    /// Starts a list inside this one.
+    pub fn begin_list(&mut self) -> Result<DatumListGuard<'_>, core::fmt::Error> {
+        let result = self.writer.begin_list(self.f);
+        if result.is_err() {
+            self.done = true;
+        }
+        result
+    }
+
+    /// Ends the list.
+    pub fn end(mut self) -> core::fmt::Result {
+        self.close()
+    }
+
+    fn close(&mut self) -> core::fmt::Result {
+        self.done = true;
+        self.writer
+            .write_token(self.f, &DatumToken::<&str>::ListEnd(0))
+    }
+}
+
+impl Drop for DatumListGuard<'_> {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        #[cfg(feature = "std")]
+        let panicking = std::thread::panicking();
+        #[cfg(not(feature = "std"))]
+        let panicking = false;
+        debug_assert!(panicking, "list guard dropped without end()");
+        // keeps the output balanced; errors can't be reported from here
+        let _ = self.close();
+    }
+}